@@ -8,18 +8,26 @@ use solana_program::{
     program_option::COption,
     program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::{rent::Rent, Sysvar},
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
-use spl_token::instruction::AuthorityType;
+use spl_token_2022::instruction::AuthorityType;
 
 use crate::{
+    curve::{PoolState, BPS_DENOMINATOR, MAX_MARKET_PRICE},
     error::SwapError,
-    instruction::{AdminInitializeData, AdminInstruction, CommitNewAdmin},
+    instruction::{
+        AdminInitializeData, AdminInstruction, CommitNewAdmin, SeedPositionData, SetTargetsData,
+    },
+    math::Decimal,
     processor::{
-        assert_rent_exempt, assert_uninitialized, authority_id, set_authority, unpack_mint,
+        assert_account_count, assert_rent_exempt, assert_token_program, assert_uninitialized,
+        authority_id, set_authority, token_close_account, token_transfer, unpack_mint,
         unpack_token_account,
     },
-    state::{ConfigInfo, SwapInfo, PROGRAM_VERSION},
+    state::{
+        ConfigInfo, LiquidityPosition, LiquidityProvider, PauseFlags, SwapInfo, DRAIN_TIMELOCK,
+        PROGRAM_VERSION,
+    },
     state::{Fees, Rewards},
 };
 
@@ -59,6 +67,109 @@ pub fn process_admin_instruction(
             msg!("Instruction: SetRewardsInfo");
             set_new_rewards(program_id, &new_rewards, accounts)
         }
+        AdminInstruction::SetPauseFlags(pause_flags) => {
+            msg!("Instruction: SetPauseFlags");
+            set_pause_flags(program_id, pause_flags, accounts)
+        }
+        AdminInstruction::SetTargets(SetTargetsData {
+            base_target,
+            quote_target,
+        }) => {
+            msg!("Instruction: SetTargets");
+            set_targets(program_id, base_target, quote_target, accounts)
+        }
+        AdminInstruction::ProposeDrain => {
+            msg!("Instruction: ProposeDrain");
+            propose_drain(program_id, accounts)
+        }
+        AdminInstruction::ExecuteDrain => {
+            msg!("Instruction: ExecuteDrain");
+            execute_drain(program_id, accounts)
+        }
+        AdminInstruction::SetMinClaimAmount(min_claim_amount) => {
+            msg!("Instruction: SetMinClaimAmount");
+            set_min_claim_amount(program_id, min_claim_amount, accounts)
+        }
+        AdminInstruction::SetAdminSplit(admin_split_bps) => {
+            msg!("Instruction: SetAdminSplit");
+            set_admin_split(program_id, admin_split_bps, accounts)
+        }
+        AdminInstruction::SetMinPoolSupply(min_pool_supply) => {
+            msg!("Instruction: SetMinPoolSupply");
+            set_min_pool_supply(program_id, min_pool_supply, accounts)
+        }
+        AdminInstruction::SetMinRewardLiquidity(min_reward_liquidity) => {
+            msg!("Instruction: SetMinRewardLiquidity");
+            set_min_reward_liquidity(program_id, min_reward_liquidity, accounts)
+        }
+        AdminInstruction::SetRewardMint(deltafi_mint) => {
+            msg!("Instruction: SetRewardMint");
+            set_reward_mint(program_id, &deltafi_mint, accounts)
+        }
+        AdminInstruction::CloseSwap => {
+            msg!("Instruction: CloseSwap");
+            close_swap(program_id, accounts)
+        }
+        AdminInstruction::SetMaxOracleMove(max_oracle_move_bps) => {
+            msg!("Instruction: SetMaxOracleMove");
+            set_max_oracle_move(program_id, max_oracle_move_bps, accounts)
+        }
+        AdminInstruction::SeedPosition(seed_position_data) => {
+            msg!("Instruction: SeedPosition");
+            seed_position(program_id, seed_position_data, accounts)
+        }
+        AdminInstruction::SetPriceTick(price_tick) => {
+            msg!("Instruction: SetPriceTick");
+            set_price_tick(program_id, price_tick, accounts)
+        }
+        AdminInstruction::ResyncClaimSchedule => {
+            msg!("Instruction: ResyncClaimSchedule");
+            resync_claim_schedule(program_id, accounts)
+        }
+        AdminInstruction::SetMaxTradeAmount(max_trade_amount) => {
+            msg!("Instruction: SetMaxTradeAmount");
+            set_max_trade_amount(program_id, max_trade_amount, accounts)
+        }
+        AdminInstruction::SetStrictClaimDestination(strict_claim_destination) => {
+            msg!("Instruction: SetStrictClaimDestination");
+            set_strict_claim_destination(program_id, strict_claim_destination, accounts)
+        }
+        AdminInstruction::SetSwapCooldown(swap_cooldown_seconds) => {
+            msg!("Instruction: SetSwapCooldown");
+            set_swap_cooldown(program_id, swap_cooldown_seconds, accounts)
+        }
+        AdminInstruction::SyncReserves => {
+            msg!("Instruction: SyncReserves");
+            sync_reserves(program_id, accounts)
+        }
+        AdminInstruction::SetMaxAllowedSlippage(max_allowed_slippage_bps) => {
+            msg!("Instruction: SetMaxAllowedSlippage");
+            set_max_allowed_slippage(program_id, max_allowed_slippage_bps, accounts)
+        }
+        AdminInstruction::SetOracleFree(oracle_free) => {
+            msg!("Instruction: SetOracleFree");
+            set_oracle_free(program_id, oracle_free, accounts)
+        }
+        AdminInstruction::SetManualPrice(market_price) => {
+            msg!("Instruction: SetManualPrice");
+            set_manual_price(program_id, market_price, accounts)
+        }
+        AdminInstruction::SetAllowedTokenProgram(allowed_token_program) => {
+            msg!("Instruction: SetAllowedTokenProgram");
+            set_allowed_token_program(program_id, allowed_token_program, accounts)
+        }
+        AdminInstruction::SetMaxEmaDivergence(max_ema_divergence_bps) => {
+            msg!("Instruction: SetMaxEmaDivergence");
+            set_max_ema_divergence(program_id, max_ema_divergence_bps, accounts)
+        }
+        AdminInstruction::BatchSetFees(new_fees) => {
+            msg!("Instruction: BatchSetFees");
+            batch_set_fees(program_id, &new_fees, accounts)
+        }
+        AdminInstruction::TopUpRewardBudget(amount) => {
+            msg!("Instruction: TopUpRewardBudget");
+            top_up_reward_budget(program_id, amount, accounts)
+        }
     }
 }
 
@@ -82,6 +193,7 @@ fn initialize(
     rewards: &Rewards,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    assert_account_count(accounts, 6)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let market_autority_info = next_account_info(account_info_iter)?;
@@ -106,6 +218,7 @@ fn initialize(
         return Err(SwapError::InvalidProgramAddress.into());
     }
     let token_program_id = *token_program_info.key;
+    assert_token_program(&config, &token_program_id)?;
     let deltafi_mint = unpack_mint(deltafi_mint_info, &token_program_id)?;
     if COption::Some(*market_autority_info.key) != deltafi_mint.mint_authority {
         return Err(SwapError::InvalidOwner.into());
@@ -116,12 +229,15 @@ fn initialize(
         return Err(SwapError::InvalidFreezeAuthority.into());
     }
 
+    fees.validate_against_policy()?;
+
     config.version = PROGRAM_VERSION;
     config.bump_seed = bump_seed;
     config.admin_key = *admin_info.key;
     config.deltafi_mint = *deltafi_mint_info.key;
     config.fees = Fees::new(fees);
     config.rewards = Rewards::new(rewards);
+    config.admin_split_bps = BPS_DENOMINATOR as u16;
     ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
     Ok(())
 }
@@ -129,6 +245,7 @@ fn initialize(
 /// Pause swap
 #[inline(never)]
 fn pause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -142,7 +259,10 @@ fn pause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     is_admin(&config.admin_key, admin_info)?;
 
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    token_swap.is_paused = true;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.pause_flags = PauseFlags::ALL;
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
 }
@@ -150,6 +270,7 @@ fn pause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
 /// Unpause swap
 #[inline(never)]
 fn unpause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -163,7 +284,10 @@ fn unpause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     is_admin(&config.admin_key, admin_info)?;
 
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    token_swap.is_paused = false;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.pause_flags = PauseFlags::NONE;
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
 }
@@ -171,6 +295,7 @@ fn unpause(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
 /// Set fee account
 #[inline(never)]
 fn set_fee_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 6)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -185,13 +310,21 @@ fn set_fee_account(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResu
 
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
+    assert_token_program(&config, token_program_info.key)?;
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
     if *authority_info.key != authority_id(program_id, swap_info.key, token_swap.nonce)? {
         return Err(SwapError::InvalidProgramAddress.into());
     }
     let new_admin_fee_account = unpack_token_account(new_fee_account_info, token_program_info.key)?;
-    if *authority_info.key != new_admin_fee_account.owner {
-        return Err(SwapError::InvalidOwner.into());
+    // program authorities have no private key, fees sent to them are locked
+    if new_admin_fee_account.owner == *authority_info.key
+        || new_admin_fee_account.owner
+            == authority_id(program_id, config_info.key, config.bump_seed)?
+    {
+        return Err(SwapError::InvalidOutputOwner.into());
     }
     if new_admin_fee_account.mint == token_swap.token_a_mint {
         token_swap.admin_fee_key_a = *new_fee_account_info.key;
@@ -212,6 +345,7 @@ fn commit_new_admin(
     new_admin_key: Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    assert_account_count(accounts, 4)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let admin_info = next_account_info(account_info_iter)?;
@@ -224,6 +358,11 @@ fn commit_new_admin(
 
     let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
+    assert_token_program(&config, token_program_info.key)?;
+    // secondary admin fees go to accounts owned by the admin key
+    if new_admin_key == authority_id(program_id, config_info.key, config.bump_seed)? {
+        return Err(SwapError::InvalidOutputOwner.into());
+    }
 
     config.admin_key = new_admin_key;
     ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
@@ -242,6 +381,7 @@ fn commit_new_admin(
 /// Set new fees
 #[inline(never)]
 fn set_new_fees(program_id: &Pubkey, new_fees: &Fees, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -253,8 +393,12 @@ fn set_new_fees(program_id: &Pubkey, new_fees: &Fees, accounts: &[AccountInfo])
 
     let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     is_admin(&config.admin_key, admin_info)?;
+    new_fees.validate_against_policy()?;
 
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
     token_swap.fees = Fees::new(new_fees);
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
@@ -267,6 +411,7 @@ fn set_new_rewards(
     new_rewards: &Rewards,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -280,7 +425,927 @@ fn set_new_rewards(
     is_admin(&config.admin_key, admin_info)?;
 
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
     token_swap.rewards = Rewards::new(new_rewards);
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
     Ok(())
 }
+
+/// Set paused operations
+#[inline(never)]
+fn set_pause_flags(
+    program_id: &Pubkey,
+    pause_flags: PauseFlags,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.pause_flags = pause_flags;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set pool targets
+#[inline(never)]
+fn set_targets(
+    program_id: &Pubkey,
+    base_target: u128,
+    quote_target: u128,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    let pool_state = &mut token_swap.pool_state;
+    let (base_target, quote_target) = if base_target == 0 && quote_target == 0 {
+        let (base_target, quote_target, _) = PoolState::canonical_targets(
+            pool_state.market_price,
+            pool_state.base_reserve,
+            pool_state.quote_reserve,
+            pool_state.slope,
+        )?;
+        (base_target, quote_target)
+    } else {
+        (
+            Decimal::from_scaled_val(base_target),
+            Decimal::from_scaled_val(quote_target),
+        )
+    };
+    pool_state.set_targets(base_target, quote_target)?;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Propose emergency drain (start drain timelock)
+#[inline(never)]
+fn propose_drain(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 6)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let treasury_a_info = next_account_info(account_info_iter)?;
+    let treasury_b_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if *treasury_a_info.key == token_swap.token_a || *treasury_b_info.key == token_swap.token_b {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    config.drain_swap = *swap_info.key;
+    config.drain_treasury_a = *treasury_a_info.key;
+    config.drain_treasury_b = *treasury_b_info.key;
+    config.drain_unlock_ts = clock
+        .unix_timestamp
+        .checked_add(DRAIN_TIMELOCK)
+        .ok_or(SwapError::CalculationFailure)?;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Execute emergency drain
+#[inline(never)]
+fn execute_drain(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 10)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let treasury_a_info = next_account_info(account_info_iter)?;
+    let treasury_b_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    assert_token_program(&config, token_program_info.key)?;
+    if config.drain_unlock_ts == 0 {
+        return Err(SwapError::DrainNotProposed.into());
+    }
+    if clock.unix_timestamp < config.drain_unlock_ts {
+        return Err(SwapError::DrainTimelockActive.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if *swap_info.key != config.drain_swap
+        || *treasury_a_info.key != config.drain_treasury_a
+        || *treasury_b_info.key != config.drain_treasury_b
+    {
+        return Err(SwapError::DrainTargetMismatch.into());
+    }
+    if *authority_info.key != authority_id(program_id, swap_info.key, token_swap.nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *token_a_info.key != token_swap.token_a || *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+
+    let token_a = unpack_token_account(token_a_info, token_program_info.key)?;
+    let token_b = unpack_token_account(token_b_info, token_program_info.key)?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_a_info.clone(),
+        treasury_a_info.clone(),
+        authority_info.clone(),
+        token_swap.nonce,
+        token_a.amount,
+    )?;
+    token_transfer(
+        swap_info.key,
+        token_program_info.clone(),
+        token_b_info.clone(),
+        treasury_b_info.clone(),
+        authority_info.clone(),
+        token_swap.nonce,
+        token_b.amount,
+    )?;
+
+    token_swap.pause_flags = PauseFlags::ALL;
+    token_swap.pool_state.base_reserve = Decimal::zero();
+    token_swap.pool_state.quote_reserve = Decimal::zero();
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+
+    config.drain_unlock_ts = 0;
+    config.drain_swap = Pubkey::default();
+    config.drain_treasury_a = Pubkey::default();
+    config.drain_treasury_b = Pubkey::default();
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set minimum liquidity reward claim
+#[inline(never)]
+fn set_min_claim_amount(
+    program_id: &Pubkey,
+    min_claim_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.min_claim_amount = min_claim_amount;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set admin fee split between primary and secondary admin fee accounts
+#[inline(never)]
+fn set_admin_split(
+    program_id: &Pubkey,
+    admin_split_bps: u16,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if admin_split_bps as u64 > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.admin_split_bps = admin_split_bps;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set minimum pool token supply left by a partial withdraw
+#[inline(never)]
+fn set_min_pool_supply(
+    program_id: &Pubkey,
+    min_pool_supply: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.min_pool_supply = min_pool_supply;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set whether reward claims must mint to an account of the position owner
+#[inline(never)]
+fn set_strict_claim_destination(
+    program_id: &Pubkey,
+    strict_claim_destination: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.strict_claim_destination = strict_claim_destination;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set minimum liquidity for a position to accrue rewards
+#[inline(never)]
+fn set_min_reward_liquidity(
+    program_id: &Pubkey,
+    min_reward_liquidity: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.min_reward_liquidity = min_reward_liquidity;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Replace the DELTAFI mint liquidity and swap rewards are minted from
+#[inline(never)]
+fn set_reward_mint(
+    program_id: &Pubkey,
+    deltafi_mint: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let market_autority_info = next_account_info(account_info_iter)?;
+    let deltafi_mint_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    assert_token_program(&config, token_program_info.key)?;
+
+    if *market_autority_info.key != authority_id(program_id, config_info.key, config.bump_seed)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if deltafi_mint_info.key != deltafi_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    let new_mint = unpack_mint(deltafi_mint_info, token_program_info.key)?;
+    if COption::Some(*market_autority_info.key) != new_mint.mint_authority {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if new_mint.freeze_authority.is_some()
+        && new_mint.freeze_authority != COption::Some(*admin_info.key)
+    {
+        return Err(SwapError::InvalidFreezeAuthority.into());
+    }
+
+    config.deltafi_mint = *deltafi_mint;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Close a drained swap pool and reclaim the rent of its accounts
+#[inline(never)]
+fn close_swap(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 9)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    assert_token_program(&config, token_program_info.key)?;
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if *authority_info.key != authority_id(program_id, swap_info.key, token_swap.nonce)? {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if *token_a_info.key != token_swap.token_a || *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    if destination_info.key == swap_info.key {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let pool_mint = unpack_mint(pool_mint_info, token_program_info.key)?;
+    if pool_mint.supply != 0 {
+        return Err(SwapError::InvalidSupply.into());
+    }
+    let token_a = unpack_token_account(token_a_info, token_program_info.key)?;
+    let token_b = unpack_token_account(token_b_info, token_program_info.key)?;
+    if token_a.amount != 0 || token_b.amount != 0 || token_swap.pause_flags != PauseFlags::ALL {
+        return Err(SwapError::PoolNotClosed.into());
+    }
+
+    token_close_account(
+        swap_info.key,
+        token_program_info.clone(),
+        token_a_info.clone(),
+        destination_info.clone(),
+        authority_info.clone(),
+        token_swap.nonce,
+    )?;
+    token_close_account(
+        swap_info.key,
+        token_program_info.clone(),
+        token_b_info.clone(),
+        destination_info.clone(),
+        authority_info.clone(),
+        token_swap.nonce,
+    )?;
+
+    let swap_lamports = swap_info.lamports();
+    **destination_info.lamports.borrow_mut() = destination_info
+        .lamports()
+        .checked_add(swap_lamports)
+        .ok_or(SwapError::CalculationFailure)?;
+    **swap_info.lamports.borrow_mut() = 0;
+    swap_info.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+/// Set the largest oracle move a swap pool accepts between close updates
+#[inline(never)]
+fn set_max_oracle_move(
+    program_id: &Pubkey,
+    max_oracle_move_bps: u16,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if max_oracle_move_bps as u64 > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.max_oracle_move_bps = max_oracle_move_bps;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Seed a liquidity position migrated from a legacy program
+#[inline(never)]
+fn seed_position(
+    program_id: &Pubkey,
+    seed_position_data: SeedPositionData,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id
+        || swap_info.owner != program_id
+        || liquidity_provider_info.owner != program_id
+    {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+
+    let SeedPositionData {
+        liquidity_amount,
+        rewards_owed,
+        rewards_estimated,
+        cumulative_interest,
+        last_update_ts,
+        next_claim_ts,
+    } = seed_position_data;
+    let mut liquidity_provider = LiquidityProvider::unpack(&liquidity_provider_info.data.borrow())?;
+    liquidity_provider.seed_position(LiquidityPosition {
+        pool: *swap_info.key,
+        liquidity_amount,
+        rewards_owed,
+        rewards_estimated,
+        cumulative_interest,
+        last_update_ts,
+        next_claim_ts,
+        reward_debt: 0,
+    })?;
+    LiquidityProvider::pack(
+        liquidity_provider,
+        &mut liquidity_provider_info.data.borrow_mut(),
+    )?;
+    Ok(())
+}
+
+/// Set the price step quoted mid prices of a swap pool snap to
+#[inline(never)]
+fn set_price_tick(
+    program_id: &Pubkey,
+    price_tick: u128,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.price_tick = Decimal::from_scaled_val(price_tick);
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Move positions in a swap pool onto the current claim schedule
+#[inline(never)]
+fn resync_claim_schedule(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    if accounts.len() < 4 {
+        msg!("Expected at least 4 accounts, got {}", accounts.len());
+        return Err(SwapError::InvalidAccountCount.into());
+    }
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+
+    for liquidity_provider_info in account_info_iter {
+        if liquidity_provider_info.owner != program_id {
+            return Err(SwapError::InvalidAccountOwner.into());
+        }
+        let mut liquidity_provider =
+            LiquidityProvider::unpack(&liquidity_provider_info.data.borrow())?;
+        let (position, _) = liquidity_provider.find_position(*swap_info.key)?;
+        position.resync_claim_ts()?;
+        LiquidityProvider::pack(
+            liquidity_provider,
+            &mut liquidity_provider_info.data.borrow_mut(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Set the largest amount a single swap in a swap pool may sell
+#[inline(never)]
+fn set_max_trade_amount(
+    program_id: &Pubkey,
+    max_trade_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.max_trade_amount = max_trade_amount;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the minimum time between two swaps of the same user in a swap pool
+#[inline(never)]
+fn set_swap_cooldown(
+    program_id: &Pubkey,
+    swap_cooldown_seconds: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.swap_cooldown_seconds = swap_cooldown_seconds;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Sync pool reserves to the token account balances
+#[inline(never)]
+fn sync_reserves(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 6)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let token_a_info = next_account_info(account_info_iter)?;
+    let token_b_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    assert_token_program(&config, token_program_info.key)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if *token_a_info.key != token_swap.token_a || *token_b_info.key != token_swap.token_b {
+        return Err(SwapError::IncorrectSwapAccount.into());
+    }
+    let token_a = unpack_token_account(token_a_info, token_program_info.key)?;
+    let token_b = unpack_token_account(token_b_info, token_program_info.key)?;
+
+    let pool_state = &mut token_swap.pool_state;
+    pool_state.base_reserve = Decimal::from(token_a.amount);
+    pool_state.quote_reserve = Decimal::from(token_b.amount);
+    if pool_state.is_degenerate() {
+        return Err(SwapError::DegeneratePool.into());
+    }
+    let (base_target, quote_target, _) = PoolState::canonical_targets(
+        pool_state.market_price,
+        pool_state.base_reserve,
+        pool_state.quote_reserve,
+        pool_state.slope,
+    )?;
+    pool_state.set_targets(base_target, quote_target)?;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the slippage cap swaps of a swap pool are checked against
+#[inline(never)]
+fn set_max_allowed_slippage(
+    program_id: &Pubkey,
+    max_allowed_slippage_bps: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if max_allowed_slippage_bps > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidInput.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.max_allowed_slippage_bps = max_allowed_slippage_bps;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Switch a swap pool between oracle and manual pricing
+#[inline(never)]
+fn set_oracle_free(
+    program_id: &Pubkey,
+    oracle_free: bool,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.oracle_free = oracle_free;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the market price of an oracle-free swap pool and retarget its reserves
+#[inline(never)]
+fn set_manual_price(
+    program_id: &Pubkey,
+    market_price: u128,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if market_price > MAX_MARKET_PRICE {
+        return Err(SwapError::MarketPriceTooHigh.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if !token_swap.oracle_free {
+        return Err(SwapError::NotOracleFree.into());
+    }
+    // rejects prices below the curve minimum
+    token_swap.pool_state = PoolState::new(PoolState {
+        market_price: Decimal::from_scaled_val(market_price),
+        ..token_swap.pool_state
+    })?;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the token program accepted alongside spl_token
+#[inline(never)]
+fn set_allowed_token_program(
+    program_id: &Pubkey,
+    allowed_token_program: Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.allowed_token_program = allowed_token_program;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set the Pyth spot to EMA divergence above which an oracle price is rejected
+#[inline(never)]
+fn set_max_ema_divergence(
+    program_id: &Pubkey,
+    max_ema_divergence_bps: u16,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    config.max_ema_divergence_bps = max_ema_divergence_bps;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Set new fees on several swap pools of a config at once
+#[inline(never)]
+fn batch_set_fees(program_id: &Pubkey, new_fees: &Fees, accounts: &[AccountInfo]) -> ProgramResult {
+    if accounts.len() < 3 {
+        msg!("Expected at least 3 accounts, got {}", accounts.len());
+        return Err(SwapError::InvalidAccountCount.into());
+    }
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+    new_fees.validate_against_policy()?;
+
+    // any invalid pool fails the transaction, reverting the pools before it
+    for swap_info in account_info_iter {
+        if swap_info.owner != program_id {
+            return Err(SwapError::InvalidAccountOwner.into());
+        }
+        let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+        if token_swap.config_key != *config_info.key {
+            return Err(SwapError::InvalidConfig.into());
+        }
+        token_swap.fees = Fees::new(new_fees);
+        SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    }
+    Ok(())
+}
+
+/// Add to the reward tokens swaps on a swap pool may still mint
+#[inline(never)]
+fn top_up_reward_budget(
+    program_id: &Pubkey,
+    amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id || swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    is_admin(&config.admin_key, admin_info)?;
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    token_swap.reward_budget_remaining = token_swap
+        .reward_budget_remaining
+        .checked_add(amount)
+        .ok_or(SwapError::CalculationFailure)?;
+    SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+//! Off-chain helpers for clients building transactions against the program
+
+#![cfg(not(target_arch = "bpf"))]
+
+use std::convert::TryFrom;
+
+use crate::instruction::SwapDirection;
+
+/// Account checks, unpacking and packing of config and swap state
+const SWAP_BASE_UNITS: u32 = 18_000;
+/// Curve math selling base token
+const SELL_BASE_UNITS: u32 = 12_000;
+/// Curve math selling quote token, which divides by the market price
+const SELL_QUOTE_UNITS: u32 = 14_000;
+/// Token transfers in and out of the pool plus the admin and creator fees
+const TRANSFER_UNITS: u32 = 4 * 5_000;
+/// Loading and validating one pyth price account
+const ORACLE_UNITS: u32 = 3_000;
+/// Updating the cumulative price and reading the internal fallback price
+const TWAP_UNITS: u32 = 2_000;
+
+/// Relative compute cost of a swap, for comparing pool configurations.
+///
+/// The per-operation figures are a rough apportionment of the swap path and
+/// have not been measured under BPF, so they are not a compute budget. Size
+/// the compute unit limit from the units a simulated transaction consumed
+/// instead.
+///
+/// # Arguments
+///
+/// * direction - swap direction.
+/// * has_twap - whether the pool has its internal twap oracle open.
+/// * oracle_count - number of pyth price accounts read.
+///
+/// # Return value
+///
+/// estimated compute units
+pub fn estimated_compute_units(
+    direction: SwapDirection,
+    has_twap: bool,
+    oracle_count: usize,
+) -> u32 {
+    let curve_units = match direction {
+        SwapDirection::SellBase => SELL_BASE_UNITS,
+        SwapDirection::SellQuote => SELL_QUOTE_UNITS,
+    };
+    let twap_units = if has_twap { TWAP_UNITS } else { 0 };
+    let oracle_units = ORACLE_UNITS.saturating_mul(u32::try_from(oracle_count).unwrap_or(u32::MAX));
+
+    SWAP_BASE_UNITS
+        .saturating_add(curve_units)
+        .saturating_add(TRANSFER_UNITS)
+        .saturating_add(twap_units)
+        .saturating_add(oracle_units)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimated_compute_units() {
+        for direction in [SwapDirection::SellBase, SwapDirection::SellQuote] {
+            for has_twap in [false, true] {
+                let mut last = 0;
+                for oracle_count in 0..=4 {
+                    let units = estimated_compute_units(direction, has_twap, oracle_count);
+                    assert!(units > last);
+                    last = units;
+                }
+            }
+            for oracle_count in 0..=4 {
+                assert!(
+                    estimated_compute_units(direction, true, oracle_count)
+                        > estimated_compute_units(direction, false, oracle_count)
+                );
+            }
+        }
+
+        assert_eq!(
+            estimated_compute_units(SwapDirection::SellBase, false, usize::MAX),
+            u32::MAX
+        );
+    }
+}
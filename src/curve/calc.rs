@@ -215,7 +215,10 @@ pub fn get_target_reserve(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::curve::{default_market_price, default_slope};
+    use crate::{
+        curve::{default_market_price, default_slope},
+        math::HALF_WAD,
+    };
     use proptest::prelude::*;
 
     prop_compose! {
@@ -372,18 +375,23 @@ mod tests {
         ) {
             let slope: Decimal = default_slope();
             let market_price: Decimal = default_market_price();
+            // with whole reserves and price and a slope of one half, only the
+            // penalty loses precision, at most half a scaled unit
             let fair_amount = future_reserve
                 .try_sub(current_reserve)?
-                .try_mul(market_price)?;
+                .try_mul_checked_precision(market_price, 0)?;
             let expected_target_amount: Decimal = if slope.is_zero() {
                 fair_amount
             } else {
                 let penalty_ratio = target_reserve
-                    .try_mul(target_reserve)?
+                    .try_mul_checked_precision(target_reserve, 0)?
                     .try_div(future_reserve)?
                     .try_div(current_reserve)?;
-                let penalty = penalty_ratio.try_mul(slope)?;
-                fair_amount.try_mul(penalty.try_add(Decimal::one())?.try_sub(slope)?)?
+                let penalty = penalty_ratio.try_mul_checked_precision(slope, HALF_WAD as u128)?;
+                fair_amount.try_mul_checked_precision(
+                    penalty.try_add(Decimal::one())?.try_sub(slope)?,
+                    0,
+                )?
             };
 
             assert_eq!(
@@ -3,8 +3,9 @@
 use super::*;
 use crate::{
     error::SwapError,
+    instruction::SwapDirection,
     math::{Decimal, TryAdd, TryDiv, TryMul, TrySub},
-    state::{pack_decimal, unpack_decimal},
+    state::{pack_decimal, unpack_decimal, Fees},
 };
 
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
@@ -19,6 +20,22 @@ use std::{
     convert::{TryFrom, TryInto},
 };
 
+/// Basis points in 100%
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Smallest market price, scaled, the curve accepts (1e-6 quote per base).
+/// Curve paths for pools above one divide by the market price through its
+/// reciprocal, which this bound keeps at or below 1e6.
+pub const MIN_MARKET_PRICE: u128 = 1_000;
+
+/// Largest market price, scaled, accepted as a manual price (1e6 quote per
+/// base), the reciprocal of `MIN_MARKET_PRICE`
+pub const MAX_MARKET_PRICE: u128 = 1_000_000_000_000_000;
+
+/// Largest deviation, in basis points of the oracle price, the quote per base
+/// ratio of a pool's initial deposit may have from the oracle price
+pub const MAX_INITIAL_PRICE_DEVIATION_BPS: u64 = 500;
+
 /// Multiplier status enum
 #[derive(Clone, Copy, PartialEq, Debug, Hash)]
 pub enum Multiplier {
@@ -72,6 +89,9 @@ pub struct PoolState {
 impl PoolState {
     /// Create new pool state
     pub fn new(params: PoolState) -> Result<Self, ProgramError> {
+        if params.market_price < Decimal::from_scaled_val(MIN_MARKET_PRICE) {
+            return Err(SwapError::MarketPriceTooLow.into());
+        }
         let mut pool = Self::default();
         pool.init(params);
         pool.adjust_target()?;
@@ -117,6 +137,149 @@ impl PoolState {
         Ok(())
     }
 
+    /// Regression targets and multiplier a freshly balanced pool holding the
+    /// given reserves would have: targets at the market price ratio, with the
+    /// deficit side's target solving the curve for the surplus side's excess.
+    ///
+    /// # Arguments
+    ///
+    /// * market_price - market price.
+    /// * base_reserve - base token reserve.
+    /// * quote_reserve - quote token reserve.
+    /// * slope - slope.
+    ///
+    /// # Return value
+    ///
+    /// base target, quote target and multiplier.
+    pub fn canonical_targets(
+        market_price: Decimal,
+        base_reserve: Decimal,
+        quote_reserve: Decimal,
+        slope: Decimal,
+    ) -> Result<(Decimal, Decimal, Multiplier), ProgramError> {
+        if base_reserve.is_zero() && quote_reserve.is_zero() {
+            return Ok((Decimal::zero(), Decimal::zero(), Multiplier::One));
+        }
+        if base_reserve.is_zero() || quote_reserve.is_zero() {
+            return Err(SwapError::InvalidPoolState.into());
+        }
+
+        let base_value = base_reserve.try_mul(market_price)?;
+        match base_value.cmp(&quote_reserve) {
+            Ordering::Equal => Ok((base_reserve, quote_reserve, Multiplier::One)),
+            Ordering::Greater => {
+                let quote_target =
+                    balanced_target(quote_reserve, base_value.try_sub(quote_reserve)?, slope)?;
+                Ok((
+                    quote_target.try_div(market_price)?.min(base_reserve),
+                    quote_target,
+                    Multiplier::BelowOne,
+                ))
+            }
+            Ordering::Less => {
+                let base_target = balanced_target(
+                    base_reserve,
+                    quote_reserve.try_div(market_price)?.try_sub(base_reserve)?,
+                    slope,
+                )?;
+                Ok((
+                    base_target,
+                    base_target.try_mul(market_price)?.min(quote_reserve),
+                    Multiplier::AboveOne,
+                ))
+            }
+        }
+    }
+
+    /// Replace the regression targets, deriving the multiplier status from how
+    /// the reserves sit against them, and re-adjust the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * base_target - new base token target.
+    /// * quote_target - new quote token target.
+    pub fn set_targets(&mut self, base_target: Decimal, quote_target: Decimal) -> ProgramResult {
+        if base_target.is_zero() || quote_target.is_zero() {
+            return Err(SwapError::InvalidTargets.into());
+        }
+        let multiplier = match (
+            self.base_reserve.cmp(&base_target),
+            self.quote_reserve.cmp(&quote_target),
+        ) {
+            (Ordering::Equal, Ordering::Equal) => Multiplier::One,
+            (Ordering::Less, Ordering::Greater | Ordering::Equal) => Multiplier::AboveOne,
+            (Ordering::Greater, Ordering::Less | Ordering::Equal) => Multiplier::BelowOne,
+            _ => return Err(SwapError::InvalidTargets.into()),
+        };
+
+        let mut pool_state = PoolState::new(PoolState {
+            base_target,
+            quote_target,
+            multiplier,
+            ..self.clone()
+        })
+        .map_err(|_| SwapError::InvalidTargets)?;
+        pool_state
+            .get_mid_price()
+            .map_err(|_| SwapError::InvalidTargets)?;
+
+        *self = pool_state;
+        Ok(())
+    }
+
+    /// Check that reserves are consistent with the targets and multiplier.
+    ///
+    /// A pool holding liquidity must have both reserves non-zero, and the
+    /// reserve driving the target adjustment must sit on the side of its
+    /// target implied by the multiplier. A pool left with a single non-empty
+    /// reserve is reported as degenerate: the admin recovers it by topping up
+    /// the empty token account and running `SyncReserves`.
+    pub fn validate_invariants(&self) -> ProgramResult {
+        let has_liquidity = !self.base_target.is_zero() || !self.quote_target.is_zero();
+        if has_liquidity && self.is_degenerate() {
+            return Err(SwapError::DegeneratePool.into());
+        }
+        if has_liquidity && (self.base_reserve.is_zero() || self.quote_reserve.is_zero()) {
+            return Err(SwapError::InvalidPoolState.into());
+        }
+        let consistent = match self.multiplier {
+            Multiplier::One => true,
+            Multiplier::AboveOne => self.quote_reserve >= self.quote_target,
+            Multiplier::BelowOne => self.base_reserve >= self.base_target,
+        };
+        if !consistent {
+            return Err(SwapError::InvalidPoolState.into());
+        }
+        Ok(())
+    }
+
+    /// Whether exactly one of the reserves is empty, as rounding on repeated
+    /// withdraws can leave a pool. Neither shares nor the mid price can be
+    /// computed against an empty reserve.
+    pub fn is_degenerate(&self) -> bool {
+        self.base_reserve.is_zero() != self.quote_reserve.is_zero()
+    }
+
+    /// Product of the reserves, tracked by monitoring to spot value leaking
+    /// out of the pool across trades.
+    ///
+    /// # Return value
+    ///
+    /// base_reserve * quote_reserve
+    pub fn pseudo_k(&self) -> Result<Decimal, ProgramError> {
+        self.base_reserve.try_mul(self.quote_reserve)
+    }
+
+    /// Price a constant product pool would quote on the same reserves,
+    /// ignoring the market price and the curve, to sanity check the mid price.
+    ///
+    /// # Return value
+    ///
+    /// quote_reserve / base_reserve, quote per base.
+    pub fn reserve_ratio_price(&self) -> Result<Decimal, ProgramError> {
+        self.quote_reserve.try_div(self.base_reserve)
+    }
+
     /// Get adjusted market price based on the current pool status and intelligent
     /// market making curve.
     ///
@@ -125,7 +288,37 @@ impl PoolState {
     /// adjusted market price.
     pub fn get_mid_price(&mut self) -> Result<Decimal, ProgramError> {
         self.adjust_target()?;
-        match self.multiplier {
+        self.curve_price(self.multiplier)
+    }
+
+    /// Instantaneous price of a trade in a direction, the limit of its
+    /// execution price as the trade size goes to zero.
+    ///
+    /// Off target this is the derivative of the curve at the current
+    /// reserves, the mid price. Trades out of a balanced pool start from the
+    /// targets, where both branches of the curve give the market price, so
+    /// there it differs from the mid price if the reserves are off target.
+    ///
+    /// # Arguments
+    ///
+    /// * direction - swap direction.
+    ///
+    /// # Return value
+    ///
+    /// marginal price, quote per base.
+    pub fn marginal_price(&mut self, direction: SwapDirection) -> Result<Decimal, ProgramError> {
+        self.adjust_target()?;
+        match (self.multiplier, direction) {
+            (Multiplier::One, _) => Ok(self.market_price),
+            (multiplier, _) => self.curve_price(multiplier),
+        }
+    }
+
+    /// Derivative of the curve at the current reserves,
+    /// `i / (1 - k + k * (Q0 / Q)^2)` below one and
+    /// `i * (1 - k + k * (B0 / B)^2)` otherwise
+    fn curve_price(&self, multiplier: Multiplier) -> Result<Decimal, ProgramError> {
+        match multiplier {
             Multiplier::BelowOne => {
                 let multiplier = self
                     .quote_target
@@ -153,6 +346,24 @@ impl PoolState {
         }
     }
 
+    /// Get the mid price rounded to the nearest multiple of a tick size, for
+    /// quotes on markets with discrete price levels.
+    ///
+    /// # Arguments
+    ///
+    /// * tick - price step, zero disables quantization.
+    ///
+    /// # Return value
+    ///
+    /// quantized mid price.
+    pub fn quantized_mid_price(&mut self, tick: Decimal) -> Result<Decimal, ProgramError> {
+        let mid_price = self.get_mid_price()?;
+        if tick.is_zero() {
+            return Ok(mid_price);
+        }
+        Decimal::try_from_u128(mid_price.try_div(tick)?.try_round_u128()?)?.try_mul(tick)
+    }
+
     /// Sell base token for quote token with multiplier input.
     ///
     /// # Arguments
@@ -203,6 +414,15 @@ impl PoolState {
     ///
     /// purchased quote token amount, updated multiplier.
     pub fn sell_base_token(&self, base_amount: u64) -> Result<(u64, Multiplier), ProgramError> {
+        let (quote_amount, new_multiplier) = self.sell_base_token_unrounded(base_amount)?;
+        Ok((quote_amount.try_floor_u64()?, new_multiplier))
+    }
+
+    /// Sell base token before rounding the purchased amount down.
+    fn sell_base_token_unrounded(
+        &self,
+        base_amount: u64,
+    ) -> Result<(Decimal, Multiplier), ProgramError> {
         let (quote_amount, new_multiplier) = match self.multiplier {
             Multiplier::One => (
                 self.sell_base_token_with_multiplier(base_amount.into(), Multiplier::One)?,
@@ -237,7 +457,7 @@ impl PoolState {
                 }
             }
         };
-        Ok((quote_amount.try_floor_u64()?, new_multiplier))
+        Ok((quote_amount, new_multiplier))
     }
 
     /// Sell quote token for base token with multiplier input.
@@ -290,6 +510,15 @@ impl PoolState {
     ///
     /// purchased base token amount, updated multiplier.
     pub fn sell_quote_token(&self, quote_amount: u64) -> Result<(u64, Multiplier), ProgramError> {
+        let (base_amount, new_multiplier) = self.sell_quote_token_unrounded(quote_amount)?;
+        Ok((base_amount.try_floor_u64()?, new_multiplier))
+    }
+
+    /// Sell quote token before rounding the purchased amount down.
+    fn sell_quote_token_unrounded(
+        &self,
+        quote_amount: u64,
+    ) -> Result<(Decimal, Multiplier), ProgramError> {
         let (base_amount, new_multiplier) = match self.multiplier {
             Multiplier::One => (
                 self.sell_quote_token_with_multiplier(quote_amount.into(), Multiplier::One)?,
@@ -324,7 +553,7 @@ impl PoolState {
                 }
             }
         };
-        Ok((base_amount.try_floor_u64()?, new_multiplier))
+        Ok((base_amount, new_multiplier))
     }
 
     /// Buy shares [round down]: deposit and calculate shares.
@@ -344,6 +573,7 @@ impl PoolState {
         quote_balance: u64,
         total_supply: u64,
     ) -> Result<u64, ProgramError> {
+        let first_deposit = (base_balance, quote_balance);
         let base_balance = Decimal::from(base_balance);
         let quote_balance = Decimal::from(quote_balance);
         let base_input = base_balance.try_sub(self.base_reserve)?;
@@ -354,15 +584,17 @@ impl PoolState {
         }
 
         let shares = if total_supply == 0 {
-            // case 1. initial supply
-            let shares = if self.market_price.try_mul(base_balance)? > quote_balance {
-                quote_balance.try_div(self.market_price)?
-            } else {
-                base_balance
-            };
+            // case 1. initial supply, no liquidity is locked
+            let (base_amount, quote_amount) = first_deposit;
+            let shares = initial_shares(base_balance, quote_balance, self.market_price)?;
             self.base_target = shares;
             self.quote_target = shares.try_mul(self.market_price)?;
-            shares
+            Decimal::from(first_deposit_shares(
+                base_amount,
+                quote_amount,
+                self.market_price,
+                0,
+            )?)
         } else if self.base_reserve > Decimal::zero() && self.quote_reserve > Decimal::zero() {
             // case 2. normal case
             let base_input_ratio = base_input.try_div(self.base_reserve)?;
@@ -370,15 +602,14 @@ impl PoolState {
             let mint_ratio = base_input_ratio.min(quote_input_ratio);
             let shares = mint_ratio.try_mul(total_supply)?;
 
-            self.base_target = self
-                .base_target
-                .try_mul(mint_ratio)?
-                .try_add(self.base_target)?;
-            self.quote_target = self
-                .quote_target
-                .try_mul(mint_ratio)?
-                .try_add(self.quote_target)?;
+            // targets grow by the same (1 + mint_ratio) factor as the share supply,
+            // which for a balanced deposit is also the factor the reserves grow by
+            let target_growth = Decimal::one().try_add(mint_ratio)?;
+            self.base_target = self.base_target.try_mul(target_growth)?;
+            self.quote_target = self.quote_target.try_mul(target_growth)?;
             shares
+        } else if self.is_degenerate() {
+            return Err(SwapError::DegeneratePool.into());
         } else {
             return Err(SwapError::IncorrectMint.into());
         };
@@ -407,33 +638,147 @@ impl PoolState {
         quote_min_amount: u64,
         total_supply: u64,
     ) -> Result<(u64, u64), ProgramError> {
+        if share_amount > total_supply {
+            return Err(SwapError::InsufficientLiquidity.into());
+        }
+
         let base_balance = self.base_reserve;
         let quote_balance = self.quote_reserve;
 
-        let base_amount = base_balance.try_mul(share_amount)?.try_div(total_supply)?;
-        let quote_amount = quote_balance.try_mul(share_amount)?.try_div(total_supply)?;
+        let base_amount = base_balance
+            .try_mul(share_amount)?
+            .try_div(total_supply)?
+            .try_floor_u64()?;
+        let quote_amount = quote_balance
+            .try_mul(share_amount)?
+            .try_div(total_supply)?
+            .try_floor_u64()?;
+
+        if base_amount < base_min_amount || quote_amount < quote_min_amount {
+            return Err(SwapError::WithdrawNotEnough.into());
+        }
 
-        self.base_target = self.base_target.try_sub(
-            self.base_target
-                .try_mul(share_amount)?
-                .try_div(total_supply)?,
+        // reduce targets by the same ratio the floored withdrawal reduces reserves,
+        // so target/reserve is preserved
+        self.base_target = reduce_target(
+            self.base_target,
+            base_amount,
+            base_balance,
+            share_amount,
+            total_supply,
         )?;
-        self.quote_target = self.quote_target.try_sub(
-            self.quote_target
-                .try_mul(share_amount)?
-                .try_div(total_supply)?,
+        self.quote_target = reduce_target(
+            self.quote_target,
+            quote_amount,
+            quote_balance,
+            share_amount,
+            total_supply,
         )?;
 
-        if base_amount < Decimal::from(base_min_amount)
-            || quote_amount < Decimal::from(quote_min_amount)
-        {
-            return Err(SwapError::WithdrawNotEnough.into());
+        self.base_reserve = self.base_reserve.try_sub(Decimal::from(base_amount))?;
+        self.quote_reserve = self.quote_reserve.try_sub(Decimal::from(quote_amount))?;
+
+        Ok((base_amount, quote_amount))
+    }
+
+    /// Preview a withdrawal without changing the pool state.
+    ///
+    /// # Arguments
+    ///
+    /// * pool_token_amount - share amount to sell.
+    /// * total_supply - total shares amount.
+    /// * fees - fees charged on the withdrawn amounts.
+    ///
+    /// # Return value
+    ///
+    /// base amount, quote amount paid to the user after withdraw fees.
+    pub fn preview_withdraw(
+        &self,
+        pool_token_amount: u64,
+        total_supply: u64,
+        fees: &Fees,
+    ) -> Result<(u64, u64), ProgramError> {
+        let (base_amount, quote_amount) =
+            self.clone()
+                .sell_shares(pool_token_amount, 0, 0, total_supply)?;
+        let (base_amount, _, _) = fees.apply_withdraw_fees(base_amount)?;
+        let (quote_amount, _, _) = fees.apply_withdraw_fees(quote_amount)?;
+
+        Ok((base_amount, quote_amount))
+    }
+
+    /// Preview a fee-free redemption of shares with the pool repriced at the
+    /// given market price, without changing the pool state.
+    ///
+    /// # Arguments
+    ///
+    /// * market_price - price the pool targets are adjusted to.
+    /// * pool_token_amount - share amount to redeem.
+    /// * total_supply - total shares amount.
+    ///
+    /// # Return value
+    ///
+    /// base amount, quote amount backing the shares.
+    pub fn preview_redeem(
+        &self,
+        market_price: Decimal,
+        pool_token_amount: u64,
+        total_supply: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        PoolState::new(PoolState {
+            market_price,
+            ..self.clone()
+        })?
+        .sell_shares(pool_token_amount, 0, 0, total_supply)
+    }
+
+    /// Amount a trade can sell before the multiplier flips: base sold
+    /// back to target for `AboveOne`, quote sold back to target for
+    /// `BelowOne`. Any trade moves a pool at `One` off it, so its headroom is
+    /// zero.
+    ///
+    /// # Return value
+    ///
+    /// headroom in the sold token, rounded down.
+    pub fn multiplier_headroom(&self) -> Result<u64, ProgramError> {
+        match self.multiplier {
+            Multiplier::One => Ok(0),
+            Multiplier::AboveOne => self.base_target.try_sub(self.base_reserve)?.try_floor_u64(),
+            Multiplier::BelowOne => self
+                .quote_target
+                .try_sub(self.quote_reserve)?
+                .try_floor_u64(),
         }
+    }
 
-        self.base_reserve = self.base_reserve.try_sub(base_amount)?;
-        self.quote_reserve = self.quote_reserve.try_sub(quote_amount)?;
+    /// Trade in the rebalancing direction that returns the pool to
+    /// `Multiplier::One`: selling base from `AboveOne`, selling quote from
+    /// `BelowOne`. A pool at `One` is already balanced and reports selling
+    /// zero base.
+    ///
+    /// # Return value
+    ///
+    /// direction, amount of `multiplier_headroom` to sell.
+    pub fn distance_to_balance(&self) -> Result<(SwapDirection, u64), ProgramError> {
+        let direction = match self.multiplier {
+            Multiplier::BelowOne => SwapDirection::SellQuote,
+            Multiplier::One | Multiplier::AboveOne => SwapDirection::SellBase,
+        };
+        Ok((direction, self.multiplier_headroom()?))
+    }
 
-        Ok((base_amount.try_floor_u64()?, quote_amount.try_floor_u64()?))
+    /// Quote per base a deposit should supply so `calculate_deposit_amount`
+    /// takes both amounts in full. Ratios that are not exact at WAD precision
+    /// can still trim a part per WAD of the deposit.
+    ///
+    /// # Return value
+    ///
+    /// quote reserve over base reserve, the market price for an empty pool.
+    pub fn balanced_deposit_ratio(&self) -> Result<Decimal, ProgramError> {
+        if self.base_reserve.is_zero() {
+            return Ok(self.market_price);
+        }
+        self.quote_reserve.try_div(self.base_reserve)
     }
 
     /// Calculate deposit amount according to the reserve.
@@ -490,86 +835,505 @@ impl PoolState {
             quote_in_amount.try_floor_u64()?,
         ))
     }
-}
-
-impl Sealed for PoolState {}
 
-/// PoolState packed size
-pub const POOL_STATE_SIZE: usize = 97; // 16 + 16 + 16 + 16 + 16 + 16 +1
-impl Pack for PoolState {
-    const LEN: usize = POOL_STATE_SIZE;
-    fn pack_into_slice(&self, output: &mut [u8]) {
-        let output = array_mut_ref![output, 0, POOL_STATE_SIZE];
-        let (
-            market_price,
-            slope,
-            base_reserve,
-            quote_reserve,
-            base_target,
-            quote_target,
-            multiplier,
-        ) = mut_array_refs![output, 16, 16, 16, 16, 16, 16, 1];
-        pack_decimal(self.market_price, market_price);
-        pack_decimal(self.slope, slope);
-        pack_decimal(self.base_reserve, base_reserve);
-        pack_decimal(self.quote_reserve, quote_reserve);
-        pack_decimal(self.base_target, base_target);
-        pack_decimal(self.quote_target, quote_target);
-        multiplier[0] = self.multiplier as u8;
+    /// Price a trade of the given size executes at, before fees, quoted like
+    /// the mid price as quote per base. Selling base therefore prices below
+    /// the mid price and selling quote above it.
+    ///
+    /// # Arguments
+    ///
+    /// * amount_in - amount of the sold token.
+    /// * direction - swap direction.
+    ///
+    /// # Return value
+    ///
+    /// execution price, the mid price for a zero amount.
+    pub fn execution_price(
+        &self,
+        amount_in: u64,
+        direction: SwapDirection,
+    ) -> Result<Decimal, ProgramError> {
+        if amount_in == 0 {
+            return self.clone().get_mid_price();
+        }
+        match direction {
+            SwapDirection::SellBase => {
+                Decimal::from(self.sell_base_token(amount_in)?.0).try_div(amount_in)
+            }
+            SwapDirection::SellQuote => {
+                Decimal::from(amount_in).try_div(self.sell_quote_token(amount_in)?.0)
+            }
+        }
     }
 
-    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
-        let input = array_ref![input, 0, POOL_STATE_SIZE];
-        #[allow(clippy::ptr_offset_with_cast)]
-        let (
-            market_price,
-            slope,
-            base_reserve,
-            quote_reserve,
-            base_target,
-            quote_target,
-            multiplier,
-        ) = array_refs![input, 16, 16, 16, 16, 16, 16, 1];
-        Ok(Self {
-            market_price: unpack_decimal(market_price),
-            slope: unpack_decimal(slope),
-            base_reserve: unpack_decimal(base_reserve),
-            quote_reserve: unpack_decimal(quote_reserve),
-            base_target: unpack_decimal(base_target),
-            quote_target: unpack_decimal(quote_target),
-            multiplier: multiplier[0].try_into()?,
-        })
+    /// Price impact of a trade: the share of output lost against the
+    /// current mid price, before fees.
+    ///
+    /// # Arguments
+    ///
+    /// * amount_in - amount of the sold token.
+    /// * direction - swap direction.
+    ///
+    /// # Return value
+    ///
+    /// price impact as a fraction of the ideal output.
+    pub fn price_impact(
+        &self,
+        amount_in: u64,
+        direction: SwapDirection,
+    ) -> Result<Decimal, ProgramError> {
+        if amount_in == 0 {
+            return Ok(Decimal::zero());
+        }
+        let mid_price = self.clone().get_mid_price()?;
+        let (ideal_out, amount_out) = match direction {
+            SwapDirection::SellBase => (
+                Decimal::from(amount_in).try_mul(mid_price)?,
+                self.sell_base_token_unrounded(amount_in)?.0,
+            ),
+            SwapDirection::SellQuote => (
+                Decimal::from(amount_in).try_div(mid_price)?,
+                self.sell_quote_token_unrounded(amount_in)?.0,
+            ),
+        };
+        if amount_out >= ideal_out {
+            return Ok(Decimal::zero());
+        }
+        ideal_out.try_sub(amount_out)?.try_div(ideal_out)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use proptest::prelude::*;
 
-    prop_compose! {
-        fn get_pool_argument_range()(
-            next_value in 2..=u16::MAX-1
-        )(
-            multiplier_index in 0..=2usize,
-            base_target in 1..=next_value,
-            quote_target in 1..=next_value,
-            base_reserve in next_value..=u16::MAX,
-            quote_reserve in next_value..=u16::MAX
-        ) -> (Multiplier ,Decimal, Decimal, Decimal, Decimal) {
-            let multiplier_arry = [Multiplier::One, Multiplier::AboveOne, Multiplier::BelowOne];
-            (multiplier_arry[multiplier_index], Decimal::from(base_target as u64), Decimal::from(quote_target as u64), Decimal::from(base_reserve as u64), Decimal::from(quote_reserve as u64))
+    /// Smallest slippage tolerance a swap of the given size passes with,
+    /// after trade fees. Unlike `price_impact` it measures the rounded amount
+    /// the swap pays out net of the trade fee, so a minimum amount out of the
+    /// mid price output reduced by this many basis points, rounded down,
+    /// never exceeds it.
+    ///
+    /// # Arguments
+    ///
+    /// * fees - pool fees.
+    /// * amount_in - amount of the sold token.
+    /// * direction - swap direction.
+    ///
+    /// # Return value
+    ///
+    /// shortfall of the output against the mid price output, in basis
+    /// points rounded up.
+    pub fn required_slippage_bps(
+        &self,
+        fees: &Fees,
+        amount_in: u64,
+        direction: SwapDirection,
+    ) -> Result<u64, ProgramError> {
+        if amount_in == 0 {
+            return Ok(0);
+        }
+        let mid_price = self.clone().get_mid_price()?;
+        let (ideal_out, receive_amount, quote_per_token) = match direction {
+            SwapDirection::SellBase => (
+                Decimal::from(amount_in).try_mul(mid_price)?,
+                self.sell_base_token(amount_in)?.0,
+                Decimal::one(),
+            ),
+            SwapDirection::SellQuote => (
+                Decimal::from(amount_in).try_div(mid_price)?,
+                self.sell_quote_token(amount_in)?.0,
+                self.market_price,
+            ),
+        };
+        let amount_out = Decimal::from(
+            receive_amount
+                .checked_sub(fees.trade_fee(receive_amount, quote_per_token)?)
+                .ok_or(SwapError::CalculationFailure)?,
+        );
+        if amount_out >= ideal_out {
+            return Ok(0);
         }
+        ideal_out
+            .try_sub(amount_out)?
+            .try_div(ideal_out)?
+            .try_mul(BPS_DENOMINATOR)?
+            .try_ceil_u64()
     }
 
-    prop_compose! {
-        fn get_token_with_multiplier_range()(
-            next_value in 2..=u16::MAX-1
-        )(
-            multiplier_index in 0..=2usize,
-            new_multiplier_index in 0..=2usize,
-            amount in 1..=u16::MAX,
-            base_target in 1..=next_value,
+    /// Breakeven trade size where price impact catches up with the trade fee
+    /// rate. Below it the fee dominates and splitting a trade into smaller
+    /// ones no longer pays off.
+    ///
+    /// # Arguments
+    ///
+    /// * fees - pool fees.
+    /// * direction - swap direction.
+    ///
+    /// # Return value
+    ///
+    /// smallest amount in whose price impact reaches the fee rate, capped at
+    /// the amount buying out the other reserve at mid price.
+    pub fn optimal_split_threshold(
+        &self,
+        fees: &Fees,
+        direction: SwapDirection,
+    ) -> Result<u64, ProgramError> {
+        let fee_rate = if fees.trade_fee_enabled {
+            Decimal::from(fees.trade_fee_numerator).try_div(fees.trade_fee_denominator)?
+        } else {
+            Decimal::zero()
+        };
+        let mid_price = self.clone().get_mid_price()?;
+        let max_amount_in = match direction {
+            SwapDirection::SellBase => self.quote_reserve.try_div(mid_price)?,
+            SwapDirection::SellQuote => self.base_reserve.try_mul(mid_price)?,
+        };
+
+        let (mut low, mut high) = (1, max_amount_in.try_floor_u64()?.max(1));
+        if self.price_impact(high, direction)? < fee_rate {
+            return Ok(high);
+        }
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.price_impact(mid, direction)? >= fee_rate {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Trade size that moves the mid price onto the oracle price, for keepers
+    /// arbitraging the pool back in line. The search stops at the first
+    /// amount crossing the oracle price, so the pool ends up within one unit
+    /// of the sold token of it.
+    ///
+    /// # Arguments
+    ///
+    /// * oracle_price - oracle price the mid price is moved to.
+    /// * direction - swap direction, selling base lowers the mid price.
+    ///
+    /// # Return value
+    ///
+    /// smallest amount in reaching the oracle price, zero if the swap
+    /// direction moves the mid price away from it, capped at the amount
+    /// buying out the other reserve at mid price.
+    pub fn arb_amount_to_oracle(
+        &mut self,
+        oracle_price: Decimal,
+        direction: SwapDirection,
+    ) -> Result<u64, ProgramError> {
+        let reached = |price: Decimal| match direction {
+            SwapDirection::SellBase => price <= oracle_price,
+            SwapDirection::SellQuote => price >= oracle_price,
+        };
+        let mid_price = self.get_mid_price()?;
+        if reached(mid_price) {
+            return Ok(0);
+        }
+        let max_amount_in = match direction {
+            SwapDirection::SellBase => self.quote_reserve.try_div(mid_price)?,
+            SwapDirection::SellQuote => self.base_reserve.try_mul(mid_price)?,
+        };
+
+        let (mut low, mut high) = (1, max_amount_in.try_floor_u64()?.max(1));
+        if !reached(self.after_swap(high, direction)?.get_mid_price()?) {
+            return Ok(high);
+        }
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if reached(self.after_swap(mid, direction)?.get_mid_price()?) {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+        Ok(low)
+    }
+
+    /// Pool state after a swap, before fees.
+    fn after_swap(&self, amount_in: u64, direction: SwapDirection) -> Result<Self, ProgramError> {
+        let (base_reserve, quote_reserve, multiplier) = match direction {
+            SwapDirection::SellBase => {
+                let (amount_out, multiplier) = self.sell_base_token(amount_in)?;
+                (
+                    self.base_reserve.try_add(Decimal::from(amount_in))?,
+                    self.quote_reserve.try_sub(Decimal::from(amount_out))?,
+                    multiplier,
+                )
+            }
+            SwapDirection::SellQuote => {
+                let (amount_out, multiplier) = self.sell_quote_token(amount_in)?;
+                (
+                    self.base_reserve.try_sub(Decimal::from(amount_out))?,
+                    self.quote_reserve.try_add(Decimal::from(amount_in))?,
+                    multiplier,
+                )
+            }
+        };
+        Self::new(Self {
+            base_reserve,
+            quote_reserve,
+            multiplier,
+            ..self.clone()
+        })
+    }
+
+    /// Diff against a later snapshot of the pool.
+    ///
+    /// # Arguments
+    ///
+    /// * other - pool state after the change.
+    ///
+    /// # Return value
+    ///
+    /// signed deltas from `self` to `other` and the multiplier transition.
+    pub fn diff(&self, other: &PoolState) -> Result<PoolStateDiff, ProgramError> {
+        Ok(PoolStateDiff {
+            base_reserve: signed_delta(self.base_reserve, other.base_reserve)?,
+            quote_reserve: signed_delta(self.quote_reserve, other.quote_reserve)?,
+            base_target: signed_delta(self.base_target, other.base_target)?,
+            quote_target: signed_delta(self.quote_target, other.quote_target)?,
+            multiplier_before: self.multiplier,
+            multiplier_after: other.multiplier,
+        })
+    }
+}
+
+/// Signed change between two pool state snapshots, deltas are scaled values
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PoolStateDiff {
+    /// base reserve delta
+    pub base_reserve: i128,
+    /// quote reserve delta
+    pub quote_reserve: i128,
+    /// base target delta
+    pub base_target: i128,
+    /// quote target delta
+    pub quote_target: i128,
+    /// multiplier status before the change
+    pub multiplier_before: Multiplier,
+    /// multiplier status after the change
+    pub multiplier_after: Multiplier,
+}
+
+/// First deposit shares [round down]: the deposit valued in base at the
+/// market price, capped by its scarcer side, less `minimum_liquidity`
+/// withheld from the first depositor. `buy_shares` mints the initial supply
+/// through this with a zero `minimum_liquidity`, pools do not lock any
+/// liquidity, so it is no defense against a donation to an emptied pool.
+///
+/// # Arguments
+///
+/// * base_balance - base amount deposited.
+/// * quote_balance - quote amount deposited.
+/// * market_price - market price, quote per base.
+/// * minimum_liquidity - shares locked from the first deposit.
+///
+/// # Return value
+///
+/// shares minted to the first depositor.
+pub fn first_deposit_shares(
+    base_balance: u64,
+    quote_balance: u64,
+    market_price: Decimal,
+    minimum_liquidity: u64,
+) -> Result<u64, ProgramError> {
+    initial_shares(
+        Decimal::from(base_balance),
+        Decimal::from(quote_balance),
+        market_price,
+    )?
+    .try_floor_u64()?
+    .checked_sub(minimum_liquidity)
+    .filter(|shares| *shares > 0)
+    .ok_or_else(|| SwapError::InsufficientLiquidity.into())
+}
+
+/// Initial shares of an empty pool: the scarcer side of the deposit,
+/// valued in base at the market price.
+fn initial_shares(
+    base_balance: Decimal,
+    quote_balance: Decimal,
+    market_price: Decimal,
+) -> Result<Decimal, ProgramError> {
+    if market_price.try_mul(base_balance)? > quote_balance {
+        quote_balance.try_div(market_price)
+    } else {
+        Ok(base_balance)
+    }
+}
+
+/// Signed difference `after - before` of two decimals as a scaled value.
+fn signed_delta(before: Decimal, after: Decimal) -> Result<i128, ProgramError> {
+    let before =
+        i128::try_from(before.to_scaled_val()?).map_err(|_| SwapError::CalculationFailure)?;
+    let after =
+        i128::try_from(after.to_scaled_val()?).map_err(|_| SwapError::CalculationFailure)?;
+    Ok(after
+        .checked_sub(before)
+        .ok_or(SwapError::CalculationFailure)?)
+}
+
+/// Deficit side target at which both targets sit at the market price ratio,
+/// given the surplus side holds `surplus_value` more, valued in deficit tokens:
+/// `reserve * (1 + (sqrt(1 + slope * surplus_value / reserve) - 1) / slope)`.
+fn balanced_target(
+    reserve: Decimal,
+    surplus_value: Decimal,
+    slope: Decimal,
+) -> Result<Decimal, ProgramError> {
+    if slope.is_zero() {
+        return surplus_value.try_div(2)?.try_add(reserve);
+    }
+    if slope > Decimal::one() {
+        return Err(SwapError::InvalidSlope.into());
+    }
+    let square_root = Decimal::geometric_mean(
+        slope
+            .try_mul(surplus_value)?
+            .try_div(reserve)?
+            .try_add(Decimal::one())?,
+        Decimal::one(),
+    )?;
+    square_root
+        .try_sub(Decimal::one())?
+        .try_div(slope)?
+        .try_add(Decimal::one())?
+        .try_mul(reserve)
+}
+
+/// Reduce a target in proportion to the amount withdrawn from its reserve,
+/// falling back to the share ratio for an empty reserve.
+fn reduce_target(
+    target: Decimal,
+    amount: u64,
+    reserve: Decimal,
+    share_amount: u64,
+    total_supply: u64,
+) -> Result<Decimal, ProgramError> {
+    let reduction = if reserve.is_zero() {
+        target.try_mul(share_amount)?.try_div(total_supply)?
+    } else {
+        target.try_mul(amount)?.try_div(reserve)?
+    };
+    target.try_sub(reduction)
+}
+
+/// Minimum amount out accepted for an expected output and a max slippage
+///
+/// # Arguments
+///
+/// * expected_out - quoted output amount.
+/// * slippage_bps - max slippage in basis points, at most 10_000.
+///
+/// # Return value
+///
+/// minimum amount out, rounded down
+pub fn min_out_with_slippage(expected_out: u64, slippage_bps: u64) -> Result<u64, ProgramError> {
+    let remaining_bps = BPS_DENOMINATOR
+        .checked_sub(slippage_bps)
+        .ok_or(SwapError::InvalidInput)?;
+    let min_out = (expected_out as u128)
+        .checked_mul(remaining_bps as u128)
+        .ok_or(SwapError::CalculationFailure)?
+        .checked_div(BPS_DENOMINATOR as u128)
+        .ok_or(SwapError::CalculationFailure)?;
+    Ok(u64::try_from(min_out).map_err(|_| SwapError::CalculationFailure)?)
+}
+
+/// Scaled slope, as carried by `InitializeData.slope`, from basis points
+///
+/// # Arguments
+///
+/// * bps - slope in basis points, at most 10_000.
+///
+/// # Return value
+///
+/// slope as a scaled decimal value
+pub fn slope_from_bps(bps: u16) -> Result<u64, ProgramError> {
+    if u64::from(bps) > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidSlope.into());
+    }
+    let slope = Decimal::from_bps(bps.into()).to_scaled_val()?;
+    Ok(u64::try_from(slope).map_err(|_| SwapError::CalculationFailure)?)
+}
+
+impl Sealed for PoolState {}
+
+/// PoolState packed size
+pub const POOL_STATE_SIZE: usize = 97; // 16 + 16 + 16 + 16 + 16 + 16 +1
+impl Pack for PoolState {
+    const LEN: usize = POOL_STATE_SIZE;
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, POOL_STATE_SIZE];
+        let (
+            market_price,
+            slope,
+            base_reserve,
+            quote_reserve,
+            base_target,
+            quote_target,
+            multiplier,
+        ) = mut_array_refs![output, 16, 16, 16, 16, 16, 16, 1];
+        pack_decimal(self.market_price, market_price);
+        pack_decimal(self.slope, slope);
+        pack_decimal(self.base_reserve, base_reserve);
+        pack_decimal(self.quote_reserve, quote_reserve);
+        pack_decimal(self.base_target, base_target);
+        pack_decimal(self.quote_target, quote_target);
+        multiplier[0] = self.multiplier as u8;
+    }
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, POOL_STATE_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (
+            market_price,
+            slope,
+            base_reserve,
+            quote_reserve,
+            base_target,
+            quote_target,
+            multiplier,
+        ) = array_refs![input, 16, 16, 16, 16, 16, 16, 1];
+        Ok(Self {
+            market_price: unpack_decimal(market_price),
+            slope: unpack_decimal(slope),
+            base_reserve: unpack_decimal(base_reserve),
+            quote_reserve: unpack_decimal(quote_reserve),
+            base_target: unpack_decimal(base_target),
+            quote_target: unpack_decimal(quote_target),
+            multiplier: multiplier[0].try_into()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{math::WAD, state::DEFAULT_TEST_FEES};
+    use proptest::prelude::*;
+
+    prop_compose! {
+        fn get_pool_argument_range()(
+            next_value in 2..=u16::MAX-1
+        )(
+            multiplier_index in 0..=2usize,
+            base_target in 1..=next_value,
+            quote_target in 1..=next_value,
+            base_reserve in next_value..=u16::MAX,
+            quote_reserve in next_value..=u16::MAX
+        ) -> (Multiplier ,Decimal, Decimal, Decimal, Decimal) {
+            let multiplier_arry = [Multiplier::One, Multiplier::AboveOne, Multiplier::BelowOne];
+            (multiplier_arry[multiplier_index], Decimal::from(base_target as u64), Decimal::from(quote_target as u64), Decimal::from(base_reserve as u64), Decimal::from(quote_reserve as u64))
+        }
+    }
+
+    prop_compose! {
+        fn get_token_with_multiplier_range()(
+            next_value in 2..=u16::MAX-1
+        )(
+            multiplier_index in 0..=2usize,
+            new_multiplier_index in 0..=2usize,
+            amount in 1..=u16::MAX,
+            base_target in 1..=next_value,
             quote_target in 1..=next_value,
             base_reserve in next_value..=u16::MAX,
             quote_reserve in next_value..=u16::MAX
@@ -598,6 +1362,20 @@ mod tests {
         }
     }
 
+    prop_compose! {
+        fn get_balanced_deposit_range()(
+            base_unit in 1..=u16::MAX,
+            quote_unit in 1..=u16::MAX,
+            base_target in 1..=u16::MAX,
+            quote_target in 1..=u16::MAX,
+            denominator in 1..=100u64,
+            numerator in 1..=100u64,
+            total_supply in 1..=u16::MAX,
+        ) -> (u64, u64, Decimal, Decimal, u64, u64, u64) {
+            (base_unit as u64, quote_unit as u64, Decimal::from(base_target as u64), Decimal::from(quote_target as u64), denominator, numerator, total_supply as u64)
+        }
+    }
+
     prop_compose! {
         fn get_sell_shares_range()(
             next_value in 1..=u16::MAX,
@@ -634,6 +1412,74 @@ mod tests {
         }
     }
 
+    prop_compose! {
+        fn get_donation_range()(
+            base_amount in 1_000_000..=u32::MAX as u64,
+            quote_amount in 100_000_000..=100 * u32::MAX as u64,
+            base_donation in 0..=u32::MAX as u64,
+            quote_donation in 0..=100 * u32::MAX as u64,
+            multiple in 1..=10u64,
+            minimum_liquidity in 0..=1_000u64,
+        ) -> (u64, u64, u64, u64, u64, u64) {
+            (base_amount, quote_amount, base_donation, quote_donation, multiple, minimum_liquidity)
+        }
+    }
+
+    /// Multiplier a pool moved off `One` by `direction` ends in once a
+    /// rebalancing trade overshoots
+    fn overshoot_multiplier(direction: SwapDirection) -> Multiplier {
+        match direction {
+            SwapDirection::SellBase => Multiplier::AboveOne,
+            SwapDirection::SellQuote => Multiplier::BelowOne,
+        }
+    }
+
+    /// Value in quote at the default market price of 100
+    fn value_at_market_price(base_amount: u64, quote_amount: u64) -> u128 {
+        base_amount as u128 * 100 + quote_amount as u128
+    }
+
+    /// First deposit into an empty pool, a donation to its vaults, then a
+    /// second deposit of `multiple` times the first. Returns the pool with
+    /// both depositors' shares and the total supply, including the
+    /// `minimum_liquidity` withheld from the first depositor. A zero
+    /// `minimum_liquidity` is what `buy_shares` mints on-chain.
+    fn donation_scenario(
+        base_amount: u64,
+        quote_amount: u64,
+        base_donation: u64,
+        quote_donation: u64,
+        multiple: u64,
+        minimum_liquidity: u64,
+    ) -> Result<(PoolState, u64, u64, u64), ProgramError> {
+        let mut pool_state = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            ..PoolState::default()
+        };
+        let initial_supply = pool_state.buy_shares(base_amount, quote_amount, 0)?;
+        let first_shares = first_deposit_shares(
+            base_amount,
+            quote_amount,
+            default_market_price(),
+            minimum_liquidity,
+        )?;
+        assert_eq!(first_shares, initial_supply - minimum_liquidity);
+
+        // the deposit is measured from the vault balances, donation included
+        let second_shares = pool_state.buy_shares(
+            base_amount * (1 + multiple) + base_donation,
+            quote_amount * (1 + multiple) + quote_donation,
+            initial_supply,
+        )?;
+        Ok((
+            pool_state,
+            first_shares,
+            second_shares,
+            initial_supply + second_shares,
+        ))
+    }
+
     proptest! {
         #[test]
         fn test_init(
@@ -889,6 +1735,38 @@ mod tests {
             assert_eq!(buy_shares_amount, expected_shares_amount.try_floor_u64()?);
         }
 
+        #[test]
+        fn test_buy_shares_balanced_deposit_keeps_target_ratio(
+            (base_unit, quote_unit, base_target, quote_target, denominator, numerator, total_supply) in get_balanced_deposit_range()
+        ) {
+            // reserves of `unit * denominator` grown by `unit * numerator` on both sides
+            let base_reserve = Decimal::from(base_unit * denominator);
+            let quote_reserve = Decimal::from(quote_unit * denominator);
+            let mut pool_state = PoolState {
+                market_price: default_market_price(),
+                slope: default_slope(),
+                base_target,
+                quote_target,
+                base_reserve,
+                quote_reserve,
+                multiplier: Multiplier::One,
+            };
+            pool_state.buy_shares(
+                base_unit * (denominator + numerator),
+                quote_unit * (denominator + numerator),
+                total_supply,
+            )?;
+
+            let tolerance = |ratio: Decimal| ratio.try_div(1_000_000_000).and_then(|r| r.try_add(Decimal::from_scaled_val(2)));
+            let before = base_target.try_div(base_reserve)?;
+            let after = pool_state.base_target.try_div(pool_state.base_reserve)?;
+            assert!(before.max(after).try_sub(before.min(after))? <= tolerance(before)?);
+
+            let before = quote_target.try_div(quote_reserve)?;
+            let after = pool_state.quote_target.try_div(pool_state.quote_reserve)?;
+            assert!(before.max(after).try_sub(before.min(after))? <= tolerance(before)?);
+        }
+
         #[test]
         fn test_sell_shares(
             (multiplier, share_amount, base_min_amount, quote_min_amount, total_supply, base_target, quote_target, base_reserve, quote_reserve) in get_sell_shares_range()
@@ -915,6 +1793,252 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_sell_shares_keeps_target_ratio(
+            base_reserve in 1_000_000..=u32::MAX as u64,
+            quote_reserve in 1_000_000..=u32::MAX as u64,
+            base_target in 1_000_000..=u32::MAX as u64,
+            quote_target in 1_000_000..=u32::MAX as u64,
+            total_supply in 1_000_000..=u32::MAX as u64,
+            withdraw_divisors in prop::collection::vec(2..=1_000u64, 1..=20),
+        ) {
+            let mut pool_state = PoolState {
+                market_price: default_market_price(),
+                slope: default_slope(),
+                base_target: Decimal::from(base_target),
+                quote_target: Decimal::from(quote_target),
+                base_reserve: Decimal::from(base_reserve),
+                quote_reserve: Decimal::from(quote_reserve),
+                multiplier: Multiplier::One,
+            };
+            let base_ratio = pool_state.base_target.try_div(pool_state.base_reserve)?;
+            let quote_ratio = pool_state.quote_target.try_div(pool_state.quote_reserve)?;
+            let mid_price = pool_state.clone().get_mid_price()?;
+
+            let mut total_supply = total_supply;
+            for divisor in withdraw_divisors {
+                let share_amount = total_supply / divisor;
+                pool_state.sell_shares(share_amount, 0, 0, total_supply)?;
+                total_supply -= share_amount;
+            }
+
+            // tolerance of one part in a million, well above a unit of rounding per withdraw
+            let within_tolerance = |expected: Decimal, actual: Decimal| -> Result<bool, ProgramError> {
+                let diff = expected.max(actual).try_sub(expected.min(actual))?;
+                Ok(diff <= expected.try_div(1_000_000)?)
+            };
+            assert!(within_tolerance(base_ratio, pool_state.base_target.try_div(pool_state.base_reserve)?)?);
+            assert!(within_tolerance(quote_ratio, pool_state.quote_target.try_div(pool_state.quote_reserve)?)?);
+            assert!(within_tolerance(mid_price, pool_state.get_mid_price()?)?);
+        }
+
+        #[test]
+        fn test_first_deposit_cannot_profit_from_donation(
+            (base_amount, quote_amount, base_donation, quote_donation, multiple, minimum_liquidity) in get_donation_range()
+        ) {
+            let (pool_state, first_shares, _, total_supply) = donation_scenario(
+                base_amount, quote_amount, base_donation, quote_donation, multiple, minimum_liquidity,
+            )?;
+
+            // the first depositor donates to the vault ahead of the second deposit
+            let (base_out, quote_out) = pool_state.clone().sell_shares(first_shares, 0, 0, total_supply)?;
+            assert!(
+                value_at_market_price(base_out, quote_out)
+                    <= value_at_market_price(base_amount + base_donation, quote_amount + quote_donation)
+            );
+        }
+
+        #[test]
+        fn test_second_deposit_fair_after_donation(
+            (base_amount, quote_amount, base_donation, quote_donation, multiple, minimum_liquidity) in get_donation_range()
+        ) {
+            let (pool_state, _, second_shares, total_supply) = donation_scenario(
+                base_amount, quote_amount, base_donation, quote_donation, multiple, minimum_liquidity,
+            )?;
+
+            // at most a unit of each token lost to rounding down
+            let (base_out, quote_out) = pool_state.clone().sell_shares(second_shares, 0, 0, total_supply)?;
+            assert!(
+                value_at_market_price(base_out + 1, quote_out + 1)
+                    >= value_at_market_price(base_amount * multiple, quote_amount * multiple)
+            );
+        }
+
+        #[test]
+        fn test_distance_to_balance(
+            base_target in 1_000_000..=u32::MAX as u64,
+            quote_target in 1_000_000..=u32::MAX as u64,
+            deficit in 1..1_000_000u64,
+            surplus in 1..=u32::MAX as u64,
+        ) {
+            let balanced = PoolState {
+                market_price: default_market_price(),
+                slope: default_slope(),
+                base_target: Decimal::from(base_target),
+                quote_target: Decimal::from(quote_target),
+                base_reserve: Decimal::from(base_target),
+                quote_reserve: Decimal::from(quote_target),
+                multiplier: Multiplier::One,
+            };
+
+            let above_one = PoolState {
+                base_reserve: Decimal::from(base_target - deficit),
+                quote_reserve: Decimal::from(quote_target + surplus),
+                multiplier: Multiplier::AboveOne,
+                ..balanced.clone()
+            };
+            assert_eq!(above_one.distance_to_balance()?, (SwapDirection::SellBase, deficit));
+            assert_eq!(above_one.sell_base_token(deficit)?.1, Multiplier::One);
+            let balanced_after = above_one.after_swap(deficit, SwapDirection::SellBase)?;
+            assert_eq!(balanced_after.distance_to_balance()?, (SwapDirection::SellBase, 0));
+
+            let below_one = PoolState {
+                base_reserve: Decimal::from(base_target + surplus),
+                quote_reserve: Decimal::from(quote_target - deficit),
+                multiplier: Multiplier::BelowOne,
+                ..balanced
+            };
+            assert_eq!(below_one.distance_to_balance()?, (SwapDirection::SellQuote, deficit));
+            assert_eq!(below_one.sell_quote_token(deficit)?.1, Multiplier::One);
+            let balanced_after = below_one.after_swap(deficit, SwapDirection::SellQuote)?;
+            assert_eq!(balanced_after.distance_to_balance()?, (SwapDirection::SellBase, 0));
+        }
+
+        #[test]
+        fn test_distance_to_balance_after_swap(
+            base_reserve in 1_000_000..=u32::MAX as u64,
+            amount_divisor in 2..=1_000u64,
+            direction in prop_oneof![Just(SwapDirection::SellBase), Just(SwapDirection::SellQuote)],
+        ) {
+            let balanced = PoolState::new(PoolState {
+                market_price: default_market_price(),
+                slope: default_slope(),
+                base_target: Decimal::from(base_reserve),
+                quote_target: Decimal::from(base_reserve * 100),
+                base_reserve: Decimal::from(base_reserve),
+                quote_reserve: Decimal::from(base_reserve * 100),
+                multiplier: Multiplier::One,
+            })?;
+            let amount_in = match direction {
+                SwapDirection::SellBase => base_reserve / amount_divisor,
+                SwapDirection::SellQuote => base_reserve * 100 / amount_divisor,
+            };
+            let pool_state = balanced.after_swap(amount_in, direction)?;
+
+            // the adjusted target leaves a fraction of a token the rounded
+            // down distance stops short of
+            let (rebalance_direction, distance) = pool_state.distance_to_balance()?;
+            assert_ne!(rebalance_direction, direction);
+            let reached = |amount: u64| match rebalance_direction {
+                SwapDirection::SellBase => pool_state.sell_base_token(amount).map(|(_, m)| m),
+                SwapDirection::SellQuote => pool_state.sell_quote_token(amount).map(|(_, m)| m),
+            };
+            assert_ne!(reached(distance)?, overshoot_multiplier(direction));
+            assert_eq!(reached(distance + 1)?, overshoot_multiplier(direction));
+        }
+
+        #[test]
+        fn test_swap_diff_monotonicity(
+            base_reserve in 1_000_000..=u32::MAX as u64,
+            quote_reserve in 1_000_000..=u32::MAX as u64,
+            amount_divisor in 10..=10_000u64,
+        ) {
+            let pool_state = PoolState::new(PoolState {
+                market_price: default_market_price(),
+                slope: default_slope(),
+                base_target: Decimal::from(base_reserve),
+                quote_target: Decimal::from(quote_reserve),
+                base_reserve: Decimal::from(base_reserve),
+                quote_reserve: Decimal::from(quote_reserve),
+                multiplier: Multiplier::One,
+            })?;
+            let to_scaled = |amount: u64| Decimal::from(amount).to_scaled_val().unwrap() as i128;
+
+            // sell base: base reserve grows, quote reserve shrinks by the amount out
+            let base_amount = base_reserve / amount_divisor;
+            let (quote_out, multiplier) = pool_state.sell_base_token(base_amount)?;
+            let after = PoolState::new(PoolState {
+                base_reserve: Decimal::from(base_reserve + base_amount),
+                quote_reserve: Decimal::from(quote_reserve - quote_out),
+                multiplier,
+                ..pool_state.clone()
+            })?;
+            let diff = pool_state.diff(&after)?;
+            assert_eq!(diff.base_reserve, to_scaled(base_amount));
+            assert_eq!(diff.quote_reserve, -to_scaled(quote_out));
+            assert!(diff.base_reserve > 0);
+            assert!(diff.quote_reserve <= 0);
+            assert_eq!(diff.base_target, 0);
+            assert_eq!(diff.multiplier_before, Multiplier::One);
+
+            // sell quote: the mirror image
+            let quote_amount = quote_reserve / amount_divisor;
+            let (base_out, multiplier) = pool_state.sell_quote_token(quote_amount)?;
+            let after = PoolState::new(PoolState {
+                base_reserve: Decimal::from(base_reserve - base_out),
+                quote_reserve: Decimal::from(quote_reserve + quote_amount),
+                multiplier,
+                ..pool_state.clone()
+            })?;
+            let diff = pool_state.diff(&after)?;
+            assert_eq!(diff.quote_reserve, to_scaled(quote_amount));
+            assert_eq!(diff.base_reserve, -to_scaled(base_out));
+            assert!(diff.quote_reserve > 0);
+            assert!(diff.base_reserve <= 0);
+            assert_eq!(diff.quote_target, 0);
+        }
+
+        #[test]
+        fn test_pseudo_k_non_decreasing(
+            base_reserve in 1_000_000..=u32::MAX as u64,
+            amount_divisor in 10..=10_000u64,
+            deposit_divisor in 1..=1_000u64,
+        ) {
+            // reserves at the market price, so the curve quotes around the oracle
+            let quote_reserve = default_market_price().try_mul(base_reserve)?.try_floor_u64()?;
+            let pool_state = PoolState::new(PoolState {
+                market_price: default_market_price(),
+                slope: default_slope(),
+                base_target: Decimal::from(base_reserve),
+                quote_target: Decimal::from(quote_reserve),
+                base_reserve: Decimal::from(base_reserve),
+                quote_reserve: Decimal::from(quote_reserve),
+                multiplier: Multiplier::One,
+            })?;
+            let k = pool_state.pseudo_k()?;
+
+            // the trade fee stays in the pool, only the net amount leaves
+            let base_amount = base_reserve / amount_divisor;
+            let (quote_out, multiplier) = pool_state.sell_base_token(base_amount)?;
+            let quote_out = quote_out - DEFAULT_TEST_FEES.trade_fee(quote_out, Decimal::one())?;
+            let after = PoolState::new(PoolState {
+                base_reserve: Decimal::from(base_reserve + base_amount),
+                quote_reserve: Decimal::from(quote_reserve - quote_out),
+                multiplier,
+                ..pool_state.clone()
+            })?;
+            assert!(after.pseudo_k()? >= k);
+
+            let quote_amount = quote_reserve / amount_divisor;
+            let (base_out, multiplier) = pool_state.sell_quote_token(quote_amount)?;
+            let base_out = base_out - DEFAULT_TEST_FEES.trade_fee(base_out, default_market_price())?;
+            let after = PoolState::new(PoolState {
+                base_reserve: Decimal::from(base_reserve - base_out),
+                quote_reserve: Decimal::from(quote_reserve + quote_amount),
+                multiplier,
+                ..pool_state.clone()
+            })?;
+            assert!(after.pseudo_k()? >= k);
+
+            let mut after = pool_state.clone();
+            after.buy_shares(
+                base_reserve + base_reserve / deposit_divisor,
+                quote_reserve + quote_reserve / deposit_divisor,
+                base_reserve,
+            )?;
+            assert!(after.pseudo_k()? >= k);
+        }
+
         #[test]
         fn test_calculate_deposit_amount (
             (multiplier, base_in_amount, quote_in_amount, base_target, quote_target, base_reserve, quote_reserve) in get_calculate_deposit_range()
@@ -985,16 +2109,772 @@ mod tests {
     }
 
     #[test]
-    fn test_failure() {
-        assert_eq!(
-            Multiplier::try_from(3u8),
-            Err(ProgramError::InvalidAccountData)
-        );
+    fn test_min_market_price() {
+        let pool_at = |market_price: Decimal| {
+            PoolState::new(PoolState {
+                market_price,
+                slope: default_slope(),
+                base_target: Decimal::from(1_000_000_000u64),
+                quote_target: Decimal::from(1_000u64),
+                base_reserve: Decimal::from(1_000_000_000u64),
+                quote_reserve: Decimal::from(1_000u64),
+                multiplier: Multiplier::One,
+            })
+        };
 
-        let mut pool_state = PoolState {
-            market_price: default_market_price(),
-            slope: default_slope(),
-            base_target: Decimal::from(200_000u64),
+        for market_price in [0, 1, MIN_MARKET_PRICE - 1] {
+            assert_eq!(
+                pool_at(Decimal::from_scaled_val(market_price)),
+                Err(SwapError::MarketPriceTooLow.into())
+            );
+        }
+
+        // quote sold at the minimum price still converts to base
+        let pool = pool_at(Decimal::from_scaled_val(MIN_MARKET_PRICE)).unwrap();
+        let (base_amount, multiplier) = pool.sell_quote_token(100).unwrap();
+        assert!(base_amount > 0);
+        assert_eq!(multiplier, Multiplier::AboveOne);
+    }
+
+    #[test]
+    fn test_multiplier_headroom() {
+        let pool = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000_000u64),
+            quote_target: Decimal::from(100_000_000_000u64),
+            base_reserve: Decimal::from(1_000_000_000u64),
+            quote_reserve: Decimal::from(100_000_000_000u64),
+            multiplier: Multiplier::One,
+        };
+        assert_eq!(pool.multiplier_headroom().unwrap(), 0);
+
+        // selling less than the headroom keeps the multiplier, selling it
+        // exactly brings the pool back to one
+        let (_, multiplier) = pool.sell_quote_token(5_000_000_000).unwrap();
+        assert_eq!(multiplier, Multiplier::AboveOne);
+        let above_one = PoolState {
+            base_reserve: Decimal::from(950_000_000u64),
+            quote_reserve: Decimal::from(105_000_000_000u64),
+            multiplier,
+            ..pool.clone()
+        };
+        let headroom = above_one.multiplier_headroom().unwrap();
+        assert_eq!(headroom, 50_000_000);
+        assert_eq!(
+            above_one.sell_base_token(headroom - 1).unwrap().1,
+            Multiplier::AboveOne
+        );
+        assert_eq!(
+            above_one.sell_base_token(headroom).unwrap().1,
+            Multiplier::One
+        );
+
+        let (_, multiplier) = pool.sell_base_token(50_000_000).unwrap();
+        assert_eq!(multiplier, Multiplier::BelowOne);
+        let below_one = PoolState {
+            base_reserve: Decimal::from(1_050_000_000u64),
+            quote_reserve: Decimal::from(95_000_000_000u64),
+            multiplier,
+            ..pool
+        };
+        let headroom = below_one.multiplier_headroom().unwrap();
+        assert_eq!(headroom, 5_000_000_000);
+        assert_eq!(
+            below_one.sell_quote_token(headroom - 1).unwrap().1,
+            Multiplier::BelowOne
+        );
+        assert_eq!(
+            below_one.sell_quote_token(headroom).unwrap().1,
+            Multiplier::One
+        );
+    }
+
+    #[test]
+    fn test_balanced_deposit_ratio() {
+        let pool_at = |base_reserve: u64, quote_reserve: u64| PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(base_reserve),
+            quote_target: Decimal::from(quote_reserve),
+            base_reserve: Decimal::from(base_reserve),
+            quote_reserve: Decimal::from(quote_reserve),
+            multiplier: Multiplier::One,
+        };
+
+        // whole-number ratios are consumed exactly
+        for (base_reserve, quote_reserve) in [
+            (0, 0),
+            (1_000_000_000, 100_000_000_000),
+            (40_000_000_000, 800_000_000_000),
+        ] {
+            let pool = pool_at(base_reserve, quote_reserve);
+            let ratio = pool.balanced_deposit_ratio().unwrap();
+            for base_in_amount in [1_000_000u64, 7_000_000_000, 123_456_000_000] {
+                let quote_in_amount = ratio
+                    .try_mul(base_in_amount)
+                    .unwrap()
+                    .try_floor_u64()
+                    .unwrap();
+                assert_eq!(
+                    pool.calculate_deposit_amount(base_in_amount, quote_in_amount)
+                        .unwrap(),
+                    (base_in_amount, quote_in_amount)
+                );
+            }
+        }
+
+        // otherwise the ratio and the increase ratios are truncated to WAD,
+        // which can trim a part per WAD of the deposit and the reserve
+        let (base_reserve, quote_reserve) = (3_000_000_000u64, 2_000_000_000u64);
+        let pool = pool_at(base_reserve, quote_reserve);
+        let ratio = pool.balanced_deposit_ratio().unwrap();
+        for base_in_amount in [1_000_000u64, 7_000_000_000, 123_456_000_000] {
+            let quote_in_amount = ratio
+                .try_mul(base_in_amount)
+                .unwrap()
+                .try_floor_u64()
+                .unwrap();
+            let (base_used, quote_used) = pool
+                .calculate_deposit_amount(base_in_amount, quote_in_amount)
+                .unwrap();
+            assert!(base_in_amount - base_used <= (base_in_amount + base_reserve) / WAD + 2);
+            assert!(quote_in_amount - quote_used <= (quote_in_amount + quote_reserve) / WAD + 2);
+        }
+
+        assert_eq!(
+            pool_at(0, 0).balanced_deposit_ratio().unwrap(),
+            default_market_price()
+        );
+        assert_eq!(
+            pool_at(40_000_000_000, 800_000_000_000)
+                .balanced_deposit_ratio()
+                .unwrap(),
+            Decimal::from(20u64)
+        );
+    }
+
+    #[test]
+    fn test_reserve_ratio_price() {
+        let balanced = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000_000_000u64),
+            quote_target: Decimal::from(100_000_000_000_000u64),
+            base_reserve: Decimal::from(1_000_000_000_000u64),
+            quote_reserve: Decimal::from(100_000_000_000_000u64),
+            multiplier: Multiplier::One,
+        };
+        assert_eq!(
+            balanced.reserve_ratio_price().unwrap(),
+            default_market_price()
+        );
+        assert_eq!(
+            balanced.clone().get_mid_price().unwrap(),
+            default_market_price()
+        );
+
+        // with a slope below one the curve is flatter than a constant product,
+        // so the mid price moves off the market price less than the reserve
+        // ratio does. The reserves put an exact square under the curve's root
+        let below_one = PoolState {
+            base_target: Decimal::from(2_000_000_000_000u64),
+            quote_target: Decimal::from(200_000_000_000_000u64),
+            base_reserve: Decimal::from(3_500_000_000_000u64),
+            quote_reserve: Decimal::from(100_000_000_000_000u64),
+            multiplier: Multiplier::BelowOne,
+            ..balanced.clone()
+        };
+        let mid_price = below_one.clone().get_mid_price().unwrap();
+        assert_eq!(mid_price, Decimal::from(40u64));
+        assert!(below_one.reserve_ratio_price().unwrap() < mid_price);
+
+        let above_one = PoolState {
+            base_target: Decimal::from(2_000_000_000_000u64),
+            quote_target: Decimal::from(200_000_000_000_000u64),
+            base_reserve: Decimal::from(1_000_000_000_000u64),
+            quote_reserve: Decimal::from(350_000_000_000_000u64),
+            multiplier: Multiplier::AboveOne,
+            ..balanced.clone()
+        };
+        let mid_price = above_one.clone().get_mid_price().unwrap();
+        assert_eq!(mid_price, Decimal::from(250u64));
+        assert_eq!(
+            above_one.reserve_ratio_price().unwrap(),
+            Decimal::from(350u64)
+        );
+
+        let empty = PoolState {
+            base_reserve: Decimal::zero(),
+            ..balanced
+        };
+        assert!(empty.reserve_ratio_price().is_err());
+    }
+
+    #[test]
+    fn test_marginal_price() {
+        let balanced = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000_000_000u64),
+            quote_target: Decimal::from(100_000_000_000_000u64),
+            base_reserve: Decimal::from(1_000_000_000_000u64),
+            quote_reserve: Decimal::from(100_000_000_000_000u64),
+            multiplier: Multiplier::One,
+        };
+        let below_one = balanced
+            .after_swap(100_000_000_000, SwapDirection::SellBase)
+            .unwrap();
+        let above_one = balanced
+            .after_swap(10_000_000_000_000, SwapDirection::SellQuote)
+            .unwrap();
+        assert_eq!(below_one.multiplier, Multiplier::BelowOne);
+        assert_eq!(above_one.multiplier, Multiplier::AboveOne);
+
+        for pool in [&balanced, &below_one, &above_one] {
+            let mid_price = pool.clone().get_mid_price().unwrap();
+            for direction in [SwapDirection::SellBase, SwapDirection::SellQuote] {
+                assert_eq!(pool.clone().marginal_price(direction).unwrap(), mid_price);
+            }
+        }
+
+        // a balanced pool holding base beyond its target trades from the
+        // targets, at the market price, while its mid price reads the reserves
+        let off_target = PoolState {
+            base_reserve: Decimal::from(1_200_000_000_000u64),
+            ..balanced.clone()
+        };
+        assert!(off_target.clone().get_mid_price().unwrap() < default_market_price());
+
+        for pool in [&balanced, &off_target] {
+            // execution price converges onto it from the trade's side
+            for (direction, reserve) in [
+                (SwapDirection::SellBase, 1_000_000_000_000u64),
+                (SwapDirection::SellQuote, 100_000_000_000_000u64),
+            ] {
+                let marginal_price = pool.clone().marginal_price(direction).unwrap();
+                assert_eq!(marginal_price, default_market_price());
+
+                let mut last_gap = Decimal::from(u64::MAX);
+                for divisor in [100, 10_000, 1_000_000] {
+                    let price = pool.execution_price(reserve / divisor, direction).unwrap();
+                    let gap = match direction {
+                        SwapDirection::SellBase => marginal_price.try_sub(price).unwrap(),
+                        SwapDirection::SellQuote => price.try_sub(marginal_price).unwrap(),
+                    };
+                    assert!(gap <= last_gap);
+                    last_gap = gap;
+                }
+                // within 0.01% for a trade of a millionth of the reserve
+                assert!(last_gap < marginal_price.try_mul(Decimal::from_bps(1)).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_execution_price() {
+        let mut pool = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000_000_000u64),
+            quote_target: Decimal::from(100_000_000_000_000u64),
+            base_reserve: Decimal::from(1_000_000_000_000u64),
+            quote_reserve: Decimal::from(100_000_000_000_000u64),
+            multiplier: Multiplier::One,
+        };
+        let mid_price = pool.get_mid_price().unwrap();
+
+        assert_eq!(
+            pool.execution_price(0, SwapDirection::SellBase).unwrap(),
+            mid_price
+        );
+
+        // sizes from 0.01% to 10% of the sold reserve
+        for (direction, reserve) in [
+            (SwapDirection::SellBase, 1_000_000_000_000u64),
+            (SwapDirection::SellQuote, 100_000_000_000_000u64),
+        ] {
+            let mut last_gap = Decimal::zero();
+            for divisor in [10_000, 1_000, 100, 10] {
+                let price = pool.execution_price(reserve / divisor, direction).unwrap();
+                let gap = match direction {
+                    SwapDirection::SellBase => mid_price.try_sub(price).unwrap(),
+                    SwapDirection::SellQuote => price.try_sub(mid_price).unwrap(),
+                };
+                assert!(gap > last_gap);
+                last_gap = gap;
+            }
+        }
+    }
+
+    #[test]
+    fn test_required_slippage_bps() {
+        let mut pool = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000_000_000u64),
+            quote_target: Decimal::from(100_000_000_000_000u64),
+            base_reserve: Decimal::from(1_000_000_000_000u64),
+            quote_reserve: Decimal::from(100_000_000_000_000u64),
+            multiplier: Multiplier::One,
+        };
+        let mid_price = pool.get_mid_price().unwrap();
+        assert_eq!(
+            pool.required_slippage_bps(&DEFAULT_TEST_FEES, 0, SwapDirection::SellBase)
+                .unwrap(),
+            0
+        );
+
+        // sizes from 0.1% to 10% of the sold reserve
+        for (direction, reserve) in [
+            (SwapDirection::SellBase, 1_000_000_000_000u64),
+            (SwapDirection::SellQuote, 100_000_000_000_000u64),
+        ] {
+            let mut last_bps = 0;
+            for divisor in [1_000, 100, 10] {
+                let amount_in = reserve / divisor;
+                let bps = pool
+                    .required_slippage_bps(&DEFAULT_TEST_FEES, amount_in, direction)
+                    .unwrap();
+                assert!(bps > last_bps);
+                last_bps = bps;
+
+                let (ideal_out, receive_amount, quote_per_token) = match direction {
+                    SwapDirection::SellBase => (
+                        Decimal::from(amount_in).try_mul(mid_price).unwrap(),
+                        pool.sell_base_token(amount_in).unwrap().0,
+                        Decimal::one(),
+                    ),
+                    SwapDirection::SellQuote => (
+                        Decimal::from(amount_in).try_div(mid_price).unwrap(),
+                        pool.sell_quote_token(amount_in).unwrap().0,
+                        pool.market_price,
+                    ),
+                };
+                // the tolerance covers the output net of the trade fee
+                let amount_out = receive_amount
+                    - DEFAULT_TEST_FEES
+                        .trade_fee(receive_amount, quote_per_token)
+                        .unwrap();
+                let minimum_out = |bps: u64| {
+                    ideal_out
+                        .try_mul(Decimal::one().try_sub(Decimal::from_bps(bps)).unwrap())
+                        .unwrap()
+                        .try_floor_u64()
+                        .unwrap()
+                };
+                // the tolerance just covers the output, one bps less does not
+                assert!(minimum_out(bps) <= amount_out);
+                assert!(minimum_out(bps - 1) > amount_out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimal_split_threshold() {
+        let pool_at = |reserve: u64| PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(reserve),
+            quote_target: Decimal::from(reserve),
+            base_reserve: Decimal::from(reserve),
+            quote_reserve: Decimal::from(reserve),
+            multiplier: Multiplier::One,
+        };
+        let low_fees = Fees {
+            trade_fee_numerator: 1,
+            trade_fee_denominator: 100,
+            ..DEFAULT_TEST_FEES
+        };
+        let fee_rate = Decimal::from(DEFAULT_TEST_FEES.trade_fee_numerator)
+            .try_div(DEFAULT_TEST_FEES.trade_fee_denominator)
+            .unwrap();
+
+        for direction in [SwapDirection::SellBase, SwapDirection::SellQuote] {
+            let shallow = pool_at(1_000_000_000);
+            let deep = pool_at(10_000_000_000);
+
+            let threshold = shallow
+                .optimal_split_threshold(&DEFAULT_TEST_FEES, direction)
+                .unwrap();
+            assert!(shallow.price_impact(threshold, direction).unwrap() >= fee_rate);
+            assert!(shallow.price_impact(threshold - 1, direction).unwrap() < fee_rate);
+
+            // deeper liquidity absorbs more before impact catches up with fees
+            assert!(
+                deep.optimal_split_threshold(&DEFAULT_TEST_FEES, direction)
+                    .unwrap()
+                    > threshold
+            );
+
+            // a lower fee is overtaken by price impact at a smaller size
+            assert!(
+                shallow
+                    .optimal_split_threshold(&low_fees, direction)
+                    .unwrap()
+                    < threshold
+            );
+        }
+
+        let no_fees = Fees {
+            trade_fee_enabled: false,
+            ..DEFAULT_TEST_FEES
+        };
+        assert_eq!(
+            pool_at(1_000_000_000)
+                .optimal_split_threshold(&no_fees, SwapDirection::SellBase)
+                .unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_arb_amount_to_oracle() {
+        let mut pool = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000_000_000u64),
+            quote_target: Decimal::from(100_000_000_000_000u64),
+            base_reserve: Decimal::from(1_000_000_000_000u64),
+            quote_reserve: Decimal::from(100_000_000_000_000u64),
+            multiplier: Multiplier::One,
+        })
+        .unwrap();
+        let mid_price = pool.get_mid_price().unwrap();
+        let opposite = |direction: SwapDirection| match direction {
+            SwapDirection::SellBase => SwapDirection::SellQuote,
+            SwapDirection::SellQuote => SwapDirection::SellBase,
+        };
+
+        // pool priced above the oracle is sold base, below it is sold quote
+        for (oracle_percent, direction) in [
+            (98, SwapDirection::SellBase),
+            (102, SwapDirection::SellQuote),
+        ] {
+            let oracle_price = mid_price
+                .try_mul(Decimal::from_percent(oracle_percent))
+                .unwrap();
+            let amount_in = pool.arb_amount_to_oracle(oracle_price, direction).unwrap();
+            assert!(amount_in > 0);
+
+            // the amount is the first reaching the oracle price
+            let reached = pool
+                .after_swap(amount_in, direction)
+                .unwrap()
+                .get_mid_price()
+                .unwrap();
+            let short = pool
+                .after_swap(amount_in - 1, direction)
+                .unwrap()
+                .get_mid_price()
+                .unwrap();
+            match direction {
+                SwapDirection::SellBase => assert!(reached <= oracle_price && short > oracle_price),
+                SwapDirection::SellQuote => {
+                    assert!(reached >= oracle_price && short < oracle_price)
+                }
+            }
+
+            // trading the other way only moves the price further off
+            assert_eq!(
+                pool.arb_amount_to_oracle(oracle_price, opposite(direction))
+                    .unwrap(),
+                0
+            );
+        }
+
+        // an oracle price the pool can reach is landed on
+        for (reserve, direction) in [
+            (1_000_000_000_000u64, SwapDirection::SellBase),
+            (100_000_000_000_000u64, SwapDirection::SellQuote),
+        ] {
+            let oracle_price = pool
+                .after_swap(reserve, direction)
+                .unwrap()
+                .get_mid_price()
+                .unwrap();
+            assert_ne!(oracle_price, mid_price);
+            let amount_in = pool.arb_amount_to_oracle(oracle_price, direction).unwrap();
+            assert!(amount_in <= reserve);
+            let mut aligned = pool.after_swap(amount_in, direction).unwrap();
+            assert_eq!(aligned.get_mid_price().unwrap(), oracle_price);
+
+            // and an imbalanced pool is brought back to its market price
+            let amount_back = aligned
+                .arb_amount_to_oracle(mid_price, opposite(direction))
+                .unwrap();
+            let mut rebalanced = aligned
+                .after_swap(amount_back, opposite(direction))
+                .unwrap();
+            assert_eq!(rebalanced.get_mid_price().unwrap(), mid_price);
+        }
+    }
+
+    #[test]
+    fn test_buy_shares_one_sided_deposit() {
+        let mut pool_state = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000u64),
+            quote_target: Decimal::from(100_000_000u64),
+            base_reserve: Decimal::from(1_000_000u64),
+            quote_reserve: Decimal::from(100_000_000u64),
+            multiplier: Multiplier::One,
+        })
+        .unwrap();
+
+        // a quote-less deposit into an established pool mints no shares,
+        // which is why process_deposit rejects it with ImbalancedDeposit
+        assert_eq!(
+            pool_state
+                .clone()
+                .buy_shares(1_100_000, 100_000_000, 1_000_000)
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            pool_state.buy_shares(1_000_000, 110_000_000, 1_000_000),
+            Err(SwapError::InsufficientFunds.into())
+        );
+    }
+
+    #[test]
+    fn test_pool_state_diff() {
+        let before = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000u64),
+            quote_target: Decimal::from(100_000u64),
+            base_reserve: Decimal::from(1_000u64),
+            quote_reserve: Decimal::from(100_000u64),
+            multiplier: Multiplier::One,
+        };
+        let after = PoolState {
+            base_target: Decimal::from(1_500u64),
+            quote_target: Decimal::from(100_000u64),
+            base_reserve: Decimal::from(1_200u64),
+            quote_reserve: Decimal::from(99_000u64),
+            multiplier: Multiplier::AboveOne,
+            ..before.clone()
+        };
+
+        let wad = WAD as i128;
+        let diff = before.diff(&after).unwrap();
+        assert_eq!(
+            diff,
+            PoolStateDiff {
+                base_reserve: 200 * wad,
+                quote_reserve: -1_000 * wad,
+                base_target: 500 * wad,
+                quote_target: 0,
+                multiplier_before: Multiplier::One,
+                multiplier_after: Multiplier::AboveOne,
+            }
+        );
+
+        let reverse = after.diff(&before).unwrap();
+        assert_eq!(reverse.base_reserve, -diff.base_reserve);
+        assert_eq!(reverse.quote_reserve, -diff.quote_reserve);
+        assert_eq!(reverse.base_target, -diff.base_target);
+        assert_eq!(reverse.multiplier_before, Multiplier::AboveOne);
+        assert_eq!(reverse.multiplier_after, Multiplier::One);
+
+        let unchanged = before.diff(&before).unwrap();
+        assert_eq!(unchanged.base_reserve, 0);
+        assert_eq!(unchanged.quote_target, 0);
+
+        let huge = PoolState {
+            base_reserve: Decimal::from_scaled_val(u128::MAX),
+            ..before.clone()
+        };
+        assert_eq!(
+            before.diff(&huge),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_set_targets() {
+        let pool_state = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000u64),
+            quote_target: Decimal::from(10_000_000u64),
+            base_reserve: Decimal::from(1_000_000u64),
+            quote_reserve: Decimal::from(10_000_000u64),
+            multiplier: Multiplier::One,
+        };
+        let mid_price = pool_state.clone().get_mid_price().unwrap();
+
+        let mut balanced = pool_state.clone();
+        balanced
+            .set_targets(Decimal::from(1_000_000u64), Decimal::from(10_000_000u64))
+            .unwrap();
+        assert_eq!(balanced, pool_state);
+
+        // base in surplus: base target below reserve
+        let mut below_one = pool_state.clone();
+        below_one
+            .set_targets(Decimal::from(500_000u64), Decimal::from(20_000_000u64))
+            .unwrap();
+        assert_eq!(below_one.multiplier, Multiplier::BelowOne);
+        assert_eq!(below_one.base_target, Decimal::from(500_000u64));
+        assert!(below_one.quote_target > below_one.quote_reserve);
+        assert!(below_one.get_mid_price().unwrap() < mid_price);
+
+        // quote in surplus: base target above reserve
+        let mut above_one = pool_state.clone();
+        above_one
+            .set_targets(Decimal::from(1_100_000u64), Decimal::from(5_000_000u64))
+            .unwrap();
+        assert_eq!(above_one.multiplier, Multiplier::AboveOne);
+        assert_eq!(above_one.quote_target, Decimal::from(5_000_000u64));
+        assert!(above_one.base_target >= above_one.base_reserve);
+
+        for (base_target, quote_target) in [
+            (0u64, 10_000_000u64),
+            (1_000_000, 0),
+            // both sides in deficit
+            (1_100_000, 11_000_000),
+            // both sides in surplus
+            (900_000, 9_000_000),
+        ] {
+            let mut invalid = pool_state.clone();
+            assert_eq!(
+                invalid.set_targets(Decimal::from(base_target), Decimal::from(quote_target)),
+                Err(SwapError::InvalidTargets.into())
+            );
+            assert_eq!(invalid, pool_state);
+        }
+    }
+
+    #[test]
+    fn test_canonical_targets() {
+        let market_price = default_market_price();
+        let slope = default_slope();
+
+        // a balanced pool already sits at its canonical targets
+        assert_eq!(
+            PoolState::canonical_targets(
+                market_price,
+                Decimal::from(1_000_000u64),
+                Decimal::from(100_000_000u64),
+                slope,
+            ),
+            Ok((
+                Decimal::from(1_000_000u64),
+                Decimal::from(100_000_000u64),
+                Multiplier::One
+            ))
+        );
+        assert_eq!(
+            PoolState::canonical_targets(market_price, Decimal::zero(), Decimal::zero(), slope),
+            Ok((Decimal::zero(), Decimal::zero(), Multiplier::One))
+        );
+        assert_eq!(
+            PoolState::canonical_targets(
+                market_price,
+                Decimal::from(1_000_000u64),
+                Decimal::zero(),
+                slope
+            ),
+            Err(SwapError::InvalidPoolState.into())
+        );
+
+        // reserves for which the curve's square root is exact, so the
+        // canonical targets are a fixed point of adjust_target and set_targets
+        for (base_reserve, quote_reserve, base_target, quote_target, multiplier) in [
+            (
+                2_800_000u64,
+                80_000_000u64,
+                1_600_000u64,
+                160_000_000u64,
+                Multiplier::BelowOne,
+            ),
+            (
+                400_000,
+                140_000_000,
+                800_000,
+                80_000_000,
+                Multiplier::AboveOne,
+            ),
+        ] {
+            let pool_state = PoolState {
+                market_price,
+                slope,
+                base_target: Decimal::from(base_target),
+                quote_target: Decimal::from(quote_target),
+                base_reserve: Decimal::from(base_reserve),
+                quote_reserve: Decimal::from(quote_reserve),
+                multiplier,
+            };
+            assert_eq!(
+                PoolState::canonical_targets(
+                    market_price,
+                    pool_state.base_reserve,
+                    pool_state.quote_reserve,
+                    slope
+                ),
+                Ok((pool_state.base_target, pool_state.quote_target, multiplier))
+            );
+            let mut adjusted = pool_state.clone();
+            adjusted.adjust_target().unwrap();
+            assert_eq!(adjusted, pool_state);
+            let mut reset = pool_state.clone();
+            reset
+                .set_targets(pool_state.base_target, pool_state.quote_target)
+                .unwrap();
+            assert_eq!(reset, pool_state);
+        }
+
+        // targets stay at the market price ratio for any imbalance
+        for (base_reserve, quote_reserve, multiplier) in [
+            (1_500_000u64, 80_000_000u64, Multiplier::BelowOne),
+            (600_000, 140_000_000, Multiplier::AboveOne),
+        ] {
+            let (base_reserve, quote_reserve) =
+                (Decimal::from(base_reserve), Decimal::from(quote_reserve));
+            let (base_target, quote_target, canonical_multiplier) =
+                PoolState::canonical_targets(market_price, base_reserve, quote_reserve, slope)
+                    .unwrap();
+            assert_eq!(canonical_multiplier, multiplier);
+            assert_eq!(base_target.try_mul(market_price).unwrap(), quote_target);
+            match multiplier {
+                Multiplier::BelowOne => {
+                    assert!(base_target < base_reserve);
+                    assert!(quote_target > quote_reserve);
+                }
+                _ => {
+                    assert!(base_target > base_reserve);
+                    assert!(quote_target < quote_reserve);
+                }
+            }
+        }
+
+        // without slope the targets split the pool value at the market price
+        let (base_target, quote_target, multiplier) = PoolState::canonical_targets(
+            market_price,
+            Decimal::from(1_500_000u64),
+            Decimal::from(80_000_000u64),
+            Decimal::zero(),
+        )
+        .unwrap();
+        assert_eq!(multiplier, Multiplier::BelowOne);
+        assert_eq!(base_target, Decimal::from(1_150_000u64));
+        assert_eq!(quote_target, Decimal::from(115_000_000u64));
+    }
+
+    #[test]
+    fn test_failure() {
+        assert_eq!(
+            Multiplier::try_from(3u8),
+            Err(ProgramError::InvalidAccountData)
+        );
+
+        let mut pool_state = PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(200_000u64),
             quote_target: Decimal::from(200_000u64),
             base_reserve: Decimal::from(100_000u64),
             quote_reserve: Decimal::from(100_000u64),
@@ -1019,10 +2899,16 @@ mod tests {
         );
 
         pool_state.base_reserve = Decimal::from(0u64);
+        assert_eq!(
+            pool_state.buy_shares(500_000_000u64, 1_000_000_000u64, 1_000_000_000u64),
+            Err(SwapError::DegeneratePool.into())
+        );
+        pool_state.quote_reserve = Decimal::from(0u64);
         assert_eq!(
             pool_state.buy_shares(500_000_000u64, 1_000_000_000u64, 1_000_000_000u64),
             Err(SwapError::IncorrectMint.into())
         );
+        pool_state.quote_reserve = Decimal::from(500_000_000u64);
 
         pool_state.base_reserve = Decimal::from(1_000_000_000u64);
         assert_eq!(
@@ -1034,6 +2920,12 @@ mod tests {
             ),
             Err(SwapError::WithdrawNotEnough.into())
         );
+
+        let total_supply = 1_000_000_000u64;
+        assert_eq!(
+            pool_state.sell_shares(total_supply + 1, 0, 0, total_supply),
+            Err(SwapError::InsufficientLiquidity.into())
+        );
     }
 
     #[test]
@@ -1053,4 +2945,426 @@ mod tests {
         let unpacked = PoolState::unpack_from_slice(&packed).unwrap();
         assert_eq!(pool_state, unpacked);
     }
+
+    #[test]
+    fn test_min_out_with_slippage() {
+        assert_eq!(min_out_with_slippage(1_000_000, 0).unwrap(), 1_000_000);
+        assert_eq!(min_out_with_slippage(1_000_000, 50).unwrap(), 995_000);
+        assert_eq!(min_out_with_slippage(1_000_000, 10_000).unwrap(), 0);
+        assert_eq!(min_out_with_slippage(999, 1).unwrap(), 998);
+        assert_eq!(min_out_with_slippage(u64::MAX, 0).unwrap(), u64::MAX);
+        assert_eq!(
+            min_out_with_slippage(u64::MAX, 5_000).unwrap(),
+            u64::MAX / 2
+        );
+        assert_eq!(
+            min_out_with_slippage(1_000_000, 10_001),
+            Err(SwapError::InvalidInput.into())
+        );
+    }
+
+    #[test]
+    fn test_slope_from_bps() {
+        assert_eq!(slope_from_bps(0).unwrap(), 0);
+        assert_eq!(
+            slope_from_bps(5_000).unwrap() as u128,
+            default_slope().to_scaled_val().unwrap()
+        );
+        assert_eq!(
+            slope_from_bps(10_000).unwrap() as u128,
+            Decimal::one().to_scaled_val().unwrap()
+        );
+        for bps in 0..=10_000u16 {
+            let slope = Decimal::from_scaled_val(slope_from_bps(bps).unwrap().into());
+            assert!(slope <= Decimal::one());
+            assert_eq!(slope.to_bps().unwrap(), bps as u64);
+        }
+        assert_eq!(slope_from_bps(10_001), Err(SwapError::InvalidSlope.into()));
+        assert_eq!(
+            slope_from_bps(u16::MAX),
+            Err(SwapError::InvalidSlope.into())
+        );
+    }
+
+    #[test]
+    fn test_validate_invariants() {
+        let pool = |multiplier,
+                    base_target: u64,
+                    quote_target: u64,
+                    base_reserve: u64,
+                    quote_reserve: u64| PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(base_target),
+            quote_target: Decimal::from(quote_target),
+            base_reserve: Decimal::from(base_reserve),
+            quote_reserve: Decimal::from(quote_reserve),
+            multiplier,
+        };
+        let invalid = Err(SwapError::InvalidPoolState.into());
+
+        // empty pool
+        assert_eq!(
+            pool(Multiplier::One, 0, 0, 0, 0).validate_invariants(),
+            Ok(())
+        );
+        // balanced pool
+        assert_eq!(
+            pool(Multiplier::One, 100, 10_000, 100, 10_000).validate_invariants(),
+            Ok(())
+        );
+        // base sold into the pool
+        assert_eq!(
+            pool(Multiplier::BelowOne, 100, 10_000, 110, 9_000).validate_invariants(),
+            Ok(())
+        );
+        // quote sold into the pool
+        assert_eq!(
+            pool(Multiplier::AboveOne, 100, 10_000, 90, 11_000).validate_invariants(),
+            Ok(())
+        );
+
+        // drained reserves with live targets
+        let degenerate = Err(SwapError::DegeneratePool.into());
+        for multiplier in [Multiplier::One, Multiplier::AboveOne, Multiplier::BelowOne] {
+            assert_eq!(
+                pool(multiplier, 100, 10_000, 0, 10_000).validate_invariants(),
+                degenerate
+            );
+            assert_eq!(
+                pool(multiplier, 100, 10_000, 100, 0).validate_invariants(),
+                degenerate
+            );
+            assert_eq!(
+                pool(multiplier, 100, 0, 0, 0).validate_invariants(),
+                invalid
+            );
+        }
+        // above one with quote reserve under its target
+        assert_eq!(
+            pool(Multiplier::AboveOne, 100, 10_000, 90, 9_000).validate_invariants(),
+            invalid
+        );
+        // below one with base reserve under its target
+        assert_eq!(
+            pool(Multiplier::BelowOne, 100, 10_000, 90, 9_000).validate_invariants(),
+            invalid
+        );
+    }
+
+    #[test]
+    fn test_quantized_mid_price() {
+        let mut pool_state = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000u64),
+            quote_target: Decimal::from(100_000_000u64),
+            base_reserve: Decimal::from(1_100_000u64),
+            quote_reserve: Decimal::from(90_000_000u64),
+            multiplier: Multiplier::BelowOne,
+        })
+        .unwrap();
+        let mid_price = pool_state.clone().get_mid_price().unwrap();
+
+        // zero tick leaves the mid price untouched
+        assert_eq!(
+            pool_state.quantized_mid_price(Decimal::zero()).unwrap(),
+            mid_price
+        );
+
+        for tick in [
+            Decimal::one().try_div(1_000).unwrap(),
+            Decimal::one().try_div(4).unwrap(),
+            Decimal::from(5u64),
+        ] {
+            let quantized = pool_state.quantized_mid_price(tick).unwrap();
+            let ticks = quantized.try_div(tick).unwrap();
+            assert_eq!(
+                Decimal::try_from_u128(ticks.try_floor_u128().unwrap()).unwrap(),
+                ticks
+            );
+            // nearest tick is at most half a tick away
+            let distance = if quantized > mid_price {
+                quantized.try_sub(mid_price).unwrap()
+            } else {
+                mid_price.try_sub(quantized).unwrap()
+            };
+            assert!(distance <= tick.try_div(2).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_preview_withdraw() {
+        let pool_state = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000u64),
+            quote_target: Decimal::from(100_000_000u64),
+            base_reserve: Decimal::from(1_000_000u64),
+            quote_reserve: Decimal::from(100_000_000u64),
+            multiplier: Multiplier::One,
+        })
+        .unwrap();
+        let total_supply = 3_000_000;
+        let share_amount = 1_000_000;
+
+        let preview = pool_state
+            .preview_withdraw(share_amount, total_supply, &DEFAULT_TEST_FEES)
+            .unwrap();
+
+        let mut withdrawn = pool_state.clone();
+        let (base_amount, quote_amount) = withdrawn
+            .sell_shares(share_amount, 0, 0, total_supply)
+            .unwrap();
+        let (base_net, _, _) = DEFAULT_TEST_FEES.apply_withdraw_fees(base_amount).unwrap();
+        let (quote_net, _, _) = DEFAULT_TEST_FEES.apply_withdraw_fees(quote_amount).unwrap();
+        assert_eq!(preview, (base_net, quote_net));
+        assert!(base_net < base_amount && quote_net < quote_amount);
+
+        // the previewed pool is left untouched
+        assert_ne!(withdrawn, pool_state);
+        assert_eq!(
+            pool_state.preview_withdraw(total_supply + 1, total_supply, &DEFAULT_TEST_FEES),
+            Err(SwapError::InsufficientLiquidity.into())
+        );
+    }
+
+    #[test]
+    fn test_preview_redeem() {
+        let pool_state = PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000u64),
+            quote_target: Decimal::from(100_000_000u64),
+            base_reserve: Decimal::from(1_200_000u64),
+            quote_reserve: Decimal::from(80_000_000u64),
+            multiplier: Multiplier::BelowOne,
+        })
+        .unwrap();
+        let total_supply = 4_000_000;
+        let oracle_price = Decimal::from(90u64);
+
+        let full = pool_state
+            .preview_redeem(oracle_price, total_supply, total_supply)
+            .unwrap();
+        assert_eq!(full, (1_200_000, 80_000_000));
+        for share_amount in [1_000_000, 2_000_000] {
+            let (base_amount, quote_amount) = pool_state
+                .preview_redeem(oracle_price, share_amount, total_supply)
+                .unwrap();
+            assert_eq!(base_amount * total_supply / share_amount, full.0);
+            assert_eq!(quote_amount * total_supply / share_amount, full.1);
+        }
+
+        // redemption skips the withdraw fees
+        let (base_amount, quote_amount) = pool_state
+            .preview_redeem(default_market_price(), 1_000_000, total_supply)
+            .unwrap();
+        let preview = pool_state
+            .preview_withdraw(1_000_000, total_supply, &DEFAULT_TEST_FEES)
+            .unwrap();
+        assert!(preview.0 < base_amount && preview.1 < quote_amount);
+    }
+}
+
+/// Exhaustive checks of the multiplier state machine driven by
+/// `sell_base_token` and `sell_quote_token`, with reserves constructed so the
+/// amount bringing a pool back to one is an exact integer.
+#[cfg(test)]
+mod multiplier_transition_tests {
+    use super::*;
+
+    const TARGET_BASE: u64 = 1_000_000_000;
+    const TARGET_QUOTE: u64 = 100_000_000_000;
+    /// Base amount an above one pool needs to be sold to get back to one
+    const BACK_TO_ONE_BASE: u64 = 50_000_000;
+    /// Quote amount a below one pool needs to be sold to get back to one
+    const BACK_TO_ONE_QUOTE: u64 = 5_000_000_000;
+
+    fn one_pool() -> PoolState {
+        PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(TARGET_BASE),
+            quote_target: Decimal::from(TARGET_QUOTE),
+            base_reserve: Decimal::from(TARGET_BASE),
+            quote_reserve: Decimal::from(TARGET_QUOTE),
+            multiplier: Multiplier::One,
+        }
+    }
+
+    /// Pool short `BACK_TO_ONE_BASE` of its base target, holding exactly the
+    /// quote surplus the curve pays for selling it back
+    fn above_one_pool() -> PoolState {
+        let pool = one_pool();
+        let base_reserve = Decimal::from(TARGET_BASE - BACK_TO_ONE_BASE);
+        let quote_surplus = get_target_amount(
+            pool.base_target,
+            pool.base_target,
+            base_reserve,
+            pool.market_price,
+            pool.slope,
+        )
+        .unwrap();
+        PoolState {
+            base_reserve,
+            quote_reserve: pool.quote_target.try_add(quote_surplus).unwrap(),
+            multiplier: Multiplier::AboveOne,
+            ..pool
+        }
+    }
+
+    /// Pool short `BACK_TO_ONE_QUOTE` of its quote target, holding exactly the
+    /// base surplus the curve pays for selling it back
+    fn below_one_pool() -> PoolState {
+        let pool = one_pool();
+        let quote_reserve = Decimal::from(TARGET_QUOTE - BACK_TO_ONE_QUOTE);
+        let base_surplus = get_target_amount(
+            pool.quote_target,
+            pool.quote_target,
+            quote_reserve,
+            pool.market_price.reciprocal().unwrap(),
+            pool.slope,
+        )
+        .unwrap();
+        PoolState {
+            base_reserve: pool.base_target.try_add(base_surplus).unwrap(),
+            quote_reserve,
+            multiplier: Multiplier::BelowOne,
+            ..pool
+        }
+    }
+
+    fn sell(pool: &PoolState, direction: SwapDirection, amount: u64) -> (Decimal, Multiplier) {
+        match direction {
+            SwapDirection::SellBase => pool.sell_base_token_unrounded(amount).unwrap(),
+            SwapDirection::SellQuote => pool.sell_quote_token_unrounded(amount).unwrap(),
+        }
+    }
+
+    /// Expected multiplier after every kind of trade from every starting
+    /// multiplier, on both sides of the back to one crossover
+    #[test]
+    fn test_multiplier_transitions() {
+        use Multiplier::*;
+        use SwapDirection::*;
+
+        let cases = [
+            // a balanced pool always leaves one, away from the side sold
+            (one_pool(), SellBase, 1, BelowOne),
+            (one_pool(), SellBase, BACK_TO_ONE_BASE, BelowOne),
+            (one_pool(), SellQuote, 1, AboveOne),
+            (one_pool(), SellQuote, BACK_TO_ONE_QUOTE, AboveOne),
+            // trading further from the targets never crosses
+            (above_one_pool(), SellQuote, 1, AboveOne),
+            (above_one_pool(), SellQuote, BACK_TO_ONE_QUOTE, AboveOne),
+            (below_one_pool(), SellBase, 1, BelowOne),
+            (below_one_pool(), SellBase, BACK_TO_ONE_BASE, BelowOne),
+            // trading back towards the targets: Greater, Equal and Less
+            (above_one_pool(), SellBase, 1, AboveOne),
+            (above_one_pool(), SellBase, BACK_TO_ONE_BASE - 1, AboveOne),
+            (above_one_pool(), SellBase, BACK_TO_ONE_BASE, One),
+            (above_one_pool(), SellBase, BACK_TO_ONE_BASE + 1, BelowOne),
+            (above_one_pool(), SellBase, 2 * BACK_TO_ONE_BASE, BelowOne),
+            (below_one_pool(), SellQuote, 1, BelowOne),
+            (below_one_pool(), SellQuote, BACK_TO_ONE_QUOTE - 1, BelowOne),
+            (below_one_pool(), SellQuote, BACK_TO_ONE_QUOTE, One),
+            (below_one_pool(), SellQuote, BACK_TO_ONE_QUOTE + 1, AboveOne),
+            (below_one_pool(), SellQuote, 2 * BACK_TO_ONE_QUOTE, AboveOne),
+        ];
+        for (pool, direction, amount, expected) in cases {
+            let (_, multiplier) = sell(&pool, direction, amount);
+            assert_eq!(
+                multiplier, expected,
+                "{:?} selling {} from {:?}",
+                direction, amount, pool.multiplier
+            );
+        }
+    }
+
+    /// The amount received grows by about one marginal price per unit sold
+    /// on both sides of the crossover, and lands exactly on the surplus held
+    /// over the target when the pool returns to one.
+    #[test]
+    fn test_received_continuous_across_crossover() {
+        let above_one = above_one_pool();
+        let below_one = below_one_pool();
+        let cases = [
+            (
+                &above_one,
+                SwapDirection::SellBase,
+                BACK_TO_ONE_BASE,
+                above_one
+                    .quote_reserve
+                    .try_sub(above_one.quote_target)
+                    .unwrap(),
+                above_one.market_price,
+            ),
+            (
+                &below_one,
+                SwapDirection::SellQuote,
+                BACK_TO_ONE_QUOTE,
+                below_one
+                    .base_reserve
+                    .try_sub(below_one.base_target)
+                    .unwrap(),
+                below_one.market_price.reciprocal().unwrap(),
+            ),
+        ];
+
+        for (pool, direction, back_to_one, surplus, marginal_price) in cases {
+            assert_eq!(sell(pool, direction, back_to_one).0, surplus);
+
+            // a step may exceed the marginal price by curve precision and by
+            // the whole unit the one multiplier path rounds its reserve to
+            let max_step = marginal_price
+                .try_mul(Decimal::from_percent(110))
+                .unwrap()
+                .try_add(Decimal::one())
+                .unwrap();
+            let received: Vec<Decimal> = (back_to_one - 3..=back_to_one + 3)
+                .map(|amount| sell(pool, direction, amount).0)
+                .collect();
+            for step in received.windows(2) {
+                assert!(step[1] >= step[0], "{:?} is not monotonic", direction);
+                assert!(
+                    step[1].try_sub(step[0]).unwrap() <= max_step,
+                    "{:?} jumps across the crossover",
+                    direction
+                );
+            }
+
+            // over a wider window the crossover costs no more than rounding
+            let window = 1_000;
+            let expected = marginal_price.try_mul(2 * window).unwrap();
+            let actual = sell(pool, direction, back_to_one + window)
+                .0
+                .try_sub(sell(pool, direction, back_to_one - window).0)
+                .unwrap();
+            assert!(actual <= expected.try_mul(Decimal::from_percent(101)).unwrap());
+            assert!(
+                actual.try_add(Decimal::one()).unwrap()
+                    >= expected.try_mul(Decimal::from_percent(99)).unwrap()
+            );
+        }
+    }
+
+    /// Rounded amounts never decrease as more is sold through the crossover
+    #[test]
+    fn test_rounded_received_monotonic_across_crossover() {
+        let above_one = above_one_pool();
+        let below_one = below_one_pool();
+        let mut previous = 0;
+        for amount in BACK_TO_ONE_BASE - 100..=BACK_TO_ONE_BASE + 100 {
+            let (received, _) = above_one.sell_base_token(amount).unwrap();
+            assert!(received >= previous);
+            previous = received;
+        }
+        let mut previous = 0;
+        for amount in BACK_TO_ONE_QUOTE - 1_000..=BACK_TO_ONE_QUOTE + 1_000 {
+            let (received, _) = below_one.sell_quote_token(amount).unwrap();
+            assert!(received >= previous);
+            previous = received;
+        }
+    }
 }
@@ -0,0 +1,105 @@
+//! Diagnostics of failed swaps, returned to clients alongside the error
+
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    program::set_return_data,
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+};
+
+use crate::error::SwapError;
+
+/// Guard a swap failed on, written as return data before the error is
+/// returned so clients can tell which bound was crossed and by how much
+///
+/// Amounts are in token units, prices are scaled `Decimal` values.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SwapDiagnostic {
+    /// Code of the returned `SwapError`
+    pub error_code: u32,
+    /// Bound the guard enforced
+    pub expected: u128,
+    /// Value the swap reached
+    pub actual: u128,
+}
+
+impl SwapDiagnostic {
+    /// Diagnostic of a failed guard
+    ///
+    /// # Arguments
+    ///
+    /// * error - error the guard returns.
+    /// * expected - bound the guard enforced.
+    /// * actual - value the swap reached.
+    pub fn new(error: SwapError, expected: u128, actual: u128) -> Self {
+        Self {
+            error_code: error as u32,
+            expected,
+            actual,
+        }
+    }
+
+    /// Write the diagnostic as return data
+    ///
+    /// # Return value
+    ///
+    /// error of the failed guard.
+    pub fn fail(&self) -> ProgramError {
+        let mut data = [0u8; SWAP_DIAGNOSTIC_SIZE];
+        self.pack_into_slice(&mut data);
+        set_return_data(&data);
+        ProgramError::Custom(self.error_code)
+    }
+}
+
+impl Sealed for SwapDiagnostic {}
+const SWAP_DIAGNOSTIC_SIZE: usize = 36; // 4 + 16 + 16
+impl Pack for SwapDiagnostic {
+    const LEN: usize = SWAP_DIAGNOSTIC_SIZE;
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, SWAP_DIAGNOSTIC_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (error_code, expected, actual) = array_refs![input, 4, 16, 16];
+        Ok(Self {
+            error_code: u32::from_le_bytes(*error_code),
+            expected: u128::from_le_bytes(*expected),
+            actual: u128::from_le_bytes(*actual),
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, SWAP_DIAGNOSTIC_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (error_code, expected, actual) = mut_array_refs![output, 4, 16, 16];
+        *error_code = self.error_code.to_le_bytes();
+        *expected = self.expected.to_le_bytes();
+        *actual = self.actual.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_diagnostic_packing() {
+        let diagnostic = SwapDiagnostic::new(SwapError::ExceededSlippage, 1_000, 999);
+        let mut packed = [0u8; SwapDiagnostic::LEN];
+        diagnostic.pack_into_slice(&mut packed);
+
+        let mut expect = (SwapError::ExceededSlippage as u32).to_le_bytes().to_vec();
+        expect.extend_from_slice(&1_000u128.to_le_bytes());
+        expect.extend_from_slice(&999u128.to_le_bytes());
+        assert_eq!(packed.to_vec(), expect);
+        assert_eq!(
+            SwapDiagnostic::unpack_from_slice(&packed).unwrap(),
+            diagnostic
+        );
+
+        assert_eq!(
+            diagnostic.fail(),
+            ProgramError::from(SwapError::ExceededSlippage)
+        );
+    }
+}
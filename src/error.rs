@@ -151,6 +151,97 @@ pub enum SwapError {
     /// Invalid slope, slope must be in range [0.0,1.0]
     #[error("Invalid slope")]
     InvalidSlope,
+    /// The pool does not hold the output amount the curve computed
+    #[error("Pool insolvent")]
+    PoolInsolvent,
+    /// Pool targets are inconsistent with the reserves
+    #[error("Invalid targets")]
+    InvalidTargets,
+    /// No emergency drain has been proposed
+    #[error("Drain not proposed")]
+    DrainNotProposed,
+    /// Emergency drain timelock has not expired yet
+    #[error("Drain timelock active")]
+    DrainTimelockActive,
+
+    // 45
+    /// Deposit must include both tokens
+    #[error("Imbalanced deposit")]
+    ImbalancedDeposit,
+    /// Pool reserves are inconsistent with targets or multiplier
+    #[error("Pool state is inconsistent")]
+    InvalidPoolState,
+    /// Claimable rewards are below the configured minimum
+    #[error("Claim amount below minimum")]
+    ClaimBelowMinimum,
+    /// Swap is bound to a different config
+    #[error("Invalid config")]
+    InvalidConfig,
+    /// Fee is above the maximum allowed by policy
+    #[error("Fee exceeds policy")]
+    FeeExceedsPolicy,
+
+    // 50
+    /// Market price is below the minimum the curve supports
+    #[error("Market price below minimum")]
+    MarketPriceTooLow,
+    /// Initial deposit ratio deviates too far from the oracle price
+    #[error("Initial deposit ratio deviates too far from the oracle price")]
+    PriceDeviationTooLarge,
+    /// The instruction was given an unexpected number of accounts
+    #[error("Unexpected number of accounts")]
+    InvalidAccountCount,
+    /// The swap pool still holds reserves or is not fully paused
+    #[error("Swap pool is not drained and fully paused")]
+    PoolNotClosed,
+    /// The oracle price moved more than allowed since the last pool update
+    #[error("Oracle price moved too far since the last pool update")]
+    OraclePriceMoveTooLarge,
+
+    // 55
+    /// The liquidity provider already has a position in the pool
+    #[error("Liquidity position already exists for this pool")]
+    PositionAlreadyExists,
+    /// Minting the rewards would overflow the reward mint supply
+    #[error("Reward mint supply exceeded")]
+    RewardSupplyExceeded,
+    /// Trade size exceeds the pool's maximum trade amount
+    #[error("Trade exceeds the pool's maximum trade amount")]
+    TradeTooLarge,
+    /// Initial pool token destination already holds tokens
+    #[error("Pool token destination is not empty")]
+    DestinationNotEmpty,
+    /// Swap submitted before the user's swap cooldown elapsed
+    #[error("Swap cooldown active")]
+    CooldownActive,
+
+    // 60
+    /// Pool holds liquidity with only one of its reserves non-empty
+    #[error("Pool has a single non-empty reserve")]
+    DegeneratePool,
+    /// Minimum amount out implies more slippage than the pool allows
+    #[error("Minimum amount out exceeds the pool's slippage cap")]
+    SlippageCapExceeded,
+    /// Market price is above the maximum the curve supports
+    #[error("Market price above maximum")]
+    MarketPriceTooHigh,
+    /// Manual price update on a pool that still prices from its oracle
+    #[error("Pool is not in oracle-free mode")]
+    NotOracleFree,
+    /// Token mint carries an extension the swap cannot honor
+    #[error("Unsupported token mint extension")]
+    UnsupportedMintExtension,
+
+    // 65
+    /// Multiplication truncated more than the allowed precision
+    #[error("Precision loss exceeded")]
+    PrecisionLossExceeded,
+    /// The instruction ran after its deadline
+    #[error("Deadline exceeded")]
+    DeadlineExceeded,
+    /// Drain accounts differ from the proposed drain.
+    #[error("Drain accounts differ from the proposed drain")]
+    DrainTargetMismatch,
 }
 impl From<SwapError> for ProgramError {
     fn from(e: SwapError) -> Self {
@@ -244,6 +335,61 @@ impl PrintProgramError for SwapError {
             SwapError::InvalidSlope => {
                 msg!("Error: Invalid slope. Slope must be in range [0.0,1.0]")
             }
+            SwapError::PoolInsolvent => msg!("Error: Pool reserve cannot cover the swap output"),
+            SwapError::InvalidTargets => {
+                msg!("Error: Pool targets are inconsistent with the reserves")
+            }
+            SwapError::DrainNotProposed => msg!("Error: No emergency drain has been proposed"),
+            SwapError::DrainTimelockActive => {
+                msg!("Error: Emergency drain timelock has not expired yet")
+            }
+            SwapError::ImbalancedDeposit => msg!("Error: Deposit must include both tokens"),
+            SwapError::InvalidPoolState => {
+                msg!("Error: Pool reserves are inconsistent with targets or multiplier")
+            }
+            SwapError::ClaimBelowMinimum => {
+                msg!("Error: Claimable rewards are below the configured minimum")
+            }
+            SwapError::InvalidConfig => msg!("Error: Swap does not belong to this config"),
+            SwapError::FeeExceedsPolicy => {
+                msg!("Error: Fee is above the maximum allowed by policy")
+            }
+            SwapError::MarketPriceTooLow => msg!("Error: Market price below minimum"),
+            SwapError::PriceDeviationTooLarge => {
+                msg!("Error: Initial deposit ratio deviates too far from the oracle price")
+            }
+            SwapError::InvalidAccountCount => msg!("Error: Unexpected number of accounts"),
+            SwapError::PoolNotClosed => msg!("Error: Swap pool is not drained and fully paused"),
+            SwapError::OraclePriceMoveTooLarge => {
+                msg!("Error: Oracle price moved too far since the last pool update")
+            }
+            SwapError::PositionAlreadyExists => {
+                msg!("Error: Liquidity position already exists for this pool")
+            }
+            SwapError::RewardSupplyExceeded => msg!("Error: Reward mint supply exceeded"),
+            SwapError::TradeTooLarge => {
+                msg!("Error: Trade exceeds the pool's maximum trade amount")
+            }
+            SwapError::DestinationNotEmpty => msg!("Error: Pool token destination is not empty"),
+            SwapError::CooldownActive => msg!("Error: Swap cooldown active"),
+            SwapError::DegeneratePool => {
+                msg!("Error: Pool has a single non-empty reserve, sync its reserves to recover")
+            }
+            SwapError::SlippageCapExceeded => {
+                msg!("Error: Minimum amount out exceeds the pool's slippage cap")
+            }
+            SwapError::MarketPriceTooHigh => msg!("Error: Market price above maximum"),
+            SwapError::NotOracleFree => msg!("Error: Pool is not in oracle-free mode"),
+            SwapError::UnsupportedMintExtension => {
+                msg!("Error: Token mint has a transfer fee or transfer hook")
+            }
+            SwapError::PrecisionLossExceeded => {
+                msg!("Error: Multiplication truncated more than the allowed precision")
+            }
+            SwapError::DeadlineExceeded => msg!("Error: Deadline exceeded"),
+            SwapError::DrainTargetMismatch => {
+                msg!("Error: Drain accounts differ from the proposed drain")
+            }
         }
     }
 }
@@ -0,0 +1,265 @@
+//! Pool health scoring for integrators monitoring LP tokens used as collateral
+
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::{Pack, Sealed},
+};
+
+use crate::{
+    curve::PoolState,
+    math::{Decimal, TryMul, TrySub},
+    state::{pack_bool, unpack_bool, PauseFlags},
+};
+
+/// Largest deviation of the pool mid price from the oracle price, in basis
+/// points of the oracle price, a healthy pool keeps
+pub const MAX_HEALTHY_DEVIATION_BPS: u64 = 100;
+/// Largest distance of a reserve from its target, in basis points of the
+/// target, a balanced pool keeps
+pub const MAX_HEALTHY_IMBALANCE_BPS: u64 = 2_000;
+/// Score of a pool passing every health check
+pub const MAX_HEALTH_SCORE: u8 = 100;
+/// Score each passed health check adds
+const CHECK_SCORE: u8 = MAX_HEALTH_SCORE / 4;
+
+/// Health checks of a pool, returned by `GetPoolHealth` after its score
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PoolHealth {
+    /// Oracle prices are fresh and confident enough to be used
+    pub oracle_fresh: bool,
+    /// Pool mid price is within `MAX_HEALTHY_DEVIATION_BPS` of the oracle
+    /// price, never set without a fresh oracle price
+    pub deviation_within_bounds: bool,
+    /// No operation of the pool is paused
+    pub not_paused: bool,
+    /// Both reserves are within `MAX_HEALTHY_IMBALANCE_BPS` of their targets
+    pub reserves_balanced: bool,
+}
+
+impl PoolHealth {
+    /// Run the health checks of a pool
+    ///
+    /// # Arguments
+    ///
+    /// * pool_state - pool state as stored on chain.
+    /// * oracle_price - oracle price, none if it is stale or unusable.
+    /// * pause_flags - paused operations of the pool.
+    ///
+    /// # Return value
+    ///
+    /// health checks of the pool.
+    pub fn new(
+        pool_state: &PoolState,
+        oracle_price: Option<Decimal>,
+        pause_flags: PauseFlags,
+    ) -> Result<Self, ProgramError> {
+        let deviation_within_bounds = match oracle_price {
+            Some(oracle_price) => within_bps(
+                pool_state.clone().get_mid_price()?,
+                oracle_price,
+                MAX_HEALTHY_DEVIATION_BPS,
+            )?,
+            None => false,
+        };
+
+        Ok(Self {
+            oracle_fresh: oracle_price.is_some(),
+            deviation_within_bounds,
+            not_paused: pause_flags == PauseFlags::NONE,
+            reserves_balanced: within_bps(
+                pool_state.base_reserve,
+                pool_state.base_target,
+                MAX_HEALTHY_IMBALANCE_BPS,
+            )? && within_bps(
+                pool_state.quote_reserve,
+                pool_state.quote_target,
+                MAX_HEALTHY_IMBALANCE_BPS,
+            )?,
+        })
+    }
+
+    /// Composite score, `MAX_HEALTH_SCORE` split evenly between the checks
+    pub fn score(&self) -> u8 {
+        [
+            self.oracle_fresh,
+            self.deviation_within_bounds,
+            self.not_paused,
+            self.reserves_balanced,
+        ]
+        .iter()
+        .filter(|passed| **passed)
+        .count() as u8
+            * CHECK_SCORE
+    }
+}
+
+/// Check a value is within `bps` basis points of a reference
+fn within_bps(value: Decimal, reference: Decimal, bps: u64) -> Result<bool, ProgramError> {
+    let deviation = if value > reference {
+        value.try_sub(reference)?
+    } else {
+        reference.try_sub(value)?
+    };
+    Ok(deviation <= reference.try_mul(Decimal::from_bps(bps))?)
+}
+
+impl Sealed for PoolHealth {}
+const POOL_HEALTH_SIZE: usize = 5; // 1 + 1 + 1 + 1 + 1
+impl Pack for PoolHealth {
+    const LEN: usize = POOL_HEALTH_SIZE;
+
+    /// The leading score is derived from the checks and not read back
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, POOL_HEALTH_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (_score, oracle_fresh, deviation_within_bounds, not_paused, reserves_balanced) =
+            array_refs![input, 1, 1, 1, 1, 1];
+        Ok(Self {
+            oracle_fresh: unpack_bool(oracle_fresh)?,
+            deviation_within_bounds: unpack_bool(deviation_within_bounds)?,
+            not_paused: unpack_bool(not_paused)?,
+            reserves_balanced: unpack_bool(reserves_balanced)?,
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, POOL_HEALTH_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (score, oracle_fresh, deviation_within_bounds, not_paused, reserves_balanced) =
+            mut_array_refs![output, 1, 1, 1, 1, 1];
+        *score = self.score().to_le_bytes();
+        pack_bool(self.oracle_fresh, oracle_fresh);
+        pack_bool(self.deviation_within_bounds, deviation_within_bounds);
+        pack_bool(self.not_paused, not_paused);
+        pack_bool(self.reserves_balanced, reserves_balanced);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::curve::{default_market_price, default_slope, Multiplier};
+
+    fn balanced_pool() -> PoolState {
+        PoolState::new(PoolState {
+            market_price: default_market_price(),
+            slope: default_slope(),
+            base_target: Decimal::from(1_000_000u64),
+            quote_target: Decimal::from(100_000_000u64),
+            base_reserve: Decimal::from(1_000_000u64),
+            quote_reserve: Decimal::from(100_000_000u64),
+            multiplier: Multiplier::One,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_healthy_pool() {
+        let health = PoolHealth::new(
+            &balanced_pool(),
+            Some(default_market_price()),
+            PauseFlags::NONE,
+        )
+        .unwrap();
+        assert_eq!(
+            health,
+            PoolHealth {
+                oracle_fresh: true,
+                deviation_within_bounds: true,
+                not_paused: true,
+                reserves_balanced: true,
+            }
+        );
+        assert_eq!(health.score(), MAX_HEALTH_SCORE);
+    }
+
+    #[test]
+    fn test_degraded_pool() {
+        let healthy = PoolHealth::new(
+            &balanced_pool(),
+            Some(default_market_price()),
+            PauseFlags::NONE,
+        )
+        .unwrap();
+
+        // oracle price 2% away from the pool
+        let oracle_price = default_market_price()
+            .try_mul(Decimal::from_percent(102))
+            .unwrap();
+        let divergent =
+            PoolHealth::new(&balanced_pool(), Some(oracle_price), PauseFlags::NONE).unwrap();
+        assert_eq!(
+            divergent,
+            PoolHealth {
+                deviation_within_bounds: false,
+                ..healthy
+            }
+        );
+        assert_eq!(divergent.score(), MAX_HEALTH_SCORE - CHECK_SCORE);
+
+        let paused = PoolHealth::new(
+            &balanced_pool(),
+            Some(default_market_price()),
+            PauseFlags::SWAPS,
+        )
+        .unwrap();
+        assert_eq!(
+            paused,
+            PoolHealth {
+                not_paused: false,
+                ..healthy
+            }
+        );
+        assert_eq!(paused.score(), MAX_HEALTH_SCORE - CHECK_SCORE);
+
+        // base reserve 30% over its target, quoted at the moved mid price
+        let imbalanced_pool = PoolState {
+            base_reserve: Decimal::from(1_300_000u64),
+            quote_reserve: Decimal::from(95_000_000u64),
+            multiplier: Multiplier::BelowOne,
+            ..balanced_pool()
+        };
+        let mid_price = imbalanced_pool.clone().get_mid_price().unwrap();
+        let imbalanced =
+            PoolHealth::new(&imbalanced_pool, Some(mid_price), PauseFlags::NONE).unwrap();
+        assert_eq!(
+            imbalanced,
+            PoolHealth {
+                reserves_balanced: false,
+                ..healthy
+            }
+        );
+        assert_eq!(imbalanced.score(), MAX_HEALTH_SCORE - CHECK_SCORE);
+
+        // without an oracle price the deviation cannot be checked either
+        let stale = PoolHealth::new(&balanced_pool(), None, PauseFlags::NONE).unwrap();
+        assert_eq!(
+            stale,
+            PoolHealth {
+                oracle_fresh: false,
+                deviation_within_bounds: false,
+                ..healthy
+            }
+        );
+        assert_eq!(stale.score(), MAX_HEALTH_SCORE - 2 * CHECK_SCORE);
+
+        let unhealthy = PoolHealth::new(&imbalanced_pool, None, PauseFlags::ALL).unwrap();
+        assert_eq!(unhealthy, PoolHealth::default());
+        assert_eq!(unhealthy.score(), 0);
+    }
+
+    #[test]
+    fn test_pool_health_packing() {
+        let health = PoolHealth {
+            oracle_fresh: true,
+            deviation_within_bounds: false,
+            not_paused: true,
+            reserves_balanced: true,
+        };
+        let mut packed = [0u8; PoolHealth::LEN];
+        health.pack_into_slice(&mut packed);
+        assert_eq!(packed, [75, 1, 0, 1, 1]);
+        assert_eq!(PoolHealth::unpack_from_slice(&packed).unwrap(), health);
+    }
+}
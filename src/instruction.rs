@@ -9,12 +9,13 @@ use solana_program::{
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::{Pubkey, PUBKEY_BYTES},
+    system_program,
     sysvar::{clock, rent},
 };
 
 use crate::{
     error::SwapError,
-    state::{Fees, Rewards},
+    state::{swap_cooldown_address, Fees, PauseFlags, Rewards},
 };
 
 /// Instruction Type
@@ -31,8 +32,8 @@ impl InstructionType {
     pub fn check(input: &[u8]) -> Option<Self> {
         let (&tag, _rest) = input.split_first()?;
         match tag {
-            100..=106 => Some(Self::Admin),
-            0..=7 => Some(Self::Swap),
+            100..=131 => Some(Self::Admin),
+            0..=34 => Some(Self::Swap),
             _ => None,
         }
     }
@@ -41,16 +42,25 @@ impl InstructionType {
 /// SWAP INSTRUNCTION DATA
 /// Initialize instruction data
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct InitializeData {
     /// Nonce used to create valid program address
     pub nonce: u8,
-    /// Slope variable - real value * 10**18, 0 <= slope <= 1
+    /// Slope variable - real value * 10**9, 0 <= slope <= 1, or basis points
+    /// of at most 10_000 when `slope_in_bps` is set
     pub slope: u64,
     /// mid price
     pub mid_price: u128,
     /// flag to know about twap open
     pub is_open_twap: bool,
+    /// Share of the trade fee paid to the pool creator, in basis points
+    pub creator_fee_bps: u16,
+    /// Invert oracle A price, for feeds quoted as USD/TOKEN
+    pub invert_a: bool,
+    /// Invert oracle B price, for feeds quoted as USD/TOKEN
+    pub invert_b: bool,
+    /// Read `slope` as basis points instead of a scaled value
+    pub slope_in_bps: bool,
 }
 
 /// Swap direction
@@ -63,6 +73,20 @@ pub enum SwapDirection {
     SellQuote,
 }
 
+/// Source of the market price a swap trades against
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PriceSource {
+    /// price set by the admin on an oracle-free pool
+    Manual,
+    /// pyth oracle price
+    Pyth,
+    /// time weighted average of the pool mid price, used when pyth is stale
+    Twap,
+    /// current pool mid price
+    PoolMid,
+}
+
 /// Swap instruction data
 #[repr(C)]
 #[derive(Clone, Debug, PartialEq)]
@@ -73,6 +97,28 @@ pub struct SwapData {
     pub minimum_amount_out: u64,
     /// Swap direction 0 -> Sell Base Token, 1 -> Sell Quote Token
     pub swap_direction: SwapDirection,
+    /// Unix timestamp after which the instruction fails, none for no deadline
+    pub deadline: Option<i64>,
+}
+
+/// Quote instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuoteData {
+    /// SOURCE amount to price
+    pub amount_in: u64,
+    /// Swap direction 0 -> Sell Base Token, 1 -> Sell Quote Token
+    pub swap_direction: SwapDirection,
+}
+
+/// Fee change preview instruction data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreviewFeeChangeData {
+    /// Output of the sample trade before fees, in quote tokens
+    pub sample_amount: u64,
+    /// Proposed fees
+    pub new_fees: Fees,
 }
 
 /// Deposit instruction data
@@ -85,6 +131,21 @@ pub struct DepositData {
     pub token_b_amount: u64,
     /// Minimum LP tokens to mint, prevents excessive slippage
     pub min_mint_amount: u64,
+    /// Unix timestamp after which the instruction fails, none for no deadline
+    pub deadline: Option<i64>,
+}
+
+/// Realized volatility instruction data, an earlier snapshot of the pool's
+/// price accumulators
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RealizedVolData {
+    /// Scaled `base_price_cumulative_last` at the snapshot
+    pub base_price_cumulative: u128,
+    /// Scaled `base_price_squared_cumulative_last` at the snapshot
+    pub base_price_squared_cumulative: u128,
+    /// `block_timestamp_last` at the snapshot
+    pub block_timestamp: u64,
 }
 
 /// Withdraw instruction data
@@ -98,6 +159,8 @@ pub struct WithdrawData {
     pub minimum_token_a_amount: u64,
     /// Minimum amount of token B to receive, prevents excessive slippage
     pub minimum_token_b_amount: u64,
+    /// Unix timestamp after which the instruction fails, none for no deadline
+    pub deadline: Option<i64>,
 }
 
 /// Withdraw instruction data
@@ -130,9 +193,37 @@ pub struct CommitNewAdmin {
     pub new_admin_key: Pubkey,
 }
 
+/// Set pool targets data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetTargetsData {
+    /// Base token target, scaled
+    pub base_target: u128,
+    /// Quote token target, scaled
+    pub quote_target: u128,
+}
+
+/// Seed liquidity position data
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SeedPositionData {
+    /// Amount of liquidity owned by the position
+    pub liquidity_amount: u64,
+    /// Rewards amount owed
+    pub rewards_owed: u64,
+    /// Rewards amount estimated in the current claim period
+    pub rewards_estimated: u64,
+    /// Cumulative interest
+    pub cumulative_interest: u128,
+    /// Last updated timestamp
+    pub last_update_ts: i64,
+    /// Next claim timestamp
+    pub next_claim_ts: i64,
+}
+
 /// Admin only instructions.
 #[repr(C)]
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum AdminInstruction {
     /// Admin initialization instruction
     Initialize(AdminInitializeData),
@@ -148,6 +239,190 @@ pub enum AdminInstruction {
     SetNewFees(Fees),
     /// TODO: Docs
     SetNewRewards(Rewards),
+    /// Set which operations are paused on a swap pool
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetPauseFlags(PauseFlags),
+    /// Overwrite the regression targets of a swap pool, zero targets reset
+    /// them to the pool's canonical targets
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetTargets(SetTargetsData),
+    /// Propose an emergency drain of a swap pool into the given treasury
+    /// accounts, executable once the drain timelock expires
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[]` token_a treasury Account to drain INTO
+    ///   4. `[]` token_b treasury Account to drain INTO
+    ///   5. `[]` Clock sysvar
+    ProposeDrain,
+    /// Execute a proposed emergency drain, moving all reserves of the proposed
+    /// swap pool to the proposed treasury and pausing the pool
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[]` $authority
+    ///   3. `[signer]` Admin
+    ///   4. `[writable]` token_a Base Account to drain FROM
+    ///   5. `[writable]` token_b Quote Account to drain FROM
+    ///   6. `[writable]` token_a treasury Account to drain INTO
+    ///   7. `[writable]` token_b treasury Account to drain INTO
+    ///   8. `[]` Token program id
+    ///   9. `[]` Clock sysvar
+    ExecuteDrain,
+    /// Set the minimum rewards owed for a liquidity reward claim
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[signer]` Admin
+    SetMinClaimAmount(u64),
+    /// Set the share of admin fees sent to the primary admin fee account, in basis points
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[signer]` Admin
+    SetAdminSplit(u16),
+    /// Set the pool token supply a partial withdraw cannot drop below
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[signer]` Admin
+    SetMinPoolSupply(u64),
+    /// Set the liquidity a position in a swap pool needs to accrue rewards
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetMinRewardLiquidity(u64),
+    /// Replace the DELTAFI mint rewards are minted from
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[]` $market_authority
+    ///   2. `[]` New deltafi mint
+    ///   3. `[signer]` Admin
+    ///   4. `[]` Token program id
+    SetRewardMint(Pubkey),
+    /// Close a drained and fully paused swap pool, closing its empty reserve
+    /// accounts and reclaiming the rent of all three accounts
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[]` $authority
+    ///   3. `[signer]` Admin
+    ///   4. `[]` Pool mint
+    ///   5. `[writable]` token_a Base Account to close
+    ///   6. `[writable]` token_b Quote Account to close
+    ///   7. `[writable]` Destination of the reclaimed lamports
+    ///   8. `[]` Token program id
+    CloseSwap,
+    /// Set the largest oracle move, in basis points, a swap pool accepts
+    /// within `ORACLE_MOVE_WINDOW` of its last update
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetMaxOracleMove(u16),
+    /// Write a fully specified liquidity position migrated from a legacy
+    /// program into a liquidity provider, bypassing the deposit flow
+    ///
+    ///   0. `[]` Config
+    ///   1. `[]` Token-swap the position is in
+    ///   2. `[writable]` Liquidity provider
+    ///   3. `[signer]` Admin
+    SeedPosition(SeedPositionData),
+    /// Set the price step, as a scaled `Decimal`, quoted mid prices of a swap
+    /// pool snap to, 0 disables quantization
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetPriceTick(u128),
+    /// Move the next claim timestamp of positions in a swap pool onto the
+    /// current claim schedule, one `MIN_CLAIM_PERIOD` after their last update
+    ///
+    ///   0. `[]` Config
+    ///   1. `[]` Token-swap the positions are in
+    ///   2. `[signer]` Admin
+    ///   3. ..3+N `[writable]` N liquidity provider accounts
+    ResyncClaimSchedule,
+    /// Set the largest amount a single swap in a swap pool may sell, 0
+    /// removes the cap
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetMaxTradeAmount(u64),
+    /// Require liquidity reward claims to mint to a token account owned by
+    /// the liquidity provider owner
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[signer]` Admin
+    SetStrictClaimDestination(bool),
+    /// Set the minimum seconds between two swaps of the same user in a swap
+    /// pool, 0 disables the cooldown
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetSwapCooldown(u64),
+    /// Set the pool reserves to the balances of its token accounts and
+    /// retarget them at the market price, recovering a pool left with a
+    /// single non-empty reserve once its empty token account is topped up
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    ///   3. `[]` Token-swap token a account
+    ///   4. `[]` Token-swap token b account
+    ///   5. `[]` Token program id
+    SyncReserves,
+    /// Set the largest slippage in bps a swap's minimum amount out may
+    /// accept against the mid price estimate, 0 disables the cap
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetMaxAllowedSlippage(u64),
+    /// Switch a swap pool between oracle pricing and the manually set market
+    /// price
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetOracleFree(bool),
+    /// Set the market price of a swap pool in oracle-free mode
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    SetManualPrice(u128),
+    /// Accept token accounts of a token program other than spl_token, such
+    /// as Token-2022, default pubkey to accept spl_token only
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[signer]` Admin
+    SetAllowedTokenProgram(Pubkey),
+    /// Set the divergence between a Pyth spot price and its EMA above which
+    /// the price is rejected, in basis points, zero to disable
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[signer]` Admin
+    SetMaxEmaDivergence(u16),
+    /// Set new fees on every listed swap pool of a config
+    ///
+    ///   0. `[]` Config
+    ///   1. `[signer]` Admin
+    ///   2. ..2+N `[writable]` N token-swaps
+    BatchSetFees(Fees),
+    /// Add to the reward tokens swaps on a swap pool may still mint
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[signer]` Admin
+    TopUpRewardBudget(u64),
 }
 
 impl AdminInstruction {
@@ -156,31 +431,148 @@ impl AdminInstruction {
         let (&tag, rest) = input
             .split_first()
             .ok_or(SwapError::InstructionUnpackError)?;
-        Ok(match tag {
+        let (instruction, rest) = match tag {
             100 => {
-                let (fees, rest) = rest.split_at(Fees::LEN);
-                let fees = Fees::unpack_unchecked(fees)?;
-                let (rewards, _rest) = rest.split_at(Rewards::LEN);
-                let rewards = Rewards::unpack_unchecked(rewards)?;
-                Self::Initialize(AdminInitializeData { fees, rewards })
-            }
-            101 => Self::Pause,
-            102 => Self::Unpause,
-            103 => Self::SetFeeAccount,
+                let (fees, rest) = unpack_packed::<Fees>(rest)?;
+                let (rewards, rest) = unpack_packed::<Rewards>(rest)?;
+                (
+                    Self::Initialize(AdminInitializeData { fees, rewards }),
+                    rest,
+                )
+            }
+            101 => (Self::Pause, rest),
+            102 => (Self::Unpause, rest),
+            103 => (Self::SetFeeAccount, rest),
             104 => {
-                let (new_admin_key, _) = unpack_pubkey(rest)?;
-                Self::CommitNewAdmin(CommitNewAdmin { new_admin_key })
+                let (new_admin_key, rest) = unpack_pubkey(rest)?;
+                (Self::CommitNewAdmin(CommitNewAdmin { new_admin_key }), rest)
             }
             105 => {
-                let fees = Fees::unpack_unchecked(rest)?;
-                Self::SetNewFees(fees)
+                let (fees, rest) = unpack_packed::<Fees>(rest)?;
+                (Self::SetNewFees(fees), rest)
             }
             106 => {
-                let rewards = Rewards::unpack_unchecked(rest)?;
-                Self::SetNewRewards(rewards)
+                let (rewards, rest) = unpack_packed::<Rewards>(rest)?;
+                (Self::SetNewRewards(rewards), rest)
+            }
+            107 => {
+                let (bits, rest) = unpack_u8(rest)?;
+                let pause_flags =
+                    PauseFlags::from_bits(bits).ok_or(SwapError::InstructionUnpackError)?;
+                (Self::SetPauseFlags(pause_flags), rest)
+            }
+            108 => {
+                let (base_target, rest) = unpack_u128(rest)?;
+                let (quote_target, rest) = unpack_u128(rest)?;
+                (
+                    Self::SetTargets(SetTargetsData {
+                        base_target,
+                        quote_target,
+                    }),
+                    rest,
+                )
+            }
+            109 => (Self::ProposeDrain, rest),
+            110 => (Self::ExecuteDrain, rest),
+            111 => {
+                let (min_claim_amount, rest) = unpack_u64(rest)?;
+                (Self::SetMinClaimAmount(min_claim_amount), rest)
+            }
+            112 => {
+                let (admin_split_bps, rest) = unpack_u16(rest)?;
+                (Self::SetAdminSplit(admin_split_bps), rest)
+            }
+            113 => {
+                let (min_pool_supply, rest) = unpack_u64(rest)?;
+                (Self::SetMinPoolSupply(min_pool_supply), rest)
+            }
+            114 => {
+                let (min_reward_liquidity, rest) = unpack_u64(rest)?;
+                (Self::SetMinRewardLiquidity(min_reward_liquidity), rest)
+            }
+            115 => {
+                let (deltafi_mint, rest) = unpack_pubkey(rest)?;
+                (Self::SetRewardMint(deltafi_mint), rest)
+            }
+            116 => (Self::CloseSwap, rest),
+            117 => {
+                let (max_oracle_move_bps, rest) = unpack_u16(rest)?;
+                (Self::SetMaxOracleMove(max_oracle_move_bps), rest)
+            }
+            118 => {
+                let (liquidity_amount, rest) = unpack_u64(rest)?;
+                let (rewards_owed, rest) = unpack_u64(rest)?;
+                let (rewards_estimated, rest) = unpack_u64(rest)?;
+                let (cumulative_interest, rest) = unpack_u128(rest)?;
+                let (last_update_ts, rest) = unpack_i64(rest)?;
+                let (next_claim_ts, rest) = unpack_i64(rest)?;
+                (
+                    Self::SeedPosition(SeedPositionData {
+                        liquidity_amount,
+                        rewards_owed,
+                        rewards_estimated,
+                        cumulative_interest,
+                        last_update_ts,
+                        next_claim_ts,
+                    }),
+                    rest,
+                )
+            }
+            119 => {
+                let (price_tick, rest) = unpack_u128(rest)?;
+                (Self::SetPriceTick(price_tick), rest)
+            }
+            120 => (Self::ResyncClaimSchedule, rest),
+            121 => {
+                let (max_trade_amount, rest) = unpack_u64(rest)?;
+                (Self::SetMaxTradeAmount(max_trade_amount), rest)
+            }
+            122 => {
+                let (strict_claim_destination, rest) = unpack_bool(rest)?;
+                (
+                    Self::SetStrictClaimDestination(strict_claim_destination),
+                    rest,
+                )
+            }
+            123 => {
+                let (swap_cooldown_seconds, rest) = unpack_u64(rest)?;
+                (Self::SetSwapCooldown(swap_cooldown_seconds), rest)
+            }
+            124 => (Self::SyncReserves, rest),
+            125 => {
+                let (max_allowed_slippage_bps, rest) = unpack_u64(rest)?;
+                (Self::SetMaxAllowedSlippage(max_allowed_slippage_bps), rest)
+            }
+            126 => {
+                let (oracle_free, rest) = unpack_bool(rest)?;
+                (Self::SetOracleFree(oracle_free), rest)
+            }
+            127 => {
+                let (market_price, rest) = unpack_u128(rest)?;
+                (Self::SetManualPrice(market_price), rest)
+            }
+            128 => {
+                let (allowed_token_program, rest) = unpack_pubkey(rest)?;
+                (Self::SetAllowedTokenProgram(allowed_token_program), rest)
+            }
+            129 => {
+                let (max_ema_divergence_bps, rest) = unpack_u16(rest)?;
+                (Self::SetMaxEmaDivergence(max_ema_divergence_bps), rest)
+            }
+            130 => {
+                let (fees, rest) = unpack_packed::<Fees>(rest)?;
+                (Self::BatchSetFees(fees), rest)
+            }
+            131 => {
+                let (amount, rest) = unpack_u64(rest)?;
+                (Self::TopUpRewardBudget(amount), rest)
             }
             _ => return Err(SwapError::InvalidInstruction.into()),
-        })
+        };
+        if !rest.is_empty() {
+            return Err(SwapError::InstructionUnpackError.into());
+        }
+        Ok(instruction)
     }
 
     /// Packs a [AdminInstruction](enum.AdminInstruciton.html) into a byte buffer.
@@ -215,6 +607,109 @@ impl AdminInstruction {
                 Pack::pack_into_slice(rewards, &mut rewards_slice[..]);
                 buf.extend_from_slice(&rewards_slice);
             }
+            Self::SetPauseFlags(pause_flags) => {
+                buf.push(107);
+                buf.push(pause_flags.bits());
+            }
+            Self::SetTargets(SetTargetsData {
+                base_target,
+                quote_target,
+            }) => {
+                buf.push(108);
+                buf.extend_from_slice(&base_target.to_le_bytes());
+                buf.extend_from_slice(&quote_target.to_le_bytes());
+            }
+            Self::ProposeDrain => buf.push(109),
+            Self::ExecuteDrain => buf.push(110),
+            Self::SetMinClaimAmount(min_claim_amount) => {
+                buf.push(111);
+                buf.extend_from_slice(&min_claim_amount.to_le_bytes());
+            }
+            Self::SetAdminSplit(admin_split_bps) => {
+                buf.push(112);
+                buf.extend_from_slice(&admin_split_bps.to_le_bytes());
+            }
+            Self::SetMinPoolSupply(min_pool_supply) => {
+                buf.push(113);
+                buf.extend_from_slice(&min_pool_supply.to_le_bytes());
+            }
+            Self::SetMinRewardLiquidity(min_reward_liquidity) => {
+                buf.push(114);
+                buf.extend_from_slice(&min_reward_liquidity.to_le_bytes());
+            }
+            Self::SetRewardMint(deltafi_mint) => {
+                buf.push(115);
+                buf.extend_from_slice(deltafi_mint.as_ref());
+            }
+            Self::CloseSwap => buf.push(116),
+            Self::SetMaxOracleMove(max_oracle_move_bps) => {
+                buf.push(117);
+                buf.extend_from_slice(&max_oracle_move_bps.to_le_bytes());
+            }
+            Self::SeedPosition(SeedPositionData {
+                liquidity_amount,
+                rewards_owed,
+                rewards_estimated,
+                cumulative_interest,
+                last_update_ts,
+                next_claim_ts,
+            }) => {
+                buf.push(118);
+                buf.extend_from_slice(&liquidity_amount.to_le_bytes());
+                buf.extend_from_slice(&rewards_owed.to_le_bytes());
+                buf.extend_from_slice(&rewards_estimated.to_le_bytes());
+                buf.extend_from_slice(&cumulative_interest.to_le_bytes());
+                buf.extend_from_slice(&last_update_ts.to_le_bytes());
+                buf.extend_from_slice(&next_claim_ts.to_le_bytes());
+            }
+            Self::SetPriceTick(price_tick) => {
+                buf.push(119);
+                buf.extend_from_slice(&price_tick.to_le_bytes());
+            }
+            Self::ResyncClaimSchedule => buf.push(120),
+            Self::SetMaxTradeAmount(max_trade_amount) => {
+                buf.push(121);
+                buf.extend_from_slice(&max_trade_amount.to_le_bytes());
+            }
+            Self::SetStrictClaimDestination(strict_claim_destination) => {
+                buf.push(122);
+                buf.push(*strict_claim_destination as u8);
+            }
+            Self::SetSwapCooldown(swap_cooldown_seconds) => {
+                buf.push(123);
+                buf.extend_from_slice(&swap_cooldown_seconds.to_le_bytes());
+            }
+            Self::SyncReserves => buf.push(124),
+            Self::SetMaxAllowedSlippage(max_allowed_slippage_bps) => {
+                buf.push(125);
+                buf.extend_from_slice(&max_allowed_slippage_bps.to_le_bytes());
+            }
+            Self::SetOracleFree(oracle_free) => {
+                buf.push(126);
+                buf.push(*oracle_free as u8);
+            }
+            Self::SetManualPrice(market_price) => {
+                buf.push(127);
+                buf.extend_from_slice(&market_price.to_le_bytes());
+            }
+            Self::SetAllowedTokenProgram(allowed_token_program) => {
+                buf.push(128);
+                buf.extend_from_slice(allowed_token_program.as_ref());
+            }
+            Self::SetMaxEmaDivergence(max_ema_divergence_bps) => {
+                buf.push(129);
+                buf.extend_from_slice(&max_ema_divergence_bps.to_le_bytes());
+            }
+            Self::BatchSetFees(fees) => {
+                buf.push(130);
+                let mut fees_slice = [0u8; Fees::LEN];
+                Pack::pack_into_slice(fees, &mut fees_slice[..]);
+                buf.extend_from_slice(&fees_slice);
+            }
+            Self::TopUpRewardBudget(amount) => {
+                buf.push(131);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
         }
         buf
     }
@@ -366,6 +861,33 @@ pub fn set_new_fees(
     })
 }
 
+/// Creates a 'batch_set_fees' instruction
+pub fn batch_set_fees(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    swap_pubkeys: Vec<Pubkey>,
+    new_fees: Fees,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::BatchSetFees(new_fees).pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+    accounts.extend(
+        swap_pubkeys
+            .into_iter()
+            .map(|pubkey| AccountMeta::new(pubkey, false)),
+    );
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
 /// Creates a 'set_rewards' instruction.
 pub fn set_new_rewards(
     program_id: Pubkey,
@@ -389,269 +911,70 @@ pub fn set_new_rewards(
     })
 }
 
-/// Instructions supported by the pool SwapInfo program.
-#[repr(C)]
-#[derive(Debug, PartialEq)]
-pub enum SwapInstruction {
-    ///   Initializes a new SwapInfo.
-    ///
-    ///   0. `[writable, signer]` New Token-swap to create.
-    ///   1. `[]` $authority derived from `create_program_address(&[Token-swap account])`
-    ///   2. `[]` admin Account.
-    ///   3. `[]` admin_fee_a admin fee Account for token_a.
-    ///   4. `[]` admin_fee_b admin fee Account for token_b.
-    ///   5. `[]` token_a Account. Must be non zero, owned by $authority.
-    ///   6. `[]` token_b Account. Must be non zero, owned by $authority.
-    ///   7. `[writable]` Pool Token Mint. Must be empty, owned by $authority.
-    Initialize(InitializeData),
-
-    ///   Swap the tokens in the pool.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` token_(A|B) SOURCE Account, amount is transferable by $authority,
-    ///   3. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
-    ///   4. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
-    ///   5. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
-    ///   6. `[writable]` token_(A|B) admin fee Account. Must have same mint as DESTINATION token.
-    ///   7. `[]` Token program id
-    ///   8. `[]` Clock sysvar
-    Swap(SwapData),
-
-    ///   Deposit some tokens into the pool.  The output is a "pool" token representing ownership
-    ///   into the pool. Inputs are converted to the current ratio.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` token_a $authority can transfer amount,
-    ///   3. `[writable]` token_b $authority can transfer amount,
-    ///   4. `[writable]` token_a Base Account to deposit into.
-    ///   5. `[writable]` token_b Base Account to deposit into.
-    ///   6. `[writable]` Pool MINT account, $authority is the owner.
-    ///   7. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
-    ///   8. `[]` Token program id
-    ///   9. `[]` Clock sysvar
-    Deposit(DepositData),
+/// Creates a 'set_pause_flags' instruction.
+pub fn set_pause_flags(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    pause_flags: PauseFlags,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetPauseFlags(pause_flags).pack();
 
-    ///   Withdraw tokens from the pool at the current ratio.
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` Pool mint account, $authority is the owner
-    ///   3. `[writable]` SOURCE Pool account, amount is transferable by $authority.
-    ///   4. `[writable]` token_a Swap Account to withdraw FROM.
-    ///   5. `[writable]` token_b Swap Account to withdraw FROM.
-    ///   6. `[writable]` token_a user Account to credit.
-    ///   7. `[writable]` token_b user Account to credit.
-    ///   8. `[writable]` admin_fee_a admin fee Account for token_a.
-    ///   9. `[writable]` admin_fee_b admin fee Account for token_b.
-    ///   10. `[]` Token program id
-    Withdraw(WithdrawData),
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
 
-    // ///   Withdraw one token from the pool at the current ratio.
-    // ///
-    // ///   0. `[]` Token-swap
-    // ///   1. `[]` $authority
-    // ///   2. `[writable]` Pool mint account, $authority is the owner
-    // ///   3. `[writable]` SOURCE Pool account, amount is transferable by $authority.
-    // ///   4. `[writable]` token_(A|B) BASE token Swap Account to withdraw FROM.
-    // ///   5. `[writable]` token_(A|B) QUOTE token Swap Account to exchange to base token.
-    // ///   6. `[writable]` token_(A|B) BASE token user Account to credit.
-    // ///   7. `[writable]` token_(A|B) admin fee Account. Must have same mint as BASE token.
-    // ///   8. `[]` Token program id
-    // ///   9. `[]` Clock sysvar
-    // WithdrawOne(WithdrawOneData),
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
 
-    // ///   Calc the receive amount in the pool.
-    // ///
-    // ///   0. `[]` Token-swap
-    // ///   1. `[]` $authority
-    // ///   2. `[writable]` token_(A|B) SOURCE Account, amount is transferable by $authority,
-    // ///   3. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
-    // ///   4. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
-    // ///   5. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
-    // ///   6. `[writable]` token_(A|B) admin fee Account. Must have same mint as DESTINATION token.
-    // ///   7. `[]` Token program id
-    // ///   8. `[]` Clock sysvar
-    // CalcReceiveAmount(SwapData),
-    /// Initialize liquidity provider account
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[writable]` liquidity provider info
-    ///   2. `[signer]` liquidity provider owner
-    ///   3. `[]` Token program id
-    ///   4. `[]` Clock sysvar
-    InitializeLiquidityProvider,
+/// Creates a 'set_targets' instruction.
+pub fn set_targets(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    targets: SetTargetsData,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetTargets(targets).pack();
 
-    /// Claim deltafi reward of liquidity provider
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` $authority
-    ///   2. `[writable]` Liquidity provider info
-    ///   3. `[signer]` Liquidity provider owner
-    ///   4. `[writable]` Rewards receiver
-    ///   5. `[writable]` Rewards mint deltafi
-    ///   6. `[]` Token program id
-    ClaimLiquidityRewards,
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
 
-    /// Refresh liquidity obligation
-    ///
-    ///   0. `[]` Token-swap
-    ///   1. `[]` Clock sysvar
-    ///   .. `[]` Liquidity provider accounts - refreshed, all, in order.
-    RefreshLiquidityObligation,
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-impl SwapInstruction {
-    /// Unpacks a byte buffer into a [SwapInstruction](enum.SwapInstruction.html).
-    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        let (&tag, rest) = input
-            .split_first()
-            .ok_or(SwapError::InstructionUnpackError)?;
-        Ok(match tag {
-            0x0 => {
-                let (&nonce, rest) = rest
-                    .split_first()
-                    .ok_or(SwapError::InstructionUnpackError)?;
-                let (slope, rest) = unpack_u64(rest)?;
-                let (mid_price, rest) = unpack_u128(rest)?;
-                let (is_open_twap, _) = unpack_bool(rest)?;
-                Self::Initialize(InitializeData {
-                    nonce,
-                    slope,
-                    mid_price,
-                    is_open_twap,
-                })
-            }
-            0x1 => {
-                let (amount_in, rest) = unpack_u64(rest)?;
-                let (minimum_amount_out, rest) = unpack_u64(rest)?;
-                let (swap_direction, _) = unpack_swap_direction(rest)?;
-                Self::Swap(SwapData {
-                    amount_in,
-                    minimum_amount_out,
-                    swap_direction,
-                })
-            }
-            0x2 => {
-                let (token_a_amount, rest) = unpack_u64(rest)?;
-                let (token_b_amount, rest) = unpack_u64(rest)?;
-                let (min_mint_amount, _) = unpack_u64(rest)?;
-                Self::Deposit(DepositData {
-                    token_a_amount,
-                    token_b_amount,
-                    min_mint_amount,
-                })
-            }
-            0x3 => {
-                let (pool_token_amount, rest) = unpack_u64(rest)?;
-                let (minimum_token_a_amount, rest) = unpack_u64(rest)?;
-                let (minimum_token_b_amount, _) = unpack_u64(rest)?;
-                Self::Withdraw(WithdrawData {
-                    pool_token_amount,
-                    minimum_token_a_amount,
-                    minimum_token_b_amount,
-                })
-            }
-            0x4 => Self::InitializeLiquidityProvider,
-            0x5 => Self::ClaimLiquidityRewards,
-            0x6 => Self::RefreshLiquidityObligation,
-            _ => return Err(SwapError::InvalidInstruction.into()),
-        })
-    }
-
-    /// Packs a [SwapInstruction](enum.SwapInstruction.html) into a byte buffer.
-    pub fn pack(&self) -> Vec<u8> {
-        let mut buf = Vec::with_capacity(size_of::<Self>());
-        match *self {
-            Self::Initialize(InitializeData {
-                nonce,
-                slope,
-                mid_price,
-                is_open_twap,
-            }) => {
-                buf.push(0x0);
-                buf.push(nonce);
-                buf.extend_from_slice(&slope.to_le_bytes());
-                buf.extend_from_slice(&mid_price.to_le_bytes());
-                buf.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
-            }
-            Self::Swap(SwapData {
-                amount_in,
-                minimum_amount_out,
-                swap_direction,
-            }) => {
-                buf.push(0x1);
-                buf.extend_from_slice(&amount_in.to_le_bytes());
-                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
-                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
-            }
-            Self::Deposit(DepositData {
-                token_a_amount,
-                token_b_amount,
-                min_mint_amount,
-            }) => {
-                buf.push(0x2);
-                buf.extend_from_slice(&token_a_amount.to_le_bytes());
-                buf.extend_from_slice(&token_b_amount.to_le_bytes());
-                buf.extend_from_slice(&min_mint_amount.to_le_bytes());
-            }
-            Self::Withdraw(WithdrawData {
-                pool_token_amount,
-                minimum_token_a_amount,
-                minimum_token_b_amount,
-            }) => {
-                buf.push(0x3);
-                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
-                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
-                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
-            }
-            Self::InitializeLiquidityProvider => {
-                buf.push(0x4);
-            }
-            Self::ClaimLiquidityRewards => {
-                buf.push(0x5);
-            }
-            Self::RefreshLiquidityObligation => {
-                buf.push(0x6);
-            }
-        }
-        buf
-    }
-}
-
-/// Creates an 'initialize' instruction.
-pub fn initialize(
+/// Creates a 'propose_drain' instruction
+pub fn propose_drain(
     program_id: Pubkey,
     config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
-    authority_pubkey: Pubkey,
-    admin_fee_a_pubkey: Pubkey,
-    admin_fee_b_pubkey: Pubkey,
-    token_a_pubkey: Pubkey,
-    token_b_pubkey: Pubkey,
-    pool_mint_pubkey: Pubkey,
-    destination_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    init_data: InitializeData,
+    admin_pubkey: Pubkey,
+    treasury_token_a_pubkey: Pubkey,
+    treasury_token_b_pubkey: Pubkey,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Initialize(init_data).pack();
+    let data = AdminInstruction::ProposeDrain.pack();
 
     let accounts = vec![
-        AccountMeta::new_readonly(config_pubkey, false),
-        AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(authority_pubkey, false),
-        AccountMeta::new_readonly(admin_fee_a_pubkey, false),
-        AccountMeta::new_readonly(admin_fee_b_pubkey, false),
-        AccountMeta::new_readonly(token_a_pubkey, false),
-        AccountMeta::new_readonly(token_b_pubkey, false),
-        AccountMeta::new(pool_mint_pubkey, false),
-        AccountMeta::new(destination_pubkey, false),
-        AccountMeta::new_readonly(pyth_a_pubkey, false),
-        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new_readonly(treasury_token_a_pubkey, false),
+        AccountMeta::new_readonly(treasury_token_b_pubkey, false),
         AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
     ];
 
     Ok(Instruction {
@@ -661,44 +984,18 @@ pub fn initialize(
     })
 }
 
-/// Creates a 'swap' instruction.
-pub fn swap(
+/// Creates a 'set_min_claim_amount' instruction
+pub fn set_min_claim_amount(
     program_id: Pubkey,
     config_pubkey: Pubkey,
-    swap_pubkey: Pubkey,
-    market_authority_pubkey: Pubkey,
-    swap_authority_pubkey: Pubkey,
-    user_transfer_authority_pubkey: Pubkey,
-    source_pubkey: Pubkey,
-    swap_source_pubkey: Pubkey,
-    swap_destination_pubkey: Pubkey,
-    destination_pubkey: Pubkey,
-    reward_token_pubkey: Pubkey,
-    reward_mint_pubkey: Pubkey,
-    admin_fee_destination_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    swap_data: SwapData,
+    admin_pubkey: Pubkey,
+    min_claim_amount: u64,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Swap(swap_data).pack();
+    let data = AdminInstruction::SetMinClaimAmount(min_claim_amount).pack();
 
     let accounts = vec![
-        AccountMeta::new_readonly(config_pubkey, false),
-        AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(market_authority_pubkey, false),
-        AccountMeta::new_readonly(swap_authority_pubkey, false),
-        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
-        AccountMeta::new(source_pubkey, false),
-        AccountMeta::new(swap_source_pubkey, false),
-        AccountMeta::new(swap_destination_pubkey, false),
-        AccountMeta::new(destination_pubkey, false),
-        AccountMeta::new(reward_token_pubkey, false),
-        AccountMeta::new(reward_mint_pubkey, false),
-        AccountMeta::new(admin_fee_destination_pubkey, false),
-        AccountMeta::new_readonly(pyth_a_pubkey, false),
-        AccountMeta::new_readonly(pyth_b_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
@@ -708,42 +1005,18 @@ pub fn swap(
     })
 }
 
-/// Creates a 'deposit' instruction.
-pub fn deposit(
+/// Creates a 'set_admin_split' instruction
+pub fn set_admin_split(
     program_id: Pubkey,
-    swap_pubkey: Pubkey,
-    authority_pubkey: Pubkey,
-    user_transfer_authority_pubkey: Pubkey,
-    deposit_token_a_pubkey: Pubkey,
-    deposit_token_b_pubkey: Pubkey,
-    swap_token_a_pubkey: Pubkey,
-    swap_token_b_pubkey: Pubkey,
-    pool_mint_pubkey: Pubkey,
-    destination_pubkey: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    deposit_data: DepositData,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    admin_split_bps: u16,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Deposit(deposit_data).pack();
+    let data = AdminInstruction::SetAdminSplit(admin_split_bps).pack();
 
     let accounts = vec![
-        AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(authority_pubkey, false),
-        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
-        AccountMeta::new(deposit_token_a_pubkey, false),
-        AccountMeta::new(deposit_token_b_pubkey, false),
-        AccountMeta::new(swap_token_a_pubkey, false),
-        AccountMeta::new(swap_token_b_pubkey, false),
-        AccountMeta::new(pool_mint_pubkey, false),
-        AccountMeta::new(destination_pubkey, false),
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new_readonly(pyth_a_pubkey, false),
-        AccountMeta::new_readonly(pyth_b_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
@@ -753,46 +1026,18 @@ pub fn deposit(
     })
 }
 
-/// Creates a 'withdraw' instruction.
-pub fn withdraw(
+/// Creates a 'set_min_pool_supply' instruction
+pub fn set_min_pool_supply(
     program_id: Pubkey,
-    swap_pubkey: Pubkey,
-    authority_pubkey: Pubkey,
-    user_transfer_authority_pubkey: Pubkey,
-    pool_mint_pubkey: Pubkey,
-    source_pubkey: Pubkey,
-    swap_token_a_pubkey: Pubkey,
-    swap_token_b_pubkey: Pubkey,
-    destination_token_a_pubkey: Pubkey,
-    destination_token_b_pubkey: Pubkey,
-    admin_fee_a_pubkey: Pubkey,
-    admin_fee_b_pubkey: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
-    pyth_a_pubkey: Pubkey,
-    pyth_b_pubkey: Pubkey,
-    withdraw_data: WithdrawData,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    min_pool_supply: u64,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::Withdraw(withdraw_data).pack();
+    let data = AdminInstruction::SetMinPoolSupply(min_pool_supply).pack();
 
     let accounts = vec![
-        AccountMeta::new(swap_pubkey, false),
-        AccountMeta::new_readonly(authority_pubkey, false),
-        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
-        AccountMeta::new(pool_mint_pubkey, false),
-        AccountMeta::new(source_pubkey, false),
-        AccountMeta::new(swap_token_a_pubkey, false),
-        AccountMeta::new(swap_token_b_pubkey, false),
-        AccountMeta::new(destination_token_a_pubkey, false),
-        AccountMeta::new(destination_token_b_pubkey, false),
-        AccountMeta::new(admin_fee_a_pubkey, false),
-        AccountMeta::new(admin_fee_b_pubkey, false),
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new_readonly(pyth_a_pubkey, false),
-        AccountMeta::new_readonly(pyth_b_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
@@ -802,69 +1047,114 @@ pub fn withdraw(
     })
 }
 
-/// Creates `InitializeLiquidityProvider` instruction
-pub fn init_liquidity_provider(
+/// Creates a 'set_min_reward_liquidity' instruction
+pub fn set_min_reward_liquidity(
     program_id: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    min_reward_liquidity: u64,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::InitializeLiquidityProvider.pack();
+    let data = AdminInstruction::SetMinRewardLiquidity(min_reward_liquidity).pack();
 
     let accounts = vec![
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
         program_id,
-        data,
         accounts,
+        data,
     })
 }
 
-/// Creates `ClaimLiquidityRewards` instruction
-pub fn claim_liquidity_rewards(
+/// Creates a 'set_max_oracle_move' instruction
+pub fn set_max_oracle_move(
     program_id: Pubkey,
     config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
-    market_authority_info: Pubkey,
-    liquidity_provider_pubkey: Pubkey,
-    liquidity_owner_pubkey: Pubkey,
-    claim_destination_pubkey: Pubkey,
-    claim_mint_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    max_oracle_move_bps: u16,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::ClaimLiquidityRewards.pack();
+    let data = AdminInstruction::SetMaxOracleMove(max_oracle_move_bps).pack();
 
     let accounts = vec![
         AccountMeta::new_readonly(config_pubkey, false),
-        AccountMeta::new_readonly(swap_pubkey, false),
-        AccountMeta::new_readonly(market_authority_info, false),
-        AccountMeta::new(liquidity_provider_pubkey, false),
-        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
-        AccountMeta::new(claim_destination_pubkey, false),
-        AccountMeta::new(claim_mint_pubkey, false),
-        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
 
     Ok(Instruction {
         program_id,
+        accounts,
         data,
+    })
+}
+
+/// Creates a 'seed_position' instruction
+pub fn seed_position(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    seed_position_data: SeedPositionData,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SeedPosition(seed_position_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
         accounts,
+        data,
     })
 }
 
-/// Creates `RefreshLiquidityObligation` instruction
-pub fn refresh_liquidity_obligation(
+/// Creates a 'set_price_tick' instruction
+pub fn set_price_tick(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    price_tick: u128,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetPriceTick(price_tick).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'resync_claim_schedule' instruction
+pub fn resync_claim_schedule(
     program_id: Pubkey,
+    config_pubkey: Pubkey,
     swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
     liquidity_provider_pubkeys: Vec<Pubkey>,
 ) -> Result<Instruction, ProgramError> {
-    let data = SwapInstruction::RefreshLiquidityObligation.pack();
+    let data = AdminInstruction::ResyncClaimSchedule.pack();
 
     let mut accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
         AccountMeta::new_readonly(swap_pubkey, false),
-        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(admin_pubkey, true),
     ];
     accounts.extend(
         liquidity_provider_pubkeys
@@ -874,216 +1164,2960 @@ pub fn refresh_liquidity_obligation(
 
     Ok(Instruction {
         program_id,
+        accounts,
         data,
+    })
+}
+
+/// Creates a 'set_max_trade_amount' instruction
+pub fn set_max_trade_amount(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    max_trade_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetMaxTradeAmount(max_trade_amount).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
         accounts,
+        data,
     })
 }
 
-fn unpack_u128(input: &[u8]) -> Result<(u128, &[u8]), ProgramError> {
-    if input.len() < 16 {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (amount, rest) = input.split_at(16);
-    let amount = amount
-        .get(..16)
-        .and_then(|slice| slice.try_into().ok())
-        .map(u128::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((amount, rest))
+/// Creates a 'top_up_reward_budget' instruction
+pub fn top_up_reward_budget(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::TopUpRewardBudget(amount).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-#[allow(dead_code)]
-fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
-    if input.len() < 8 {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (amount, rest) = input.split_at(8);
-    let amount = amount
-        .get(..8)
-        .and_then(|slice| slice.try_into().ok())
-        .map(i64::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((amount, rest))
+/// Creates a 'set_strict_claim_destination' instruction
+pub fn set_strict_claim_destination(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    strict_claim_destination: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetStrictClaimDestination(strict_claim_destination).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
-    if input.len() < 8 {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (amount, rest) = input.split_at(8);
-    let amount = amount
-        .get(..8)
-        .and_then(|slice| slice.try_into().ok())
-        .map(u64::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((amount, rest))
+/// Creates a 'set_swap_cooldown' instruction
+pub fn set_swap_cooldown(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    swap_cooldown_seconds: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetSwapCooldown(swap_cooldown_seconds).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
-    if input.is_empty() {
-        return Err(SwapError::InstructionUnpackError.into());
-    }
-    let (bytes, rest) = input.split_at(1);
-    let value = bytes
-        .get(..1)
-        .and_then(|slice| slice.try_into().ok())
-        .map(u8::from_le_bytes)
-        .ok_or(SwapError::InstructionUnpackError)?;
-    Ok((value, rest))
+/// Creates a 'sync_reserves' instruction
+pub fn sync_reserves(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    token_a_pubkey: Pubkey,
+    token_b_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SyncReserves.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new_readonly(token_a_pubkey, false),
+        AccountMeta::new_readonly(token_b_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_max_allowed_slippage' instruction
+pub fn set_max_allowed_slippage(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    max_allowed_slippage_bps: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetMaxAllowedSlippage(max_allowed_slippage_bps).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_oracle_free' instruction
+pub fn set_oracle_free(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    oracle_free: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetOracleFree(oracle_free).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_manual_price' instruction
+pub fn set_manual_price(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    market_price: u128,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetManualPrice(market_price).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
 }
 
-fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
-    let (value, rest) = unpack_u8(input)?;
-    let value = match u8::from_le(value) {
-        0 => false,
-        1 => true,
-        _ => return Err(SwapError::InstructionUnpackError.into()),
-    };
-    Ok((value, rest))
-}
+/// Creates a 'set_allowed_token_program' instruction
+pub fn set_allowed_token_program(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    allowed_token_program: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetAllowedTokenProgram(allowed_token_program).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_max_ema_divergence' instruction
+pub fn set_max_ema_divergence(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    max_ema_divergence_bps: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetMaxEmaDivergence(max_ema_divergence_bps).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'set_reward_mint' instruction
+pub fn set_reward_mint(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    market_authority_pubkey: Pubkey,
+    deltafi_mint_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::SetRewardMint(deltafi_mint_pubkey).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(market_authority_pubkey, false),
+        AccountMeta::new_readonly(deltafi_mint_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'close_swap' instruction
+pub fn close_swap(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::CloseSwap.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'execute_drain' instruction
+pub fn execute_drain(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    admin_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    treasury_token_a_pubkey: Pubkey,
+    treasury_token_b_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = AdminInstruction::ExecuteDrain.pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(admin_pubkey, true),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(treasury_token_a_pubkey, false),
+        AccountMeta::new(treasury_token_b_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Instructions supported by the pool SwapInfo program.
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SwapInstruction {
+    ///   Initializes a new SwapInfo.
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[writable]` New Token-swap to create.
+    ///   2. `[]` $authority derived from `create_program_address(&[Token-swap account])`
+    ///   3. `[]` admin_fee_a admin fee Account for token_a.
+    ///   4. `[]` admin_fee_b admin fee Account for token_b.
+    ///   5. `[]` token_a Account. Must be non zero, owned by $authority.
+    ///   6. `[]` token_b Account. Must be non zero, owned by $authority.
+    ///   7. `[]` token_a Mint, without unsupported Token-2022 extensions.
+    ///   8. `[]` token_b Mint, without unsupported Token-2022 extensions.
+    ///   9. `[writable]` Pool Token Mint. Must be empty, owned by $authority.
+    ///   10. `[writable]` Pool Account to deposit the initial pool tokens.
+    ///   11. `[]` Pyth price account for token a
+    ///   12. `[]` Pyth price account for token b
+    ///   13. `[]` Clock sysvar
+    ///   14. `[]` Token program id
+    ///   15. `[signer]` Pool creator
+    Initialize(InitializeData),
+
+    ///   Swap the tokens in the pool.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[writable]` token_(A|B) SOURCE Account, amount is transferable by $authority,
+    ///   3. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    ///   4. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
+    ///   5. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
+    ///   6. `[writable]` token_(A|B) admin fee Account. Must have same mint as DESTINATION token.
+    ///   7. `[]` Token program id
+    ///   8. `[]` Clock sysvar
+    ///
+    ///   Pools with a swap cooldown also take the `SwapCooldown` of the user
+    ///   transfer authority, writable, as the last account.
+    Swap(SwapData),
+
+    ///   Deposit some tokens into the pool.  The output is a "pool" token representing ownership
+    ///   into the pool. Inputs are converted to the current ratio. The pool's
+    ///   deposit reward, if enabled, is credited to the liquidity position and
+    ///   becomes claimable at its next claim timestamp.
+    ///
+    ///   0. `[]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[]` $authority
+    ///   3. `[signer]` User transfer authority
+    ///   4. `[writable]` token_a $authority can transfer amount,
+    ///   5. `[writable]` token_b $authority can transfer amount,
+    ///   6. `[writable]` token_a Base Account to deposit into.
+    ///   7. `[writable]` token_b Base Account to deposit into.
+    ///   8. `[writable]` Pool MINT account, $authority is the owner.
+    ///   9. `[writable]` Pool Account to deposit the generated tokens, user is the owner.
+    ///   10. `[writable]` Liquidity provider
+    ///   11. `[signer]` Liquidity provider owner
+    ///   12. `[]` Pyth price account for token a
+    ///   13. `[]` Pyth price account for token b
+    ///   14. `[]` Clock sysvar
+    ///   15. `[]` Token program id
+    Deposit(DepositData),
+
+    ///   Withdraw tokens from the pool at the current ratio.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[writable]` Pool mint account, $authority is the owner
+    ///   3. `[writable]` SOURCE Pool account, amount is transferable by $authority.
+    ///   4. `[writable]` token_a Swap Account to withdraw FROM.
+    ///   5. `[writable]` token_b Swap Account to withdraw FROM.
+    ///   6. `[writable]` token_a user Account to credit.
+    ///   7. `[writable]` token_b user Account to credit.
+    ///   8. `[writable]` admin_fee_a admin fee Account for token_a.
+    ///   9. `[writable]` admin_fee_b admin fee Account for token_b.
+    ///   10. `[]` Token program id
+    Withdraw(WithdrawData),
+
+    // ///   Withdraw one token from the pool at the current ratio.
+    // ///
+    // ///   0. `[]` Token-swap
+    // ///   1. `[]` $authority
+    // ///   2. `[writable]` Pool mint account, $authority is the owner
+    // ///   3. `[writable]` SOURCE Pool account, amount is transferable by $authority.
+    // ///   4. `[writable]` token_(A|B) BASE token Swap Account to withdraw FROM.
+    // ///   5. `[writable]` token_(A|B) QUOTE token Swap Account to exchange to base token.
+    // ///   6. `[writable]` token_(A|B) BASE token user Account to credit.
+    // ///   7. `[writable]` token_(A|B) admin fee Account. Must have same mint as BASE token.
+    // ///   8. `[]` Token program id
+    // ///   9. `[]` Clock sysvar
+    // WithdrawOne(WithdrawOneData),
+
+    // ///   Calc the receive amount in the pool.
+    // ///
+    // ///   0. `[]` Token-swap
+    // ///   1. `[]` $authority
+    // ///   2. `[writable]` token_(A|B) SOURCE Account, amount is transferable by $authority,
+    // ///   3. `[writable]` token_(A|B) Base Account to swap INTO.  Must be the SOURCE token.
+    // ///   4. `[writable]` token_(A|B) Base Account to swap FROM.  Must be the DESTINATION token.
+    // ///   5. `[writable]` token_(A|B) DESTINATION Account assigned to USER as the owner.
+    // ///   6. `[writable]` token_(A|B) admin fee Account. Must have same mint as DESTINATION token.
+    // ///   7. `[]` Token program id
+    // ///   8. `[]` Clock sysvar
+    // CalcReceiveAmount(SwapData),
+    /// Initialize liquidity provider account
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` liquidity provider info
+    ///   2. `[signer]` liquidity provider owner
+    ///   3. `[]` Token program id
+    ///   4. `[]` Clock sysvar
+    InitializeLiquidityProvider,
+
+    /// Claim deltafi reward of liquidity provider
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` $authority
+    ///   2. `[writable]` Liquidity provider info
+    ///   3. `[signer]` Liquidity provider owner
+    ///   4. `[writable]` Rewards receiver
+    ///   5. `[writable]` Rewards mint deltafi
+    ///   6. `[]` Token program id
+    ClaimLiquidityRewards,
+
+    /// Refresh liquidity obligation
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Clock sysvar
+    ///   .. `[]` Liquidity provider accounts - refreshed, all, in order.
+    RefreshLiquidityObligation,
+
+    /// Get token accounts, pool mint and authority of a swap,
+    /// returned as a packed `SwapAccountsBundle` via return data
+    ///
+    ///   0. `[]` Token-swap
+    GetSwapAccounts,
+
+    /// Get claim status of the liquidity position in the given pool,
+    /// returned as a packed `ClaimStatus` via return data
+    ///
+    ///   0. `[]` Liquidity provider info
+    ///   1. `[]` Clock sysvar
+    GetClaimStatus(Pubkey),
+
+    /// Get pools created, fees, rewards, admin key and drain state of the config,
+    /// returned as a packed `ConfigStats` via return data
+    ///
+    ///   0. `[]` Config
+    GetConfigStats,
+
+    /// Get the annualized liquidity reward APR of a swap,
+    /// returned as a scaled `Decimal` (u128) via return data.
+    /// Rewards pay the mid price worth of DELTAFI per lp token per claim
+    /// period, so the DELTAFI price cancels out and the APR is the mid price
+    /// over the lp token value, annualized
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pool mint
+    ///   2. `[]` Token program id
+    GetRewardApr,
+
+    /// Grow a liquidity provider account from the legacy layout to the current
+    /// one, funding the extra rent from the payer
+    ///
+    ///   0. `[writable]` Liquidity provider info
+    ///   1. `[signer]` Liquidity provider owner
+    ///   2. `[writable, signer]` Payer
+    ///   3. `[]` Rent sysvar
+    ///   4. `[]` System program
+    MigrateLiquidityProvider,
+
+    /// Get the trade size in the given direction above which splitting into
+    /// smaller trades still beats the trade fee, returned as a u64 via
+    /// return data
+    ///
+    ///   0. `[]` Token-swap
+    GetSplitThreshold(SwapDirection),
+
+    /// Check whether a swap would go through at current prices without
+    /// executing it. Returns a bool followed by the relative deviation of
+    /// the swap's execution price from the market price, as a scaled
+    /// `Decimal` (u128), via return data. A swap the pool cannot fill
+    /// reports a deviation of one.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pyth price account for token a
+    ///   2. `[]` Pyth price account for token b
+    ///   3. `[]` Clock sysvar
+    CheckSwapAllowed(SwapData),
+
+    /// Get the claim status of a liquidity position in the swap as if it
+    /// were refreshed now, returned as a packed `ClaimStatus` via return data.
+    /// Nothing is written back to the liquidity provider.
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Liquidity provider info
+    ///   2. `[]` Clock sysvar
+    PreviewRewards,
+
+    /// Get the price a swap of the given size would execute at before fees,
+    /// quoted as quote per base, returned as a scaled `Decimal` (u128) via
+    /// return data
+    ///
+    ///   0. `[]` Token-swap
+    GetExecutionPrice(QuoteData),
+
+    /// Get the quote per base ratio a deposit should supply to be taken in
+    /// full, returned as a scaled `Decimal` (u128) via return data
+    ///
+    ///   0. `[]` Token-swap
+    GetBalancedDepositRatio,
+
+    /// Get the multiplier status of the pool followed by the amount a trade
+    /// can sell before it flips, returned via return data as the multiplier
+    /// byte (0 one, 1 above one, 2 below one) and a u64 headroom
+    ///
+    ///   0. `[]` Token-swap
+    GetMultiplier,
+
+    /// Get the DELTAFI minted as swap and liquidity rewards under a config,
+    /// returned as a u128 via return data
+    ///
+    ///   0. `[]` Config
+    GetTotalRewardsMinted,
+
+    /// Get the token a and token b amounts a withdrawal of the given pool
+    /// token amount would pay out after withdraw fees, returned as two u64
+    /// via return data
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pool mint account
+    ///   2. `[]` Token program id
+    PreviewWithdraw(u64),
+
+    /// Get how closely the pool tracks its oracle, returned via return data
+    /// as the pool mid price snapped to the pool's price tick and the oracle
+    /// price, both scaled `Decimal` (u128), followed by their deviation in
+    /// basis points of the oracle price as a u64
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pyth price account for token a
+    ///   2. `[]` Pyth price account for token b
+    ///   3. `[]` Clock sysvar
+    GetPriceHealth,
+
+    /// Get the health checks of a pool and their composite score, returned
+    /// as a packed `PoolHealth` via return data
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pyth price account for token a
+    ///   2. `[]` Pyth price account for token b
+    ///   3. `[]` Clock sysvar
+    GetPoolHealth,
+
+    /// Get the token a and token b amounts backing the given pool token
+    /// amount, with the pool repriced at the oracle price and before withdraw
+    /// fees, returned as two u64 via return data
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pool mint account
+    ///   2. `[]` Token program id
+    ///   3. `[]` Pyth price account for token a
+    ///   4. `[]` Pyth price account for token b
+    ///   5. `[]` Clock sysvar
+    PreviewRedeem(u64),
+
+    /// Get the timestamp the pool was initialized at and its age in seconds,
+    /// returned as two u64 via return data
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Clock sysvar
+    GetPoolAge,
+
+    /// Get the smallest slippage tolerance, in basis points of the output
+    /// quoted at the mid price, a swap of the given size passes with after
+    /// trade fees, returned as a u64 via return data
+    ///
+    ///   0. `[]` Token-swap
+    GetRequiredSlippage(QuoteData),
+
+    /// Create the swap cooldown of a user in a swap pool at its program
+    /// derived address
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[writable]` Swap cooldown, derived by `swap_cooldown_address`
+    ///   2. `[]` User transfer authority the cooldown applies to
+    ///   3. `[writable, signer]` Payer of the account rent
+    ///   4. `[]` Rent sysvar
+    ///   5. `[]` System program
+    InitSwapCooldown,
+
+    /// Get the amount in, before fees, a swap in the given direction needs to
+    /// move the pool mid price onto the oracle price, returned as a u64 via
+    /// return data
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Pyth price account for token a
+    ///   2. `[]` Pyth price account for token b
+    ///   3. `[]` Clock sysvar
+    GetArbAmountToOracle(SwapDirection),
+
+    /// Get the net output and admin fee of a sample trade under the current
+    /// fees of a pool and under proposed fees, returned as four u64 via
+    /// return data, current net output and admin fee first
+    ///
+    ///   0. `[]` Token-swap
+    PreviewFeeChange(PreviewFeeChangeData),
+    /// Get the instantaneous price of a swap in the given direction, quoted
+    /// as quote per base, returned as a scaled `Decimal` (u128) via return
+    /// data
+    ///
+    ///   0. `[]` Token-swap
+    GetMarginalPrice(SwapDirection),
+    /// Withdraw tokens from the pool, claiming the liquidity rewards owed to
+    /// the position first
+    ///
+    ///   0. `[writable]` Config
+    ///   1. `[writable]` Token-swap
+    ///   2. `[]` $authority
+    ///   3. `[signer]` User transfer authority
+    ///   4. `[writable]` Pool mint account, $authority is the owner
+    ///   5. `[writable]` SOURCE Pool account, amount is transferable by $authority.
+    ///   6. `[writable]` token_a Swap Account to withdraw FROM.
+    ///   7. `[writable]` token_b Swap Account to withdraw FROM.
+    ///   8. `[writable]` token_a user Account to credit.
+    ///   9. `[writable]` token_b user Account to credit.
+    ///   10. `[writable]` admin_fee_a admin fee Account for token_a.
+    ///   11. `[writable]` admin_fee_b admin fee Account for token_b.
+    ///   12. `[writable]` secondary admin fee Account for token_a.
+    ///   13. `[writable]` secondary admin fee Account for token_b.
+    ///   14. `[writable]` Liquidity provider
+    ///   15. `[signer]` Liquidity provider owner
+    ///   16. `[]` Pyth price account for token a
+    ///   17. `[]` Pyth price account for token b
+    ///   18. `[]` Clock sysvar
+    ///   19. `[]` Token program id
+    ///   20. `[]` Market authority
+    ///   21. `[writable]` DELTAFI reward account to credit.
+    ///   22. `[writable]` DELTAFI mint account.
+    WithdrawAndClaim(WithdrawData),
+    /// Get the time weighted variance of the pool mid price since a snapshot
+    /// of its price accumulators and the volatility, its square root, both
+    /// returned as scaled `Decimal` (u128) via return data
+    ///
+    ///   0. `[]` Token-swap
+    GetRealizedVol(RealizedVolData),
+    /// Get the price implied by the reserve ratio, quote reserve over base
+    /// reserve, and the mid price of the curve snapped to the pool's price
+    /// tick, both returned as scaled `Decimal` (u128) via return data
+    ///
+    ///   0. `[]` Token-swap
+    GetImpliedPrices,
+    /// Get the source of the market price the next swap would trade against,
+    /// given the current oracle freshness, returned as a `PriceSource` byte
+    /// followed by the market price as a scaled `Decimal` (u128) via return data
+    ///
+    ///   0. `[]` Config
+    ///   1. `[]` Token-swap
+    ///   2. `[]` Pyth price account for token a
+    ///   3. `[]` Pyth price account for token b
+    ///   4. `[]` Clock sysvar
+    GetPriceSource,
+    /// Get the mid price of the curve snapped to the pool's price tick,
+    /// returned as a scaled `Decimal` (u128) via return data
+    ///
+    ///   0. `[]` Token-swap
+    GetMidPrice,
+    /// Get the annualized fee APR LPs earned through reserve growth since
+    /// the pool was initialized, from its cumulative volume, returned as a
+    /// scaled `Decimal` (u128) via return data
+    ///
+    ///   0. `[]` Token-swap
+    ///   1. `[]` Clock sysvar
+    GetFeeApr,
+}
+
+impl SwapInstruction {
+    /// Unpacks a byte buffer into a [SwapInstruction](enum.SwapInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(SwapError::InstructionUnpackError)?;
+        let (instruction, rest) = match tag {
+            0x0 => {
+                let (&nonce, rest) = rest
+                    .split_first()
+                    .ok_or(SwapError::InstructionUnpackError)?;
+                let (slope, rest) = unpack_u64(rest)?;
+                let (mid_price, rest) = unpack_u128(rest)?;
+                let (is_open_twap, rest) = unpack_bool(rest)?;
+                let (creator_fee_bps, rest) = unpack_u16(rest)?;
+                let (invert_a, rest) = unpack_bool(rest)?;
+                let (invert_b, rest) = unpack_bool(rest)?;
+                let (slope_in_bps, rest) = unpack_bool(rest)?;
+                (
+                    Self::Initialize(InitializeData {
+                        nonce,
+                        slope,
+                        mid_price,
+                        is_open_twap,
+                        creator_fee_bps,
+                        invert_a,
+                        invert_b,
+                        slope_in_bps,
+                    }),
+                    rest,
+                )
+            }
+            0x1 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (deadline, rest) = unpack_deadline(rest)?;
+                (
+                    Self::Swap(SwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        deadline,
+                    }),
+                    rest,
+                )
+            }
+            0x2 => {
+                let (token_a_amount, rest) = unpack_u64(rest)?;
+                let (token_b_amount, rest) = unpack_u64(rest)?;
+                let (min_mint_amount, rest) = unpack_u64(rest)?;
+                let (deadline, rest) = unpack_deadline(rest)?;
+                (
+                    Self::Deposit(DepositData {
+                        token_a_amount,
+                        token_b_amount,
+                        min_mint_amount,
+                        deadline,
+                    }),
+                    rest,
+                )
+            }
+            0x3 => {
+                let (pool_token_amount, rest) = unpack_u64(rest)?;
+                let (minimum_token_a_amount, rest) = unpack_u64(rest)?;
+                let (minimum_token_b_amount, rest) = unpack_u64(rest)?;
+                let (deadline, rest) = unpack_deadline(rest)?;
+                (
+                    Self::Withdraw(WithdrawData {
+                        pool_token_amount,
+                        minimum_token_a_amount,
+                        minimum_token_b_amount,
+                        deadline,
+                    }),
+                    rest,
+                )
+            }
+            0x4 => (Self::InitializeLiquidityProvider, rest),
+            0x5 => (Self::ClaimLiquidityRewards, rest),
+            0x6 => (Self::RefreshLiquidityObligation, rest),
+            0x7 => (Self::GetSwapAccounts, rest),
+            0x8 => {
+                let (pool, rest) = unpack_pubkey(rest)?;
+                (Self::GetClaimStatus(pool), rest)
+            }
+            0x9 => (Self::GetConfigStats, rest),
+            0xa => (Self::GetRewardApr, rest),
+            0xb => (Self::MigrateLiquidityProvider, rest),
+            0xc => {
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                (Self::GetSplitThreshold(swap_direction), rest)
+            }
+            0xd => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (minimum_amount_out, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                let (deadline, rest) = unpack_deadline(rest)?;
+                (
+                    Self::CheckSwapAllowed(SwapData {
+                        amount_in,
+                        minimum_amount_out,
+                        swap_direction,
+                        deadline,
+                    }),
+                    rest,
+                )
+            }
+            0xe => (Self::PreviewRewards, rest),
+            0xf => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                (
+                    Self::GetExecutionPrice(QuoteData {
+                        amount_in,
+                        swap_direction,
+                    }),
+                    rest,
+                )
+            }
+            0x10 => (Self::GetBalancedDepositRatio, rest),
+            0x11 => (Self::GetMultiplier, rest),
+            0x12 => (Self::GetTotalRewardsMinted, rest),
+            0x13 => {
+                let (pool_token_amount, rest) = unpack_u64(rest)?;
+                (Self::PreviewWithdraw(pool_token_amount), rest)
+            }
+            0x14 => (Self::GetPriceHealth, rest),
+            0x15 => (Self::GetPoolHealth, rest),
+            0x16 => {
+                let (pool_token_amount, rest) = unpack_u64(rest)?;
+                (Self::PreviewRedeem(pool_token_amount), rest)
+            }
+            0x17 => (Self::GetPoolAge, rest),
+            0x18 => {
+                let (amount_in, rest) = unpack_u64(rest)?;
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                (
+                    Self::GetRequiredSlippage(QuoteData {
+                        amount_in,
+                        swap_direction,
+                    }),
+                    rest,
+                )
+            }
+            0x19 => (Self::InitSwapCooldown, rest),
+            0x1a => {
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                (Self::GetArbAmountToOracle(swap_direction), rest)
+            }
+            0x1b => {
+                let (sample_amount, rest) = unpack_u64(rest)?;
+                let (new_fees, rest) = unpack_packed::<Fees>(rest)?;
+                (
+                    Self::PreviewFeeChange(PreviewFeeChangeData {
+                        sample_amount,
+                        new_fees,
+                    }),
+                    rest,
+                )
+            }
+            0x1c => {
+                let (swap_direction, rest) = unpack_swap_direction(rest)?;
+                (Self::GetMarginalPrice(swap_direction), rest)
+            }
+            0x1d => {
+                let (pool_token_amount, rest) = unpack_u64(rest)?;
+                let (minimum_token_a_amount, rest) = unpack_u64(rest)?;
+                let (minimum_token_b_amount, rest) = unpack_u64(rest)?;
+                let (deadline, rest) = unpack_deadline(rest)?;
+                (
+                    Self::WithdrawAndClaim(WithdrawData {
+                        pool_token_amount,
+                        minimum_token_a_amount,
+                        minimum_token_b_amount,
+                        deadline,
+                    }),
+                    rest,
+                )
+            }
+            0x1e => {
+                let (base_price_cumulative, rest) = unpack_u128(rest)?;
+                let (base_price_squared_cumulative, rest) = unpack_u128(rest)?;
+                let (block_timestamp, rest) = unpack_u64(rest)?;
+                (
+                    Self::GetRealizedVol(RealizedVolData {
+                        base_price_cumulative,
+                        base_price_squared_cumulative,
+                        block_timestamp,
+                    }),
+                    rest,
+                )
+            }
+            0x1f => (Self::GetImpliedPrices, rest),
+            0x20 => (Self::GetPriceSource, rest),
+            0x21 => (Self::GetMidPrice, rest),
+            0x22 => (Self::GetFeeApr, rest),
+            _ => return Err(SwapError::InvalidInstruction.into()),
+        };
+        if !rest.is_empty() {
+            return Err(SwapError::InstructionUnpackError.into());
+        }
+        Ok(instruction)
+    }
+
+    /// Packs a [SwapInstruction](enum.SwapInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(size_of::<Self>());
+        match *self {
+            Self::Initialize(InitializeData {
+                nonce,
+                slope,
+                mid_price,
+                is_open_twap,
+                creator_fee_bps,
+                invert_a,
+                invert_b,
+                slope_in_bps,
+            }) => {
+                buf.push(0x0);
+                buf.push(nonce);
+                buf.extend_from_slice(&slope.to_le_bytes());
+                buf.extend_from_slice(&mid_price.to_le_bytes());
+                buf.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
+                buf.extend_from_slice(&creator_fee_bps.to_le_bytes());
+                buf.extend_from_slice(&(invert_a as u8).to_le_bytes());
+                buf.extend_from_slice(&(invert_b as u8).to_le_bytes());
+                buf.extend_from_slice(&(slope_in_bps as u8).to_le_bytes());
+            }
+            Self::Swap(SwapData {
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                deadline,
+            }) => {
+                buf.push(0x1);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                pack_deadline(deadline, &mut buf);
+            }
+            Self::Deposit(DepositData {
+                token_a_amount,
+                token_b_amount,
+                min_mint_amount,
+                deadline,
+            }) => {
+                buf.push(0x2);
+                buf.extend_from_slice(&token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&token_b_amount.to_le_bytes());
+                buf.extend_from_slice(&min_mint_amount.to_le_bytes());
+                pack_deadline(deadline, &mut buf);
+            }
+            Self::Withdraw(WithdrawData {
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+                deadline,
+            }) => {
+                buf.push(0x3);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+                pack_deadline(deadline, &mut buf);
+            }
+            Self::InitializeLiquidityProvider => {
+                buf.push(0x4);
+            }
+            Self::ClaimLiquidityRewards => {
+                buf.push(0x5);
+            }
+            Self::RefreshLiquidityObligation => {
+                buf.push(0x6);
+            }
+            Self::GetSwapAccounts => {
+                buf.push(0x7);
+            }
+            Self::GetClaimStatus(pool) => {
+                buf.push(0x8);
+                buf.extend_from_slice(pool.as_ref());
+            }
+            Self::GetConfigStats => {
+                buf.push(0x9);
+            }
+            Self::GetRewardApr => {
+                buf.push(0xa);
+            }
+            Self::MigrateLiquidityProvider => {
+                buf.push(0xb);
+            }
+            Self::GetSplitThreshold(swap_direction) => {
+                buf.push(0xc);
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+            }
+            Self::CheckSwapAllowed(SwapData {
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                deadline,
+            }) => {
+                buf.push(0xd);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&minimum_amount_out.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+                pack_deadline(deadline, &mut buf);
+            }
+            Self::PreviewRewards => {
+                buf.push(0xe);
+            }
+            Self::GetExecutionPrice(QuoteData {
+                amount_in,
+                swap_direction,
+            }) => {
+                buf.push(0xf);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+            }
+            Self::GetBalancedDepositRatio => {
+                buf.push(0x10);
+            }
+            Self::GetMultiplier => {
+                buf.push(0x11);
+            }
+            Self::GetTotalRewardsMinted => {
+                buf.push(0x12);
+            }
+            Self::PreviewWithdraw(pool_token_amount) => {
+                buf.push(0x13);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+            }
+            Self::GetPriceHealth => {
+                buf.push(0x14);
+            }
+            Self::GetPoolHealth => {
+                buf.push(0x15);
+            }
+            Self::PreviewRedeem(pool_token_amount) => {
+                buf.push(0x16);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+            }
+            Self::GetPoolAge => {
+                buf.push(0x17);
+            }
+            Self::GetRequiredSlippage(QuoteData {
+                amount_in,
+                swap_direction,
+            }) => {
+                buf.push(0x18);
+                buf.extend_from_slice(&amount_in.to_le_bytes());
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+            }
+            Self::InitSwapCooldown => {
+                buf.push(0x19);
+            }
+            Self::GetArbAmountToOracle(swap_direction) => {
+                buf.push(0x1a);
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+            }
+            Self::PreviewFeeChange(PreviewFeeChangeData {
+                sample_amount,
+                ref new_fees,
+            }) => {
+                buf.push(0x1b);
+                buf.extend_from_slice(&sample_amount.to_le_bytes());
+                let mut fees_slice = [0u8; Fees::LEN];
+                Pack::pack_into_slice(new_fees, &mut fees_slice[..]);
+                buf.extend_from_slice(&fees_slice);
+            }
+            Self::GetMarginalPrice(swap_direction) => {
+                buf.push(0x1c);
+                buf.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+            }
+            Self::WithdrawAndClaim(WithdrawData {
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+                deadline,
+            }) => {
+                buf.push(0x1d);
+                buf.extend_from_slice(&pool_token_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+                buf.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+                pack_deadline(deadline, &mut buf);
+            }
+            Self::GetRealizedVol(RealizedVolData {
+                base_price_cumulative,
+                base_price_squared_cumulative,
+                block_timestamp,
+            }) => {
+                buf.push(0x1e);
+                buf.extend_from_slice(&base_price_cumulative.to_le_bytes());
+                buf.extend_from_slice(&base_price_squared_cumulative.to_le_bytes());
+                buf.extend_from_slice(&block_timestamp.to_le_bytes());
+            }
+            Self::GetImpliedPrices => {
+                buf.push(0x1f);
+            }
+            Self::GetPriceSource => {
+                buf.push(0x20);
+            }
+            Self::GetMidPrice => {
+                buf.push(0x21);
+            }
+            Self::GetFeeApr => {
+                buf.push(0x22);
+            }
+        }
+        buf
+    }
+}
+
+/// Creates an 'initialize' instruction.
+pub fn initialize(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    token_a_pubkey: Pubkey,
+    token_b_pubkey: Pubkey,
+    token_a_mint_pubkey: Pubkey,
+    token_b_mint_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    creator_pubkey: Pubkey,
+    init_data: InitializeData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Initialize(init_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(admin_fee_a_pubkey, false),
+        AccountMeta::new_readonly(admin_fee_b_pubkey, false),
+        AccountMeta::new_readonly(token_a_pubkey, false),
+        AccountMeta::new_readonly(token_b_pubkey, false),
+        AccountMeta::new_readonly(token_a_mint_pubkey, false),
+        AccountMeta::new_readonly(token_b_mint_pubkey, false),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(creator_pubkey, true),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'swap' instruction.
+pub fn swap(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    market_authority_pubkey: Pubkey,
+    swap_authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    swap_source_pubkey: Pubkey,
+    swap_destination_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    reward_token_pubkey: Pubkey,
+    reward_mint_pubkey: Pubkey,
+    admin_fee_destination_pubkey: Pubkey,
+    secondary_admin_fee_destination_pubkey: Pubkey,
+    creator_fee_destination_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    swap_data: SwapData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Swap(swap_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(market_authority_pubkey, false),
+        AccountMeta::new_readonly(swap_authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(swap_source_pubkey, false),
+        AccountMeta::new(swap_destination_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new(reward_token_pubkey, false),
+        AccountMeta::new(reward_mint_pubkey, false),
+        AccountMeta::new(admin_fee_destination_pubkey, false),
+        AccountMeta::new(secondary_admin_fee_destination_pubkey, false),
+        AccountMeta::new(creator_fee_destination_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'deposit' instruction.
+pub fn deposit(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    deposit_token_a_pubkey: Pubkey,
+    deposit_token_b_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    destination_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    deposit_data: DepositData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Deposit(deposit_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(deposit_token_a_pubkey, false),
+        AccountMeta::new(deposit_token_b_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(destination_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw' instruction.
+pub fn withdraw(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    destination_token_a_pubkey: Pubkey,
+    destination_token_b_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    secondary_admin_fee_a_pubkey: Pubkey,
+    secondary_admin_fee_b_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    withdraw_data: WithdrawData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::Withdraw(withdraw_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(destination_token_a_pubkey, false),
+        AccountMeta::new(destination_token_b_pubkey, false),
+        AccountMeta::new(admin_fee_a_pubkey, false),
+        AccountMeta::new(admin_fee_b_pubkey, false),
+        AccountMeta::new(secondary_admin_fee_a_pubkey, false),
+        AccountMeta::new(secondary_admin_fee_b_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a 'withdraw_and_claim' instruction.
+pub fn withdraw_and_claim(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    authority_pubkey: Pubkey,
+    user_transfer_authority_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    source_pubkey: Pubkey,
+    swap_token_a_pubkey: Pubkey,
+    swap_token_b_pubkey: Pubkey,
+    destination_token_a_pubkey: Pubkey,
+    destination_token_b_pubkey: Pubkey,
+    admin_fee_a_pubkey: Pubkey,
+    admin_fee_b_pubkey: Pubkey,
+    secondary_admin_fee_a_pubkey: Pubkey,
+    secondary_admin_fee_b_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    market_authority_pubkey: Pubkey,
+    claim_destination_pubkey: Pubkey,
+    claim_mint_pubkey: Pubkey,
+    withdraw_data: WithdrawData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::WithdrawAndClaim(withdraw_data).pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new(swap_pubkey, false),
+        AccountMeta::new_readonly(authority_pubkey, false),
+        AccountMeta::new_readonly(user_transfer_authority_pubkey, true),
+        AccountMeta::new(pool_mint_pubkey, false),
+        AccountMeta::new(source_pubkey, false),
+        AccountMeta::new(swap_token_a_pubkey, false),
+        AccountMeta::new(swap_token_b_pubkey, false),
+        AccountMeta::new(destination_token_a_pubkey, false),
+        AccountMeta::new(destination_token_b_pubkey, false),
+        AccountMeta::new(admin_fee_a_pubkey, false),
+        AccountMeta::new(admin_fee_b_pubkey, false),
+        AccountMeta::new(secondary_admin_fee_a_pubkey, false),
+        AccountMeta::new(secondary_admin_fee_b_pubkey, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(market_authority_pubkey, false),
+        AccountMeta::new(claim_destination_pubkey, false),
+        AccountMeta::new(claim_mint_pubkey, false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates `InitializeLiquidityProvider` instruction
+pub fn init_liquidity_provider(
+    program_id: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitializeLiquidityProvider.pack();
+
+    let accounts = vec![
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `ClaimLiquidityRewards` instruction
+pub fn claim_liquidity_rewards(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    market_authority_info: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    claim_destination_pubkey: Pubkey,
+    claim_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::ClaimLiquidityRewards.pack();
+
+    let accounts = vec![
+        AccountMeta::new(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(market_authority_info, false),
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new(claim_destination_pubkey, false),
+        AccountMeta::new(claim_mint_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `RefreshLiquidityObligation` instruction
+pub fn refresh_liquidity_obligation(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    liquidity_provider_pubkeys: Vec<Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::RefreshLiquidityObligation.pack();
+
+    let mut accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+    accounts.extend(
+        liquidity_provider_pubkeys
+            .into_iter()
+            .map(|pubkey| AccountMeta::new(pubkey, false)),
+    );
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetSwapAccounts` instruction
+pub fn get_swap_accounts(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetSwapAccounts.pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetClaimStatus` instruction
+pub fn get_claim_status(
+    program_id: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    pool_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetClaimStatus(pool_pubkey).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetConfigStats` instruction
+pub fn get_config_stats(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetConfigStats.pack();
+
+    let accounts = vec![AccountMeta::new_readonly(config_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetRewardApr` instruction
+pub fn get_reward_apr(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetRewardApr.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `MigrateLiquidityProvider` instruction
+pub fn migrate_liquidity_provider(
+    program_id: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+    liquidity_owner_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::MigrateLiquidityProvider.pack();
+
+    let accounts = vec![
+        AccountMeta::new(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(liquidity_owner_pubkey, true),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetSplitThreshold` instruction
+pub fn get_split_threshold(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    swap_direction: SwapDirection,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetSplitThreshold(swap_direction).pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `CheckSwapAllowed` instruction
+pub fn check_swap_allowed(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    swap_direction: SwapDirection,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::CheckSwapAllowed(SwapData {
+        amount_in,
+        minimum_amount_out,
+        swap_direction,
+        deadline: None,
+    })
+    .pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `PreviewRewards` instruction
+pub fn preview_rewards(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::PreviewRewards.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(liquidity_provider_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetExecutionPrice` instruction
+pub fn get_execution_price(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetExecutionPrice(QuoteData {
+        amount_in,
+        swap_direction,
+    })
+    .pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetBalancedDepositRatio` instruction
+pub fn get_balanced_deposit_ratio(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetBalancedDepositRatio.pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetMultiplier` instruction
+pub fn get_multiplier(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetMultiplier.pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetTotalRewardsMinted` instruction
+pub fn get_total_rewards_minted(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetTotalRewardsMinted.pack();
+
+    let accounts = vec![AccountMeta::new_readonly(config_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `PreviewWithdraw` instruction
+pub fn preview_withdraw(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    pool_token_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::PreviewWithdraw(pool_token_amount).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetPriceHealth` instruction
+pub fn get_price_health(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetPriceHealth.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetPoolHealth` instruction
+pub fn get_pool_health(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetPoolHealth.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `PreviewRedeem` instruction
+pub fn preview_redeem(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pool_mint_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    pool_token_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::PreviewRedeem(pool_token_amount).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pool_mint_pubkey, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetPoolAge` instruction
+pub fn get_pool_age(program_id: Pubkey, swap_pubkey: Pubkey) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetPoolAge.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetRequiredSlippage` instruction
+pub fn get_required_slippage(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetRequiredSlippage(QuoteData {
+        amount_in,
+        swap_direction,
+    })
+    .pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `InitSwapCooldown` instruction
+pub fn init_swap_cooldown(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    user_pubkey: Pubkey,
+    payer_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::InitSwapCooldown.pack();
+    let (swap_cooldown_pubkey, _) = swap_cooldown_address(&program_id, &swap_pubkey, &user_pubkey);
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new(swap_cooldown_pubkey, false),
+        AccountMeta::new_readonly(user_pubkey, false),
+        AccountMeta::new(payer_pubkey, true),
+        AccountMeta::new_readonly(rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetArbAmountToOracle` instruction
+pub fn get_arb_amount_to_oracle(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+    swap_direction: SwapDirection,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetArbAmountToOracle(swap_direction).pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `PreviewFeeChange` instruction
+pub fn preview_fee_change(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    sample_amount: u64,
+    new_fees: Fees,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::PreviewFeeChange(PreviewFeeChangeData {
+        sample_amount,
+        new_fees,
+    })
+    .pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetMarginalPrice` instruction
+pub fn get_marginal_price(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    swap_direction: SwapDirection,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetMarginalPrice(swap_direction).pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetRealizedVol` instruction
+pub fn get_realized_vol(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+    realized_vol_data: RealizedVolData,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetRealizedVol(realized_vol_data).pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetImpliedPrices` instruction
+pub fn get_implied_prices(
+    program_id: Pubkey,
+    swap_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetImpliedPrices.pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetPriceSource` instruction
+pub fn get_price_source(
+    program_id: Pubkey,
+    config_pubkey: Pubkey,
+    swap_pubkey: Pubkey,
+    pyth_a_pubkey: Pubkey,
+    pyth_b_pubkey: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetPriceSource.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(config_pubkey, false),
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(pyth_a_pubkey, false),
+        AccountMeta::new_readonly(pyth_b_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetMidPrice` instruction
+pub fn get_mid_price(program_id: Pubkey, swap_pubkey: Pubkey) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetMidPrice.pack();
+
+    let accounts = vec![AccountMeta::new_readonly(swap_pubkey, false)];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+/// Creates `GetFeeApr` instruction
+pub fn get_fee_apr(program_id: Pubkey, swap_pubkey: Pubkey) -> Result<Instruction, ProgramError> {
+    let data = SwapInstruction::GetFeeApr.pack();
+
+    let accounts = vec![
+        AccountMeta::new_readonly(swap_pubkey, false),
+        AccountMeta::new_readonly(clock::id(), false),
+    ];
+
+    Ok(Instruction {
+        program_id,
+        data,
+        accounts,
+    })
+}
+
+fn unpack_u128(input: &[u8]) -> Result<(u128, &[u8]), ProgramError> {
+    if input.len() < 16 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (amount, rest) = input.split_at(16);
+    let amount = amount
+        .get(..16)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u128::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((amount, rest))
+}
+
+fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), ProgramError> {
+    if input.len() < 8 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (amount, rest) = input.split_at(8);
+    let amount = amount
+        .get(..8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(i64::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((amount, rest))
+}
+
+fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), ProgramError> {
+    if input.len() < 2 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (amount, rest) = input.split_at(2);
+    let amount = amount
+        .get(..2)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u16::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((amount, rest))
+}
+
+/// Unpacks a trailing deadline, absent in instructions built before it existed
+fn unpack_deadline(input: &[u8]) -> Result<(Option<i64>, &[u8]), ProgramError> {
+    if input.is_empty() {
+        return Ok((None, input));
+    }
+    let (deadline, rest) = unpack_i64(input)?;
+    Ok((Some(deadline), rest))
+}
+
+fn pack_deadline(deadline: Option<i64>, buf: &mut Vec<u8>) {
+    if let Some(deadline) = deadline {
+        buf.extend_from_slice(&deadline.to_le_bytes());
+    }
+}
+
+fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), ProgramError> {
+    if input.len() < 8 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (amount, rest) = input.split_at(8);
+    let amount = amount
+        .get(..8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((amount, rest))
+}
+
+fn unpack_u8(input: &[u8]) -> Result<(u8, &[u8]), ProgramError> {
+    if input.is_empty() {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (bytes, rest) = input.split_at(1);
+    let value = bytes
+        .get(..1)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u8::from_le_bytes)
+        .ok_or(SwapError::InstructionUnpackError)?;
+    Ok((value, rest))
+}
+
+fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), ProgramError> {
+    let (value, rest) = unpack_u8(input)?;
+    let value = match u8::from_le(value) {
+        0 => false,
+        1 => true,
+        _ => return Err(SwapError::InstructionUnpackError.into()),
+    };
+    Ok((value, rest))
+}
+
+fn unpack_swap_direction(input: &[u8]) -> Result<(SwapDirection, &[u8]), ProgramError> {
+    let (value, rest) = unpack_u8(input)?;
+    let value = match u8::from_le(value) {
+        0 => SwapDirection::SellBase,
+        1 => SwapDirection::SellQuote,
+        _ => return Err(SwapError::InstructionUnpackError.into()),
+    };
+    Ok((value, rest))
+}
+
+#[allow(dead_code)]
+fn unpack_bytes32(input: &[u8]) -> Result<(&[u8; 32], &[u8]), ProgramError> {
+    if input.len() < 32 {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (bytes, rest) = input.split_at(32);
+    Ok((
+        bytes
+            .try_into()
+            .map_err(|_| SwapError::InstructionUnpackError)?,
+        rest,
+    ))
+}
+
+#[allow(dead_code)]
+fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
+    if input.len() < PUBKEY_BYTES {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (key, rest) = input.split_at(PUBKEY_BYTES);
+    let pk = Pubkey::new(key);
+    Ok((pk, rest))
+}
+
+fn unpack_packed<T: Pack>(input: &[u8]) -> Result<(T, &[u8]), ProgramError> {
+    if input.len() < T::LEN {
+        return Err(SwapError::InstructionUnpackError.into());
+    }
+    let (packed, rest) = input.split_at(T::LEN);
+    Ok((T::unpack_unchecked(packed)?, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        curve::{default_market_price, default_slope},
+        state::{DEFAULT_TEST_FEES, DEFAULT_TEST_REWARDS},
+    };
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_pack_admin_init_config() {
+        let fees = DEFAULT_TEST_FEES;
+        let rewards = DEFAULT_TEST_REWARDS;
+        let check = AdminInstruction::Initialize(AdminInitializeData {
+            fees: fees.clone(),
+            rewards: rewards.clone(),
+        });
+        let packed = check.pack();
+        let mut expect = vec![100];
+        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
+        expect.push(fees.trade_fee_enabled as u8);
+        expect.push(fees.withdraw_fee_enabled as u8);
+        expect.extend_from_slice(&fees.min_fee.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.deposit_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.deposit_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.deposit_reward_cap.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_new_fees() {
+        let fees = DEFAULT_TEST_FEES;
+        let check = AdminInstruction::SetNewFees(fees.clone());
+        let packed = check.pack();
+        let mut expect = vec![105];
+        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
+        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
+        expect.push(fees.trade_fee_enabled as u8);
+        expect.push(fees.withdraw_fee_enabled as u8);
+        expect.extend_from_slice(&fees.min_fee.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_batch_set_fees() {
+        let fees = DEFAULT_TEST_FEES;
+        let check = AdminInstruction::BatchSetFees(fees.clone());
+        let packed = check.pack();
+        let mut expect = vec![130];
+        let mut fees_slice = [0u8; Fees::LEN];
+        fees.pack_into_slice(&mut fees_slice);
+        expect.extend_from_slice(&fees_slice);
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_top_up_reward_budget() {
+        let amount: u64 = 1_000_000_000;
+        let check = AdminInstruction::TopUpRewardBudget(amount);
+        let packed = check.pack();
+        let mut expect = vec![131];
+        expect.extend_from_slice(&amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_new_rewards() {
+        let rewards = DEFAULT_TEST_REWARDS;
+        let check = AdminInstruction::SetNewRewards(rewards.clone());
+        let packed = check.pack();
+        let mut expect = vec![106];
+        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.deposit_reward_numerator.to_le_bytes());
+        expect.extend_from_slice(&rewards.deposit_reward_denominator.to_le_bytes());
+        expect.extend_from_slice(&rewards.deposit_reward_cap.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_set_pause_flags() {
+        let mut pause_flags = PauseFlags::DEPOSITS;
+        pause_flags.insert(PauseFlags::WITHDRAWALS);
+        let check = AdminInstruction::SetPauseFlags(pause_flags);
+        let packed = check.pack();
+        let expect = vec![107, 0b110];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        assert_eq!(
+            AdminInstruction::unpack(&[107, 0b1000]).unwrap_err(),
+            SwapError::InstructionUnpackError.into()
+        );
+    }
+
+    #[test]
+    fn test_pack_admin_set_targets() {
+        let base_target: u128 = 1_000_000_000_000;
+        let quote_target: u128 = 20_000_000_000_000;
+        let check = AdminInstruction::SetTargets(SetTargetsData {
+            base_target,
+            quote_target,
+        });
+        let packed = check.pack();
+        let mut expect = vec![108];
+        expect.extend_from_slice(&base_target.to_le_bytes());
+        expect.extend_from_slice(&quote_target.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_admin_drain() {
+        let check = AdminInstruction::ProposeDrain;
+        let packed = check.pack();
+        let expect = vec![109];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        let check = AdminInstruction::ExecuteDrain;
+        let packed = check.pack();
+        let expect = vec![110];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_min_claim_amount() {
+        let min_claim_amount: u64 = 1_000;
+        let check = AdminInstruction::SetMinClaimAmount(min_claim_amount);
+        let packed = check.pack();
+        let mut expect = vec![111];
+        expect.extend_from_slice(&min_claim_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_admin_split() {
+        let admin_split_bps: u16 = 7_500;
+        let check = AdminInstruction::SetAdminSplit(admin_split_bps);
+        let packed = check.pack();
+        let mut expect = vec![112];
+        expect.extend_from_slice(&admin_split_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_min_pool_supply() {
+        let min_pool_supply: u64 = 1_000_000;
+        let check = AdminInstruction::SetMinPoolSupply(min_pool_supply);
+        let packed = check.pack();
+        let mut expect = vec![113];
+        expect.extend_from_slice(&min_pool_supply.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_min_reward_liquidity() {
+        let min_reward_liquidity: u64 = 1_000_000;
+        let check = AdminInstruction::SetMinRewardLiquidity(min_reward_liquidity);
+        let packed = check.pack();
+        let mut expect = vec![114];
+        expect.extend_from_slice(&min_reward_liquidity.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_reward_mint() {
+        let deltafi_mint = Pubkey::new_unique();
+        let check = AdminInstruction::SetRewardMint(deltafi_mint);
+        let packed = check.pack();
+        let mut expect = vec![115];
+        expect.extend_from_slice(deltafi_mint.as_ref());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_max_oracle_move() {
+        let max_oracle_move_bps: u16 = 1_000;
+        let check = AdminInstruction::SetMaxOracleMove(max_oracle_move_bps);
+        let packed = check.pack();
+        let mut expect = vec![117];
+        expect.extend_from_slice(&max_oracle_move_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_seed_position() {
+        let liquidity_amount: u64 = 2_000_000_000;
+        let rewards_owed: u64 = 500;
+        let rewards_estimated: u64 = 300;
+        let cumulative_interest: u128 = 1_000;
+        let last_update_ts: i64 = 1_000_000;
+        let next_claim_ts: i64 = 3_592_000;
+        let check = AdminInstruction::SeedPosition(SeedPositionData {
+            liquidity_amount,
+            rewards_owed,
+            rewards_estimated,
+            cumulative_interest,
+            last_update_ts,
+            next_claim_ts,
+        });
+        let packed = check.pack();
+        let mut expect = vec![118];
+        expect.extend_from_slice(&liquidity_amount.to_le_bytes());
+        expect.extend_from_slice(&rewards_owed.to_le_bytes());
+        expect.extend_from_slice(&rewards_estimated.to_le_bytes());
+        expect.extend_from_slice(&cumulative_interest.to_le_bytes());
+        expect.extend_from_slice(&last_update_ts.to_le_bytes());
+        expect.extend_from_slice(&next_claim_ts.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_price_tick() {
+        let price_tick: u128 = 10_000_000;
+        let check = AdminInstruction::SetPriceTick(price_tick);
+        let packed = check.pack();
+        let mut expect = vec![119];
+        expect.extend_from_slice(&price_tick.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_resync_claim_schedule() {
+        let check = AdminInstruction::ResyncClaimSchedule;
+        let packed = check.pack();
+        let expect = vec![120];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_max_trade_amount() {
+        let max_trade_amount: u64 = 5_000_000_000;
+        let check = AdminInstruction::SetMaxTradeAmount(max_trade_amount);
+        let packed = check.pack();
+        let mut expect = vec![121];
+        expect.extend_from_slice(&max_trade_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_sync_reserves() {
+        let check = AdminInstruction::SyncReserves;
+        let packed = check.pack();
+        let expect = vec![124];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_oracle_free() {
+        let check = AdminInstruction::SetOracleFree(true);
+        let packed = check.pack();
+        let expect = vec![126, 1];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_manual_price() {
+        let market_price: u128 = 52_000_000_000;
+        let check = AdminInstruction::SetManualPrice(market_price);
+        let packed = check.pack();
+        let mut expect = vec![127];
+        expect.extend_from_slice(&market_price.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_allowed_token_program() {
+        let allowed_token_program_raw = [4u8; 32];
+        let check = AdminInstruction::SetAllowedTokenProgram(Pubkey::new_from_array(
+            allowed_token_program_raw,
+        ));
+        let packed = check.pack();
+        let mut expect = vec![128];
+        expect.extend_from_slice(&allowed_token_program_raw);
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_max_ema_divergence() {
+        let max_ema_divergence_bps: u16 = 250;
+        let check = AdminInstruction::SetMaxEmaDivergence(max_ema_divergence_bps);
+        let packed = check.pack();
+        let mut expect = vec![129];
+        expect.extend_from_slice(&max_ema_divergence_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_max_allowed_slippage() {
+        let max_allowed_slippage_bps: u64 = 500;
+        let check = AdminInstruction::SetMaxAllowedSlippage(max_allowed_slippage_bps);
+        let packed = check.pack();
+        let mut expect = vec![125];
+        expect.extend_from_slice(&max_allowed_slippage_bps.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_swap_cooldown() {
+        let swap_cooldown_seconds: u64 = 30;
+        let check = AdminInstruction::SetSwapCooldown(swap_cooldown_seconds);
+        let packed = check.pack();
+        let mut expect = vec![123];
+        expect.extend_from_slice(&swap_cooldown_seconds.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_set_strict_claim_destination() {
+        let check = AdminInstruction::SetStrictClaimDestination(true);
+        let packed = check.pack();
+        let expect = vec![122, 1];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_close_swap() {
+        let check = AdminInstruction::CloseSwap;
+        let packed = check.pack();
+        let expect = vec![116];
+        assert_eq!(packed, expect);
+        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_swap_initialization() {
+        let nonce: u8 = 255;
+        let slope: u64 = default_slope().to_scaled_val().unwrap().try_into().unwrap();
+        let mid_price = default_market_price().to_scaled_val().unwrap();
+        let is_open_twap = true;
+        let creator_fee_bps: u16 = 2_000;
+        let invert_a = false;
+        let invert_b = true;
+        let slope_in_bps = false;
+        let check = SwapInstruction::Initialize(InitializeData {
+            nonce,
+            slope,
+            mid_price,
+            is_open_twap,
+            creator_fee_bps,
+            invert_a,
+            invert_b,
+            slope_in_bps,
+        });
+        let packed = check.pack();
+        let mut expect = vec![0];
+        expect.extend_from_slice(&nonce.to_le_bytes());
+        expect.extend_from_slice(&slope.to_le_bytes());
+        expect.extend_from_slice(&mid_price.to_le_bytes());
+        expect.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
+        expect.extend_from_slice(&creator_fee_bps.to_le_bytes());
+        expect.extend_from_slice(&(invert_a as u8).to_le_bytes());
+        expect.extend_from_slice(&(invert_b as u8).to_le_bytes());
+        expect.extend_from_slice(&(slope_in_bps as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_swap() {
+        let amount_in: u64 = 1_000_000;
+        let minimum_amount_out: u64 = 500_000;
+        let swap_direction: SwapDirection = SwapDirection::SellBase;
+        let check = SwapInstruction::Swap(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            deadline: None,
+        });
+        let packed = check.pack();
+        let mut expect = vec![1];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_deadline() {
+        let deadline: i64 = 1_650_000_000;
+        let check = SwapInstruction::Swap(SwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 500_000,
+            swap_direction: SwapDirection::SellQuote,
+            deadline: Some(deadline),
+        });
+        let packed = check.pack();
+        let mut expect = vec![1];
+        expect.extend_from_slice(&1_000_000u64.to_le_bytes());
+        expect.extend_from_slice(&500_000u64.to_le_bytes());
+        expect.extend_from_slice(&(SwapDirection::SellQuote as u8).to_le_bytes());
+        expect.extend_from_slice(&deadline.to_le_bytes());
+        assert_eq!(packed, expect);
+        assert_eq!(SwapInstruction::unpack(&expect).unwrap(), check);
+
+        let check = SwapInstruction::Deposit(DepositData {
+            token_a_amount: 1_000_000,
+            token_b_amount: 2_000_000,
+            min_mint_amount: 3_000_000,
+            deadline: Some(deadline),
+        });
+        let packed = check.pack();
+        assert_eq!(packed.len(), 1 + 3 * 8 + 8);
+        assert_eq!(packed[25..], deadline.to_le_bytes());
+        assert_eq!(SwapInstruction::unpack(&packed).unwrap(), check);
+
+        let check = SwapInstruction::Withdraw(WithdrawData {
+            pool_token_amount: 1_000_000,
+            minimum_token_a_amount: 2_000_000,
+            minimum_token_b_amount: 3_000_000,
+            deadline: Some(deadline),
+        });
+        let packed = check.pack();
+        assert_eq!(packed[25..], deadline.to_le_bytes());
+        assert_eq!(SwapInstruction::unpack(&packed).unwrap(), check);
+
+        // a partial deadline is malformed rather than absent
+        assert_eq!(
+            SwapInstruction::unpack(&packed[..29]),
+            Err(SwapError::InstructionUnpackError.into())
+        );
+    }
+
+    #[test]
+    fn test_pack_deposit() {
+        let token_a_amount: u64 = 1_000_000;
+        let token_b_amount: u64 = 500_000;
+        let min_mint_amount: u64 = 500_000;
+        let check = SwapInstruction::Deposit(DepositData {
+            token_a_amount,
+            token_b_amount,
+            min_mint_amount,
+            deadline: None,
+        });
+        let packed = check.pack();
+        let mut expect = vec![2];
+        expect.extend_from_slice(&token_a_amount.to_le_bytes());
+        expect.extend_from_slice(&token_b_amount.to_le_bytes());
+        expect.extend_from_slice(&min_mint_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_swap_accounts() {
+        let check = SwapInstruction::GetSwapAccounts;
+        let packed = check.pack();
+        let expect = vec![7];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_claim_status() {
+        let pool_key_raw = [2u8; 32];
+        let check = SwapInstruction::GetClaimStatus(Pubkey::new_from_array(pool_key_raw));
+        let packed = check.pack();
+        let mut expect = vec![8];
+        expect.extend_from_slice(&pool_key_raw);
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_config_stats() {
+        let check = SwapInstruction::GetConfigStats;
+        let packed = check.pack();
+        let expect = vec![9];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_reward_apr() {
+        let check = SwapInstruction::GetRewardApr;
+        let packed = check.pack();
+        let expect = vec![10];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_migrate_liquidity_provider() {
+        let check = SwapInstruction::MigrateLiquidityProvider;
+        let packed = check.pack();
+        let expect = vec![11];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_split_threshold() {
+        let check = SwapInstruction::GetSplitThreshold(SwapDirection::SellQuote);
+        let packed = check.pack();
+        let expect = vec![12, 1];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_check_swap_allowed() {
+        let amount_in: u64 = 1_000_000;
+        let minimum_amount_out: u64 = 500_000;
+        let swap_direction = SwapDirection::SellQuote;
+        let check = SwapInstruction::CheckSwapAllowed(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            deadline: None,
+        });
+        let packed = check.pack();
+        let mut expect = vec![13];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_preview_rewards() {
+        let check = SwapInstruction::PreviewRewards;
+        let packed = check.pack();
+        let expect = vec![14];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_execution_price() {
+        let amount_in: u64 = 1_000_000;
+        let swap_direction = SwapDirection::SellBase;
+        let check = SwapInstruction::GetExecutionPrice(QuoteData {
+            amount_in,
+            swap_direction,
+        });
+        let packed = check.pack();
+        let mut expect = vec![15];
+        expect.extend_from_slice(&amount_in.to_le_bytes());
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_balanced_deposit_ratio() {
+        let check = SwapInstruction::GetBalancedDepositRatio;
+        let packed = check.pack();
+        let expect = vec![16];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_multiplier() {
+        let check = SwapInstruction::GetMultiplier;
+        let packed = check.pack();
+        let expect = vec![17];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_total_rewards_minted() {
+        let check = SwapInstruction::GetTotalRewardsMinted;
+        let packed = check.pack();
+        let expect = vec![18];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_price_health() {
+        let check = SwapInstruction::GetPriceHealth;
+        let packed = check.pack();
+        let expect = vec![20];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_pool_health() {
+        let check = SwapInstruction::GetPoolHealth;
+        let packed = check.pack();
+        let expect = vec![21];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_preview_redeem() {
+        let pool_token_amount: u64 = 1_000_000;
+        let check = SwapInstruction::PreviewRedeem(pool_token_amount);
+        let packed = check.pack();
+        let mut expect = vec![22];
+        expect.extend_from_slice(&pool_token_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
 
-fn unpack_swap_direction(input: &[u8]) -> Result<(SwapDirection, &[u8]), ProgramError> {
-    let (value, rest) = unpack_u8(input)?;
-    let value = match u8::from_le(value) {
-        0 => SwapDirection::SellBase,
-        1 => SwapDirection::SellQuote,
-        _ => return Err(SwapError::InstructionUnpackError.into()),
-    };
-    Ok((value, rest))
-}
+    #[test]
+    fn test_pack_get_pool_age() {
+        let check = SwapInstruction::GetPoolAge;
+        let packed = check.pack();
+        let expect = vec![23];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
 
-#[allow(dead_code)]
-fn unpack_bytes32(input: &[u8]) -> Result<(&[u8; 32], &[u8]), ProgramError> {
-    if input.len() < 32 {
-        return Err(SwapError::InstructionUnpackError.into());
+    #[test]
+    fn test_pack_init_swap_cooldown() {
+        let check = SwapInstruction::InitSwapCooldown;
+        let packed = check.pack();
+        let expect = vec![25];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
     }
-    let (bytes, rest) = input.split_at(32);
-    Ok((
-        bytes
-            .try_into()
-            .map_err(|_| SwapError::InstructionUnpackError)?,
-        rest,
-    ))
-}
 
-#[allow(dead_code)]
-fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), ProgramError> {
-    if input.len() < PUBKEY_BYTES {
-        return Err(SwapError::InstructionUnpackError.into());
+    #[test]
+    fn test_pack_get_arb_amount_to_oracle() {
+        let swap_direction = SwapDirection::SellBase;
+        let check = SwapInstruction::GetArbAmountToOracle(swap_direction);
+        let packed = check.pack();
+        let mut expect = vec![26];
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
     }
-    let (key, rest) = input.split_at(PUBKEY_BYTES);
-    let pk = Pubkey::new(key);
-    Ok((pk, rest))
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        curve::{default_market_price, default_slope},
-        state::{DEFAULT_TEST_FEES, DEFAULT_TEST_REWARDS},
-    };
+    #[test]
+    fn test_pack_get_marginal_price() {
+        let swap_direction = SwapDirection::SellQuote;
+        let check = SwapInstruction::GetMarginalPrice(swap_direction);
+        let packed = check.pack();
+        let mut expect = vec![28];
+        expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
 
     #[test]
-    fn test_pack_admin_init_config() {
-        let fees = DEFAULT_TEST_FEES;
-        let rewards = DEFAULT_TEST_REWARDS;
-        let check = AdminInstruction::Initialize(AdminInitializeData {
-            fees: fees.clone(),
-            rewards: rewards.clone(),
+    fn test_pack_get_realized_vol() {
+        let base_price_cumulative: u128 = 36_000_000_000_000;
+        let base_price_squared_cumulative: u128 = 360_000_000_000_000;
+        let block_timestamp: u64 = 1_650_000_000;
+        let check = SwapInstruction::GetRealizedVol(RealizedVolData {
+            base_price_cumulative,
+            base_price_squared_cumulative,
+            block_timestamp,
         });
         let packed = check.pack();
-        let mut expect = vec![100];
-        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        let mut expect = vec![30];
+        expect.extend_from_slice(&base_price_cumulative.to_le_bytes());
+        expect.extend_from_slice(&base_price_squared_cumulative.to_le_bytes());
+        expect.extend_from_slice(&block_timestamp.to_le_bytes());
         assert_eq!(packed, expect);
-        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
 
     #[test]
-    fn test_pack_admin_set_new_fees() {
-        let fees = DEFAULT_TEST_FEES;
-        let check = AdminInstruction::SetNewFees(fees.clone());
+    fn test_pack_get_implied_prices() {
+        let check = SwapInstruction::GetImpliedPrices;
         let packed = check.pack();
-        let mut expect = vec![105];
-        expect.extend_from_slice(&fees.admin_trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.admin_withdraw_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
-        expect.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
+        let expect = vec![31];
         assert_eq!(packed, expect);
-        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
 
     #[test]
-    fn test_pack_admin_set_new_rewards() {
-        let rewards = DEFAULT_TEST_REWARDS;
-        let check = AdminInstruction::SetNewRewards(rewards.clone());
+    fn test_pack_get_price_source() {
+        let check = SwapInstruction::GetPriceSource;
         let packed = check.pack();
-        let mut expect = vec![106];
-        expect.extend_from_slice(&rewards.trade_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_denominator.to_le_bytes());
-        expect.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
-        expect.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        let expect = vec![32];
         assert_eq!(packed, expect);
-        let unpacked = AdminInstruction::unpack(&expect).unwrap();
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
 
     #[test]
-    fn test_pack_swap_initialization() {
-        let nonce: u8 = 255;
-        let slope: u64 = default_slope().to_scaled_val().unwrap().try_into().unwrap();
-        let mid_price = default_market_price().to_scaled_val().unwrap();
-        let is_open_twap = true;
-        let check = SwapInstruction::Initialize(InitializeData {
-            nonce,
-            slope,
-            mid_price,
-            is_open_twap,
+    fn test_pack_get_mid_price() {
+        let check = SwapInstruction::GetMidPrice;
+        let packed = check.pack();
+        let expect = vec![33];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_get_fee_apr() {
+        let check = SwapInstruction::GetFeeApr;
+        let packed = check.pack();
+        let expect = vec![34];
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn test_pack_preview_fee_change() {
+        let sample_amount: u64 = 1_000_000;
+        let new_fees = DEFAULT_TEST_FEES;
+        let check = SwapInstruction::PreviewFeeChange(PreviewFeeChangeData {
+            sample_amount,
+            new_fees: new_fees.clone(),
         });
         let packed = check.pack();
-        let mut expect = vec![0];
-        expect.extend_from_slice(&nonce.to_le_bytes());
-        expect.extend_from_slice(&slope.to_le_bytes());
-        expect.extend_from_slice(&mid_price.to_le_bytes());
-        expect.extend_from_slice(&(is_open_twap as u8).to_le_bytes());
+        let mut expect = vec![27];
+        expect.extend_from_slice(&sample_amount.to_le_bytes());
+        let mut fees_slice = [0u8; Fees::LEN];
+        new_fees.pack_into_slice(&mut fees_slice[..]);
+        expect.extend_from_slice(&fees_slice);
         assert_eq!(packed, expect);
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
 
     #[test]
-    fn test_pack_swap() {
+    fn test_pack_get_required_slippage() {
         let amount_in: u64 = 1_000_000;
-        let minimum_amount_out: u64 = 500_000;
-        let swap_direction: SwapDirection = SwapDirection::SellBase;
-        let check = SwapInstruction::Swap(SwapData {
+        let swap_direction = SwapDirection::SellQuote;
+        let check = SwapInstruction::GetRequiredSlippage(QuoteData {
             amount_in,
-            minimum_amount_out,
             swap_direction,
         });
         let packed = check.pack();
-        let mut expect = vec![1];
+        let mut expect = vec![24];
         expect.extend_from_slice(&amount_in.to_le_bytes());
-        expect.extend_from_slice(&minimum_amount_out.to_le_bytes());
         expect.extend_from_slice(&(swap_direction as u8).to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
@@ -1091,20 +4125,12 @@ mod tests {
     }
 
     #[test]
-    fn test_pack_deposit() {
-        let token_a_amount: u64 = 1_000_000;
-        let token_b_amount: u64 = 500_000;
-        let min_mint_amount: u64 = 500_000;
-        let check = SwapInstruction::Deposit(DepositData {
-            token_a_amount,
-            token_b_amount,
-            min_mint_amount,
-        });
+    fn test_pack_preview_withdraw() {
+        let pool_token_amount: u64 = 1_000_000;
+        let check = SwapInstruction::PreviewWithdraw(pool_token_amount);
         let packed = check.pack();
-        let mut expect = vec![2];
-        expect.extend_from_slice(&token_a_amount.to_le_bytes());
-        expect.extend_from_slice(&token_b_amount.to_le_bytes());
-        expect.extend_from_slice(&min_mint_amount.to_le_bytes());
+        let mut expect = vec![19];
+        expect.extend_from_slice(&pool_token_amount.to_le_bytes());
         assert_eq!(packed, expect);
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
@@ -1119,6 +4145,7 @@ mod tests {
             pool_token_amount,
             minimum_token_a_amount,
             minimum_token_b_amount,
+            deadline: None,
         });
         let packed = check.pack();
         let mut expect = vec![3];
@@ -1129,4 +4156,331 @@ mod tests {
         let unpacked = SwapInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
+
+    #[test]
+    fn test_pack_withdraw_and_claim() {
+        let minimum_token_a_amount: u64 = 1_000_000;
+        let minimum_token_b_amount: u64 = 500_000;
+        let pool_token_amount: u64 = 500_000;
+        let check = SwapInstruction::WithdrawAndClaim(WithdrawData {
+            pool_token_amount,
+            minimum_token_a_amount,
+            minimum_token_b_amount,
+            deadline: None,
+        });
+        let packed = check.pack();
+        let mut expect = vec![29];
+        expect.extend_from_slice(&pool_token_amount.to_le_bytes());
+        expect.extend_from_slice(&minimum_token_a_amount.to_le_bytes());
+        expect.extend_from_slice(&minimum_token_b_amount.to_le_bytes());
+        assert_eq!(packed, expect);
+        let unpacked = SwapInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    prop_compose! {
+        fn arb_pubkey()(bytes in any::<[u8; 32]>()) -> Pubkey {
+            Pubkey::new_from_array(bytes)
+        }
+    }
+
+    prop_compose! {
+        fn arb_fees()(
+            numerators in any::<[u64; 4]>(),
+            denominators in any::<[u64; 4]>(),
+            trade_fee_enabled in any::<bool>(),
+            withdraw_fee_enabled in any::<bool>(),
+            min_fee in any::<u64>(),
+        ) -> Fees {
+            Fees {
+                admin_trade_fee_numerator: numerators[0],
+                admin_trade_fee_denominator: denominators[0],
+                admin_withdraw_fee_numerator: numerators[1],
+                admin_withdraw_fee_denominator: denominators[1],
+                trade_fee_numerator: numerators[2],
+                trade_fee_denominator: denominators[2],
+                withdraw_fee_numerator: numerators[3],
+                withdraw_fee_denominator: denominators[3],
+                trade_fee_enabled,
+                withdraw_fee_enabled,
+                min_fee,
+            }
+        }
+    }
+
+    prop_compose! {
+        fn arb_rewards()(values in any::<[u64; 8]>()) -> Rewards {
+            Rewards {
+                trade_reward_numerator: values[0],
+                trade_reward_denominator: values[1],
+                trade_reward_cap: values[2],
+                liquidity_reward_numerator: values[3],
+                liquidity_reward_denominator: values[4],
+                deposit_reward_numerator: values[5],
+                deposit_reward_denominator: values[6],
+                deposit_reward_cap: values[7],
+            }
+        }
+    }
+
+    prop_compose! {
+        fn arb_pause_flags()(bits in any::<u8>()) -> PauseFlags {
+            PauseFlags::from_bits(bits & PauseFlags::ALL.bits()).unwrap()
+        }
+    }
+
+    fn arb_swap_direction() -> impl Strategy<Value = SwapDirection> {
+        prop_oneof![
+            Just(SwapDirection::SellBase),
+            Just(SwapDirection::SellQuote)
+        ]
+    }
+
+    prop_compose! {
+        fn arb_initialize_data()(
+            nonce in any::<u8>(),
+            slope in any::<u64>(),
+            mid_price in any::<u128>(),
+            is_open_twap in any::<bool>(),
+            creator_fee_bps in any::<u16>(),
+            invert_a in any::<bool>(),
+            invert_b in any::<bool>(),
+            slope_in_bps in any::<bool>(),
+        ) -> InitializeData {
+            InitializeData {
+                nonce,
+                slope,
+                mid_price,
+                is_open_twap,
+                creator_fee_bps,
+                invert_a,
+                invert_b,
+                slope_in_bps,
+            }
+        }
+    }
+
+    prop_compose! {
+        fn arb_swap_data()(
+            amount_in in any::<u64>(),
+            minimum_amount_out in any::<u64>(),
+            swap_direction in arb_swap_direction(),
+            deadline in any::<Option<i64>>(),
+        ) -> SwapData {
+            SwapData { amount_in, minimum_amount_out, swap_direction, deadline }
+        }
+    }
+
+    prop_compose! {
+        fn arb_quote_data()(
+            amount_in in any::<u64>(),
+            swap_direction in arb_swap_direction(),
+        ) -> QuoteData {
+            QuoteData { amount_in, swap_direction }
+        }
+    }
+
+    prop_compose! {
+        fn arb_preview_fee_change_data()(
+            sample_amount in any::<u64>(),
+            new_fees in arb_fees(),
+        ) -> PreviewFeeChangeData {
+            PreviewFeeChangeData { sample_amount, new_fees }
+        }
+    }
+
+    prop_compose! {
+        fn arb_deposit_data()(
+            token_a_amount in any::<u64>(),
+            token_b_amount in any::<u64>(),
+            min_mint_amount in any::<u64>(),
+            deadline in any::<Option<i64>>(),
+        ) -> DepositData {
+            DepositData { token_a_amount, token_b_amount, min_mint_amount, deadline }
+        }
+    }
+
+    prop_compose! {
+        fn arb_withdraw_data()(
+            pool_token_amount in any::<u64>(),
+            minimum_token_a_amount in any::<u64>(),
+            minimum_token_b_amount in any::<u64>(),
+            deadline in any::<Option<i64>>(),
+        ) -> WithdrawData {
+            WithdrawData {
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+                deadline,
+            }
+        }
+    }
+
+    prop_compose! {
+        fn arb_realized_vol_data()(
+            base_price_cumulative in any::<u128>(),
+            base_price_squared_cumulative in any::<u128>(),
+            block_timestamp in any::<u64>(),
+        ) -> RealizedVolData {
+            RealizedVolData { base_price_cumulative, base_price_squared_cumulative, block_timestamp }
+        }
+    }
+
+    prop_compose! {
+        fn arb_set_targets_data()(
+            base_target in any::<u128>(),
+            quote_target in any::<u128>(),
+        ) -> SetTargetsData {
+            SetTargetsData { base_target, quote_target }
+        }
+    }
+
+    prop_compose! {
+        fn arb_seed_position_data()(
+            liquidity_amount in any::<u64>(),
+            rewards_owed in any::<u64>(),
+            rewards_estimated in any::<u64>(),
+            cumulative_interest in any::<u128>(),
+            last_update_ts in any::<i64>(),
+            next_claim_ts in any::<i64>(),
+        ) -> SeedPositionData {
+            SeedPositionData {
+                liquidity_amount,
+                rewards_owed,
+                rewards_estimated,
+                cumulative_interest,
+                last_update_ts,
+                next_claim_ts,
+            }
+        }
+    }
+
+    fn arb_swap_instruction() -> impl Strategy<Value = SwapInstruction> {
+        prop_oneof![
+            arb_initialize_data().prop_map(SwapInstruction::Initialize),
+            arb_swap_data().prop_map(SwapInstruction::Swap),
+            arb_deposit_data().prop_map(SwapInstruction::Deposit),
+            arb_withdraw_data().prop_map(SwapInstruction::Withdraw),
+            Just(SwapInstruction::InitializeLiquidityProvider),
+            Just(SwapInstruction::ClaimLiquidityRewards),
+            Just(SwapInstruction::RefreshLiquidityObligation),
+            Just(SwapInstruction::GetSwapAccounts),
+            arb_pubkey().prop_map(SwapInstruction::GetClaimStatus),
+            Just(SwapInstruction::GetConfigStats),
+            Just(SwapInstruction::GetRewardApr),
+            Just(SwapInstruction::MigrateLiquidityProvider),
+            arb_swap_direction().prop_map(SwapInstruction::GetSplitThreshold),
+            arb_swap_data().prop_map(SwapInstruction::CheckSwapAllowed),
+            Just(SwapInstruction::PreviewRewards),
+            arb_quote_data().prop_map(SwapInstruction::GetExecutionPrice),
+            Just(SwapInstruction::GetBalancedDepositRatio),
+            Just(SwapInstruction::GetMultiplier),
+            Just(SwapInstruction::GetTotalRewardsMinted),
+            any::<u64>().prop_map(SwapInstruction::PreviewWithdraw),
+            Just(SwapInstruction::GetPriceHealth),
+            Just(SwapInstruction::GetPoolHealth),
+            any::<u64>().prop_map(SwapInstruction::PreviewRedeem),
+            Just(SwapInstruction::GetPoolAge),
+            arb_quote_data().prop_map(SwapInstruction::GetRequiredSlippage),
+            Just(SwapInstruction::InitSwapCooldown),
+            arb_swap_direction().prop_map(SwapInstruction::GetArbAmountToOracle),
+            arb_preview_fee_change_data().prop_map(SwapInstruction::PreviewFeeChange),
+            arb_swap_direction().prop_map(SwapInstruction::GetMarginalPrice),
+            arb_withdraw_data().prop_map(SwapInstruction::WithdrawAndClaim),
+            arb_realized_vol_data().prop_map(SwapInstruction::GetRealizedVol),
+            Just(SwapInstruction::GetImpliedPrices),
+            Just(SwapInstruction::GetPriceSource),
+            Just(SwapInstruction::GetMidPrice),
+            Just(SwapInstruction::GetFeeApr),
+        ]
+    }
+
+    fn arb_admin_instruction() -> impl Strategy<Value = AdminInstruction> {
+        prop_oneof![
+            (arb_fees(), arb_rewards()).prop_map(|(fees, rewards)| {
+                AdminInstruction::Initialize(AdminInitializeData { fees, rewards })
+            }),
+            Just(AdminInstruction::Pause),
+            Just(AdminInstruction::Unpause),
+            Just(AdminInstruction::SetFeeAccount),
+            arb_pubkey().prop_map(|new_admin_key| {
+                AdminInstruction::CommitNewAdmin(CommitNewAdmin { new_admin_key })
+            }),
+            arb_fees().prop_map(AdminInstruction::SetNewFees),
+            arb_rewards().prop_map(AdminInstruction::SetNewRewards),
+            arb_pause_flags().prop_map(AdminInstruction::SetPauseFlags),
+            arb_set_targets_data().prop_map(AdminInstruction::SetTargets),
+            Just(AdminInstruction::ProposeDrain),
+            Just(AdminInstruction::ExecuteDrain),
+            any::<u64>().prop_map(AdminInstruction::SetMinClaimAmount),
+            any::<u16>().prop_map(AdminInstruction::SetAdminSplit),
+            any::<u64>().prop_map(AdminInstruction::SetMinPoolSupply),
+            any::<u64>().prop_map(AdminInstruction::SetMinRewardLiquidity),
+            arb_pubkey().prop_map(AdminInstruction::SetRewardMint),
+            Just(AdminInstruction::CloseSwap),
+            any::<u16>().prop_map(AdminInstruction::SetMaxOracleMove),
+            arb_seed_position_data().prop_map(AdminInstruction::SeedPosition),
+            any::<u128>().prop_map(AdminInstruction::SetPriceTick),
+            Just(AdminInstruction::ResyncClaimSchedule),
+            any::<u64>().prop_map(AdminInstruction::SetMaxTradeAmount),
+            any::<bool>().prop_map(AdminInstruction::SetStrictClaimDestination),
+            any::<u64>().prop_map(AdminInstruction::SetSwapCooldown),
+            Just(AdminInstruction::SyncReserves),
+            any::<u64>().prop_map(AdminInstruction::SetMaxAllowedSlippage),
+            any::<bool>().prop_map(AdminInstruction::SetOracleFree),
+            any::<u128>().prop_map(AdminInstruction::SetManualPrice),
+            arb_pubkey().prop_map(AdminInstruction::SetAllowedTokenProgram),
+            any::<u16>().prop_map(AdminInstruction::SetMaxEmaDivergence),
+            arb_fees().prop_map(AdminInstruction::BatchSetFees),
+            any::<u64>().prop_map(AdminInstruction::TopUpRewardBudget),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_swap_instruction_round_trip(instruction in arb_swap_instruction()) {
+            let packed = instruction.pack();
+            prop_assert!(matches!(
+                InstructionType::check(&packed),
+                Some(InstructionType::Swap)
+            ));
+            prop_assert_eq!(SwapInstruction::unpack(&packed)?, instruction);
+        }
+
+        #[test]
+        fn test_admin_instruction_round_trip(instruction in arb_admin_instruction()) {
+            let packed = instruction.pack();
+            prop_assert!(matches!(
+                InstructionType::check(&packed),
+                Some(InstructionType::Admin)
+            ));
+            prop_assert_eq!(AdminInstruction::unpack(&packed)?, instruction);
+        }
+
+        #[test]
+        fn test_swap_instruction_trailing_bytes(
+            instruction in arb_swap_instruction(),
+            trailing in any::<u8>(),
+        ) {
+            let mut packed = instruction.pack();
+            packed.push(trailing);
+            prop_assert_eq!(
+                SwapInstruction::unpack(&packed),
+                Err(SwapError::InstructionUnpackError.into())
+            );
+        }
+
+        #[test]
+        fn test_admin_instruction_trailing_bytes(
+            instruction in arb_admin_instruction(),
+            trailing in any::<u8>(),
+        ) {
+            let mut packed = instruction.pack();
+            packed.push(trailing);
+            prop_assert_eq!(
+                AdminInstruction::unpack(&packed),
+                Err(SwapError::InstructionUnpackError.into())
+            );
+        }
+    }
 }
@@ -3,9 +3,12 @@
 //! An Uniswap-like program for the Solana blockchain.
 
 pub mod admin;
+pub mod client;
 pub mod curve;
+pub mod diagnostic;
 pub mod entrypoint;
 pub mod error;
+pub mod health;
 pub mod instruction;
 pub mod math;
 pub mod processor;
@@ -58,6 +58,20 @@ impl Decimal {
         Self(U192::from(percent as u64 * PERCENT_SCALER))
     }
 
+    /// Create scaled decimal from basis points
+    pub fn from_bps(bps: u64) -> Self {
+        Self(U192::from(bps) * U192::from(BPS_SCALER))
+    }
+
+    /// Value in basis points, floored, erroring if it does not fit in u64
+    pub fn to_bps(&self) -> Result<u64, ProgramError> {
+        let bps = self
+            .0
+            .checked_div(U192::from(BPS_SCALER))
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(u64::try_from(bps).map_err(|_| SwapError::CalculationFailure)?)
+    }
+
     /// Return raw scaled value if it fits within u128
     #[allow(clippy::wrong_self_convention)]
     pub fn to_scaled_val(&self) -> Result<u128, ProgramError> {
@@ -69,6 +83,15 @@ impl Decimal {
         Self(U192::from(scaled_val))
     }
 
+    /// Create decimal from u128 value, erroring instead of panicking on overflow
+    pub fn try_from_u128(val: u128) -> Result<Self, ProgramError> {
+        Ok(Self(
+            Self::wad()
+                .checked_mul(U192::from(val))
+                .ok_or(SwapError::CalculationFailure)?,
+        ))
+    }
+
     /// Round scaled decimal to u128
     pub fn try_round_u128(&self) -> Result<u128, ProgramError> {
         let rounded_val = Self::half_wad()
@@ -133,13 +156,26 @@ impl Decimal {
 
     /// Square root decimal
     pub fn sqrt(&self) -> Result<Self, ProgramError> {
-        Ok(Self::from(
-            sqrt(self.try_round_u128()?).ok_or(SwapError::CalculationFailure)?,
+        Self::try_from_u128(sqrt(self.try_round_u128()?).ok_or(SwapError::CalculationFailure)?)
+    }
+
+    /// Geometric mean `sqrt(a * b)`, taken over the scaled product so the
+    /// result keeps full precision. The scaled product must fit in u128, which
+    /// holds for values up to about 1.8e10 each.
+    pub fn geometric_mean(a: Decimal, b: Decimal) -> Result<Self, ProgramError> {
+        let product = u128::try_from(a.0.checked_mul(b.0).ok_or(SwapError::CalculationFailure)?)
+            .map_err(|_| SwapError::CalculationFailure)?;
+        Ok(Self::from_scaled_val(
+            sqrt(product).ok_or(SwapError::CalculationFailure)?,
         ))
     }
 
-    /// Reciprocal decimal
+    /// Reciprocal decimal, failing for zero. The smallest non-zero value
+    /// has a reciprocal of WAD, which still packs into a scaled u128.
     pub fn reciprocal(&self) -> Result<Self, ProgramError> {
+        if self.0.is_zero() {
+            return Err(SwapError::CalculationFailure.into());
+        }
         Ok(Self(
             Self::wad()
                 .checked_pow(U192::from(2u64))
@@ -148,6 +184,57 @@ impl Decimal {
                 .ok_or(SwapError::CalculationFailure)?,
         ))
     }
+
+    /// Multiply, failing if the digits truncated below the scale exceed
+    /// `max_loss_scaled`. The loss is the remainder of the scaled product by
+    /// WAD, so `0` demands an exact product and `WAD - 1` accepts any.
+    pub fn try_mul_checked_precision(
+        self,
+        rhs: Self,
+        max_loss_scaled: u128,
+    ) -> Result<Self, ProgramError> {
+        let (product, loss) = self
+            .0
+            .checked_mul(rhs.0)
+            .ok_or(SwapError::CalculationFailure)?
+            .div_mod(Self::wad());
+        if loss > U192::from(max_loss_scaled) {
+            return Err(SwapError::PrecisionLossExceeded.into());
+        }
+        Ok(Self(product))
+    }
+
+    /// Add modulo 2^128 of the scaled value, the width decimals are packed with
+    pub fn wrapping_add(&self, rhs: Self) -> Self {
+        Self::from_scaled_val(self.0.low_u128().wrapping_add(rhs.0.low_u128()))
+    }
+
+    /// Subtract modulo 2^128 of the scaled value, the width decimals are packed with
+    pub fn wrapping_sub(&self, rhs: Self) -> Self {
+        Self::from_scaled_val(self.0.low_u128().wrapping_sub(rhs.0.low_u128()))
+    }
+
+    /// Format with a fixed number of decimal places, rounding half up
+    pub fn to_string_with_precision(&self, places: usize) -> String {
+        if places >= SCALE {
+            return format!("{}{}", self, "0".repeat(places - SCALE));
+        }
+        let unit = U192::exp10(SCALE - places);
+        let mut rounded = self.0 / unit;
+        if (self.0 % unit) * 2 >= unit {
+            rounded += U192::one();
+        }
+        if places == 0 {
+            return rounded.to_string();
+        }
+        let places_scaler = U192::exp10(places);
+        format!(
+            "{}.{:0>width$}",
+            rounded / places_scaler,
+            (rounded % places_scaler).to_string(),
+            width = places
+        )
+    }
 }
 
 impl fmt::Display for Decimal {
@@ -267,6 +354,217 @@ impl TryMul<Decimal> for Decimal {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_try_from_u128() {
+        assert_eq!(Decimal::try_from_u128(0).unwrap(), Decimal::zero());
+        assert_eq!(Decimal::try_from_u128(1).unwrap(), Decimal::one());
+        assert_eq!(
+            Decimal::try_from_u128(u64::MAX as u128).unwrap(),
+            Decimal::from(u64::MAX)
+        );
+
+        // WAD * u128::MAX still fits in U192, so the full u128 range converts exactly
+        let max = Decimal::try_from_u128(u128::MAX).unwrap();
+        assert_eq!(max, Decimal::from(u128::MAX));
+        assert_eq!(max.try_floor_u128().unwrap(), u128::MAX);
+        assert_eq!(max.try_ceil_u128().unwrap(), u128::MAX);
+        assert_eq!(
+            Decimal::try_from_u128(u128::MAX - 1)
+                .unwrap()
+                .try_floor_u128()
+                .unwrap(),
+            u128::MAX - 1
+        );
+
+        // scaling past the boundary is reported instead of wrapping
+        assert!(max.try_mul(1u64 << 33).is_ok());
+        assert_eq!(
+            max.try_mul(u64::MAX),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_bps() {
+        assert_eq!(Decimal::from_bps(0), Decimal::zero());
+        assert_eq!(Decimal::from_bps(10_000), Decimal::one());
+        assert_eq!(
+            Decimal::from_bps(50),
+            Decimal::from_percent(1).try_div(2).unwrap()
+        );
+        for bps in [0, 1, 25, 10_000, 123_456, u64::MAX] {
+            assert_eq!(Decimal::from_bps(bps).to_bps().unwrap(), bps);
+        }
+
+        // partial basis points are floored
+        assert_eq!(
+            Decimal::from_bps(25)
+                .try_add(Decimal::from_scaled_val(BPS_SCALER as u128 - 1))
+                .unwrap()
+                .to_bps()
+                .unwrap(),
+            25
+        );
+
+        // u64::MAX basis points is the largest value that converts
+        let max = Decimal::from_bps(u64::MAX);
+        assert_eq!(
+            max.try_add(Decimal::from_scaled_val(BPS_SCALER as u128 - 1))
+                .unwrap()
+                .to_bps()
+                .unwrap(),
+            u64::MAX
+        );
+        assert_eq!(
+            max.try_add(Decimal::from_scaled_val(BPS_SCALER as u128))
+                .unwrap()
+                .to_bps(),
+            Err(SwapError::CalculationFailure.into())
+        );
+        assert_eq!(
+            Decimal::from(u64::MAX).to_bps(),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let max = Decimal::from_scaled_val(u128::MAX);
+        let one = Decimal::one();
+
+        assert_eq!(
+            max.wrapping_add(one),
+            Decimal::from_scaled_val(WAD as u128 - 1)
+        );
+        assert_eq!(max.wrapping_add(one).wrapping_sub(max), one);
+        assert_eq!(
+            Decimal::zero().wrapping_sub(one).wrapping_add(one),
+            Decimal::zero()
+        );
+        assert_eq!(Decimal::from(3u64).wrapping_sub(one), Decimal::from(2u64));
+    }
+
+    #[test]
+    fn test_geometric_mean() {
+        for x in [
+            Decimal::from_scaled_val(1),
+            Decimal::one().try_div(3).unwrap(),
+            Decimal::from(7u64),
+            Decimal::from(150u64).try_div(7).unwrap(),
+            Decimal::from(10_000_000_000u64),
+        ] {
+            let mean = Decimal::geometric_mean(x, x).unwrap();
+            assert!(mean.0 <= x.0 && x.0 - mean.0 <= U192::from(1u64));
+        }
+
+        assert_eq!(
+            Decimal::geometric_mean(Decimal::from(2u64), Decimal::from(8u64)).unwrap(),
+            Decimal::from(4u64)
+        );
+        // sqrt(18.75 * 21.428571428) = 20.044593143...
+        assert_eq!(
+            Decimal::geometric_mean(
+                Decimal::from_scaled_val(18_750_000_000),
+                Decimal::from_scaled_val(21_428_571_428)
+            )
+            .unwrap(),
+            Decimal::from_scaled_val(20_044_593_143)
+        );
+        assert_eq!(
+            Decimal::geometric_mean(Decimal::zero(), Decimal::from(5u64)).unwrap(),
+            Decimal::zero()
+        );
+        assert_eq!(
+            Decimal::geometric_mean(Decimal::from(u64::MAX), Decimal::from(u64::MAX)),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_reciprocal() {
+        assert_eq!(
+            Decimal::zero().reciprocal(),
+            Err(SwapError::CalculationFailure.into())
+        );
+
+        // the smallest representable value inverts to WAD and still packs
+        let tiny = Decimal::from_scaled_val(1).reciprocal().unwrap();
+        assert_eq!(tiny, Decimal::from(WAD));
+        assert!(tiny.to_scaled_val().is_ok());
+
+        assert_eq!(
+            Decimal::from(4u64).reciprocal().unwrap(),
+            Decimal::one().try_div(4).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_try_mul_checked_precision() {
+        // 2.5 * 4 is exact
+        let two_and_half = Decimal::from(5u64).try_div(2).unwrap();
+        assert_eq!(
+            two_and_half
+                .try_mul_checked_precision(Decimal::from(4u64), 0)
+                .unwrap(),
+            Decimal::from(10u64)
+        );
+
+        // the smallest value squared truncates to zero, losing its whole product
+        let smallest = Decimal::from_scaled_val(1);
+        assert_eq!(
+            smallest.try_mul_checked_precision(smallest, 0),
+            Err(SwapError::PrecisionLossExceeded.into())
+        );
+        assert_eq!(
+            smallest.try_mul_checked_precision(smallest, 1).unwrap(),
+            Decimal::zero()
+        );
+
+        // 0.333333333 squared is 0.111111110888888889, truncating
+        // 888_888_889 of the product
+        let third = Decimal::one().try_div(3).unwrap();
+        assert_eq!(
+            third.try_mul_checked_precision(third, 888_888_888),
+            Err(SwapError::PrecisionLossExceeded.into())
+        );
+        assert_eq!(
+            third.try_mul_checked_precision(third, 888_888_889).unwrap(),
+            third.try_mul(third).unwrap()
+        );
+        assert_eq!(
+            third
+                .try_mul_checked_precision(third, WAD as u128 - 1)
+                .unwrap(),
+            third.try_mul(third).unwrap()
+        );
+
+        assert_eq!(
+            Decimal::from_scaled_val(u128::MAX)
+                .try_mul_checked_precision(Decimal::from_scaled_val(u128::MAX), WAD as u128 - 1),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_to_string_with_precision() {
+        let value = Decimal::from_scaled_val(1_005_000_000);
+        assert_eq!(value.to_string_with_precision(2), "1.01");
+        assert_eq!(value.to_string_with_precision(3), "1.005");
+        assert_eq!(value.to_string_with_precision(0), "1");
+
+        let dust = Decimal::from_scaled_val(2);
+        assert_eq!(dust.to_string_with_precision(4), "0.0000");
+        assert_eq!(dust.to_string_with_precision(9), "0.000000002");
+        assert_eq!(Decimal::zero().to_string_with_precision(4), "0.0000");
+
+        assert_eq!(
+            Decimal::from_scaled_val(999_999_999).to_string_with_precision(2),
+            "1.00"
+        );
+        assert_eq!(Decimal::one().to_string_with_precision(11), "1.00000000000");
+        assert_eq!(Decimal::one().to_string(), "1.000000000");
+    }
+
     #[test]
     fn test_decimal() {
         assert_eq!(Decimal::from(0u64), Decimal::zero());
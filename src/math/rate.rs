@@ -189,6 +189,7 @@ impl TryMul<Rate> for Rate {
 #[cfg(test)]
 mod test {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn test_rate() {
@@ -327,4 +328,20 @@ mod test {
             Rate::from_scaled_val(2)
         );
     }
+    proptest! {
+        #[test]
+        fn test_rate_decimal_round_trip(scaled_val in any::<u128>()) {
+            let rate = Rate::from_scaled_val(scaled_val);
+            assert_eq!(Rate::try_from(Decimal::from(rate)).unwrap(), rate);
+        }
+
+        #[test]
+        fn test_rate_from_decimal_out_of_range(excess in 1..u128::MAX) {
+            let decimal = Decimal(U192::from(u128::MAX) + U192::from(excess));
+            assert_eq!(
+                Rate::try_from(decimal),
+                Err(SwapError::CalculationFailure.into())
+            );
+        }
+    }
 }
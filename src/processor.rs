@@ -2,34 +2,47 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    clock::UnixTimestamp,
     entrypoint::ProgramResult,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
+    program_option::COption,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
+    system_instruction,
     sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
-use spl_token::{
+use spl_token::state::{Account, Mint};
+use spl_token_2022::{
+    extension::{BaseState, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
     instruction::AuthorityType,
-    state::{Account, Mint},
 };
 
 use crate::{
     admin::process_admin_instruction,
-    curve::{Multiplier, PoolState},
+    curve::{
+        min_out_with_slippage, slope_from_bps, Multiplier, PoolState, BPS_DENOMINATOR,
+        MAX_INITIAL_PRICE_DEVIATION_BPS,
+    },
+    diagnostic::SwapDiagnostic,
     error::SwapError,
+    health::PoolHealth,
     instruction::{
-        DepositData, InitializeData, InstructionType, SwapData, SwapDirection, SwapInstruction,
-        WithdrawData,
+        DepositData, InitializeData, InstructionType, PreviewFeeChangeData, PriceSource, QuoteData,
+        RealizedVolData, SwapData, SwapDirection, SwapInstruction, WithdrawData,
     },
-    math::{Decimal, TryAdd, TryDiv, TryMul, TrySub},
+    math::{Decimal, TryAdd, TryDiv, TryMul, TrySub, SCALE},
     pyth,
-    state::{ConfigInfo, LiquidityProvider, SwapInfo},
+    state::{
+        fee_apr, reward_apr, reward_rate, swap_cooldown_address, ClaimStatus, ConfigInfo,
+        ConfigStats, Fees, LiquidityProvider, PauseFlags, SwapAccountsBundle, SwapCooldown,
+        SwapInfo, DEFAULT_REWARD_BUDGET, SWAP_COOLDOWN_SEED,
+    },
 };
 
 /// Processes an [Instruction](enum.Instruction.html).
@@ -53,14 +66,30 @@ fn process_swap_instruction(
             slope,
             mid_price,
             is_open_twap,
+            creator_fee_bps,
+            invert_a,
+            invert_b,
+            slope_in_bps,
         }) => {
             msg!("Instruction: Initialize");
-            process_initialize(program_id, nonce, slope, mid_price, is_open_twap, accounts)
+            process_initialize(
+                program_id,
+                nonce,
+                slope,
+                mid_price,
+                is_open_twap,
+                creator_fee_bps,
+                invert_a,
+                invert_b,
+                slope_in_bps,
+                accounts,
+            )
         }
         SwapInstruction::Swap(SwapData {
             amount_in,
             minimum_amount_out,
             swap_direction,
+            deadline,
         }) => {
             msg!("Instruction: Swap");
             process_swap(
@@ -68,6 +97,7 @@ fn process_swap_instruction(
                 amount_in,
                 minimum_amount_out,
                 swap_direction,
+                deadline,
                 accounts,
             )
         }
@@ -75,6 +105,7 @@ fn process_swap_instruction(
             token_a_amount,
             token_b_amount,
             min_mint_amount,
+            deadline,
         }) => {
             msg!("Instruction: Deposit");
             process_deposit(
@@ -82,6 +113,7 @@ fn process_swap_instruction(
                 token_a_amount,
                 token_b_amount,
                 min_mint_amount,
+                deadline,
                 accounts,
             )
         }
@@ -89,6 +121,7 @@ fn process_swap_instruction(
             pool_token_amount,
             minimum_token_a_amount,
             minimum_token_b_amount,
+            deadline,
         }) => {
             msg!("Instruction: Withdraw");
             process_withdraw(
@@ -96,6 +129,7 @@ fn process_swap_instruction(
                 pool_token_amount,
                 minimum_token_a_amount,
                 minimum_token_b_amount,
+                deadline,
                 accounts,
             )
         }
@@ -111,6 +145,161 @@ fn process_swap_instruction(
             msg!("Instruction: Claim Liquidity Rewards");
             process_claim_liquidity_rewards(program_id, accounts)
         }
+        SwapInstruction::GetSwapAccounts => {
+            msg!("Instruction: Get Swap Accounts");
+            process_get_swap_accounts(program_id, accounts)
+        }
+        SwapInstruction::GetClaimStatus(pool) => {
+            msg!("Instruction: Get Claim Status");
+            process_get_claim_status(program_id, &pool, accounts)
+        }
+        SwapInstruction::GetConfigStats => {
+            msg!("Instruction: Get Config Stats");
+            process_get_config_stats(program_id, accounts)
+        }
+        SwapInstruction::GetRewardApr => {
+            msg!("Instruction: Get Reward Apr");
+            process_get_reward_apr(program_id, accounts)
+        }
+        SwapInstruction::MigrateLiquidityProvider => {
+            msg!("Instruction: Migrate Liquidity Provider");
+            process_migrate_liquidity_provider(program_id, accounts)
+        }
+        SwapInstruction::GetSplitThreshold(swap_direction) => {
+            msg!("Instruction: Get Split Threshold");
+            process_get_split_threshold(program_id, swap_direction, accounts)
+        }
+        SwapInstruction::CheckSwapAllowed(SwapData {
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            deadline,
+        }) => {
+            msg!("Instruction: Check Swap Allowed");
+            process_check_swap_allowed(
+                program_id,
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                deadline,
+                accounts,
+            )
+        }
+        SwapInstruction::PreviewRewards => {
+            msg!("Instruction: Preview Rewards");
+            process_preview_rewards(program_id, accounts)
+        }
+        SwapInstruction::GetExecutionPrice(QuoteData {
+            amount_in,
+            swap_direction,
+        }) => {
+            msg!("Instruction: Get Execution Price");
+            process_get_execution_price(program_id, amount_in, swap_direction, accounts)
+        }
+        SwapInstruction::GetBalancedDepositRatio => {
+            msg!("Instruction: Get Balanced Deposit Ratio");
+            process_get_balanced_deposit_ratio(program_id, accounts)
+        }
+        SwapInstruction::GetMultiplier => {
+            msg!("Instruction: Get Multiplier");
+            process_get_multiplier(program_id, accounts)
+        }
+        SwapInstruction::GetTotalRewardsMinted => {
+            msg!("Instruction: Get Total Rewards Minted");
+            process_get_total_rewards_minted(program_id, accounts)
+        }
+        SwapInstruction::PreviewWithdraw(pool_token_amount) => {
+            msg!("Instruction: Preview Withdraw");
+            process_preview_withdraw(program_id, pool_token_amount, accounts)
+        }
+        SwapInstruction::GetPriceHealth => {
+            msg!("Instruction: Get Price Health");
+            process_get_price_health(program_id, accounts)
+        }
+        SwapInstruction::GetPoolHealth => {
+            msg!("Instruction: Get Pool Health");
+            process_get_pool_health(program_id, accounts)
+        }
+        SwapInstruction::PreviewRedeem(pool_token_amount) => {
+            msg!("Instruction: Preview Redeem");
+            process_preview_redeem(program_id, pool_token_amount, accounts)
+        }
+        SwapInstruction::GetPoolAge => {
+            msg!("Instruction: Get Pool Age");
+            process_get_pool_age(program_id, accounts)
+        }
+        SwapInstruction::GetRequiredSlippage(QuoteData {
+            amount_in,
+            swap_direction,
+        }) => {
+            msg!("Instruction: Get Required Slippage");
+            process_get_required_slippage(program_id, amount_in, swap_direction, accounts)
+        }
+        SwapInstruction::InitSwapCooldown => {
+            msg!("Instruction: Init Swap Cooldown");
+            process_init_swap_cooldown(program_id, accounts)
+        }
+        SwapInstruction::GetArbAmountToOracle(swap_direction) => {
+            msg!("Instruction: Get Arb Amount To Oracle");
+            process_get_arb_amount_to_oracle(program_id, swap_direction, accounts)
+        }
+        SwapInstruction::PreviewFeeChange(PreviewFeeChangeData {
+            sample_amount,
+            new_fees,
+        }) => {
+            msg!("Instruction: Preview Fee Change");
+            process_preview_fee_change(program_id, sample_amount, &new_fees, accounts)
+        }
+        SwapInstruction::GetMarginalPrice(swap_direction) => {
+            msg!("Instruction: Get Marginal Price");
+            process_get_marginal_price(program_id, swap_direction, accounts)
+        }
+        SwapInstruction::WithdrawAndClaim(WithdrawData {
+            pool_token_amount,
+            minimum_token_a_amount,
+            minimum_token_b_amount,
+            deadline,
+        }) => {
+            msg!("Instruction: Withdraw And Claim");
+            process_withdraw_and_claim(
+                program_id,
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+                deadline,
+                accounts,
+            )
+        }
+        SwapInstruction::GetRealizedVol(RealizedVolData {
+            base_price_cumulative,
+            base_price_squared_cumulative,
+            block_timestamp,
+        }) => {
+            msg!("Instruction: Get Realized Vol");
+            process_get_realized_vol(
+                program_id,
+                Decimal::from_scaled_val(base_price_cumulative),
+                Decimal::from_scaled_val(base_price_squared_cumulative),
+                block_timestamp,
+                accounts,
+            )
+        }
+        SwapInstruction::GetImpliedPrices => {
+            msg!("Instruction: Get Implied Prices");
+            process_get_implied_prices(program_id, accounts)
+        }
+        SwapInstruction::GetPriceSource => {
+            msg!("Instruction: Get Price Source");
+            process_get_price_source(program_id, accounts)
+        }
+        SwapInstruction::GetMidPrice => {
+            msg!("Instruction: Get Mid Price");
+            process_get_mid_price(program_id, accounts)
+        }
+        SwapInstruction::GetFeeApr => {
+            msg!("Instruction: Get Fee Apr");
+            process_get_fee_apr(program_id, accounts)
+        }
     }
 }
 
@@ -120,8 +309,13 @@ fn process_initialize(
     slope: u64,
     mid_price: u128,
     is_open_twap: bool,
+    creator_fee_bps: u16,
+    invert_a: bool,
+    invert_b: bool,
+    slope_in_bps: bool,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    assert_account_count(accounts, 16)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -130,25 +324,45 @@ fn process_initialize(
     let admin_fee_b_info = next_account_info(account_info_iter)?;
     let token_a_info = next_account_info(account_info_iter)?;
     let token_b_info = next_account_info(account_info_iter)?;
+    let token_a_mint_info = next_account_info(account_info_iter)?;
+    let token_b_mint_info = next_account_info(account_info_iter)?;
     let pool_mint_info = next_account_info(account_info_iter)?;
     let destination_info = next_account_info(account_info_iter)?;
     let pyth_a_price_info = next_account_info(account_info_iter)?;
     let pyth_b_price_info = next_account_info(account_info_iter)?;
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
     let token_program_info = next_account_info(account_info_iter)?;
+    let creator_info = next_account_info(account_info_iter)?;
 
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !creator_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if creator_fee_bps as u64 > BPS_DENOMINATOR {
+        return Err(SwapError::InvalidInput.into());
+    }
     assert_uninitialized::<SwapInfo>(swap_info)?;
     if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
         return Err(SwapError::InvalidProgramAddress.into());
     }
 
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
     let token_program_id = *token_program_info.key;
+    assert_token_program(&config, &token_program_id)?;
     let destination = unpack_token_account(destination_info, &token_program_id)?;
     let token_a = unpack_token_account(token_a_info, &token_program_id)?;
     let token_b = unpack_token_account(token_b_info, &token_program_id)?;
     let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
     let admin_fee_key_a = unpack_token_account(admin_fee_a_info, &token_program_id)?;
     let admin_fee_key_b = unpack_token_account(admin_fee_b_info, &token_program_id)?;
+    // admin fees paid into a reserve would silently inflate the pool
+    for admin_fee_info in [admin_fee_a_info, admin_fee_b_info] {
+        if admin_fee_info.key == token_a_info.key || admin_fee_info.key == token_b_info.key {
+            return Err(SwapError::InvalidAdmin.into());
+        }
+    }
     if *authority_info.key != token_a.owner {
         return Err(SwapError::InvalidOwner.into());
     }
@@ -167,6 +381,12 @@ fn process_initialize(
     if token_a.mint == token_b.mint {
         return Err(SwapError::RepeatedMint.into());
     }
+    if token_a.mint != *token_a_mint_info.key || token_b.mint != *token_b_mint_info.key {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    // the reserve mints are checked for extensions the swap cannot honor
+    unpack_mint(token_a_mint_info, &token_program_id)?;
+    unpack_mint(token_b_mint_info, &token_program_id)?;
     if token_a.mint != admin_fee_key_a.mint {
         return Err(SwapError::InvalidAdmin.into());
     }
@@ -199,9 +419,24 @@ fn process_initialize(
     if pool_mint.freeze_authority.is_some() {
         return Err(SwapError::InvalidFreezeAuthority.into());
     }
+    // pool token amounts are scaled like `Decimal`
+    if usize::from(pool_mint.decimals) != SCALE {
+        return Err(SwapError::IncorrectMint.into());
+    }
     if pool_mint.supply != 0 {
         return Err(SwapError::InvalidSupply.into());
     }
+    if destination.mint != *pool_mint_info.key {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    if destination.amount != 0 {
+        return Err(SwapError::DestinationNotEmpty.into());
+    }
+    let slope = if slope_in_bps {
+        slope_from_bps(u16::try_from(slope).map_err(|_| SwapError::InvalidSlope)?)?
+    } else {
+        slope
+    };
     if Decimal::from_scaled_val(slope as u128).lt(&Decimal::zero())
         || Decimal::from_scaled_val(slope as u128).gt(&Decimal::one())
     {
@@ -209,8 +444,26 @@ fn process_initialize(
     }
 
     // getting price from pyth or initial mid_price
-    let market_price = get_market_price_from_pyth(pyth_a_price_info, pyth_b_price_info, clock)
-        .unwrap_or_else(|_| Decimal::from_scaled_val(mid_price));
+    let market_price = match get_market_price_from_pyth(
+        pyth_a_price_info,
+        pyth_b_price_info,
+        invert_a,
+        invert_b,
+        config.max_ema_divergence_bps,
+        clock,
+    ) {
+        Ok(oracle_price) => {
+            // a mispriced initial deposit is arbitraged against its depositor
+            let deposit_price = Decimal::from(token_b.amount).try_div(token_a.amount)?;
+            if price_deviation(deposit_price, oracle_price)?
+                > oracle_price.try_mul(Decimal::from_bps(MAX_INITIAL_PRICE_DEVIATION_BPS))?
+            {
+                return Err(SwapError::PriceDeviationTooLarge.into());
+            }
+            oracle_price
+        }
+        Err(_) => Decimal::from_scaled_val(mid_price),
+    };
 
     let mut pool_state = PoolState::new(PoolState {
         market_price,
@@ -223,14 +476,23 @@ fn process_initialize(
     })?;
 
     let mint_amount = pool_state.buy_shares(token_a.amount, token_b.amount, pool_mint.supply)?;
+    // the recorded reserves must be exactly the tokens the authority holds
+    if pool_state.base_reserve != Decimal::from(token_a.amount)
+        || pool_state.quote_reserve != Decimal::from(token_b.amount)
+    {
+        return Err(SwapError::InvalidPoolState.into());
+    }
 
     let block_timestamp_last: u64 = clock.unix_timestamp.try_into().unwrap();
-    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    config.pools_created = config
+        .pools_created
+        .checked_add(1)
+        .ok_or(SwapError::CalculationFailure)?;
 
     SwapInfo::pack(
         SwapInfo {
             is_initialized: true,
-            is_paused: false,
+            pause_flags: PauseFlags::NONE,
             nonce,
             token_a: *token_a_info.key,
             token_b: *token_b_info.key,
@@ -239,16 +501,35 @@ fn process_initialize(
             token_b_mint: token_b.mint,
             admin_fee_key_a: *admin_fee_a_info.key,
             admin_fee_key_b: *admin_fee_b_info.key,
-            fees: config.fees,
-            rewards: config.rewards,
+            fees: config.fees.clone(),
+            rewards: config.rewards.clone(),
             pool_state,
             is_open_twap,
             block_timestamp_last,
             cumulative_ticks: 0,
             base_price_cumulative_last: Decimal::zero(),
+            creator_key: *creator_info.key,
+            creator_fee_bps,
+            invert_a,
+            invert_b,
+            config_key: *config_info.key,
+            min_reward_liquidity: 0,
+            last_oracle_price: Decimal::zero(),
+            max_oracle_move_bps: 0,
+            price_tick: Decimal::zero(),
+            max_trade_amount: 0,
+            creation_ts: block_timestamp_last,
+            swap_cooldown_seconds: 0,
+            max_allowed_slippage_bps: 0,
+            oracle_free: false,
+            base_price_squared_cumulative_last: Decimal::zero(),
+            reward_budget_remaining: DEFAULT_REWARD_BUDGET,
+            pool_mint_decimals: pool_mint.decimals,
+            cumulative_volume: 0,
         },
         &mut swap_info.data.borrow_mut(),
     )?;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
 
     token_mint_to(
         swap_info.key,
@@ -268,8 +549,14 @@ fn process_swap(
     amount_in: u64,
     minimum_amount_out: u64,
     swap_direction: SwapDirection,
+    deadline: Option<i64>,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    // the swap cooldown of the user transfer authority is optional
+    if accounts.len() != 18 && accounts.len() != 19 {
+        msg!("Expected 18 or 19 accounts, got {}", accounts.len());
+        return Err(SwapError::InvalidAccountCount.into());
+    }
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -283,20 +570,46 @@ fn process_swap(
     let reward_token_info = next_account_info(account_info_iter)?;
     let reward_mint_info = next_account_info(account_info_iter)?;
     let admin_destination_info = next_account_info(account_info_iter)?;
+    let secondary_admin_destination_info = next_account_info(account_info_iter)?;
+    let creator_destination_info = next_account_info(account_info_iter)?;
     let pyth_a_price_info = next_account_info(account_info_iter)?;
     let pyth_b_price_info = next_account_info(account_info_iter)?;
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    assert_deadline(deadline, clock)?;
     let token_program_info = next_account_info(account_info_iter)?;
+    let swap_cooldown_info = next_account_info(account_info_iter).ok();
 
     if swap_info.owner != program_id || config_info.owner != program_id {
         return Err(ProgramError::IncorrectProgramId);
     }
 
-    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    if token_swap.is_paused {
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if token_swap.pause_flags.contains(PauseFlags::SWAPS) {
         return Err(SwapError::IsPaused.into());
     }
+    if token_swap.max_trade_amount > 0 && amount_in > token_swap.max_trade_amount {
+        msg!(
+            "Trade of {} exceeds the maximum of {}",
+            amount_in,
+            token_swap.max_trade_amount
+        );
+        return Err(SwapError::TradeTooLarge.into());
+    }
+    if token_swap.swap_cooldown_seconds > 0 {
+        record_swap_cooldown(
+            program_id,
+            swap_info.key,
+            user_transfer_authority_info.key,
+            swap_cooldown_info.ok_or(SwapError::InvalidAccountCount)?,
+            token_swap.swap_cooldown_seconds,
+            clock.unix_timestamp,
+        )?;
+    }
+    token_swap.pool_state.validate_invariants()?;
     let swap_nonce = token_swap.nonce;
     if *swap_authority_info.key != authority_id(program_id, swap_info.key, swap_nonce)? {
         return Err(SwapError::InvalidProgramAddress.into());
@@ -320,10 +633,12 @@ fn process_swap(
     }
 
     let token_program_id = *token_program_info.key;
+    assert_token_program(&config, &token_program_id)?;
     let token_a = unpack_token_account(swap_source_info, &token_program_id)?;
     let token_b = unpack_token_account(swap_destination_info, &token_program_id)?;
     let reward_token = unpack_token_account(reward_token_info, &token_program_id)?;
     let reward_mint = unpack_mint(reward_mint_info, &token_program_id)?;
+    let creator_destination = unpack_token_account(creator_destination_info, &token_program_id)?;
 
     // TODO: ======== Need check more =========
     let market_nonce = config.bump_seed;
@@ -360,6 +675,9 @@ fn process_swap(
             if token_a.amount < amount_in {
                 return Err(SwapError::InsufficientFunds.into());
             }
+            if creator_destination.mint != token_b.mint {
+                return Err(SwapError::IncorrectMint.into());
+            }
         }
         SwapDirection::SellQuote => {
             if *swap_destination_info.key == token_swap.token_a
@@ -375,34 +693,85 @@ fn process_swap(
             if token_b.amount < amount_in {
                 return Err(SwapError::InsufficientFunds.into());
             }
+            if creator_destination.mint != token_a.mint {
+                return Err(SwapError::IncorrectMint.into());
+            }
         }
     }
+    if creator_destination.owner != token_swap.creator_key {
+        return Err(SwapError::InvalidOwner.into());
+    }
 
-    let (new_market_price, base_price_cumulative_last) =
-        get_new_market_price(&mut token_swap, pyth_a_price_info, pyth_b_price_info, clock)?;
+    let oracle_price = read_oracle_price(
+        &token_swap,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        config.max_ema_divergence_bps,
+        clock,
+    );
+    let (new_market_price, base_price_cumulative_last, base_price_squared_cumulative_last) =
+        get_new_market_price(
+            &mut token_swap,
+            oracle_price,
+            clock.unix_timestamp.try_into().unwrap(),
+        )?;
 
     let state = PoolState::new(PoolState {
         market_price: new_market_price,
         ..token_swap.pool_state
     })?;
+    if token_swap.max_allowed_slippage_bps > 0 {
+        check_slippage_cap(
+            &state,
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            token_swap.max_allowed_slippage_bps,
+        )?;
+    }
 
     let (receive_amount, new_multiplier) = match swap_direction {
         SwapDirection::SellBase => state.sell_base_token(amount_in)?,
         SwapDirection::SellQuote => state.sell_quote_token(amount_in)?,
     };
     let fees = &token_swap.fees;
-    let trade_fee = fees.trade_fee(receive_amount)?;
-    let admin_fee = fees.admin_trade_fee(trade_fee)?;
+    let trade_fee = fees.trade_fee(
+        receive_amount,
+        quote_per_received_token(state.market_price, swap_direction),
+    )?;
+    // creator share is taken before the admin/LP split so the trader pays the same total fee
+    let creator_fee = token_swap.creator_fee(trade_fee)?;
+    let admin_fee = fees.admin_trade_fee(
+        trade_fee
+            .checked_sub(creator_fee)
+            .ok_or(SwapError::CalculationFailure)?,
+    )?;
     let rewards = &token_swap.rewards;
-    let amount_to_reward = rewards.trade_reward_u64(amount_in)?;
+    // the swap still goes through once the pool's reward budget runs out, it
+    // just mints nothing
+    let amount_to_reward = rewards
+        .trade_reward_u64(amount_in)?
+        .min(token_swap.reward_budget_remaining);
+    assert_reward_supply(&reward_mint, amount_to_reward)?;
     let amount_out = receive_amount
         .checked_sub(trade_fee)
         .ok_or(SwapError::CalculationFailure)?;
 
     if amount_out < minimum_amount_out {
-        return Err(SwapError::ExceededSlippage.into());
-    }
-
+        return Err(SwapDiagnostic::new(
+            SwapError::ExceededSlippage,
+            minimum_amount_out as u128,
+            amount_out as u128,
+        )
+        .fail());
+    }
+
+    // admin and creator fees leave the paying token account with the trade, so
+    // the reserves only keep the lp share of the trade fee
+    let amount_paid = amount_out
+        .checked_add(admin_fee)
+        .and_then(|amount| amount.checked_add(creator_fee))
+        .ok_or(SwapError::CalculationFailure)?;
     let (base_balance, quote_balance) = match swap_direction {
         SwapDirection::SellBase => (
             token_a
@@ -411,14 +780,14 @@ fn process_swap(
                 .ok_or(SwapError::CalculationFailure)?,
             token_b
                 .amount
-                .checked_sub(amount_out)
-                .ok_or(SwapError::CalculationFailure)?,
+                .checked_sub(amount_paid)
+                .ok_or(SwapError::PoolInsolvent)?,
         ),
         SwapDirection::SellQuote => (
             token_a
                 .amount
-                .checked_sub(amount_out)
-                .ok_or(SwapError::CalculationFailure)?,
+                .checked_sub(amount_paid)
+                .ok_or(SwapError::PoolInsolvent)?,
             token_b
                 .amount
                 .checked_add(amount_in)
@@ -441,6 +810,17 @@ fn process_swap(
         .ok_or(SwapError::CalculationFailure)?;
     token_swap.block_timestamp_last = clock.unix_timestamp.try_into().unwrap();
     token_swap.base_price_cumulative_last = base_price_cumulative_last;
+    token_swap.base_price_squared_cumulative_last = base_price_squared_cumulative_last;
+    token_swap.reward_budget_remaining = token_swap
+        .reward_budget_remaining
+        .checked_sub(amount_to_reward)
+        .ok_or(SwapError::CalculationFailure)?;
+    // volume is counted on the quote side, before fees
+    let quote_volume = match swap_direction {
+        SwapDirection::SellBase => receive_amount,
+        SwapDirection::SellQuote => amount_in,
+    };
+    token_swap.cumulative_volume = token_swap.cumulative_volume.saturating_add(quote_volume);
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
 
     match swap_direction {
@@ -463,15 +843,26 @@ fn process_swap(
                 swap_nonce,
                 amount_out,
             )?;
-            token_transfer(
+            transfer_admin_fee(
+                &config,
                 swap_info.key,
                 token_program_info.clone(),
                 swap_destination_info.clone(),
                 admin_destination_info.clone(),
+                secondary_admin_destination_info.clone(),
                 swap_authority_info.clone(),
                 swap_nonce,
                 admin_fee,
             )?;
+            token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                swap_destination_info.clone(),
+                creator_destination_info.clone(),
+                swap_authority_info.clone(),
+                swap_nonce,
+                creator_fee,
+            )?;
             token_mint_to(
                 config_info.key,
                 token_program_info.clone(),
@@ -501,15 +892,26 @@ fn process_swap(
                 swap_nonce,
                 amount_out,
             )?;
-            token_transfer(
+            transfer_admin_fee(
+                &config,
                 swap_info.key,
                 token_program_info.clone(),
                 swap_source_info.clone(),
                 admin_destination_info.clone(),
+                secondary_admin_destination_info.clone(),
                 swap_authority_info.clone(),
                 swap_nonce,
                 admin_fee,
             )?;
+            token_transfer(
+                swap_info.key,
+                token_program_info.clone(),
+                swap_source_info.clone(),
+                creator_destination_info.clone(),
+                swap_authority_info.clone(),
+                swap_nonce,
+                creator_fee,
+            )?;
             token_mint_to(
                 config_info.key,
                 token_program_info.clone(),
@@ -522,6 +924,9 @@ fn process_swap(
         }
     };
 
+    config.record_rewards_minted(amount_to_reward)?;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+
     Ok(())
 }
 
@@ -530,9 +935,12 @@ fn process_deposit(
     token_a_amount: u64,
     token_b_amount: u64,
     min_mint_amount: u64,
+    deadline: Option<i64>,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    assert_account_count(accounts, 16)?;
     let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
     let authority_info = next_account_info(account_info_iter)?;
     let user_transfer_authority_info = next_account_info(account_info_iter)?;
@@ -547,16 +955,22 @@ fn process_deposit(
     let pyth_a_price_info = next_account_info(account_info_iter)?;
     let pyth_b_price_info = next_account_info(account_info_iter)?;
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    assert_deadline(deadline, clock)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    if swap_info.owner != program_id {
+    if swap_info.owner != program_id || config_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
 
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-    if token_swap.is_paused {
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if token_swap.pause_flags.contains(PauseFlags::DEPOSITS) {
         return Err(SwapError::IsPaused.into());
     }
+    token_swap.pool_state.validate_invariants()?;
 
     let nonce = token_swap.nonce;
     if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
@@ -577,6 +991,11 @@ fn process_deposit(
     if token_b_info.key == source_b_info.key {
         return Err(SwapError::InvalidInput.into());
     }
+    // shares are minted by the smaller of the two input ratios,
+    // so a one-sided deposit would mint nothing for the tokens paid in
+    if token_a_amount == 0 || token_b_amount == 0 {
+        return Err(SwapError::ImbalancedDeposit.into());
+    }
 
     let mut liquidity_provider =
         LiquidityProvider::unpack(&liquidity_provider_info.data.borrow_mut())?;
@@ -591,13 +1010,31 @@ fn process_deposit(
     }
 
     let token_program_id = *token_program_info.key;
+    assert_token_program(&config, &token_program_id)?;
     let token_a = unpack_token_account(token_a_info, &token_program_id)?;
     let token_b = unpack_token_account(token_b_info, &token_program_id)?;
     let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+    if pool_mint.mint_authority != COption::Some(*authority_info.key) {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if pool_mint.decimals != token_swap.pool_mint_decimals {
+        return Err(SwapError::IncorrectMint.into());
+    }
 
     // updating price from pyth price
-    let (new_market_price, base_price_cumulative_last) =
-        get_new_market_price(&mut token_swap, pyth_a_price_info, pyth_b_price_info, clock)?;
+    let oracle_price = read_oracle_price(
+        &token_swap,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        config.max_ema_divergence_bps,
+        clock,
+    );
+    let (new_market_price, base_price_cumulative_last, base_price_squared_cumulative_last) =
+        get_new_market_price(
+            &mut token_swap,
+            oracle_price,
+            clock.unix_timestamp.try_into().unwrap(),
+        )?;
 
     let mut state = PoolState::new(PoolState {
         market_price: new_market_price,
@@ -617,9 +1054,12 @@ fn process_deposit(
         return Err(SwapError::ExceededSlippage.into());
     }
 
-    liquidity_provider
-        .find_or_add_position(*swap_info.key, clock.unix_timestamp)?
-        .deposit(pool_mint_amount)?;
+    let position = liquidity_provider.find_or_add_position(*swap_info.key, clock.unix_timestamp)?;
+    // pools do not keep a reward per share accumulator yet
+    position.deposit(pool_mint_amount, Decimal::zero())?;
+    if position.liquidity_amount >= token_swap.min_reward_liquidity {
+        position.credit_deposit_reward(token_swap.rewards.deposit_reward_u64(pool_mint_amount)?)?;
+    }
     LiquidityProvider::pack(
         liquidity_provider,
         &mut liquidity_provider_info.data.borrow_mut(),
@@ -635,6 +1075,7 @@ fn process_deposit(
 
     token_swap.block_timestamp_last = clock.unix_timestamp.try_into().unwrap();
     token_swap.base_price_cumulative_last = base_price_cumulative_last;
+    token_swap.base_price_squared_cumulative_last = base_price_squared_cumulative_last;
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
 
     token_transfer(
@@ -673,9 +1114,12 @@ fn process_withdraw(
     pool_token_amount: u64,
     minimum_token_a_amount: u64,
     minimum_token_b_amount: u64,
+    deadline: Option<i64>,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    assert_account_count(accounts, 20)?;
     let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
     let authority_info = next_account_info(account_info_iter)?;
     let user_transfer_authority_info = next_account_info(account_info_iter)?;
@@ -687,18 +1131,29 @@ fn process_withdraw(
     let dest_token_b_info = next_account_info(account_info_iter)?;
     let admin_fee_dest_a_info = next_account_info(account_info_iter)?;
     let admin_fee_dest_b_info = next_account_info(account_info_iter)?;
+    let secondary_admin_fee_dest_a_info = next_account_info(account_info_iter)?;
+    let secondary_admin_fee_dest_b_info = next_account_info(account_info_iter)?;
     let liquidity_provider_info = next_account_info(account_info_iter)?;
     let liquidity_owner_info = next_account_info(account_info_iter)?;
     let pyth_a_price_info = next_account_info(account_info_iter)?;
     let pyth_b_price_info = next_account_info(account_info_iter)?;
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+    assert_deadline(deadline, clock)?;
     let token_program_info = next_account_info(account_info_iter)?;
 
-    if swap_info.owner != program_id {
+    if swap_info.owner != program_id || config_info.owner != program_id {
         return Err(SwapError::InvalidAccountOwner.into());
     }
 
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+    if token_swap.pause_flags.contains(PauseFlags::WITHDRAWALS) {
+        return Err(SwapError::IsPaused.into());
+    }
+    token_swap.pool_state.validate_invariants()?;
     let nonce = token_swap.nonce;
     if *authority_info.key != authority_id(program_id, swap_info.key, nonce)? {
         return Err(SwapError::InvalidProgramAddress.into());
@@ -726,7 +1181,14 @@ fn process_withdraw(
     }
 
     let token_program_id = *token_program_info.key;
+    assert_token_program(&config, &token_program_id)?;
     let pool_mint = unpack_mint(pool_mint_info, &token_program_id)?;
+    if pool_mint.mint_authority != COption::Some(*authority_info.key) {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if pool_mint.decimals != token_swap.pool_mint_decimals {
+        return Err(SwapError::IncorrectMint.into());
+    }
     if pool_mint.supply == 0 {
         return Err(SwapError::EmptySupply.into());
     }
@@ -743,8 +1205,19 @@ fn process_withdraw(
         return Err(SwapError::InvalidSigner.into());
     }
 
-    let (new_market_price, base_price_cumulative_last) =
-        get_new_market_price(&mut token_swap, pyth_a_price_info, pyth_b_price_info, clock)?;
+    let oracle_price = read_oracle_price(
+        &token_swap,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        config.max_ema_divergence_bps,
+        clock,
+    );
+    let (new_market_price, base_price_cumulative_last, base_price_squared_cumulative_last) =
+        get_new_market_price(
+            &mut token_swap,
+            oracle_price,
+            clock.unix_timestamp.try_into().unwrap(),
+        )?;
 
     let mut state = PoolState::new(PoolState {
         market_price: new_market_price,
@@ -758,18 +1231,19 @@ fn process_withdraw(
         pool_mint.supply,
     )?;
 
-    let fees = &token_swap.fees;
-    let withdraw_fee_base = fees.withdraw_fee(base_out_amount)?;
-    let admin_fee_base = fees.admin_withdraw_fee(withdraw_fee_base)?;
-    let base_out_amount = base_out_amount
-        .checked_sub(withdraw_fee_base)
+    // only a full exit may take the supply below the configured floor
+    let remaining_supply = pool_mint
+        .supply
+        .checked_sub(pool_token_amount)
         .ok_or(SwapError::CalculationFailure)?;
+    if remaining_supply != 0 && remaining_supply < config.min_pool_supply {
+        return Err(SwapError::WithdrawNotEnough.into());
+    }
 
-    let withdraw_fee_quote = fees.withdraw_fee(quote_out_amount)?;
-    let admin_fee_quote = fees.admin_withdraw_fee(withdraw_fee_quote)?;
-    let quote_out_amount = quote_out_amount
-        .checked_sub(withdraw_fee_quote)
-        .ok_or(SwapError::CalculationFailure)?;
+    let (base_out_amount, admin_fee_base, _) =
+        token_swap.fees.apply_withdraw_fees(base_out_amount)?;
+    let (quote_out_amount, admin_fee_quote, _) =
+        token_swap.fees.apply_withdraw_fees(quote_out_amount)?;
 
     let (_, position_index) = liquidity_provider.find_position(*swap_info.key)?;
     liquidity_provider.withdraw(pool_token_amount, position_index)?;
@@ -787,6 +1261,7 @@ fn process_withdraw(
         .ok_or(SwapError::CalculationFailure)?;
     token_swap.block_timestamp_last = clock.unix_timestamp.try_into().unwrap();
     token_swap.base_price_cumulative_last = base_price_cumulative_last;
+    token_swap.base_price_squared_cumulative_last = base_price_squared_cumulative_last;
     SwapInfo::pack(token_swap, &mut swap_info.data.borrow_mut())?;
 
     token_transfer(
@@ -798,11 +1273,13 @@ fn process_withdraw(
         nonce,
         base_out_amount,
     )?;
-    token_transfer(
+    transfer_admin_fee(
+        &config,
         swap_info.key,
         token_program_info.clone(),
         token_a_info.clone(),
         admin_fee_dest_a_info.clone(),
+        secondary_admin_fee_dest_a_info.clone(),
         authority_info.clone(),
         nonce,
         admin_fee_base,
@@ -816,11 +1293,13 @@ fn process_withdraw(
         nonce,
         quote_out_amount,
     )?;
-    token_transfer(
+    transfer_admin_fee(
+        &config,
         swap_info.key,
         token_program_info.clone(),
         token_b_info.clone(),
         admin_fee_dest_b_info.clone(),
+        secondary_admin_fee_dest_b_info.clone(),
         authority_info.clone(),
         nonce,
         admin_fee_quote,
@@ -839,6 +1318,7 @@ fn process_withdraw(
 }
 
 fn process_init_liquidity_provider(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
     let account_info_iter = &mut accounts.iter();
     let liquidity_provider_info = next_account_info(account_info_iter)?;
     let liquidity_owner_info = next_account_info(account_info_iter)?;
@@ -865,7 +1345,63 @@ fn process_init_liquidity_provider(program_id: &Pubkey, accounts: &[AccountInfo]
     Ok(())
 }
 
+fn process_migrate_liquidity_provider(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let liquidity_owner_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    if !liquidity_owner_info.is_signer {
+        return Err(SwapError::InvalidSigner.into());
+    }
+
+    let liquidity_provider =
+        LiquidityProvider::unpack_versioned(&liquidity_provider_info.data.borrow())?;
+    if liquidity_provider.owner != *liquidity_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+    if liquidity_provider_info.data_len() == LiquidityProvider::LEN {
+        return Ok(());
+    }
+
+    let rent_shortfall = rent
+        .minimum_balance(LiquidityProvider::LEN)
+        .saturating_sub(liquidity_provider_info.lamports());
+    if rent_shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(
+                payer_info.key,
+                liquidity_provider_info.key,
+                rent_shortfall,
+            ),
+            &[
+                payer_info.clone(),
+                liquidity_provider_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+
+    liquidity_provider_info.realloc(LiquidityProvider::LEN, true)?;
+    LiquidityProvider::pack(
+        liquidity_provider,
+        &mut liquidity_provider_info.data.borrow_mut(),
+    )?;
+
+    Ok(())
+}
+
 fn process_claim_liquidity_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 8)?;
     let account_info_iter = &mut accounts.iter();
     let config_info = next_account_info(account_info_iter)?;
     let swap_info = next_account_info(account_info_iter)?;
@@ -880,7 +1416,11 @@ fn process_claim_liquidity_rewards(program_id: &Pubkey, accounts: &[AccountInfo]
         return Err(SwapError::InvalidAccountOwner.into());
     }
 
-    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let mut config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
     let market_nonce = config.bump_seed;
     if *market_authority_info.key != authority_id(program_id, config_info.key, market_nonce)? {
         return Err(SwapError::InvalidProgramAddress.into());
@@ -889,7 +1429,9 @@ fn process_claim_liquidity_rewards(program_id: &Pubkey, accounts: &[AccountInfo]
     if config.deltafi_mint != *claim_mint_info.key {
         return Err(SwapError::IncorrectMint.into());
     }
-    if claim_destination_info.owner == market_authority_info.key {
+    assert_token_program(&config, token_program_info.key)?;
+    let claim_destination = unpack_token_account(claim_destination_info, token_program_info.key)?;
+    if claim_destination.owner == *market_authority_info.key {
         return Err(SwapError::InvalidOwner.into());
     }
 
@@ -901,8 +1443,20 @@ fn process_claim_liquidity_rewards(program_id: &Pubkey, accounts: &[AccountInfo]
     if !liquidity_owner_info.is_signer {
         return Err(SwapError::InvalidSigner.into());
     }
+    if config.strict_claim_destination && claim_destination.owner != *liquidity_owner_info.key {
+        return Err(SwapError::InvalidOwner.into());
+    }
+
+    let (position, _) = liquidity_provider.find_position(*swap_info.key)?;
+    if position.rewards_owed < config.min_claim_amount {
+        return Err(SwapError::ClaimBelowMinimum.into());
+    }
 
     let reward_amount = liquidity_provider.claim(*swap_info.key)?;
+    assert_reward_supply(
+        &unpack_mint(claim_mint_info, token_program_info.key)?,
+        reward_amount,
+    )?;
     LiquidityProvider::pack(
         liquidity_provider,
         &mut liquidity_provider_info.data.borrow_mut(),
@@ -918,13 +1472,75 @@ fn process_claim_liquidity_rewards(program_id: &Pubkey, accounts: &[AccountInfo]
         reward_amount,
     )?;
 
+    config.record_rewards_minted(reward_amount)?;
+    ConfigInfo::pack(config, &mut config_info.data.borrow_mut())?;
+
     Ok(())
 }
 
+/// Claim the rewards owed to a position, then withdraw from it. A full
+/// withdraw only removes a position without rewards owed, so the claim runs
+/// first. Rewards below the claim minimum stay owed rather than failing the
+/// withdraw.
+fn process_withdraw_and_claim(
+    program_id: &Pubkey,
+    pool_token_amount: u64,
+    minimum_token_a_amount: u64,
+    minimum_token_b_amount: u64,
+    deadline: Option<i64>,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 23)?;
+    let (withdraw_accounts, claim_accounts) = accounts.split_at(20);
+    let config_info = &withdraw_accounts[0];
+    let swap_info = &withdraw_accounts[1];
+    let liquidity_provider_info = &withdraw_accounts[14];
+    let liquidity_owner_info = &withdraw_accounts[15];
+    let token_program_info = &withdraw_accounts[19];
+    let market_authority_info = &claim_accounts[0];
+    let claim_destination_info = &claim_accounts[1];
+    let claim_mint_info = &claim_accounts[2];
+
+    if config_info.owner != program_id || liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    let mut liquidity_provider = LiquidityProvider::unpack(&liquidity_provider_info.data.borrow())?;
+    let (position, _) = liquidity_provider.find_position(*swap_info.key)?;
+    if position.rewards_owed > 0 && position.rewards_owed >= config.min_claim_amount {
+        process_claim_liquidity_rewards(
+            program_id,
+            &[
+                config_info.clone(),
+                swap_info.clone(),
+                market_authority_info.clone(),
+                liquidity_provider_info.clone(),
+                liquidity_owner_info.clone(),
+                claim_destination_info.clone(),
+                claim_mint_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+    }
+
+    process_withdraw(
+        program_id,
+        pool_token_amount,
+        minimum_token_a_amount,
+        minimum_token_b_amount,
+        deadline,
+        withdraw_accounts,
+    )
+}
+
 fn process_refresh_liquidity_obligation(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
+    if accounts.len() < 2 {
+        msg!("Expected at least 2 accounts, got {}", accounts.len());
+        return Err(SwapError::InvalidAccountCount.into());
+    }
     let account_info_iter = &mut accounts.iter();
     let swap_info = next_account_info(account_info_iter)?;
     let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
@@ -935,16 +1551,17 @@ fn process_refresh_liquidity_obligation(
     }
 
     let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
-
-    let lp_price = token_swap.pool_state.get_mid_price()?;
-    let _deltafi_price = Decimal::one().try_div(10)?; // Temp value
-    let reward_ratio = lp_price.try_div(_deltafi_price)?;
+    let reward_ratio = reward_ratio(&mut token_swap)?;
 
     for liquidity_provider_info in account_info_iter {
         let mut liquidity_provider =
             LiquidityProvider::unpack(&liquidity_provider_info.data.borrow_mut())?;
         let (position, _) = liquidity_provider.find_position(*swap_info.key)?;
-        position.calc_and_update_rewards(reward_ratio, clock.unix_timestamp)?;
+        position.calc_and_update_rewards(
+            reward_ratio,
+            clock.unix_timestamp,
+            token_swap.min_reward_liquidity,
+        )?;
 
         LiquidityProvider::pack(
             liquidity_provider,
@@ -955,69 +1572,1112 @@ fn process_refresh_liquidity_obligation(
     Ok(())
 }
 
-fn get_new_market_price(
-    token_swap: &mut SwapInfo,
-    pyth_a_price_info: &AccountInfo,
-    pyth_b_price_info: &AccountInfo,
-    clock: &Clock,
-) -> Result<(Decimal, Decimal), ProgramError> {
-    let pool_state = &mut token_swap.pool_state;
-    let pool_mid_price = pool_state.get_mid_price()?;
-    let block_timestamp_last: u64 = clock.unix_timestamp.try_into().unwrap();
-    let mut base_price_cumulative_last = token_swap.base_price_cumulative_last;
-    if token_swap.is_open_twap {
-        let time_elapsed = block_timestamp_last - token_swap.block_timestamp_last;
-        if time_elapsed > 0
-            && !pool_state.base_reserve.is_zero()
-            && !pool_state.quote_reserve.is_zero()
-        {
-            base_price_cumulative_last =
-                base_price_cumulative_last.try_add(pool_mid_price.try_mul(time_elapsed as u64)?)?;
-        }
+fn process_get_execution_price(
+    program_id: &Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
     }
 
-    let market_price = if let Ok(market_price) =
-        get_market_price_from_pyth(pyth_a_price_info, pyth_b_price_info, clock)
-    {
-        // pyth price
-        market_price
-    } else if token_swap.is_open_twap {
-        // internal oracle price
-        base_price_cumulative_last.try_div(block_timestamp_last - token_swap.cumulative_ticks)?
-    } else {
-        // current pool middle price
-        pool_mid_price
-    };
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let price = token_swap
+        .pool_state
+        .execution_price(amount_in, swap_direction)?;
 
-    let deviation = if pool_mid_price > market_price {
-        pool_mid_price.try_sub(market_price)?
-    } else {
-        market_price.try_sub(pool_mid_price)?
-    };
+    set_return_data(&price.to_scaled_val()?.to_le_bytes());
 
-    Ok((
-        if deviation.try_mul(100u64)? > pool_mid_price {
-            market_price
-        } else {
-            pool_mid_price
+    Ok(())
+}
+
+fn process_get_balanced_deposit_ratio(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let ratio = token_swap.pool_state.balanced_deposit_ratio()?;
+
+    set_return_data(&ratio.to_scaled_val()?.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_get_multiplier(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let headroom = token_swap.pool_state.multiplier_headroom()?;
+
+    let mut data = vec![token_swap.pool_state.multiplier as u8];
+    data.extend_from_slice(&headroom.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_pool_age(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let age = token_swap.pool_age(
+        clock
+            .unix_timestamp
+            .try_into()
+            .map_err(|_| SwapError::CalculationFailure)?,
+    )?;
+
+    let mut data = token_swap.creation_ts.to_le_bytes().to_vec();
+    data.extend_from_slice(&age.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_required_slippage(
+    program_id: &Pubkey,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let slippage_bps =
+        token_swap
+            .pool_state
+            .required_slippage_bps(&token_swap.fees, amount_in, swap_direction)?;
+
+    set_return_data(&slippage_bps.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_get_arb_amount_to_oracle(
+    program_id: &Pubkey,
+    swap_direction: SwapDirection,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let oracle_price = get_market_price_from_pyth(
+        pyth_a_price_info,
+        pyth_b_price_info,
+        token_swap.invert_a,
+        token_swap.invert_b,
+        0,
+        clock,
+    )?;
+    let amount_in = token_swap
+        .pool_state
+        .arb_amount_to_oracle(oracle_price, swap_direction)?;
+
+    set_return_data(&amount_in.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_preview_fee_change(
+    program_id: &Pubkey,
+    sample_amount: u64,
+    new_fees: &Fees,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    new_fees.validate_against_policy()?;
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let (current_net, current_admin_fee, _) = token_swap
+        .fees
+        .apply_trade_fees(sample_amount, Decimal::one())?;
+    let (new_net, new_admin_fee, _) = new_fees.apply_trade_fees(sample_amount, Decimal::one())?;
+
+    let mut data = current_net.to_le_bytes().to_vec();
+    data.extend_from_slice(&current_admin_fee.to_le_bytes());
+    data.extend_from_slice(&new_net.to_le_bytes());
+    data.extend_from_slice(&new_admin_fee.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_marginal_price(
+    program_id: &Pubkey,
+    swap_direction: SwapDirection,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let price = token_swap.pool_state.marginal_price(swap_direction)?;
+
+    set_return_data(&price.to_scaled_val()?.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_get_realized_vol(
+    program_id: &Pubkey,
+    cumulative_then: Decimal,
+    squared_cumulative_then: Decimal,
+    timestamp_then: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let variance = token_swap.realized_variance_since(
+        cumulative_then,
+        squared_cumulative_then,
+        timestamp_then,
+    )?;
+    let volatility = Decimal::geometric_mean(variance, Decimal::one())?;
+
+    let mut data = variance.to_scaled_val()?.to_le_bytes().to_vec();
+    data.extend_from_slice(&volatility.to_scaled_val()?.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_implied_prices(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let reserve_ratio_price = token_swap.pool_state.reserve_ratio_price()?;
+    let mid_price = token_swap
+        .pool_state
+        .quantized_mid_price(token_swap.price_tick)?;
+
+    let mut data = reserve_ratio_price.to_scaled_val()?.to_le_bytes().to_vec();
+    data.extend_from_slice(&mid_price.to_scaled_val()?.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_price_source(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 5)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+    let swap_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id || config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    // the unpacked copy is never written back
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if token_swap.config_key != *config_info.key {
+        return Err(SwapError::InvalidConfig.into());
+    }
+
+    let oracle_price = read_oracle_price(
+        &token_swap,
+        pyth_a_price_info,
+        pyth_b_price_info,
+        config.max_ema_divergence_bps,
+        clock,
+    );
+    let now: u64 = clock.unix_timestamp.try_into().unwrap();
+    let pool_mid_price = token_swap.pool_state.get_mid_price()?;
+    let mut base_price_cumulative = token_swap.base_price_cumulative_last;
+    let time_elapsed = now.saturating_sub(token_swap.block_timestamp_last);
+    if token_swap.is_open_twap
+        && time_elapsed > 0
+        && !token_swap.pool_state.base_reserve.is_zero()
+        && !token_swap.pool_state.quote_reserve.is_zero()
+    {
+        base_price_cumulative =
+            base_price_cumulative.wrapping_add(pool_mid_price.try_mul(time_elapsed)?);
+    }
+    let (market_price, source) = select_market_price(
+        &token_swap,
+        oracle_price,
+        pool_mid_price,
+        base_price_cumulative,
+        now,
+    )?;
+
+    let mut data = vec![source as u8];
+    data.extend_from_slice(&market_price.to_scaled_val()?.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_mid_price(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let mid_price = token_swap
+        .pool_state
+        .quantized_mid_price(token_swap.price_tick)?;
+
+    set_return_data(&mid_price.to_scaled_val()?.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_get_fee_apr(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let elapsed = token_swap.pool_age(
+        clock
+            .unix_timestamp
+            .try_into()
+            .map_err(|_| SwapError::CalculationFailure)?,
+    )?;
+    // pool liquidity valued in the quote token, like the volume
+    let liquidity = token_swap
+        .pool_state
+        .base_reserve
+        .try_mul(token_swap.pool_state.get_mid_price()?)?
+        .try_add(token_swap.pool_state.quote_reserve)?;
+    let apr = if elapsed == 0 || liquidity.is_zero() {
+        Decimal::zero()
+    } else {
+        fee_apr(
+            token_swap.cumulative_volume,
+            &token_swap.fees,
+            elapsed,
+            liquidity,
+        )?
+    };
+
+    set_return_data(&apr.to_scaled_val()?.to_le_bytes());
+
+    Ok(())
+}
+
+/// Check the swap cooldown of a user has elapsed and restart it
+fn record_swap_cooldown(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    user_pubkey: &Pubkey,
+    swap_cooldown_info: &AccountInfo,
+    swap_cooldown_seconds: u64,
+    timestamp: UnixTimestamp,
+) -> ProgramResult {
+    if swap_cooldown_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    let mut swap_cooldown = SwapCooldown::unpack(&swap_cooldown_info.data.borrow())?;
+    let expected_key = Pubkey::create_program_address(
+        &[
+            SWAP_COOLDOWN_SEED,
+            swap_pubkey.as_ref(),
+            user_pubkey.as_ref(),
+            &[swap_cooldown.bump_seed],
+        ],
+        program_id,
+    )
+    .map_err(|_| SwapError::InvalidProgramAddress)?;
+    if *swap_cooldown_info.key != expected_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+
+    swap_cooldown.record_swap(swap_cooldown_seconds, timestamp)?;
+    SwapCooldown::pack(swap_cooldown, &mut swap_cooldown_info.data.borrow_mut())
+}
+
+fn process_init_swap_cooldown(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 6)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let swap_cooldown_info = next_account_info(account_info_iter)?;
+    let user_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let rent = &Rent::from_account_info(next_account_info(account_info_iter)?)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+    SwapInfo::unpack(&swap_info.data.borrow())?;
+
+    let (swap_cooldown_key, bump_seed) =
+        swap_cooldown_address(program_id, swap_info.key, user_info.key);
+    if *swap_cooldown_info.key != swap_cooldown_key {
+        return Err(SwapError::InvalidProgramAddress.into());
+    }
+    if swap_cooldown_info.owner == program_id {
+        return Err(SwapError::AlreadyInUse.into());
+    }
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_info.key,
+            swap_cooldown_info.key,
+            rent.minimum_balance(SwapCooldown::LEN),
+            SwapCooldown::LEN as u64,
+            program_id,
+        ),
+        &[
+            payer_info.clone(),
+            swap_cooldown_info.clone(),
+            system_program_info.clone(),
+        ],
+        &[&[
+            SWAP_COOLDOWN_SEED,
+            swap_info.key.as_ref(),
+            user_info.key.as_ref(),
+            &[bump_seed],
+        ]],
+    )?;
+
+    SwapCooldown::pack(
+        SwapCooldown {
+            is_initialized: true,
+            bump_seed,
+            last_swap_ts: 0,
         },
+        &mut swap_cooldown_info.data.borrow_mut(),
+    )
+}
+
+fn process_get_total_rewards_minted(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+    set_return_data(&config.total_rewards_minted.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_preview_withdraw(
+    program_id: &Pubkey,
+    pool_token_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    let pool_mint = unpack_mint(pool_mint_info, token_program_info.key)?;
+    if pool_mint.supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+
+    let (base_amount, quote_amount) = token_swap.pool_state.preview_withdraw(
+        pool_token_amount,
+        pool_mint.supply,
+        &token_swap.fees,
+    )?;
+
+    let mut data = base_amount.to_le_bytes().to_vec();
+    data.extend_from_slice(&quote_amount.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_preview_redeem(
+    program_id: &Pubkey,
+    pool_token_amount: u64,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 6)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    let pool_mint = unpack_mint(pool_mint_info, token_program_info.key)?;
+    if pool_mint.supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+
+    let oracle_price = get_market_price_from_pyth(
+        pyth_a_price_info,
+        pyth_b_price_info,
+        token_swap.invert_a,
+        token_swap.invert_b,
+        0,
+        clock,
+    )?;
+    let (base_amount, quote_amount) =
+        token_swap
+            .pool_state
+            .preview_redeem(oracle_price, pool_token_amount, pool_mint.supply)?;
+
+    let mut data = base_amount.to_le_bytes().to_vec();
+    data.extend_from_slice(&quote_amount.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_price_health(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let mid_price = token_swap
+        .pool_state
+        .quantized_mid_price(token_swap.price_tick)?;
+    let oracle_price = get_market_price_from_pyth(
+        pyth_a_price_info,
+        pyth_b_price_info,
+        token_swap.invert_a,
+        token_swap.invert_b,
+        0,
+        clock,
+    )?;
+    let deviation_bps = price_deviation(mid_price, oracle_price)?
+        .try_div(oracle_price)?
+        .to_bps()?;
+
+    let mut data = mid_price.to_scaled_val()?.to_le_bytes().to_vec();
+    data.extend_from_slice(&oracle_price.to_scaled_val()?.to_le_bytes());
+    data.extend_from_slice(&deviation_bps.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_pool_health(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    // a stale or unusable oracle is reported, not an error
+    let oracle_price = get_market_price_from_pyth(
+        pyth_a_price_info,
+        pyth_b_price_info,
+        token_swap.invert_a,
+        token_swap.invert_b,
+        0,
+        clock,
+    )
+    .ok();
+    let health = PoolHealth::new(&token_swap.pool_state, oracle_price, token_swap.pause_flags)?;
+
+    let mut data = [0u8; PoolHealth::LEN];
+    health.pack_into_slice(&mut data);
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_preview_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id || liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let reward_ratio = reward_ratio(&mut token_swap)?;
+
+    let mut liquidity_provider = LiquidityProvider::unpack(&liquidity_provider_info.data.borrow())?;
+    let (position, _) = liquidity_provider.find_position(*swap_info.key)?;
+    let claim_status = position.pending_rewards(
+        reward_ratio,
+        clock.unix_timestamp,
+        token_swap.min_reward_liquidity,
+    )?;
+
+    let mut data = [0u8; ClaimStatus::LEN];
+    claim_status.pack_into_slice(&mut data);
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_swap_accounts(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let accounts_bundle = token_swap.accounts_bundle(program_id, swap_info.key)?;
+
+    let mut data = [0u8; SwapAccountsBundle::LEN];
+    accounts_bundle.pack_into_slice(&mut data);
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_claim_status(
+    program_id: &Pubkey,
+    pool: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 2)?;
+    let account_info_iter = &mut accounts.iter();
+    let liquidity_provider_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if liquidity_provider_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut liquidity_provider = LiquidityProvider::unpack(&liquidity_provider_info.data.borrow())?;
+    let (position, _) = liquidity_provider.find_position(*pool)?;
+    let claim_status = position.claim_status(clock.unix_timestamp);
+
+    let mut data = [0u8; ClaimStatus::LEN];
+    claim_status.pack_into_slice(&mut data);
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_config_stats(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let config_info = next_account_info(account_info_iter)?;
+
+    if config_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let config = ConfigInfo::unpack(&config_info.data.borrow())?;
+
+    let mut data = [0u8; ConfigStats::LEN];
+    config.stats().pack_into_slice(&mut data);
+    set_return_data(&data);
+
+    Ok(())
+}
+
+fn process_get_reward_apr(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    assert_account_count(accounts, 3)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pool_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    if *pool_mint_info.key != token_swap.pool_mint {
+        return Err(SwapError::IncorrectMint.into());
+    }
+    let pool_mint = unpack_mint(pool_mint_info, token_program_info.key)?;
+    if pool_mint.supply == 0 {
+        return Err(SwapError::EmptySupply.into());
+    }
+
+    let mid_price = token_swap.pool_state.get_mid_price()?;
+    // lp token valued in the quote token at the mid price
+    let lp_price = token_swap
+        .pool_state
+        .base_reserve
+        .try_mul(mid_price)?
+        .try_add(token_swap.pool_state.quote_reserve)?
+        .try_div(pool_mint.supply)?;
+    let deltafi_price = deltafi_price()?;
+    let apr = reward_apr(
+        reward_rate(mid_price, deltafi_price)?,
+        lp_price,
+        deltafi_price,
+    )?;
+
+    set_return_data(&apr.to_scaled_val()?.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_get_split_threshold(
+    program_id: &Pubkey,
+    swap_direction: SwapDirection,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 1)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    let token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let threshold = token_swap
+        .pool_state
+        .optimal_split_threshold(&token_swap.fees, swap_direction)?;
+
+    set_return_data(&threshold.to_le_bytes());
+
+    Ok(())
+}
+
+fn process_check_swap_allowed(
+    program_id: &Pubkey,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    swap_direction: SwapDirection,
+    deadline: Option<i64>,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    assert_account_count(accounts, 4)?;
+    let account_info_iter = &mut accounts.iter();
+    let swap_info = next_account_info(account_info_iter)?;
+    let pyth_a_price_info = next_account_info(account_info_iter)?;
+    let pyth_b_price_info = next_account_info(account_info_iter)?;
+    let clock = &Clock::from_account_info(next_account_info(account_info_iter)?)?;
+
+    if swap_info.owner != program_id {
+        return Err(SwapError::InvalidAccountOwner.into());
+    }
+
+    // the unpacked copy is never written back
+    let mut token_swap = SwapInfo::unpack(&swap_info.data.borrow())?;
+    let oracle_price =
+        read_oracle_price(&token_swap, pyth_a_price_info, pyth_b_price_info, 0, clock);
+    let (new_market_price, _, _) = get_new_market_price(
+        &mut token_swap,
+        oracle_price,
+        clock.unix_timestamp.try_into().unwrap(),
+    )?;
+    let state = PoolState::new(PoolState {
+        market_price: new_market_price,
+        ..token_swap.pool_state
+    })?;
+
+    let (allowed, deviation) =
+        match project_swap(&state, &token_swap.fees, amount_in, swap_direction) {
+            Ok((receive_amount, amount_out)) => {
+                let execution_price = if receive_amount == 0 {
+                    Decimal::zero()
+                } else {
+                    state.execution_price(amount_in, swap_direction)?
+                };
+                (
+                    amount_out >= minimum_amount_out
+                        && !token_swap.pause_flags.contains(PauseFlags::SWAPS)
+                        && assert_deadline(deadline, clock).is_ok()
+                        && (token_swap.max_trade_amount == 0
+                            || amount_in <= token_swap.max_trade_amount)
+                        && (token_swap.max_allowed_slippage_bps == 0
+                            || check_slippage_cap(
+                                &state,
+                                amount_in,
+                                minimum_amount_out,
+                                swap_direction,
+                                token_swap.max_allowed_slippage_bps,
+                            )
+                            .is_ok()),
+                    price_deviation(execution_price, new_market_price)?
+                        .try_div(new_market_price)?,
+                )
+            }
+            Err(_) => (false, Decimal::one()),
+        };
+
+    let mut data = vec![allowed as u8];
+    data.extend_from_slice(&deviation.to_scaled_val()?.to_le_bytes());
+    set_return_data(&data);
+
+    Ok(())
+}
+
+/// Amount received before and after fees by a swap, checking the pool is
+/// left solvent and consistent
+fn project_swap(
+    state: &PoolState,
+    fees: &Fees,
+    amount_in: u64,
+    swap_direction: SwapDirection,
+) -> Result<(u64, u64), ProgramError> {
+    let (receive_amount, new_multiplier) = match swap_direction {
+        SwapDirection::SellBase => state.sell_base_token(amount_in)?,
+        SwapDirection::SellQuote => state.sell_quote_token(amount_in)?,
+    };
+    let amount_out = receive_amount
+        .checked_sub(fees.trade_fee(
+            receive_amount,
+            quote_per_received_token(state.market_price, swap_direction),
+        )?)
+        .ok_or(SwapError::CalculationFailure)?;
+
+    let (base_reserve, quote_reserve) = match swap_direction {
+        SwapDirection::SellBase => (
+            state.base_reserve.try_add(amount_in.into())?,
+            state.quote_reserve.try_sub(amount_out.into())?,
+        ),
+        SwapDirection::SellQuote => (
+            state.base_reserve.try_sub(amount_out.into())?,
+            state.quote_reserve.try_add(amount_in.into())?,
+        ),
+    };
+    PoolState::new(PoolState {
+        base_reserve,
+        quote_reserve,
+        multiplier: new_multiplier,
+        ..state.clone()
+    })?
+    .validate_invariants()?;
+
+    Ok((receive_amount, amount_out))
+}
+
+/// Absolute difference between two prices
+fn price_deviation(price: Decimal, reference: Decimal) -> Result<Decimal, ProgramError> {
+    if price > reference {
+        price.try_sub(reference)
+    } else {
+        reference.try_sub(price)
+    }
+}
+
+/// Price in quote tokens of the token a swap pays out
+fn quote_per_received_token(market_price: Decimal, swap_direction: SwapDirection) -> Decimal {
+    match swap_direction {
+        SwapDirection::SellBase => Decimal::one(),
+        SwapDirection::SellQuote => market_price,
+    }
+}
+
+/// Reject a minimum amount out below what the pool's slippage cap allows
+///
+/// The bound is taken against the amount the swap would receive at the mid
+/// price, before fees.
+fn check_slippage_cap(
+    state: &PoolState,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    swap_direction: SwapDirection,
+    max_allowed_slippage_bps: u64,
+) -> ProgramResult {
+    let mid_price = state.clone().get_mid_price()?;
+    let ideal_out = match swap_direction {
+        SwapDirection::SellBase => mid_price.try_mul(amount_in)?,
+        SwapDirection::SellQuote => Decimal::from(amount_in).try_div(mid_price)?,
+    }
+    .try_floor_u64()?;
+    let min_allowed_out = min_out_with_slippage(ideal_out, max_allowed_slippage_bps)?;
+    if minimum_amount_out < min_allowed_out {
+        return Err(SwapDiagnostic::new(
+            SwapError::SlippageCapExceeded,
+            min_allowed_out as u128,
+            minimum_amount_out as u128,
+        )
+        .fail());
+    }
+    Ok(())
+}
+
+/// DELTAFI token price used for liquidity rewards
+fn deltafi_price() -> Result<Decimal, ProgramError> {
+    Decimal::one().try_div(10) // Temp value
+}
+
+/// Liquidity rewards per lp token per claim period
+fn reward_ratio(token_swap: &mut SwapInfo) -> Result<Decimal, ProgramError> {
+    token_swap
+        .pool_state
+        .get_mid_price()?
+        .try_div(deltafi_price()?)
+}
+
+/// Oracle price of a pool, none if the pool is oracle-free or the pyth
+/// accounts hold no usable price
+fn read_oracle_price(
+    token_swap: &SwapInfo,
+    pyth_a_price_info: &AccountInfo,
+    pyth_b_price_info: &AccountInfo,
+    max_ema_divergence_bps: u16,
+    clock: &Clock,
+) -> Option<Decimal> {
+    if token_swap.oracle_free {
+        return None;
+    }
+    get_market_price_from_pyth(
+        pyth_a_price_info,
+        pyth_b_price_info,
+        token_swap.invert_a,
+        token_swap.invert_b,
+        max_ema_divergence_bps,
+        clock,
+    )
+    .ok()
+}
+
+/// Market price a pool trades at and its updated cumulative price
+///
+/// # Arguments
+///
+/// * token_swap - swap pool, its last oracle price is updated.
+/// * oracle_price - price read by `read_oracle_price`.
+/// * now - current unix timestamp.
+///
+/// # Return value
+///
+/// (market price, base price cumulative, base price squared cumulative)
+fn get_new_market_price(
+    token_swap: &mut SwapInfo,
+    oracle_price: Option<Decimal>,
+    now: u64,
+) -> Result<(Decimal, Decimal, Decimal), ProgramError> {
+    let pool_state = &mut token_swap.pool_state;
+    let pool_mid_price = pool_state.get_mid_price()?;
+    let block_timestamp_last = now;
+    let mut base_price_cumulative_last = token_swap.base_price_cumulative_last;
+    let mut base_price_squared_cumulative_last = token_swap.base_price_squared_cumulative_last;
+    if token_swap.is_open_twap {
+        let time_elapsed = block_timestamp_last - token_swap.block_timestamp_last;
+        if time_elapsed > 0
+            && !pool_state.base_reserve.is_zero()
+            && !pool_state.quote_reserve.is_zero()
+        {
+            base_price_cumulative_last =
+                base_price_cumulative_last.wrapping_add(pool_mid_price.try_mul(time_elapsed)?);
+            base_price_squared_cumulative_last = base_price_squared_cumulative_last.wrapping_add(
+                pool_mid_price
+                    .try_mul(pool_mid_price)?
+                    .try_mul(time_elapsed)?,
+            );
+        }
+    }
+
+    if token_swap.oracle_free {
+        // manual price set by the admin
+        return Ok((
+            pool_state.market_price,
+            base_price_cumulative_last,
+            base_price_squared_cumulative_last,
+        ));
+    }
+
+    if let Some(market_price) = oracle_price {
+        match token_swap.check_oracle_move(market_price, block_timestamp_last) {
+            Err(err) if err == SwapError::OraclePriceMoveTooLarge.into() => {
+                return Err(SwapDiagnostic::new(
+                    SwapError::OraclePriceMoveTooLarge,
+                    token_swap.last_oracle_price.to_scaled_val()?,
+                    market_price.to_scaled_val()?,
+                )
+                .fail());
+            }
+            result => result?,
+        }
+        token_swap.last_oracle_price = market_price;
+    }
+
+    let (market_price, _) = select_market_price(
+        token_swap,
+        oracle_price,
+        pool_mid_price,
         base_price_cumulative_last,
+        block_timestamp_last,
+    )?;
+
+    Ok((
+        market_price,
+        base_price_cumulative_last,
+        base_price_squared_cumulative_last,
     ))
 }
 
+/// Market price a pool trades at and the source it came from
+///
+/// # Arguments
+///
+/// * token_swap - swap pool.
+/// * oracle_price - price read by `read_oracle_price`, none if pyth is stale.
+/// * pool_mid_price - current pool mid price.
+/// * base_price_cumulative - cumulative price including the current block.
+/// * now - current unix timestamp.
+///
+/// # Return value
+///
+/// (market price, price source)
+fn select_market_price(
+    token_swap: &SwapInfo,
+    oracle_price: Option<Decimal>,
+    pool_mid_price: Decimal,
+    base_price_cumulative: Decimal,
+    now: u64,
+) -> Result<(Decimal, PriceSource), ProgramError> {
+    if token_swap.oracle_free {
+        // manual price set by the admin
+        return Ok((token_swap.pool_state.market_price, PriceSource::Manual));
+    }
+
+    let (market_price, source) = if let Some(market_price) = oracle_price {
+        (market_price, PriceSource::Pyth)
+    } else if token_swap.is_open_twap {
+        // internal oracle price
+        (
+            token_swap.twap_since_creation(base_price_cumulative, now)?,
+            PriceSource::Twap,
+        )
+    } else {
+        (pool_mid_price, PriceSource::PoolMid)
+    };
+
+    let deviation = price_deviation(pool_mid_price, market_price)?;
+    if deviation.try_mul(100u64)? > pool_mid_price {
+        Ok((market_price, source))
+    } else {
+        // close enough to the current pool middle price
+        Ok((pool_mid_price, PriceSource::PoolMid))
+    }
+}
+
+/// Price of token A quoted in token B, with USD/TOKEN feeds inverted to TOKEN/USD
 fn get_market_price_from_pyth(
     pyth_a_price_info: &AccountInfo,
     pyth_b_price_info: &AccountInfo,
+    invert_a: bool,
+    invert_b: bool,
+    max_ema_divergence_bps: u16,
     clock: &Clock,
 ) -> Result<Decimal, ProgramError> {
-    let price_a = get_pyth_price(pyth_a_price_info, clock)?;
-    let price_b = get_pyth_price(pyth_b_price_info, clock)?;
+    let mut price_a = get_pyth_price(pyth_a_price_info, max_ema_divergence_bps, clock)?;
+    let mut price_b = get_pyth_price(pyth_b_price_info, max_ema_divergence_bps, clock)?;
 
-    if price_a > price_b {
-        price_a.try_div(price_b)
-    } else {
-        price_b.try_div(price_a)
+    if invert_a {
+        price_a = price_a.reciprocal()?;
+    }
+    if invert_b {
+        price_b = price_b.reciprocal()?;
     }
+
+    price_a.try_div(price_b)
 }
 
 fn _get_pyth_product_quote_currency(
@@ -1064,7 +2724,13 @@ fn _get_pyth_product_quote_currency(
     Err(SwapError::InvalidOracleConfig.into())
 }
 
-fn get_pyth_price(pyth_price_info: &AccountInfo, clock: &Clock) -> Result<Decimal, ProgramError> {
+/// Pyth price of an account, rejecting a spot price that diverges from its
+/// EMA by more than `max_ema_divergence_bps`, zero to skip the check
+fn get_pyth_price(
+    pyth_price_info: &AccountInfo,
+    max_ema_divergence_bps: u16,
+    clock: &Clock,
+) -> Result<Decimal, ProgramError> {
     const STALE_AFTER_SLOTS_ELAPSED: u64 = 5;
 
     let pyth_price_data = pyth_price_info.try_borrow_data()?;
@@ -1096,29 +2762,22 @@ fn get_pyth_price(pyth_price_info: &AccountInfo, clock: &Clock) -> Result<Decima
         return Err(SwapError::InvalidOracleConfig.into());
     }
 
-    let market_price = if pyth_price.expo >= 0 {
-        let exponent = pyth_price
-            .expo
-            .try_into()
-            .map_err(|_| SwapError::CalculationFailure)?;
-        let zeros = 10u64
-            .checked_pow(exponent)
-            .ok_or(SwapError::CalculationFailure)?;
-        Decimal::from(price).try_mul(zeros)?
-    } else {
-        let exponent = pyth_price
-            .expo
-            .checked_abs()
-            .ok_or(SwapError::CalculationFailure)?
-            .try_into()
-            .map_err(|_| SwapError::CalculationFailure)?;
-        let decimals = 10u64
-            .checked_pow(exponent)
-            .ok_or(SwapError::CalculationFailure)?;
-        Decimal::from(price).try_div(decimals)?
-    };
+    // the v1 layout's twap field holds the EMA price
+    if max_ema_divergence_bps > 0 {
+        let ema_price: u64 = pyth_price.twap.try_into().map_err(|_| {
+            msg!("Oracle EMA price cannot be negative");
+            SwapError::InvalidOracleConfig
+        })?;
+        let divergence = price.max(ema_price) - price.min(ema_price);
+        if divergence as u128 * BPS_DENOMINATOR as u128
+            > ema_price as u128 * max_ema_divergence_bps as u128
+        {
+            msg!("Pyth spot price diverges from its EMA");
+            return Err(SwapError::InvalidOracleConfig.into());
+        }
+    }
 
-    Ok(market_price)
+    pyth::pyth_exponent_to_decimal(price, pyth_price.expo)
 }
 
 /// Assert and unpack account data
@@ -1143,20 +2802,139 @@ pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramRes
     }
 }
 
-/// Unpacks a spl_token `Mint`.
+/// Check that an instruction was given exactly the number of accounts it reads
+pub fn assert_account_count(accounts: &[AccountInfo], expected: usize) -> ProgramResult {
+    if accounts.len() != expected {
+        msg!("Expected {} accounts, got {}", expected, accounts.len());
+        Err(SwapError::InvalidAccountCount.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that an instruction with a deadline runs no later than it
+pub fn assert_deadline(deadline: Option<i64>, clock: &Clock) -> ProgramResult {
+    match deadline {
+        Some(deadline) if clock.unix_timestamp > deadline => {
+            msg!("Deadline {} passed, now {}", deadline, clock.unix_timestamp);
+            Err(SwapError::DeadlineExceeded.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that minting `amount` rewards keeps the reward mint supply within `u64`
+pub fn assert_reward_supply(reward_mint: &Mint, amount: u64) -> ProgramResult {
+    if reward_mint.supply.checked_add(amount).is_none() {
+        msg!(
+            "Reward mint supply {} cannot grow by {}",
+            reward_mint.supply,
+            amount
+        );
+        Err(SwapError::RewardSupplyExceeded.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that swaps may use token accounts of a token program
+pub fn assert_token_program(config: &ConfigInfo, token_program_id: &Pubkey) -> ProgramResult {
+    if config.is_allowed_token_program(token_program_id) {
+        Ok(())
+    } else {
+        msg!("Token program {} is not allowed", token_program_id);
+        Err(SwapError::IncorrectTokenProgramId.into())
+    }
+}
+
+/// Token-2022 extensions known to leave transfers moving exactly the amount
+/// requested, signed by the account owner alone. Any other extension, such
+/// as a permanent delegate able to move tokens out of the pool accounts, is
+/// rejected.
+const SUPPORTED_EXTENSIONS: [ExtensionType; 3] = [
+    ExtensionType::ImmutableOwner,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+];
+
+/// Unpacks the base state of a Token-2022 account, whose extensions follow a
+/// base state laid out as in spl_token
+fn unpack_with_extensions<S: BaseState>(data: &[u8]) -> Result<S, ProgramError> {
+    let state = StateWithExtensions::<S>::unpack(data)?;
+    if state
+        .get_extension_types()?
+        .iter()
+        .any(|extension_type| !SUPPORTED_EXTENSIONS.contains(extension_type))
+    {
+        return Err(SwapError::UnsupportedMintExtension.into());
+    }
+    Ok(state.base)
+}
+
+/// Unpacks a spl_token `Mint`, or the base state of a Token-2022 mint.
 pub fn unpack_mint(
     account_info: &AccountInfo,
     token_program_id: &Pubkey,
 ) -> Result<Mint, SwapError> {
     if account_info.owner != token_program_id {
-        Err(SwapError::IncorrectTokenProgramId)
-    } else {
-        Mint::unpack(&account_info.data.borrow()).map_err(|_| SwapError::ExpectedMint)
+        return Err(SwapError::IncorrectTokenProgramId);
+    }
+    let data = account_info.data.borrow();
+    if *token_program_id == spl_token::id() {
+        return Mint::unpack(&data).map_err(|_| SwapError::ExpectedMint);
+    }
+    match unpack_with_extensions::<spl_token_2022::state::Mint>(&data) {
+        Ok(_) => Mint::unpack(&data[..Mint::LEN]).map_err(|_| SwapError::ExpectedMint),
+        Err(err) if err == SwapError::UnsupportedMintExtension.into() => {
+            Err(SwapError::UnsupportedMintExtension)
+        }
+        Err(_) => Err(SwapError::ExpectedMint),
+    }
+}
+
+/// Transfer an admin fee, split between the primary and secondary admin fee accounts
+fn transfer_admin_fee<'a>(
+    config: &ConfigInfo,
+    swap: &Pubkey,
+    token_program: AccountInfo<'a>,
+    source: AccountInfo<'a>,
+    primary_destination: AccountInfo<'a>,
+    secondary_destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    nonce: u8,
+    admin_fee: u64,
+) -> Result<(), ProgramError> {
+    let (primary_fee, secondary_fee) = config.split_admin_fee(admin_fee)?;
+    token_transfer(
+        swap,
+        token_program.clone(),
+        source.clone(),
+        primary_destination,
+        authority.clone(),
+        nonce,
+        primary_fee,
+    )?;
+    if secondary_fee > 0 {
+        let secondary = unpack_token_account(&secondary_destination, token_program.key)?;
+        if secondary.owner != config.admin_key {
+            return Err(SwapError::InvalidAdmin.into());
+        }
+        token_transfer(
+            swap,
+            token_program,
+            source,
+            secondary_destination,
+            authority,
+            nonce,
+            secondary_fee,
+        )?;
     }
+    Ok(())
 }
 
-/// Issue a spl_token `Transfer` instruction.
-fn token_transfer<'a>(
+/// Issue a spl_token `Transfer` instruction, valid for Token-2022 accounts
+/// carrying only supported extensions.
+pub fn token_transfer<'a>(
     swap: &Pubkey,
     token_program: AccountInfo<'a>,
     source: AccountInfo<'a>,
@@ -1168,7 +2946,8 @@ fn token_transfer<'a>(
     let swap_bytes = swap.to_bytes();
     let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
     let signers = &[&authority_signature_seeds[..]];
-    let ix = spl_token::instruction::transfer(
+    #[allow(deprecated)]
+    let ix = spl_token_2022::instruction::transfer(
         token_program.key,
         source.key,
         destination.key,
@@ -1197,7 +2976,7 @@ fn token_mint_to<'a>(
     let swap_bytes = swap.to_bytes();
     let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
     let signers = &[&authority_signature_seeds[..]];
-    let ix = spl_token::instruction::mint_to(
+    let ix = spl_token_2022::instruction::mint_to(
         token_program.key,
         mint.key,
         destination.key,
@@ -1222,7 +3001,7 @@ fn token_burn<'a>(
     let swap_bytes = swap.to_bytes();
     let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
     let signers = &[&authority_signature_seeds[..]];
-    let ix = spl_token::instruction::burn(
+    let ix = spl_token_2022::instruction::burn(
         token_program.key,
         burn_account.key,
         mint.key,
@@ -1238,6 +3017,33 @@ fn token_burn<'a>(
     )
 }
 
+/// Close a token account owned by the swap authority
+pub fn token_close_account<'a>(
+    swap: &Pubkey,
+    token_program: AccountInfo<'a>,
+    account: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    nonce: u8,
+) -> ProgramResult {
+    let swap_bytes = swap.to_bytes();
+    let authority_signature_seeds = [&swap_bytes[..32], &[nonce]];
+    let signers = &[&authority_signature_seeds[..]];
+    let ix = spl_token_2022::instruction::close_account(
+        token_program.key,
+        account.key,
+        destination.key,
+        authority.key,
+        &[],
+    )?;
+
+    invoke_signed(
+        &ix,
+        &[account, destination, authority, token_program],
+        signers,
+    )
+}
+
 /// Set account authority
 pub fn set_authority<'a>(
     token_program: &AccountInfo<'a>,
@@ -1246,7 +3052,7 @@ pub fn set_authority<'a>(
     authority_type: AuthorityType,
     owner: &AccountInfo<'a>,
 ) -> ProgramResult {
-    let ix = spl_token::instruction::set_authority(
+    let ix = spl_token_2022::instruction::set_authority(
         token_program.key,
         account_to_transfer_ownership.key,
         new_authority.as_ref(),
@@ -1271,15 +3077,324 @@ pub fn authority_id(program_id: &Pubkey, my_info: &Pubkey, nonce: u8) -> Result<
         .or(Err(SwapError::InvalidProgramAddress))
 }
 
-/// Unpacks a spl_token `Account`.
+/// Unpacks a spl_token `Account`, or the base state of a Token-2022 account.
 pub fn unpack_token_account(
     account_info: &AccountInfo,
     token_program_id: &Pubkey,
 ) -> Result<Account, ProgramError> {
     if account_info.owner != token_program_id {
-        Err(SwapError::IncorrectTokenProgramId.into())
-    } else {
-        spl_token::state::Account::unpack(&account_info.data.borrow())
-            .map_err(|_| SwapError::ExpectedAccount.into())
+        return Err(SwapError::IncorrectTokenProgramId.into());
+    }
+    let data = account_info.data.borrow();
+    if *token_program_id == spl_token::id() {
+        return Account::unpack(&data).map_err(|_| SwapError::ExpectedAccount.into());
+    }
+    match unpack_with_extensions::<spl_token_2022::state::Account>(&data) {
+        Ok(_) => {
+            Account::unpack(&data[..Account::LEN]).map_err(|_| SwapError::ExpectedAccount.into())
+        }
+        Err(err) if err == SwapError::UnsupportedMintExtension.into() => Err(err),
+        Err(_) => Err(SwapError::ExpectedAccount.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ORACLE_MOVE_WINDOW;
+
+    const NOW: u64 = 1_650_000_000;
+
+    /// Balanced pool at a price of 10, last updated at `NOW`
+    fn swap_at_ten() -> SwapInfo {
+        SwapInfo {
+            is_open_twap: true,
+            block_timestamp_last: NOW,
+            creation_ts: NOW,
+            pool_state: PoolState::new(PoolState {
+                market_price: Decimal::from(10u64),
+                slope: Decimal::one().try_div(2).unwrap(),
+                base_target: Decimal::from(1_000u64),
+                quote_target: Decimal::from(10_000u64),
+                base_reserve: Decimal::from(1_000u64),
+                quote_reserve: Decimal::from(10_000u64),
+                multiplier: Multiplier::One,
+            })
+            .unwrap(),
+            ..SwapInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_new_market_price_follows_oracle() {
+        let ten = Decimal::from(10u64);
+        for elapsed in [1, 60, 3_600] {
+            // moves of more than 1% retarget the pool
+            let mut token_swap = swap_at_ten();
+            let oracle_price = Decimal::from(11u64);
+            let (market_price, cumulative, _) =
+                get_new_market_price(&mut token_swap, Some(oracle_price), NOW + elapsed).unwrap();
+            assert_eq!(market_price, oracle_price);
+            assert_eq!(cumulative, ten.try_mul(elapsed).unwrap());
+            assert_eq!(token_swap.last_oracle_price, oracle_price);
+
+            // smaller moves keep the mid price
+            let mut token_swap = swap_at_ten();
+            let oracle_price = Decimal::from(1_005u64).try_div(100).unwrap();
+            let (market_price, _, _) =
+                get_new_market_price(&mut token_swap, Some(oracle_price), NOW + elapsed).unwrap();
+            assert_eq!(market_price, ten);
+            assert_eq!(token_swap.last_oracle_price, oracle_price);
+        }
+    }
+
+    #[test]
+    fn test_new_market_price_without_oracle() {
+        let ten = Decimal::from(10u64);
+        for elapsed in [1, 60, 3_600] {
+            // a constant mid price averages to itself
+            let mut token_swap = swap_at_ten();
+            let (market_price, cumulative, _) =
+                get_new_market_price(&mut token_swap, None, NOW + elapsed).unwrap();
+            assert_eq!(market_price, ten);
+            assert_eq!(cumulative, ten.try_mul(elapsed).unwrap());
+
+            let mut token_swap = SwapInfo {
+                is_open_twap: false,
+                ..swap_at_ten()
+            };
+            assert_eq!(
+                get_new_market_price(&mut token_swap, None, NOW + elapsed).unwrap(),
+                (ten, Decimal::zero(), Decimal::zero())
+            );
+        }
+
+        // no time passed, the cumulative price is unchanged
+        let mut token_swap = SwapInfo {
+            creation_ts: NOW - 100,
+            base_price_cumulative_last: Decimal::from(1_000u64),
+            ..swap_at_ten()
+        };
+        assert_eq!(
+            get_new_market_price(&mut token_swap, None, NOW).unwrap(),
+            (ten, Decimal::from(1_000u64), Decimal::zero())
+        );
+    }
+
+    #[test]
+    fn test_new_market_price_oracle_move() {
+        let last_oracle_price = Decimal::from(10u64);
+        let oracle_price = Decimal::from(12u64);
+        for (elapsed, moved) in [
+            (1, false),
+            (ORACLE_MOVE_WINDOW, false),
+            (ORACLE_MOVE_WINDOW + 1, true),
+        ] {
+            let mut token_swap = SwapInfo {
+                last_oracle_price,
+                max_oracle_move_bps: 1_000,
+                ..swap_at_ten()
+            };
+            let result = get_new_market_price(&mut token_swap, Some(oracle_price), NOW + elapsed);
+            if moved {
+                assert_eq!(result.unwrap().0, oracle_price);
+            } else {
+                assert_eq!(result, Err(SwapError::OraclePriceMoveTooLarge.into()));
+                assert_eq!(token_swap.last_oracle_price, last_oracle_price);
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_market_price_oracle_free() {
+        let mut token_swap = SwapInfo {
+            oracle_free: true,
+            ..swap_at_ten()
+        };
+        token_swap.pool_state.market_price = Decimal::from(1_001u64).try_div(100).unwrap();
+        let (market_price, _, _) =
+            get_new_market_price(&mut token_swap, Some(Decimal::from(20u64)), NOW + 60).unwrap();
+        assert_eq!(market_price, token_swap.pool_state.market_price);
+        assert!(token_swap.last_oracle_price.is_zero());
+    }
+
+    #[test]
+    fn test_select_market_price() {
+        let ten = Decimal::from(10u64);
+        let twelve = Decimal::from(12u64);
+        // twap of 12 over the 100 seconds since creation
+        let token_swap = SwapInfo {
+            creation_ts: NOW - 100,
+            ..swap_at_ten()
+        };
+        let cumulative = Decimal::from(1_200u64);
+
+        // fresh oracle
+        let oracle_price = Decimal::from(11u64);
+        assert_eq!(
+            select_market_price(&token_swap, Some(oracle_price), ten, cumulative, NOW).unwrap(),
+            (oracle_price, PriceSource::Pyth)
+        );
+        let oracle_price = Decimal::from(1_005u64).try_div(100).unwrap();
+        assert_eq!(
+            select_market_price(&token_swap, Some(oracle_price), ten, cumulative, NOW).unwrap(),
+            (ten, PriceSource::PoolMid)
+        );
+
+        // stale oracle
+        assert_eq!(
+            select_market_price(&token_swap, None, ten, cumulative, NOW).unwrap(),
+            (twelve, PriceSource::Twap)
+        );
+        let token_swap = SwapInfo {
+            is_open_twap: false,
+            ..token_swap
+        };
+        assert_eq!(
+            select_market_price(&token_swap, None, ten, cumulative, NOW).unwrap(),
+            (ten, PriceSource::PoolMid)
+        );
+
+        let mut token_swap = SwapInfo {
+            oracle_free: true,
+            ..token_swap
+        };
+        token_swap.pool_state.market_price = twelve;
+        assert_eq!(
+            select_market_price(&token_swap, None, ten, cumulative, NOW).unwrap(),
+            (twelve, PriceSource::Manual)
+        );
+    }
+
+    /// Drive the accumulators of `token_swap` along a path of mid prices,
+    /// each held for its duration in seconds, as pool updates would
+    fn accumulate_price_path(token_swap: &mut SwapInfo, path: &[(Decimal, u64)]) {
+        for (price, duration) in path {
+            token_swap.pool_state.market_price = *price;
+            let now = token_swap.block_timestamp_last + duration;
+            let (_, cumulative, squared_cumulative) =
+                get_new_market_price(token_swap, None, now).unwrap();
+            token_swap.block_timestamp_last = now;
+            token_swap.base_price_cumulative_last = cumulative;
+            token_swap.base_price_squared_cumulative_last = squared_cumulative;
+        }
+    }
+
+    #[test]
+    fn test_realized_variance_over_price_path() {
+        let path = [10u64, 12, 8, 11, 9]
+            .iter()
+            .map(|price| Decimal::from(*price))
+            .zip([60, 30, 90, 45, 15])
+            .collect::<Vec<_>>();
+        // mean 2310 / 240 = 9.625, mean of squares 22740 / 240 = 94.75
+        let variance = Decimal::from(2_109_375u64).try_div(1_000_000).unwrap();
+
+        // the same window read across a wrap of both accumulators
+        for start in [Decimal::zero(), Decimal::from_scaled_val(u128::MAX - 1_000)] {
+            let mut token_swap = SwapInfo {
+                base_price_cumulative_last: start,
+                base_price_squared_cumulative_last: start,
+                ..swap_at_ten()
+            };
+            accumulate_price_path(&mut token_swap, &path);
+            assert_eq!(
+                token_swap.twap_since(start, NOW).unwrap(),
+                Decimal::from(9_625u64).try_div(1_000).unwrap()
+            );
+            assert_eq!(
+                token_swap
+                    .realized_variance_since(start, start, NOW)
+                    .unwrap(),
+                variance
+            );
+        }
+
+        // a constant price has no variance, even when its square rounds
+        let price = Decimal::from(10u64).try_div(3).unwrap();
+        let mut token_swap = swap_at_ten();
+        accumulate_price_path(&mut token_swap, &[(price, 60), (price, 7)]);
+        assert_eq!(
+            token_swap
+                .realized_variance_since(Decimal::zero(), Decimal::zero(), NOW)
+                .unwrap(),
+            Decimal::zero()
+        );
+    }
+
+    /// Pyth price account data at `price` with an EMA of `ema_price`, both
+    /// with an exponent of -8, valid at slot zero
+    fn pyth_price_fixture(price: i64, ema_price: i64) -> Vec<u64> {
+        let len = std::mem::size_of::<pyth::Price>();
+        // backed by u64s so the account data is aligned for the cast
+        let mut words = vec![0u64; (len + 7) / 8];
+        let pyth_price =
+            pyth::load_mut::<pyth::Price>(bytemuck::cast_slice_mut(&mut words)).unwrap();
+        pyth_price.ptype = pyth::PriceType::Price;
+        pyth_price.expo = -8;
+        pyth_price.agg.price = price;
+        pyth_price.twap = ema_price;
+        words
+    }
+
+    fn get_fixture_price(
+        mut words: Vec<u64>,
+        max_ema_divergence_bps: u16,
+    ) -> Result<Decimal, ProgramError> {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            bytemuck::cast_slice_mut(&mut words),
+            &owner,
+            false,
+            0,
+        );
+        get_pyth_price(&account_info, max_ema_divergence_bps, &Clock::default())
+    }
+
+    #[test]
+    fn test_pyth_price_diverging_from_ema() {
+        // spot at 150 after a crash from an EMA of 200
+        let fixture = || pyth_price_fixture(15_000_000_000, 20_000_000_000);
+        assert_eq!(
+            get_fixture_price(fixture(), 500),
+            Err(SwapError::InvalidOracleConfig.into())
+        );
+        assert_eq!(
+            get_fixture_price(fixture(), 2_499),
+            Err(SwapError::InvalidOracleConfig.into())
+        );
+        // a 25% divergence is within a 25% threshold, and zero skips the check
+        assert_eq!(
+            get_fixture_price(fixture(), 2_500),
+            Ok(Decimal::from(150u64))
+        );
+        assert_eq!(get_fixture_price(fixture(), 0), Ok(Decimal::from(150u64)));
+
+        // a negative EMA is never valid when checked
+        assert_eq!(
+            get_fixture_price(pyth_price_fixture(15_000_000_000, -1), 500),
+            Err(SwapError::InvalidOracleConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_pyth_price_close_to_ema() {
+        // spot at 150 against an EMA of 149.5
+        for (price, ema_price) in [
+            (15_000_000_000, 14_950_000_000),
+            (14_950_000_000, 15_000_000_000),
+            (15_000_000_000, 15_000_000_000),
+        ] {
+            assert_eq!(
+                get_fixture_price(pyth_price_fixture(price, ema_price), 50),
+                pyth::pyth_exponent_to_decimal(price as u64, -8)
+            );
+        }
     }
 }
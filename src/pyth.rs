@@ -4,7 +4,13 @@ use bytemuck::{
     cast_slice, cast_slice_mut, from_bytes, from_bytes_mut, try_cast_slice, try_cast_slice_mut,
     Pod, PodCastError, Zeroable,
 };
-use std::mem::size_of;
+use solana_program::program_error::ProgramError;
+use std::{convert::TryInto, mem::size_of};
+
+use crate::{
+    error::SwapError,
+    math::{Decimal, TryDiv, TryMul},
+};
 
 pub const MAGIC: u32 = 0xa1b2c3d4;
 pub const VERSION_2: u32 = 2;
@@ -133,3 +139,56 @@ pub fn load_mut<T: Pod>(data: &mut [u8]) -> Result<&mut T, PodCastError> {
         try_cast_slice_mut(&mut data[0..size])?,
     )))
 }
+
+/// Convert a raw Pyth price and its exponent into a decimal price
+pub fn pyth_exponent_to_decimal(price: u64, expo: i32) -> Result<Decimal, ProgramError> {
+    let exponent: u32 = expo
+        .checked_abs()
+        .ok_or(SwapError::CalculationFailure)?
+        .try_into()
+        .map_err(|_| SwapError::CalculationFailure)?;
+    let scale = 10u64
+        .checked_pow(exponent)
+        .ok_or(SwapError::CalculationFailure)?;
+    if expo >= 0 {
+        Decimal::from(price).try_mul(scale)
+    } else {
+        Decimal::from(price).try_div(scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pyth_exponent_to_decimal() {
+        assert_eq!(
+            pyth_exponent_to_decimal(150, 0).unwrap(),
+            Decimal::from(150u64)
+        );
+        assert_eq!(
+            pyth_exponent_to_decimal(15, 2).unwrap(),
+            Decimal::from(1_500u64)
+        );
+        assert_eq!(
+            pyth_exponent_to_decimal(15_000_000_000, -8).unwrap(),
+            Decimal::from(150u64)
+        );
+        assert_eq!(
+            pyth_exponent_to_decimal(12_345, -4).unwrap(),
+            Decimal::from_scaled_val(1_234_500_000)
+        );
+        assert_eq!(pyth_exponent_to_decimal(0, -8).unwrap(), Decimal::zero());
+
+        // 10^20 does not fit in a u64
+        assert_eq!(
+            pyth_exponent_to_decimal(1, 20),
+            Err(SwapError::CalculationFailure.into())
+        );
+        assert_eq!(
+            pyth_exponent_to_decimal(1, i32::MIN),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+}
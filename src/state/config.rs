@@ -1,11 +1,13 @@
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::{
+    clock::UnixTimestamp,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::{Pubkey, PUBKEY_BYTES},
 };
 
 use super::*;
+use crate::{curve::BPS_DENOMINATOR, error::SwapError};
 
 /// Current version of the program and all new accounts created
 pub const PROGRAM_VERSION: u8 = 1;
@@ -14,6 +16,9 @@ pub const PROGRAM_VERSION: u8 = 1;
 /// will have the version set to 0.
 pub const UNINITIALIZED_VERSION: u8 = 0;
 
+/// Delay between proposing and executing an emergency drain
+pub const DRAIN_TIMELOCK: UnixTimestamp = 604800;
+
 /// Dex Default Configuration information
 #[repr(C)]
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -35,6 +40,47 @@ pub struct ConfigInfo {
     pub fees: Fees,
     /// Rewards
     pub rewards: Rewards,
+
+    /// Timestamp after which a proposed drain can be executed, zero if none proposed
+    pub drain_unlock_ts: UnixTimestamp,
+
+    /// Number of swap pools initialized with this config
+    pub pools_created: u64,
+
+    /// Minimum rewards owed for a liquidity reward claim
+    pub min_claim_amount: u64,
+
+    /// Share of each admin fee sent to the primary admin fee account, in basis
+    /// points, the rest goes to a secondary account owned by the admin
+    pub admin_split_bps: u16,
+
+    /// Pool token supply a partial withdraw cannot drop below, zero to disable
+    pub min_pool_supply: u64,
+
+    /// DELTAFI minted as swap and liquidity rewards since initialization.
+    /// Amounts are u64, so the checked u128 sum cannot realistically overflow.
+    pub total_rewards_minted: u128,
+
+    /// Liquidity reward claims must mint to a token account owned by the
+    /// liquidity provider owner
+    pub strict_claim_destination: bool,
+
+    /// Token program accepted alongside spl_token, such as Token-2022,
+    /// default pubkey for none
+    pub allowed_token_program: Pubkey,
+
+    /// Divergence between a Pyth spot price and its EMA above which the
+    /// price is rejected, in basis points of the EMA, zero to disable
+    pub max_ema_divergence_bps: u16,
+
+    /// Swap pool the proposed drain moves the reserves out of
+    pub drain_swap: Pubkey,
+
+    /// Token account the proposed drain moves token a into
+    pub drain_treasury_a: Pubkey,
+
+    /// Token account the proposed drain moves token b into
+    pub drain_treasury_b: Pubkey,
 }
 
 impl Sealed for ConfigInfo {}
@@ -45,21 +91,52 @@ impl IsInitialized for ConfigInfo {
 }
 
 #[doc(hidden)]
-pub const CONFIG_INFO_SIZE: usize = 170;
+pub const CONFIG_INFO_SIZE: usize = 385;
 impl Pack for ConfigInfo {
     const LEN: usize = CONFIG_INFO_SIZE;
     #[doc(hidden)]
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
         let src = array_ref![src, 0, CONFIG_INFO_SIZE];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (version, bump_seed, admin_key, deltafi_mint, fees, rewards) = array_refs![
+        let (
+            version,
+            bump_seed,
+            admin_key,
+            deltafi_mint,
+            fees,
+            rewards,
+            drain_unlock_ts,
+            pools_created,
+            min_claim_amount,
+            admin_split_bps,
+            min_pool_supply,
+            total_rewards_minted,
+            strict_claim_destination,
+            allowed_token_program,
+            max_ema_divergence_bps,
+            drain_swap,
+            drain_treasury_a,
+            drain_treasury_b,
+        ) = array_refs![
             src,
             1,
             1,
             PUBKEY_BYTES,
             PUBKEY_BYTES,
             Fees::LEN,
-            Rewards::LEN
+            Rewards::LEN,
+            8,
+            8,
+            8,
+            2,
+            8,
+            16,
+            1,
+            PUBKEY_BYTES,
+            2,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES
         ];
 
         let version = u8::from_le_bytes(*version);
@@ -74,20 +151,63 @@ impl Pack for ConfigInfo {
             deltafi_mint: Pubkey::new_from_array(*deltafi_mint),
             fees: Fees::unpack_from_slice(fees)?,
             rewards: Rewards::unpack_from_slice(rewards)?,
+            drain_unlock_ts: i64::from_le_bytes(*drain_unlock_ts),
+            pools_created: u64::from_le_bytes(*pools_created),
+            min_claim_amount: u64::from_le_bytes(*min_claim_amount),
+            admin_split_bps: u16::from_le_bytes(*admin_split_bps),
+            min_pool_supply: u64::from_le_bytes(*min_pool_supply),
+            total_rewards_minted: u128::from_le_bytes(*total_rewards_minted),
+            strict_claim_destination: unpack_bool(strict_claim_destination)?,
+            allowed_token_program: Pubkey::new_from_array(*allowed_token_program),
+            max_ema_divergence_bps: u16::from_le_bytes(*max_ema_divergence_bps),
+            drain_swap: Pubkey::new_from_array(*drain_swap),
+            drain_treasury_a: Pubkey::new_from_array(*drain_treasury_a),
+            drain_treasury_b: Pubkey::new_from_array(*drain_treasury_b),
         })
     }
     #[doc(hidden)]
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let dst = array_mut_ref![dst, 0, CONFIG_INFO_SIZE];
         #[allow(clippy::ptr_offset_with_cast)]
-        let (version, bump_seed, admin_key, deltafi_mint, fees, rewards) = mut_array_refs![
+        let (
+            version,
+            bump_seed,
+            admin_key,
+            deltafi_mint,
+            fees,
+            rewards,
+            drain_unlock_ts,
+            pools_created,
+            min_claim_amount,
+            admin_split_bps,
+            min_pool_supply,
+            total_rewards_minted,
+            strict_claim_destination,
+            allowed_token_program,
+            max_ema_divergence_bps,
+            drain_swap,
+            drain_treasury_a,
+            drain_treasury_b,
+        ) = mut_array_refs![
             dst,
             1,
             1,
             PUBKEY_BYTES,
             PUBKEY_BYTES,
             Fees::LEN,
-            Rewards::LEN
+            Rewards::LEN,
+            8,
+            8,
+            8,
+            2,
+            8,
+            16,
+            1,
+            PUBKEY_BYTES,
+            2,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES
         ];
         *version = self.version.to_le_bytes();
         *bump_seed = self.bump_seed.to_le_bytes();
@@ -95,6 +215,115 @@ impl Pack for ConfigInfo {
         deltafi_mint.copy_from_slice(self.deltafi_mint.as_ref());
         self.fees.pack_into_slice(&mut fees[..]);
         self.rewards.pack_into_slice(&mut rewards[..]);
+        *drain_unlock_ts = self.drain_unlock_ts.to_le_bytes();
+        *pools_created = self.pools_created.to_le_bytes();
+        *min_claim_amount = self.min_claim_amount.to_le_bytes();
+        *admin_split_bps = self.admin_split_bps.to_le_bytes();
+        *min_pool_supply = self.min_pool_supply.to_le_bytes();
+        *total_rewards_minted = self.total_rewards_minted.to_le_bytes();
+        pack_bool(self.strict_claim_destination, strict_claim_destination);
+        allowed_token_program.copy_from_slice(self.allowed_token_program.as_ref());
+        *max_ema_divergence_bps = self.max_ema_divergence_bps.to_le_bytes();
+        drain_swap.copy_from_slice(self.drain_swap.as_ref());
+        drain_treasury_a.copy_from_slice(self.drain_treasury_a.as_ref());
+        drain_treasury_b.copy_from_slice(self.drain_treasury_b.as_ref());
+    }
+}
+
+impl ConfigInfo {
+    /// Split an admin fee between the primary and secondary admin fee accounts
+    ///
+    /// # Arguments
+    ///
+    /// * admin_fee - admin fee amount.
+    ///
+    /// # Return value
+    ///
+    /// primary amount, secondary amount
+    pub fn split_admin_fee(&self, admin_fee: u64) -> Result<(u64, u64), ProgramError> {
+        let primary = admin_fee
+            .checked_mul(self.admin_split_bps as u64)
+            .ok_or(SwapError::CalculationFailure)?
+            .checked_div(BPS_DENOMINATOR)
+            .ok_or(SwapError::CalculationFailure)?;
+        let secondary = admin_fee
+            .checked_sub(primary)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok((primary, secondary))
+    }
+
+    /// Whether token accounts owned by a program can be used with swaps
+    pub fn is_allowed_token_program(&self, token_program_id: &Pubkey) -> bool {
+        *token_program_id == spl_token::id()
+            || (*token_program_id == self.allowed_token_program
+                && self.allowed_token_program != Pubkey::default())
+    }
+
+    /// Add newly minted DELTAFI rewards to the running total
+    pub fn record_rewards_minted(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.total_rewards_minted = self
+            .total_rewards_minted
+            .checked_add(amount as u128)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Protocol-wide stats exposed by `GetConfigStats`
+    pub fn stats(&self) -> ConfigStats {
+        ConfigStats {
+            pools_created: self.pools_created,
+            admin_key: self.admin_key,
+            fees: self.fees.clone(),
+            rewards: self.rewards.clone(),
+            drain_unlock_ts: self.drain_unlock_ts,
+        }
+    }
+}
+
+/// Config-level view returned by `GetConfigStats`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConfigStats {
+    /// Number of swap pools initialized with this config
+    pub pools_created: u64,
+    /// Public key of admin account
+    pub admin_key: Pubkey,
+    /// Fees applied to newly created pools
+    pub fees: Fees,
+    /// Rewards applied to newly created pools
+    pub rewards: Rewards,
+    /// Timestamp after which a proposed drain can be executed, zero if none proposed
+    pub drain_unlock_ts: UnixTimestamp,
+}
+
+impl Sealed for ConfigStats {}
+const CONFIG_STATS_SIZE: usize = 186; // 8 + 32 + 74 + 64 + 8
+impl Pack for ConfigStats {
+    const LEN: usize = CONFIG_STATS_SIZE;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let src = array_ref![src, 0, CONFIG_STATS_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (pools_created, admin_key, fees, rewards, drain_unlock_ts) =
+            array_refs![src, 8, PUBKEY_BYTES, Fees::LEN, Rewards::LEN, 8];
+        Ok(Self {
+            pools_created: u64::from_le_bytes(*pools_created),
+            admin_key: Pubkey::new_from_array(*admin_key),
+            fees: Fees::unpack_from_slice(fees)?,
+            rewards: Rewards::unpack_from_slice(rewards)?,
+            drain_unlock_ts: i64::from_le_bytes(*drain_unlock_ts),
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let dst = array_mut_ref![dst, 0, CONFIG_STATS_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (pools_created, admin_key, fees, rewards, drain_unlock_ts) =
+            mut_array_refs![dst, 8, PUBKEY_BYTES, Fees::LEN, Rewards::LEN, 8];
+        *pools_created = self.pools_created.to_le_bytes();
+        admin_key.copy_from_slice(self.admin_key.as_ref());
+        self.fees.pack_into_slice(&mut fees[..]);
+        self.rewards.pack_into_slice(&mut rewards[..]);
+        *drain_unlock_ts = self.drain_unlock_ts.to_le_bytes();
     }
 }
 
@@ -112,6 +341,19 @@ mod tests {
         let deltafi_mint = Pubkey::new_from_array(deltafi_mint_raw);
         let fees = DEFAULT_TEST_FEES;
         let rewards = DEFAULT_TEST_REWARDS;
+        let drain_unlock_ts: UnixTimestamp = 1_000_000;
+        let pools_created = 7u64;
+        let min_claim_amount = 1_000u64;
+        let admin_split_bps = 7_500u16;
+        let min_pool_supply = 1_000_000u64;
+        let total_rewards_minted = u64::MAX as u128 + 1;
+        let strict_claim_destination = true;
+        let allowed_token_program_raw = [4u8; 32];
+        let allowed_token_program = Pubkey::new_from_array(allowed_token_program_raw);
+        let max_ema_divergence_bps = 250u16;
+        let drain_swap = Pubkey::new_from_array([5u8; 32]);
+        let drain_treasury_a = Pubkey::new_from_array([6u8; 32]);
+        let drain_treasury_b = Pubkey::new_from_array([7u8; 32]);
 
         let config_info = ConfigInfo {
             version,
@@ -120,6 +362,18 @@ mod tests {
             deltafi_mint,
             fees,
             rewards,
+            drain_unlock_ts,
+            pools_created,
+            min_claim_amount,
+            admin_split_bps,
+            min_pool_supply,
+            total_rewards_minted,
+            strict_claim_destination,
+            allowed_token_program,
+            max_ema_divergence_bps,
+            drain_swap,
+            drain_treasury_a,
+            drain_treasury_b,
         };
 
         let mut packed = [0u8; ConfigInfo::LEN];
@@ -143,6 +397,9 @@ mod tests {
         packed.extend_from_slice(&DEFAULT_TEST_FEES.trade_fee_denominator.to_le_bytes());
         packed.extend_from_slice(&DEFAULT_TEST_FEES.withdraw_fee_numerator.to_le_bytes());
         packed.extend_from_slice(&DEFAULT_TEST_FEES.withdraw_fee_denominator.to_le_bytes());
+        packed.push(DEFAULT_TEST_FEES.trade_fee_enabled as u8);
+        packed.push(DEFAULT_TEST_FEES.withdraw_fee_enabled as u8);
+        packed.extend_from_slice(&DEFAULT_TEST_FEES.min_fee.to_le_bytes());
         packed.extend_from_slice(&DEFAULT_TEST_REWARDS.trade_reward_numerator.to_le_bytes());
         packed.extend_from_slice(&DEFAULT_TEST_REWARDS.trade_reward_denominator.to_le_bytes());
         packed.extend_from_slice(&DEFAULT_TEST_REWARDS.trade_reward_cap.to_le_bytes());
@@ -156,6 +413,25 @@ mod tests {
                 .liquidity_reward_denominator
                 .to_le_bytes(),
         );
+        packed.extend_from_slice(&DEFAULT_TEST_REWARDS.deposit_reward_numerator.to_le_bytes());
+        packed.extend_from_slice(
+            &DEFAULT_TEST_REWARDS
+                .deposit_reward_denominator
+                .to_le_bytes(),
+        );
+        packed.extend_from_slice(&DEFAULT_TEST_REWARDS.deposit_reward_cap.to_le_bytes());
+        packed.extend_from_slice(&drain_unlock_ts.to_le_bytes());
+        packed.extend_from_slice(&pools_created.to_le_bytes());
+        packed.extend_from_slice(&min_claim_amount.to_le_bytes());
+        packed.extend_from_slice(&admin_split_bps.to_le_bytes());
+        packed.extend_from_slice(&min_pool_supply.to_le_bytes());
+        packed.extend_from_slice(&total_rewards_minted.to_le_bytes());
+        packed.push(strict_claim_destination as u8);
+        packed.extend_from_slice(&allowed_token_program_raw);
+        packed.extend_from_slice(&max_ema_divergence_bps.to_le_bytes());
+        packed.extend_from_slice(drain_swap.as_ref());
+        packed.extend_from_slice(drain_treasury_a.as_ref());
+        packed.extend_from_slice(drain_treasury_b.as_ref());
         let unpacked = ConfigInfo::unpack(&packed).unwrap();
         assert_eq!(config_info, unpacked);
 
@@ -166,4 +442,72 @@ mod tests {
         let err = ConfigInfo::unpack(&packed).unwrap_err();
         assert_eq!(err, ProgramError::UninitializedAccount);
     }
+
+    #[test]
+    fn test_config_stats_packing() {
+        let config_info = ConfigInfo {
+            version: PROGRAM_VERSION,
+            bump_seed: 255,
+            admin_key: Pubkey::new_unique(),
+            deltafi_mint: Pubkey::new_unique(),
+            fees: DEFAULT_TEST_FEES,
+            rewards: DEFAULT_TEST_REWARDS,
+            drain_unlock_ts: 1_000_000,
+            pools_created: 3,
+            min_claim_amount: 0,
+            admin_split_bps: BPS_DENOMINATOR as u16,
+            min_pool_supply: 0,
+            total_rewards_minted: 0,
+            strict_claim_destination: false,
+            allowed_token_program: Pubkey::default(),
+            max_ema_divergence_bps: 0,
+            drain_swap: Pubkey::default(),
+            drain_treasury_a: Pubkey::default(),
+            drain_treasury_b: Pubkey::default(),
+        };
+        let stats = config_info.stats();
+        assert_eq!(stats.pools_created, 3);
+        assert_eq!(stats.admin_key, config_info.admin_key);
+        assert_eq!(stats.fees, config_info.fees);
+        assert_eq!(stats.rewards, config_info.rewards);
+        assert_eq!(stats.drain_unlock_ts, config_info.drain_unlock_ts);
+
+        let mut packed = [0u8; ConfigStats::LEN];
+        stats.pack_into_slice(&mut packed);
+        assert_eq!(ConfigStats::unpack_from_slice(&packed).unwrap(), stats);
+    }
+
+    #[test]
+    fn test_split_admin_fee() {
+        let mut config_info = ConfigInfo {
+            admin_split_bps: BPS_DENOMINATOR as u16,
+            ..ConfigInfo::default()
+        };
+        assert_eq!(config_info.split_admin_fee(1_001).unwrap(), (1_001, 0));
+
+        config_info.admin_split_bps = 0;
+        assert_eq!(config_info.split_admin_fee(1_001).unwrap(), (0, 1_001));
+
+        // the primary share rounds down, the remainder goes to the secondary
+        config_info.admin_split_bps = 3_000;
+        assert_eq!(config_info.split_admin_fee(1_001).unwrap(), (300, 701));
+        assert_eq!(
+            config_info.split_admin_fee(u64::MAX),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_is_allowed_token_program() {
+        let token_2022 = Pubkey::new_unique();
+        let mut config_info = ConfigInfo::default();
+        assert!(config_info.is_allowed_token_program(&spl_token::id()));
+        assert!(!config_info.is_allowed_token_program(&token_2022));
+        assert!(!config_info.is_allowed_token_program(&Pubkey::default()));
+
+        config_info.allowed_token_program = token_2022;
+        assert!(config_info.is_allowed_token_program(&spl_token::id()));
+        assert!(config_info.is_allowed_token_program(&token_2022));
+        assert!(!config_info.is_allowed_token_program(&Pubkey::new_unique()));
+    }
 }
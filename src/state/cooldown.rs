@@ -0,0 +1,153 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+    clock::UnixTimestamp,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use super::*;
+use crate::error::SwapError;
+
+/// Seed prefix of swap cooldown addresses
+pub const SWAP_COOLDOWN_SEED: &[u8] = b"swap_cooldown";
+
+/// Address of the swap cooldown of a user in a swap pool
+///
+/// # Arguments
+///
+/// * program_id - swap program id.
+/// * swap_pubkey - swap pool address.
+/// * user_pubkey - user transfer authority of the swaps.
+///
+/// # Return value
+///
+/// swap cooldown address and its bump seed
+pub fn swap_cooldown_address(
+    program_id: &Pubkey,
+    swap_pubkey: &Pubkey,
+    user_pubkey: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            SWAP_COOLDOWN_SEED,
+            swap_pubkey.as_ref(),
+            user_pubkey.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Last swap of a user in a swap pool, kept at `swap_cooldown_address`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapCooldown {
+    /// Initialization status
+    pub is_initialized: bool,
+    /// Bump seed of the account address
+    pub bump_seed: u8,
+    /// Unix timestamp of the last swap
+    pub last_swap_ts: UnixTimestamp,
+}
+
+impl SwapCooldown {
+    /// Record a swap, rejecting it within `cooldown_seconds` of the last one
+    ///
+    /// # Arguments
+    ///
+    /// * cooldown_seconds - minimum time between two swaps.
+    /// * timestamp - current unix timestamp.
+    pub fn record_swap(
+        &mut self,
+        cooldown_seconds: u64,
+        timestamp: UnixTimestamp,
+    ) -> ProgramResult {
+        let next_swap_ts = self
+            .last_swap_ts
+            .checked_add(cooldown_seconds as i64)
+            .ok_or(SwapError::CalculationFailure)?;
+        if timestamp < next_swap_ts {
+            msg!("Swap cooldown active until {}", next_swap_ts);
+            return Err(SwapError::CooldownActive.into());
+        }
+        self.last_swap_ts = timestamp;
+        Ok(())
+    }
+}
+
+impl Sealed for SwapCooldown {}
+impl IsInitialized for SwapCooldown {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized
+    }
+}
+
+const SWAP_COOLDOWN_SIZE: usize = 10; // 1 + 1 + 8
+impl Pack for SwapCooldown {
+    const LEN: usize = SWAP_COOLDOWN_SIZE;
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, SWAP_COOLDOWN_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump_seed, last_swap_ts) = array_refs![input, 1, 1, 8];
+        Ok(Self {
+            is_initialized: unpack_bool(is_initialized)?,
+            bump_seed: u8::from_le_bytes(*bump_seed),
+            last_swap_ts: i64::from_le_bytes(*last_swap_ts),
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, SWAP_COOLDOWN_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (is_initialized, bump_seed, last_swap_ts) = mut_array_refs![output, 1, 1, 8];
+        pack_bool(self.is_initialized, is_initialized);
+        *bump_seed = self.bump_seed.to_le_bytes();
+        *last_swap_ts = self.last_swap_ts.to_le_bytes();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_cooldown_packing() {
+        let cooldown = SwapCooldown {
+            is_initialized: true,
+            bump_seed: 254,
+            last_swap_ts: 1_000_000,
+        };
+        let mut packed = [0u8; SwapCooldown::LEN];
+        SwapCooldown::pack(cooldown.clone(), &mut packed).unwrap();
+
+        let mut expect = vec![1, 254];
+        expect.extend_from_slice(&1_000_000i64.to_le_bytes());
+        assert_eq!(packed.to_vec(), expect);
+        assert_eq!(SwapCooldown::unpack(&packed).unwrap(), cooldown);
+    }
+
+    #[test]
+    fn test_record_swap() {
+        let mut cooldown = SwapCooldown {
+            is_initialized: true,
+            bump_seed: 255,
+            last_swap_ts: 0,
+        };
+        cooldown.record_swap(30, 1_000).unwrap();
+        assert_eq!(cooldown.last_swap_ts, 1_000);
+
+        assert_eq!(
+            cooldown.record_swap(30, 1_029),
+            Err(SwapError::CooldownActive.into())
+        );
+        assert_eq!(cooldown.last_swap_ts, 1_000);
+
+        cooldown.record_swap(30, 1_030).unwrap();
+        assert_eq!(cooldown.last_swap_ts, 1_030);
+
+        // without a cooldown every swap is accepted
+        cooldown.record_swap(0, 1_030).unwrap();
+    }
+}
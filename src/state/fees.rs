@@ -6,7 +6,33 @@ use solana_program::{
     program_pack::{IsInitialized, Pack, Sealed},
 };
 
-use crate::error::SwapError;
+use crate::{
+    curve::BPS_DENOMINATOR,
+    error::SwapError,
+    math::{Decimal, TryDiv},
+    state::unpack_bool,
+};
+
+/// Highest trade fee an admin may configure, in basis points
+pub const MAX_TRADE_FEE_BPS: u64 = 1_000;
+
+/// Highest withdraw fee an admin may configure, in basis points
+pub const MAX_WITHDRAW_FEE_BPS: u64 = 1_000;
+
+/// Highest trade fee floor an admin may configure, in quote token base units
+pub const MAX_MIN_FEE: u64 = 1_000_000;
+
+/// Check `numerator / denominator` is defined and does not exceed `max_bps`
+fn within_bps(numerator: u64, denominator: u64, max_bps: u64) -> bool {
+    denominator != 0
+        && numerator as u128 * BPS_DENOMINATOR as u128 <= max_bps as u128 * denominator as u128
+}
+
+/// Check `numerator / denominator` is defined and at most one, as a share of
+/// a fee can not exceed the fee itself
+fn within_share(numerator: u64, denominator: u64) -> bool {
+    denominator != 0 && numerator <= denominator
+}
 
 /// Fees struct
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -27,6 +53,12 @@ pub struct Fees {
     pub withdraw_fee_numerator: u64,
     /// Withdraw fee denominator
     pub withdraw_fee_denominator: u64,
+    /// Whether trade fees are charged
+    pub trade_fee_enabled: bool,
+    /// Whether withdraw fees are charged
+    pub withdraw_fee_enabled: bool,
+    /// Smallest trade fee, in quote token base units
+    pub min_fee: u64,
 }
 
 impl Fees {
@@ -49,7 +81,39 @@ impl Fees {
             trade_fee_denominator: params.trade_fee_denominator,
             withdraw_fee_numerator: params.withdraw_fee_numerator,
             withdraw_fee_denominator: params.withdraw_fee_denominator,
+            trade_fee_enabled: params.trade_fee_enabled,
+            withdraw_fee_enabled: params.withdraw_fee_enabled,
+            min_fee: params.min_fee,
+        }
+    }
+
+    /// Check the enabled trade and withdraw fees against the compile-time
+    /// ceilings, and that their admin shares are at most the whole fee. A
+    /// disabled fee is checked again when it is switched back on, since that
+    /// can only happen through a new set of fees.
+    pub fn validate_against_policy(&self) -> Result<(), ProgramError> {
+        let trade_fee_valid = !self.trade_fee_enabled
+            || (within_bps(
+                self.trade_fee_numerator,
+                self.trade_fee_denominator,
+                MAX_TRADE_FEE_BPS,
+            ) && within_share(
+                self.admin_trade_fee_numerator,
+                self.admin_trade_fee_denominator,
+            ) && self.min_fee <= MAX_MIN_FEE);
+        let withdraw_fee_valid = !self.withdraw_fee_enabled
+            || (within_bps(
+                self.withdraw_fee_numerator,
+                self.withdraw_fee_denominator,
+                MAX_WITHDRAW_FEE_BPS,
+            ) && within_share(
+                self.admin_withdraw_fee_numerator,
+                self.admin_withdraw_fee_denominator,
+            ));
+        if !trade_fee_valid || !withdraw_fee_valid {
+            return Err(SwapError::FeeExceedsPolicy.into());
         }
+        Ok(())
     }
 
     /// Apply admin trade fee
@@ -60,8 +124,11 @@ impl Fees {
     ///
     /// # Return value
     ///
-    /// admin trade fee
+    /// admin trade fee, zero if trade fees are disabled
     pub fn admin_trade_fee(&self, fee_amount: u64) -> Result<u64, ProgramError> {
+        if !self.trade_fee_enabled {
+            return Ok(0);
+        }
         fee_amount
             .checked_mul(self.admin_trade_fee_numerator)
             .ok_or(SwapError::CalculationFailure)?
@@ -77,8 +144,11 @@ impl Fees {
     ///
     /// # Return value
     ///
-    /// admin withdraw fee
+    /// admin withdraw fee, zero if withdraw fees are disabled
     pub fn admin_withdraw_fee(&self, fee_amount: u64) -> Result<u64, ProgramError> {
+        if !self.withdraw_fee_enabled {
+            return Ok(0);
+        }
         fee_amount
             .checked_mul(self.admin_withdraw_fee_numerator)
             .ok_or(SwapError::CalculationFailure)?
@@ -86,7 +156,37 @@ impl Fees {
             .ok_or_else(|| SwapError::CalculationFailure.into())
     }
 
-    /// Compute trade fee from amount
+    /// Compute trade fee from amount, raised to `min_fee` converted to the
+    /// traded token and capped at the trade amount
+    ///
+    /// # Arguments
+    ///
+    /// * trade_amount - trade amount.
+    /// * quote_per_token - price of the traded token in quote tokens, one for
+    ///   the quote token itself.
+    ///
+    /// # Return value
+    ///
+    /// trade fee, zero if trade fees are disabled
+    pub fn trade_fee(
+        &self,
+        trade_amount: u64,
+        quote_per_token: Decimal,
+    ) -> Result<u64, ProgramError> {
+        if !self.trade_fee_enabled {
+            return Ok(0);
+        }
+        let trade_fee = self.proportional_trade_fee(trade_amount)?;
+        if self.min_fee == 0 {
+            return Ok(trade_fee);
+        }
+        let min_fee = Decimal::from(self.min_fee)
+            .try_div(quote_per_token)?
+            .try_ceil_u64()?;
+        Ok(trade_fee.max(min_fee).min(trade_amount))
+    }
+
+    /// Compute the percentage trade fee from amount, without `min_fee`
     ///
     /// # Arguments
     ///
@@ -94,8 +194,11 @@ impl Fees {
     ///
     /// # Return value
     ///
-    /// trade fee
-    pub fn trade_fee(&self, trade_amount: u64) -> Result<u64, ProgramError> {
+    /// trade fee, zero if trade fees are disabled
+    pub fn proportional_trade_fee(&self, trade_amount: u64) -> Result<u64, ProgramError> {
+        if !self.trade_fee_enabled {
+            return Ok(0);
+        }
         trade_amount
             .checked_mul(self.trade_fee_numerator)
             .ok_or(SwapError::CalculationFailure)?
@@ -111,14 +214,68 @@ impl Fees {
     ///
     /// # Return value
     ///
-    /// withdraw fee
+    /// withdraw fee, zero if withdraw fees are disabled
     pub fn withdraw_fee(&self, withdraw_amount: u64) -> Result<u64, ProgramError> {
+        if !self.withdraw_fee_enabled {
+            return Ok(0);
+        }
         withdraw_amount
             .checked_mul(self.withdraw_fee_numerator)
             .ok_or(SwapError::CalculationFailure)?
             .checked_div(self.withdraw_fee_denominator)
             .ok_or_else(|| SwapError::CalculationFailure.into())
     }
+
+    /// Split a swap output into its fee components, before any pool creator
+    /// share of the trade fee
+    ///
+    /// # Arguments
+    ///
+    /// * gross - swap output before fees.
+    /// * quote_per_token - price of the output token in quote tokens, one for
+    ///   the quote token itself.
+    ///
+    /// # Return value
+    ///
+    /// amount paid to the user, admin trade fee and the rest of the trade fee
+    /// left in the pool for liquidity providers
+    pub fn apply_trade_fees(
+        &self,
+        gross: u64,
+        quote_per_token: Decimal,
+    ) -> Result<(u64, u64, u64), ProgramError> {
+        let trade_fee = self.trade_fee(gross, quote_per_token)?;
+        let admin_fee = self.admin_trade_fee(trade_fee)?;
+        let net = gross
+            .checked_sub(trade_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        let lp_retained = trade_fee
+            .checked_sub(admin_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok((net, admin_fee, lp_retained))
+    }
+
+    /// Split a withdrawn amount into its fee components
+    ///
+    /// # Arguments
+    ///
+    /// * gross - amount withdrawn before fees.
+    ///
+    /// # Return value
+    ///
+    /// amount paid to the user, admin withdraw fee and the rest of the
+    /// withdraw fee left in the pool for liquidity providers
+    pub fn apply_withdraw_fees(&self, gross: u64) -> Result<(u64, u64, u64), ProgramError> {
+        let withdraw_fee = self.withdraw_fee(gross)?;
+        let admin_fee = self.admin_withdraw_fee(withdraw_fee)?;
+        let net = gross
+            .checked_sub(withdraw_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        let lp_retained = withdraw_fee
+            .checked_sub(admin_fee)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok((net, admin_fee, lp_retained))
+    }
 }
 
 impl Sealed for Fees {}
@@ -128,7 +285,7 @@ impl IsInitialized for Fees {
     }
 }
 
-const FEES_SIZE: usize = 64;
+const FEES_SIZE: usize = 74;
 impl Pack for Fees {
     const LEN: usize = FEES_SIZE;
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
@@ -143,7 +300,10 @@ impl Pack for Fees {
             trade_fee_denominator,
             withdraw_fee_numerator,
             withdraw_fee_denominator,
-        ) = array_refs![input, 8, 8, 8, 8, 8, 8, 8, 8];
+            trade_fee_enabled,
+            withdraw_fee_enabled,
+            min_fee,
+        ) = array_refs![input, 8, 8, 8, 8, 8, 8, 8, 8, 1, 1, 8];
         Ok(Self {
             admin_trade_fee_numerator: u64::from_le_bytes(*admin_trade_fee_numerator),
             admin_trade_fee_denominator: u64::from_le_bytes(*admin_trade_fee_denominator),
@@ -153,6 +313,9 @@ impl Pack for Fees {
             trade_fee_denominator: u64::from_le_bytes(*trade_fee_denominator),
             withdraw_fee_numerator: u64::from_le_bytes(*withdraw_fee_numerator),
             withdraw_fee_denominator: u64::from_le_bytes(*withdraw_fee_denominator),
+            trade_fee_enabled: unpack_bool(trade_fee_enabled)?,
+            withdraw_fee_enabled: unpack_bool(withdraw_fee_enabled)?,
+            min_fee: u64::from_le_bytes(*min_fee),
         })
     }
 
@@ -167,7 +330,10 @@ impl Pack for Fees {
             trade_fee_denominator,
             withdraw_fee_numerator,
             withdraw_fee_denominator,
-        ) = mut_array_refs![output, 8, 8, 8, 8, 8, 8, 8, 8];
+            trade_fee_enabled,
+            withdraw_fee_enabled,
+            min_fee,
+        ) = mut_array_refs![output, 8, 8, 8, 8, 8, 8, 8, 8, 1, 1, 8];
         *admin_trade_fee_numerator = self.admin_trade_fee_numerator.to_le_bytes();
         *admin_trade_fee_denominator = self.admin_trade_fee_denominator.to_le_bytes();
         *admin_withdraw_fee_numerator = self.admin_withdraw_fee_numerator.to_le_bytes();
@@ -176,6 +342,9 @@ impl Pack for Fees {
         *trade_fee_denominator = self.trade_fee_denominator.to_le_bytes();
         *withdraw_fee_numerator = self.withdraw_fee_numerator.to_le_bytes();
         *withdraw_fee_denominator = self.withdraw_fee_denominator.to_le_bytes();
+        trade_fee_enabled[0] = self.trade_fee_enabled as u8;
+        withdraw_fee_enabled[0] = self.withdraw_fee_enabled as u8;
+        *min_fee = self.min_fee.to_le_bytes();
     }
 }
 
@@ -186,7 +355,10 @@ mod tests {
 
     #[test]
     fn pack_fees() {
-        let fees = DEFAULT_TEST_FEES;
+        let fees = Fees {
+            min_fee: 1_000,
+            ..DEFAULT_TEST_FEES
+        };
 
         let mut packed = [0u8; Fees::LEN];
         Pack::pack_into_slice(&fees, &mut packed[..]);
@@ -202,8 +374,17 @@ mod tests {
         packed.extend_from_slice(&fees.trade_fee_denominator.to_le_bytes());
         packed.extend_from_slice(&fees.withdraw_fee_numerator.to_le_bytes());
         packed.extend_from_slice(&fees.withdraw_fee_denominator.to_le_bytes());
+        packed.push(fees.trade_fee_enabled as u8);
+        packed.push(fees.withdraw_fee_enabled as u8);
+        packed.extend_from_slice(&fees.min_fee.to_le_bytes());
         let unpacked = Fees::unpack_from_slice(&packed).unwrap();
         assert_eq!(fees, unpacked);
+
+        packed[Fees::LEN - 9] = 2;
+        assert_eq!(
+            Fees::unpack_from_slice(&packed),
+            Err(ProgramError::InvalidAccountData)
+        );
     }
 
     #[test]
@@ -213,7 +394,7 @@ mod tests {
         let trade_amount = 1_000_000_000;
         let expected_trade_fee =
             trade_amount * fees.trade_fee_numerator / fees.trade_fee_denominator;
-        let trade_fee = fees.trade_fee(trade_amount).unwrap();
+        let trade_fee = fees.trade_fee(trade_amount, Decimal::one()).unwrap();
         assert_eq!(trade_fee, expected_trade_fee);
         let expected_admin_trade_fee =
             expected_trade_fee * fees.admin_trade_fee_numerator / fees.admin_trade_fee_denominator;
@@ -234,4 +415,244 @@ mod tests {
             expected_admin_withdraw_fee
         );
     }
+
+    #[test]
+    fn trade_fee_split() {
+        let fees = DEFAULT_TEST_FEES;
+        let gross = 1_000_000_000;
+        let trade_fee = fees.trade_fee(gross, Decimal::one()).unwrap();
+        let admin_fee = fees.admin_trade_fee(trade_fee).unwrap();
+        assert_eq!(
+            fees.apply_trade_fees(gross, Decimal::one()).unwrap(),
+            (gross - trade_fee, admin_fee, trade_fee - admin_fee)
+        );
+
+        let disabled = Fees {
+            trade_fee_enabled: false,
+            ..fees
+        };
+        assert_eq!(
+            disabled.apply_trade_fees(gross, Decimal::one()).unwrap(),
+            (gross, 0, 0)
+        );
+    }
+
+    #[test]
+    fn min_fee_results() {
+        let fees = Fees {
+            min_fee: 1_000,
+            ..DEFAULT_TEST_FEES
+        };
+        let quote = Decimal::one();
+
+        // the percentage fee above the floor is charged as is
+        assert_eq!(fees.trade_fee(1_000_000, quote).unwrap(), 60_000);
+        // below it the floor is charged
+        assert_eq!(fees.trade_fee(10_000, quote).unwrap(), 1_000);
+        // but never more than the trade
+        assert_eq!(fees.trade_fee(500, quote).unwrap(), 500);
+
+        // a base token worth 100 quote tokens floors at 10 base tokens,
+        // rounded up when the conversion is not exact
+        assert_eq!(fees.trade_fee(100, Decimal::from(100u64)).unwrap(), 10);
+        assert_eq!(fees.trade_fee(1_000, Decimal::from(3u64)).unwrap(), 334);
+        assert_eq!(
+            fees.trade_fee(1_000_000, Decimal::from(100u64)).unwrap(),
+            60_000
+        );
+
+        // the floor leaves fee APR accounting untouched
+        assert_eq!(fees.proportional_trade_fee(10_000).unwrap(), 600);
+
+        let disabled = Fees {
+            trade_fee_enabled: false,
+            ..fees
+        };
+        assert_eq!(disabled.trade_fee(10_000, quote).unwrap(), 0);
+    }
+
+    #[test]
+    fn fee_toggles() {
+        let trade_amount = 1_000_000_000;
+        let withdraw_amount = 100_000_000_000;
+
+        let mut fees = DEFAULT_TEST_FEES;
+        fees.trade_fee_enabled = false;
+        assert_eq!(fees.trade_fee(trade_amount, Decimal::one()).unwrap(), 0);
+        assert_eq!(fees.admin_trade_fee(trade_amount).unwrap(), 0);
+        assert_eq!(
+            fees.withdraw_fee(withdraw_amount).unwrap(),
+            DEFAULT_TEST_FEES.withdraw_fee(withdraw_amount).unwrap()
+        );
+        assert_eq!(
+            fees.admin_withdraw_fee(withdraw_amount).unwrap(),
+            DEFAULT_TEST_FEES
+                .admin_withdraw_fee(withdraw_amount)
+                .unwrap()
+        );
+
+        let mut fees = DEFAULT_TEST_FEES;
+        fees.withdraw_fee_enabled = false;
+        assert_eq!(fees.withdraw_fee(withdraw_amount).unwrap(), 0);
+        assert_eq!(fees.admin_withdraw_fee(withdraw_amount).unwrap(), 0);
+        assert_eq!(
+            fees.trade_fee(trade_amount, Decimal::one()).unwrap(),
+            DEFAULT_TEST_FEES
+                .trade_fee(trade_amount, Decimal::one())
+                .unwrap()
+        );
+        assert_eq!(
+            fees.admin_trade_fee(trade_amount).unwrap(),
+            DEFAULT_TEST_FEES.admin_trade_fee(trade_amount).unwrap()
+        );
+
+        // disabled fees do not touch their zeroed denominators
+        let fees = Fees {
+            trade_fee_enabled: false,
+            withdraw_fee_enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(fees.trade_fee(trade_amount, Decimal::one()).unwrap(), 0);
+        assert_eq!(fees.admin_trade_fee(trade_amount).unwrap(), 0);
+        assert_eq!(fees.withdraw_fee(withdraw_amount).unwrap(), 0);
+        assert_eq!(fees.admin_withdraw_fee(withdraw_amount).unwrap(), 0);
+    }
+
+    #[test]
+    fn fee_policy() {
+        assert_eq!(DEFAULT_TEST_FEES.validate_against_policy(), Ok(()));
+
+        let at_ceiling = Fees {
+            trade_fee_numerator: MAX_TRADE_FEE_BPS,
+            trade_fee_denominator: BPS_DENOMINATOR,
+            withdraw_fee_numerator: MAX_WITHDRAW_FEE_BPS,
+            withdraw_fee_denominator: BPS_DENOMINATOR,
+            ..DEFAULT_TEST_FEES
+        };
+        assert_eq!(at_ceiling.validate_against_policy(), Ok(()));
+
+        let trade_above = Fees {
+            trade_fee_numerator: MAX_TRADE_FEE_BPS + 1,
+            ..at_ceiling.clone()
+        };
+        assert_eq!(
+            trade_above.validate_against_policy(),
+            Err(SwapError::FeeExceedsPolicy.into())
+        );
+
+        let withdraw_above = Fees {
+            withdraw_fee_numerator: MAX_WITHDRAW_FEE_BPS + 1,
+            ..at_ceiling.clone()
+        };
+        assert_eq!(
+            withdraw_above.validate_against_policy(),
+            Err(SwapError::FeeExceedsPolicy.into())
+        );
+
+        // disabled fees are not charged, so they are not held to the ceiling
+        let disabled_above = Fees {
+            trade_fee_enabled: false,
+            ..trade_above
+        };
+        assert_eq!(disabled_above.validate_against_policy(), Ok(()));
+
+        // the trade fee floor is bounded too, as it can take the whole trade
+        let min_fee_at_ceiling = Fees {
+            min_fee: MAX_MIN_FEE,
+            ..at_ceiling.clone()
+        };
+        assert_eq!(min_fee_at_ceiling.validate_against_policy(), Ok(()));
+        let min_fee_above = Fees {
+            min_fee: MAX_MIN_FEE + 1,
+            ..at_ceiling.clone()
+        };
+        assert_eq!(
+            min_fee_above.validate_against_policy(),
+            Err(SwapError::FeeExceedsPolicy.into())
+        );
+
+        // an enabled fee with a zero denominator would fail every swap or
+        // withdraw, even a zero fee
+        for zero_denominator in [
+            Fees {
+                trade_fee_numerator: 0,
+                trade_fee_denominator: 0,
+                ..at_ceiling.clone()
+            },
+            Fees {
+                admin_trade_fee_denominator: 0,
+                ..at_ceiling.clone()
+            },
+            Fees {
+                withdraw_fee_numerator: 0,
+                withdraw_fee_denominator: 0,
+                ..at_ceiling.clone()
+            },
+            Fees {
+                admin_withdraw_fee_denominator: 0,
+                ..at_ceiling.clone()
+            },
+        ] {
+            assert_eq!(
+                zero_denominator.validate_against_policy(),
+                Err(SwapError::FeeExceedsPolicy.into())
+            );
+        }
+
+        // the admin share can not exceed the fee it is taken from
+        let admin_share_whole = Fees {
+            admin_trade_fee_numerator: 1,
+            admin_trade_fee_denominator: 1,
+            admin_withdraw_fee_numerator: 1,
+            admin_withdraw_fee_denominator: 1,
+            ..at_ceiling.clone()
+        };
+        assert_eq!(admin_share_whole.validate_against_policy(), Ok(()));
+        for admin_share_above in [
+            Fees {
+                admin_trade_fee_numerator: 3,
+                admin_trade_fee_denominator: 2,
+                ..at_ceiling.clone()
+            },
+            Fees {
+                admin_withdraw_fee_numerator: 3,
+                admin_withdraw_fee_denominator: 2,
+                ..at_ceiling.clone()
+            },
+        ] {
+            assert_eq!(
+                admin_share_above.validate_against_policy(),
+                Err(SwapError::FeeExceedsPolicy.into())
+            );
+        }
+    }
+
+    #[test]
+    fn withdraw_fee_breakdown() {
+        let fees = DEFAULT_TEST_FEES;
+        for gross in [0, 1, 16, 17, 33, 34, 99, 100, 1_000_000_007, u64::MAX / 100] {
+            let (net, admin_fee, lp_retained) = fees.apply_withdraw_fees(gross).unwrap();
+            assert_eq!(net + admin_fee + lp_retained, gross);
+
+            let withdraw_fee = fees.withdraw_fee(gross).unwrap();
+            assert_eq!(net, gross - withdraw_fee);
+            assert_eq!(admin_fee, fees.admin_withdraw_fee(withdraw_fee).unwrap());
+        }
+
+        // 6% of 100 is 6, of which the admin takes half
+        assert_eq!(fees.apply_withdraw_fees(100).unwrap(), (94, 3, 3));
+        // 6% of 50 is 3, and the odd unit stays with the pool
+        assert_eq!(fees.apply_withdraw_fees(50).unwrap(), (47, 1, 2));
+
+        let disabled = Fees {
+            withdraw_fee_enabled: false,
+            ..DEFAULT_TEST_FEES
+        };
+        assert_eq!(disabled.apply_withdraw_fees(100).unwrap(), (100, 0, 0));
+
+        assert_eq!(
+            fees.apply_withdraw_fees(u64::MAX).unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
 }
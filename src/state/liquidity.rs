@@ -9,16 +9,87 @@ use solana_program::{
 
 use crate::{
     error::SwapError,
-    math::{Decimal, TryDiv, TryMul},
-    state::unpack_bool,
+    math::{Decimal, TryDiv, TryMul, TrySub},
+    state::{unpack_bool, Fees},
 };
 
 use std::convert::TryFrom;
 
 /// Max number of positions
-pub const MAX_LIQUIDITY_POSITIONS: usize = 10;
+pub const MAX_LIQUIDITY_POSITIONS: usize = 20;
+/// Max number of positions in the legacy layout, before `MigrateLiquidityProvider`
+pub const LEGACY_MAX_LIQUIDITY_POSITIONS: usize = 10;
 /// Min period towards next claim
 pub const MIN_CLAIM_PERIOD: UnixTimestamp = 2592000;
+/// Seconds in a 365 day year
+pub const SECONDS_PER_YEAR: u64 = 31536000;
+
+/// Per-second reward accrual of one LP token in DELTAFI,
+/// as applied by `calc_and_update_rewards`
+///
+/// # Arguments
+///
+/// * mid_price - pool mid price, which the claim formula pays per lp token.
+/// * deltafi_price - deltafi token price.
+///
+/// # Return value
+///
+/// reward rate per second
+pub fn reward_rate(mid_price: Decimal, deltafi_price: Decimal) -> Result<Decimal, ProgramError> {
+    mid_price
+        .try_div(deltafi_price)?
+        .try_div(u64::try_from(MIN_CLAIM_PERIOD).unwrap())
+}
+
+/// Annualized reward APR of an LP position
+///
+/// # Arguments
+///
+/// * reward_rate - per-second reward accrual of one LP token in DELTAFI.
+/// * lp_price - lp token price.
+/// * deltafi_price - deltafi token price.
+///
+/// # Return value
+///
+/// yearly reward value over lp token value
+pub fn reward_apr(
+    reward_rate: Decimal,
+    lp_price: Decimal,
+    deltafi_price: Decimal,
+) -> Result<Decimal, ProgramError> {
+    reward_rate
+        .try_mul(SECONDS_PER_YEAR)?
+        .try_mul(deltafi_price)?
+        .try_div(lp_price)
+}
+
+/// Annualized fee APR earned by LPs through reserve growth
+///
+/// # Arguments
+///
+/// * volume - trade volume over the period, valued in the same unit as `liquidity`.
+/// * fees - pool fees, the admin share of the trade fee does not accrue to LPs.
+/// * elapsed - seconds over which the volume was traded.
+/// * liquidity - total pool liquidity value.
+///
+/// # Return value
+///
+/// yearly LP fee income over liquidity value
+pub fn fee_apr(
+    volume: u64,
+    fees: &Fees,
+    elapsed: u64,
+    liquidity: Decimal,
+) -> Result<Decimal, ProgramError> {
+    let trade_fee = fees.proportional_trade_fee(volume)?;
+    let lp_fee = trade_fee
+        .checked_sub(fees.admin_trade_fee(trade_fee)?)
+        .ok_or(SwapError::CalculationFailure)?;
+    Decimal::from(lp_fee)
+        .try_mul(SECONDS_PER_YEAR)?
+        .try_div(elapsed)?
+        .try_div(liquidity)
+}
 
 /// Liquidity user info
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -108,6 +179,26 @@ impl LiquidityProvider {
         Ok(self.positions.last_mut().unwrap())
     }
 
+    /// Add a fully specified position, as migrated from a legacy program
+    ///
+    /// # Arguments
+    ///
+    /// * position - liquidity position to add.
+    ///
+    /// # Return value
+    ///
+    /// seed status
+    pub fn seed_position(&mut self, position: LiquidityPosition) -> ProgramResult {
+        if self.find_position_index(position.pool).is_some() {
+            return Err(SwapError::PositionAlreadyExists.into());
+        }
+        if self.positions.len() >= MAX_LIQUIDITY_POSITIONS {
+            return Err(SwapError::InvalidInput.into());
+        }
+        self.positions.push(position);
+        Ok(())
+    }
+
     /// Find position index given pool address
     ///
     /// # Arguments
@@ -173,12 +264,15 @@ pub struct LiquidityPosition {
     pub rewards_owed: u64,
     /// Rewards amount estimated in new claim period
     pub rewards_estimated: u64,
-    /// Cumulative interest
-    pub cumulative_interest: u64,
+    /// Cumulative interest, informational only and wide enough to never
+    /// overflow
+    pub cumulative_interest: u128,
     /// Last updated timestamp
     pub last_update_ts: UnixTimestamp,
     /// Next claim timestamp
     pub next_claim_ts: UnixTimestamp,
+    /// Reward per share accumulator, scaled, at the last settlement
+    pub reward_debt: u128,
 }
 
 impl LiquidityPosition {
@@ -203,19 +297,23 @@ impl LiquidityPosition {
             next_claim_ts: current_ts
                 .checked_add(MIN_CLAIM_PERIOD)
                 .ok_or(SwapError::CalculationFailure)?,
+            reward_debt: 0,
         })
     }
 
-    /// Deposit liquidity
+    /// Deposit liquidity, settling the rewards accrued by the current
+    /// liquidity first
     ///
     /// # Arguments
     ///
     /// * deposit_amount - amount to deposit.
+    /// * reward_per_share - current reward per share accumulator of the pool.
     ///
     /// # Return value
     ///
     /// deposit status
-    pub fn deposit(&mut self, deposit_amount: u64) -> ProgramResult {
+    pub fn deposit(&mut self, deposit_amount: u64, reward_per_share: Decimal) -> ProgramResult {
+        self.settle_rewards(reward_per_share)?;
         self.liquidity_amount = self
             .liquidity_amount
             .checked_add(deposit_amount)
@@ -223,7 +321,26 @@ impl LiquidityPosition {
         Ok(())
     }
 
-    /// Withdraw liquidity
+    /// Credit a deposit reward to the rewards estimated in the current claim
+    /// period, so it only becomes owed at the next claim timestamp
+    ///
+    /// # Arguments
+    ///
+    /// * reward - deposit reward.
+    ///
+    /// # Return value
+    ///
+    /// credit status
+    pub fn credit_deposit_reward(&mut self, reward: u64) -> ProgramResult {
+        self.rewards_estimated = self
+            .rewards_estimated
+            .checked_add(reward)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
+    /// Withdraw liquidity, forfeiting the share of the rewards estimated in
+    /// the current claim period that the withdrawn liquidity accrued
     ///
     /// # Arguments
     ///
@@ -236,6 +353,16 @@ impl LiquidityPosition {
         if withdraw_amount > self.liquidity_amount {
             return Err(SwapError::InsufficientLiquidity.into());
         }
+        if withdraw_amount > 0 {
+            let forfeited = Decimal::from(self.rewards_estimated)
+                .try_mul(withdraw_amount)?
+                .try_div(self.liquidity_amount)?
+                .try_ceil_u64()?;
+            self.rewards_estimated = self
+                .rewards_estimated
+                .checked_sub(forfeited)
+                .ok_or(SwapError::CalculationFailure)?;
+        }
         self.liquidity_amount = self
             .liquidity_amount
             .checked_sub(withdraw_amount)
@@ -243,6 +370,46 @@ impl LiquidityPosition {
         Ok(())
     }
 
+    /// Rewards accrued since the last settlement,
+    /// `liquidity_amount * (reward_per_share - reward_debt)`
+    ///
+    /// # Arguments
+    ///
+    /// * reward_per_share - current reward per share accumulator of the pool.
+    ///
+    /// # Return value
+    ///
+    /// pending rewards
+    pub fn pending_accumulated_rewards(
+        &self,
+        reward_per_share: Decimal,
+    ) -> Result<u64, ProgramError> {
+        reward_per_share
+            .try_sub(Decimal::from_scaled_val(self.reward_debt))?
+            .try_mul(self.liquidity_amount)?
+            .try_floor_u64()
+    }
+
+    /// Move the rewards accrued since the last settlement into the estimated
+    /// rewards and record the accumulator as the new reward debt
+    ///
+    /// # Arguments
+    ///
+    /// * reward_per_share - current reward per share accumulator of the pool.
+    ///
+    /// # Return value
+    ///
+    /// settled rewards
+    pub fn settle_rewards(&mut self, reward_per_share: Decimal) -> Result<u64, ProgramError> {
+        let pending = self.pending_accumulated_rewards(reward_per_share)?;
+        self.rewards_estimated = self
+            .rewards_estimated
+            .checked_add(pending)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.reward_debt = reward_per_share.to_scaled_val()?;
+        Ok(pending)
+    }
+
     /// Update next claim timestamp
     ///
     /// # Return value
@@ -258,12 +425,28 @@ impl LiquidityPosition {
         Ok(())
     }
 
+    /// Move the next claim timestamp onto the current claim schedule, one
+    /// `MIN_CLAIM_PERIOD` after the last update, for positions scheduled
+    /// under a different period
+    ///
+    /// # Return value
+    ///
+    /// resync status
+    pub fn resync_claim_ts(&mut self) -> ProgramResult {
+        self.next_claim_ts = self
+            .last_update_ts
+            .checked_add(MIN_CLAIM_PERIOD)
+            .ok_or(SwapError::CalculationFailure)?;
+        Ok(())
+    }
+
     /// Calculate and update rewards
     ///
     /// # Arguments
     ///
     /// * rewards_ratio - rewards ratio calculated by lp token and deltafi token price.
     /// * current_ts - current unix timestamp.
+    /// * min_reward_liquidity - liquidity below which the period accrues nothing.
     ///
     /// # Return value
     ///
@@ -272,11 +455,14 @@ impl LiquidityPosition {
         &mut self,
         rewards_ratio: Decimal,
         current_ts: UnixTimestamp,
+        min_reward_liquidity: u64,
     ) -> ProgramResult {
         let calc_period = current_ts
             .checked_sub(self.last_update_ts)
             .ok_or(SwapError::CalculationFailure)?;
-        if calc_period > 0 {
+        if calc_period > 0 && self.liquidity_amount < min_reward_liquidity {
+            self.last_update_ts = current_ts;
+        } else if calc_period > 0 {
             self.rewards_estimated = rewards_ratio
                 .try_mul(self.liquidity_amount)?
                 .try_div(u64::try_from(MIN_CLAIM_PERIOD).unwrap())?
@@ -299,6 +485,28 @@ impl LiquidityPosition {
         Ok(())
     }
 
+    /// Rewards of the position as if it were refreshed now, without updating it
+    ///
+    /// # Arguments
+    ///
+    /// * rewards_ratio - rewards ratio calculated by lp token and deltafi token price.
+    /// * current_ts - current unix timestamp.
+    /// * min_reward_liquidity - liquidity below which the period accrues nothing.
+    ///
+    /// # Return value
+    ///
+    /// claim status after the refresh
+    pub fn pending_rewards(
+        &self,
+        rewards_ratio: Decimal,
+        current_ts: UnixTimestamp,
+        min_reward_liquidity: u64,
+    ) -> Result<ClaimStatus, ProgramError> {
+        let mut position = self.clone();
+        position.calc_and_update_rewards(rewards_ratio, current_ts, min_reward_liquidity)?;
+        Ok(position.claim_status(current_ts))
+    }
+
     /// Claim rewards owed
     ///
     /// # Return value
@@ -310,12 +518,86 @@ impl LiquidityPosition {
         }
         self.cumulative_interest = self
             .cumulative_interest
-            .checked_add(self.rewards_owed)
+            .checked_add(self.rewards_owed as u128)
             .ok_or(SwapError::CalculationFailure)?;
         let ret = self.rewards_owed;
         self.rewards_owed = 0;
         Ok(ret)
     }
+
+    /// Seconds left until the next claim
+    ///
+    /// # Arguments
+    ///
+    /// * now - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// seconds until next claim, zero if already claimable
+    pub fn seconds_until_claim(&self, now: UnixTimestamp) -> i64 {
+        self.next_claim_ts.saturating_sub(now).max(0)
+    }
+
+    /// Claim status of the position
+    ///
+    /// # Arguments
+    ///
+    /// * now - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// claim status
+    pub fn claim_status(&self, now: UnixTimestamp) -> ClaimStatus {
+        ClaimStatus {
+            rewards_owed: self.rewards_owed,
+            rewards_estimated: self.rewards_estimated,
+            next_claim_ts: self.next_claim_ts,
+            seconds_until_claim: self.seconds_until_claim(now),
+        }
+    }
+}
+
+/// Claim status of a liquidity position
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClaimStatus {
+    /// Rewards amount owed
+    pub rewards_owed: u64,
+    /// Rewards amount estimated in current claim period
+    pub rewards_estimated: u64,
+    /// Next claim timestamp
+    pub next_claim_ts: UnixTimestamp,
+    /// Seconds left until next claim
+    pub seconds_until_claim: i64,
+}
+
+impl Sealed for ClaimStatus {}
+const CLAIM_STATUS_SIZE: usize = 32; // 8 + 8 + 8 + 8
+impl Pack for ClaimStatus {
+    const LEN: usize = CLAIM_STATUS_SIZE;
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, CLAIM_STATUS_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (rewards_owed, rewards_estimated, next_claim_ts, seconds_until_claim) =
+            array_refs![input, 8, 8, 8, 8];
+        Ok(Self {
+            rewards_owed: u64::from_le_bytes(*rewards_owed),
+            rewards_estimated: u64::from_le_bytes(*rewards_estimated),
+            next_claim_ts: i64::from_le_bytes(*next_claim_ts),
+            seconds_until_claim: i64::from_le_bytes(*seconds_until_claim),
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, CLAIM_STATUS_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (rewards_owed, rewards_estimated, next_claim_ts, seconds_until_claim) =
+            mut_array_refs![output, 8, 8, 8, 8];
+        *rewards_owed = self.rewards_owed.to_le_bytes();
+        *rewards_estimated = self.rewards_estimated.to_le_bytes();
+        *next_claim_ts = self.next_claim_ts.to_le_bytes();
+        *seconds_until_claim = self.seconds_until_claim.to_le_bytes();
+    }
 }
 
 impl Sealed for LiquidityProvider {}
@@ -326,8 +608,73 @@ impl IsInitialized for LiquidityProvider {
 }
 
 #[doc(hidden)]
-const LIQUIDITY_POSITION_SIZE: usize = 80; // 32 + 8 + 8 + 8 + 8 + 8 + 8
-const LIQUIDITY_PROVIDER_SIZE: usize = 834; // 1 + 32 + 1 + (80 * 10)
+const LIQUIDITY_POSITION_SIZE: usize = 104; // 32 + 8 + 8 + 8 + 16 + 8 + 8 + 16
+const LIQUIDITY_PROVIDER_SIZE: usize = 2114; // 1 + 32 + 1 + (104 * 20)
+const LIQUIDITY_PROVIDER_HEADER_SIZE: usize = 1 + PUBKEY_BYTES + 1;
+/// Size of a position before `reward_debt`
+const NO_REWARD_DEBT_LIQUIDITY_POSITION_SIZE: usize = 88; // 32 + 8 + 8 + 8 + 16 + 8 + 8
+/// Size of a liquidity provider account before `reward_debt`
+pub const NO_REWARD_DEBT_LIQUIDITY_PROVIDER_SIZE: usize = LIQUIDITY_PROVIDER_HEADER_SIZE
+    + NO_REWARD_DEBT_LIQUIDITY_POSITION_SIZE * MAX_LIQUIDITY_POSITIONS;
+/// Size of a position in the legacy layouts, with a u64 `cumulative_interest`
+const LEGACY_LIQUIDITY_POSITION_SIZE: usize = 80; // 32 + 8 + 8 + 8 + 8 + 8 + 8
+const LEGACY_CUMULATIVE_INTEREST_OFFSET: usize = PUBKEY_BYTES + 8 + 8 + 8;
+/// Size of a liquidity provider account in the legacy layout
+pub const LEGACY_LIQUIDITY_PROVIDER_SIZE: usize = LIQUIDITY_PROVIDER_HEADER_SIZE
+    + LEGACY_LIQUIDITY_POSITION_SIZE * LEGACY_MAX_LIQUIDITY_POSITIONS;
+/// Size of a liquidity provider account with all position slots but a u64
+/// `cumulative_interest`
+pub const U64_INTEREST_LIQUIDITY_PROVIDER_SIZE: usize =
+    LIQUIDITY_PROVIDER_HEADER_SIZE + LEGACY_LIQUIDITY_POSITION_SIZE * MAX_LIQUIDITY_POSITIONS;
+
+impl LiquidityProvider {
+    /// Unpack a liquidity provider stored in either the current or a legacy
+    /// layout, detected by the account size
+    pub fn unpack_versioned(input: &[u8]) -> Result<Self, ProgramError> {
+        match input.len() {
+            LIQUIDITY_PROVIDER_SIZE => Self::unpack(input),
+            NO_REWARD_DEBT_LIQUIDITY_PROVIDER_SIZE => {
+                // positions gain a trailing reward debt, zero for positions
+                // that never settled against an accumulator
+                let (header, old_positions) = input.split_at(LIQUIDITY_PROVIDER_HEADER_SIZE);
+                let mut widened = vec![0u8; LIQUIDITY_PROVIDER_SIZE];
+                let (widened_header, positions) =
+                    widened.split_at_mut(LIQUIDITY_PROVIDER_HEADER_SIZE);
+                widened_header.copy_from_slice(header);
+                for (old, position) in old_positions
+                    .chunks_exact(NO_REWARD_DEBT_LIQUIDITY_POSITION_SIZE)
+                    .zip(positions.chunks_exact_mut(LIQUIDITY_POSITION_SIZE))
+                {
+                    position[..old.len()].copy_from_slice(old);
+                }
+                Self::unpack(&widened)
+            }
+            LEGACY_LIQUIDITY_PROVIDER_SIZE | U64_INTEREST_LIQUIDITY_PROVIDER_SIZE => {
+                // legacy layouts may have fewer trailing position slots, each
+                // with a u64 cumulative interest widened here to the u128 one
+                // and no reward debt
+                let (header, legacy_positions) = input.split_at(LIQUIDITY_PROVIDER_HEADER_SIZE);
+                let mut widened = vec![0u8; LIQUIDITY_PROVIDER_SIZE];
+                let (widened_header, positions) =
+                    widened.split_at_mut(LIQUIDITY_PROVIDER_HEADER_SIZE);
+                widened_header.copy_from_slice(header);
+                for (legacy, position) in legacy_positions
+                    .chunks_exact(LEGACY_LIQUIDITY_POSITION_SIZE)
+                    .zip(positions.chunks_exact_mut(LIQUIDITY_POSITION_SIZE))
+                {
+                    let (legacy_head, legacy_tail) =
+                        legacy.split_at(LEGACY_CUMULATIVE_INTEREST_OFFSET + 8);
+                    position[..legacy_head.len()].copy_from_slice(legacy_head);
+                    position[NO_REWARD_DEBT_LIQUIDITY_POSITION_SIZE - legacy_tail.len()
+                        ..NO_REWARD_DEBT_LIQUIDITY_POSITION_SIZE]
+                        .copy_from_slice(legacy_tail);
+                }
+                Self::unpack(&widened)
+            }
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
 
 impl Pack for LiquidityProvider {
     const LEN: usize = LIQUIDITY_PROVIDER_SIZE;
@@ -358,7 +705,8 @@ impl Pack for LiquidityProvider {
                 cumulative_interest,
                 last_update_ts,
                 next_claim_ts,
-            ) = mut_array_refs![position_flat, PUBKEY_BYTES, 8, 8, 8, 8, 8, 8];
+                reward_debt,
+            ) = mut_array_refs![position_flat, PUBKEY_BYTES, 8, 8, 8, 16, 8, 8, 16];
 
             pool.copy_from_slice(position.pool.as_ref());
             *liquidity_amount = position.liquidity_amount.to_le_bytes();
@@ -367,6 +715,7 @@ impl Pack for LiquidityProvider {
             *cumulative_interest = position.cumulative_interest.to_le_bytes();
             *last_update_ts = position.last_update_ts.to_le_bytes();
             *next_claim_ts = position.next_claim_ts.to_le_bytes();
+            *reward_debt = position.reward_debt.to_le_bytes();
             offset += LIQUIDITY_POSITION_SIZE;
         }
     }
@@ -398,15 +747,17 @@ impl Pack for LiquidityProvider {
                 cumulative_interest,
                 last_update_ts,
                 next_claim_ts,
-            ) = array_refs![positions_flat, PUBKEY_BYTES, 8, 8, 8, 8, 8, 8];
+                reward_debt,
+            ) = array_refs![positions_flat, PUBKEY_BYTES, 8, 8, 8, 16, 8, 8, 16];
             positions.push(LiquidityPosition {
                 pool: Pubkey::new(pool),
                 liquidity_amount: u64::from_le_bytes(*liquidity_amount),
                 rewards_owed: u64::from_le_bytes(*rewards_owed),
                 rewards_estimated: u64::from_le_bytes(*rewards_estimated),
-                cumulative_interest: u64::from_le_bytes(*cumulative_interest),
+                cumulative_interest: u128::from_le_bytes(*cumulative_interest),
                 last_update_ts: i64::from_le_bytes(*last_update_ts),
                 next_claim_ts: i64::from_le_bytes(*next_claim_ts),
+                reward_debt: u128::from_le_bytes(*reward_debt),
             });
             offset += LIQUIDITY_POSITION_SIZE;
         }
@@ -457,6 +808,7 @@ mod tests {
                     .calc_and_update_rewards(
                         Decimal::from_scaled_val(exact_rate),
                         i * REFRESH_PERIOD,
+                        0,
                     )
                     .unwrap();
                 assert!(liquidity_position.rewards_estimated < max_period_amount);
@@ -476,7 +828,7 @@ mod tests {
         };
 
         assert_eq!(
-            position.deposit(100),
+            position.deposit(100, Decimal::zero()),
             Err(SwapError::CalculationFailure.into())
         );
 
@@ -498,7 +850,7 @@ mod tests {
             Err(SwapError::InsufficientClaimAmount.into())
         );
 
-        position.cumulative_interest = u64::MAX;
+        position.cumulative_interest = u128::MAX;
         position.rewards_owed = 100;
         assert_eq!(
             position.claim_rewards(),
@@ -506,6 +858,213 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_reward_apr() {
+        // 0.000001 DELTAFI per second at equal prices
+        let apr = reward_apr(
+            Decimal::from_scaled_val(1_000),
+            Decimal::one(),
+            Decimal::one(),
+        )
+        .unwrap();
+        assert_eq!(apr, Decimal::from_scaled_val(31_536_000_000));
+
+        // mid price at 20, lp at 40, deltafi at 0.1
+        let mid_price = Decimal::from(20u64);
+        let lp_price = Decimal::from(40u64);
+        let deltafi_price = Decimal::one().try_div(10).unwrap();
+        let rate = reward_rate(mid_price, deltafi_price).unwrap();
+        // 200 / 2592000 truncated to 9 decimals
+        assert_eq!(rate, Decimal::from_scaled_val(77_160));
+        // 0.000077160 * 31536000 * 0.1 / 40
+        assert_eq!(
+            reward_apr(rate, lp_price, deltafi_price).unwrap(),
+            Decimal::from_scaled_val(6_083_294_400)
+        );
+        // the deltafi price cancels out of the claim formula
+        let deltafi_price = Decimal::one().try_div(2).unwrap();
+        let rate = reward_rate(mid_price, deltafi_price).unwrap();
+        assert_eq!(
+            reward_apr(rate, lp_price, deltafi_price).unwrap(),
+            Decimal::from_scaled_val(6_083_294_400)
+        );
+
+        assert_eq!(
+            reward_apr(rate, Decimal::zero(), deltafi_price).unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
+
+    #[test]
+    fn test_fee_apr() {
+        let mut fees = Fees {
+            trade_fee_numerator: 30,
+            trade_fee_denominator: 10_000,
+            admin_trade_fee_numerator: 0,
+            admin_trade_fee_denominator: 1,
+            trade_fee_enabled: true,
+            ..Fees::default()
+        };
+        let one_day = 86_400;
+        let liquidity = Decimal::from(100_000_000_000u64);
+
+        // 0.3% of 1e9 per day over 1e11 liquidity: 3e6 * 365 / 1e11
+        assert_eq!(
+            fee_apr(1_000_000_000, &fees, one_day, liquidity).unwrap(),
+            Decimal::from_scaled_val(10_950_000)
+        );
+
+        // a fifth of the trade fee goes to the admin
+        fees.admin_trade_fee_numerator = 1;
+        fees.admin_trade_fee_denominator = 5;
+        assert_eq!(
+            fee_apr(1_000_000_000, &fees, one_day, liquidity).unwrap(),
+            Decimal::from_scaled_val(8_760_000)
+        );
+
+        fees.trade_fee_enabled = false;
+        assert_eq!(
+            fee_apr(1_000_000_000, &fees, one_day, liquidity).unwrap(),
+            Decimal::zero()
+        );
+
+        assert_eq!(
+            fee_apr(1_000_000_000, &fees, 0, liquidity).unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
+
+    #[test]
+    fn test_seconds_until_claim() {
+        let pool = Pubkey::new_unique();
+        let position = LiquidityPosition::new(pool, 1000).unwrap();
+        let next_claim_ts = 1000 + MIN_CLAIM_PERIOD;
+
+        assert_eq!(position.seconds_until_claim(1000), MIN_CLAIM_PERIOD);
+        assert_eq!(position.seconds_until_claim(next_claim_ts - 1), 1);
+        assert_eq!(position.seconds_until_claim(next_claim_ts), 0);
+        assert_eq!(position.seconds_until_claim(next_claim_ts + 1), 0);
+        assert_eq!(position.seconds_until_claim(i64::MIN), i64::MAX);
+
+        let status = position.claim_status(next_claim_ts - 10);
+        assert_eq!(status.next_claim_ts, next_claim_ts);
+        assert_eq!(status.seconds_until_claim, 10);
+
+        let mut packed = [0u8; ClaimStatus::LEN];
+        status.pack_into_slice(&mut packed);
+        assert_eq!(ClaimStatus::unpack_from_slice(&packed).unwrap(), status);
+    }
+
+    #[test]
+    fn test_pending_rewards() {
+        let mut position = LiquidityPosition::new(Pubkey::new_unique(), 0).unwrap();
+        position.deposit(1_000_000_000, Decimal::zero()).unwrap();
+        let ratio = Decimal::from(200u64);
+
+        for now in [MIN_CLAIM_PERIOD / 2, MIN_CLAIM_PERIOD + 100] {
+            let pending = position.pending_rewards(ratio, now, 0).unwrap();
+            // preview does not touch the position
+            assert_eq!(position.last_update_ts, 0);
+
+            let mut refreshed = position.clone();
+            refreshed.calc_and_update_rewards(ratio, now, 0).unwrap();
+            assert_eq!(pending, refreshed.claim_status(now));
+        }
+    }
+
+    #[test]
+    fn test_min_reward_liquidity() {
+        let ratio = Decimal::from(200u64);
+        let min_reward_liquidity = 1_000_000;
+        let mut dust = LiquidityPosition::new(Pubkey::new_unique(), 0).unwrap();
+        dust.deposit(min_reward_liquidity - 1, Decimal::zero())
+            .unwrap();
+        let mut eligible = LiquidityPosition::new(Pubkey::new_unique(), 0).unwrap();
+        eligible
+            .deposit(min_reward_liquidity, Decimal::zero())
+            .unwrap();
+
+        let now = MIN_CLAIM_PERIOD / 2;
+        for position in [&mut dust, &mut eligible] {
+            position
+                .calc_and_update_rewards(ratio, now, min_reward_liquidity)
+                .unwrap();
+            assert_eq!(position.last_update_ts, now);
+        }
+        assert_eq!(dust.rewards_estimated, 0);
+        assert!(eligible.rewards_estimated > 0);
+
+        // the skipped period is not paid out once the threshold is lifted
+        dust.calc_and_update_rewards(ratio, now, 0).unwrap();
+        assert_eq!(dust.rewards_estimated, 0);
+    }
+
+    #[test]
+    fn test_deposit_reward_vesting() {
+        let mut position = LiquidityPosition::new(Pubkey::new_unique(), 0).unwrap();
+        position.deposit(1_000, Decimal::zero()).unwrap();
+        position.credit_deposit_reward(100).unwrap();
+        assert_eq!(position.rewards_estimated, 100);
+        assert_eq!(position.rewards_owed, 0);
+
+        // a withdraw forfeits the share of the estimate its liquidity accrued
+        position.deposit(3_000, Decimal::zero()).unwrap();
+        position.credit_deposit_reward(300).unwrap();
+        position.withdraw(3_000).unwrap();
+        assert_eq!(position.rewards_estimated, 100);
+        position.withdraw(1_000).unwrap();
+        assert_eq!(position.rewards_estimated, 0);
+
+        // a credit kept to the next claim timestamp becomes owed
+        position.deposit(1_000, Decimal::zero()).unwrap();
+        position.credit_deposit_reward(100).unwrap();
+        position
+            .calc_and_update_rewards(Decimal::zero(), MIN_CLAIM_PERIOD, 0)
+            .unwrap();
+        assert_eq!(position.rewards_owed, 100);
+        assert_eq!(position.rewards_estimated, 0);
+
+        position.rewards_estimated = u64::MAX;
+        assert_eq!(
+            position.credit_deposit_reward(1),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_seed_position() {
+        let mut provider = LiquidityProvider::new(Pubkey::new_unique(), vec![]);
+        let position = LiquidityPosition {
+            pool: Pubkey::new_unique(),
+            liquidity_amount: 1_000,
+            rewards_owed: 50,
+            ..LiquidityPosition::default()
+        };
+
+        provider.seed_position(position.clone()).unwrap();
+        assert_eq!(provider.positions, vec![position.clone()]);
+        assert_eq!(
+            provider.seed_position(position),
+            Err(SwapError::PositionAlreadyExists.into())
+        );
+
+        while provider.positions.len() < MAX_LIQUIDITY_POSITIONS {
+            provider
+                .seed_position(LiquidityPosition {
+                    pool: Pubkey::new_unique(),
+                    ..LiquidityPosition::default()
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            provider.seed_position(LiquidityPosition {
+                pool: Pubkey::new_unique(),
+                ..LiquidityPosition::default()
+            }),
+            Err(SwapError::InvalidInput.into())
+        );
+    }
+
     #[test]
     fn test_liquidity_provider_packing() {
         let is_initialized = true;
@@ -517,9 +1076,10 @@ mod tests {
         let liquidity_amount_1: u64 = 300;
         let rewards_owed_1: u64 = 100;
         let rewards_estimated_1: u64 = 40;
-        let cumulative_interest_1: u64 = 1000;
+        let cumulative_interest_1: u128 = 1000;
         let last_update_ts_1 = Clock::clone(&Default::default()).unix_timestamp;
         let next_claim_ts_1 = last_update_ts_1 + MIN_CLAIM_PERIOD;
+        let reward_debt_1: u128 = 2_000_000_000;
 
         let position_1 = LiquidityPosition {
             pool: pool_1,
@@ -529,6 +1089,7 @@ mod tests {
             cumulative_interest: cumulative_interest_1,
             last_update_ts: last_update_ts_1,
             next_claim_ts: next_claim_ts_1,
+            reward_debt: reward_debt_1,
         };
 
         let pool_2_key_raw = [3u8; 32];
@@ -536,9 +1097,10 @@ mod tests {
         let liquidity_amount_2: u64 = 500;
         let rewards_owed_2: u64 = 200;
         let rewards_estimated_2: u64 = 80;
-        let cumulative_interest_2: u64 = 2000;
+        let cumulative_interest_2: u128 = u64::MAX as u128 + 2000;
         let last_update_ts_2 = Clock::clone(&Default::default()).unix_timestamp + 300;
         let next_claim_ts_2 = last_update_ts_2 + MIN_CLAIM_PERIOD;
+        let reward_debt_2: u128 = 3_500_000_000;
 
         let position_2 = LiquidityPosition {
             pool: pool_2,
@@ -548,6 +1110,7 @@ mod tests {
             cumulative_interest: cumulative_interest_2,
             last_update_ts: last_update_ts_2,
             next_claim_ts: next_claim_ts_2,
+            reward_debt: reward_debt_2,
         };
 
         let liquidity_provider = LiquidityProvider {
@@ -571,6 +1134,7 @@ mod tests {
         packed.extend_from_slice(&cumulative_interest_1.to_le_bytes());
         packed.extend_from_slice(&last_update_ts_1.to_le_bytes());
         packed.extend_from_slice(&next_claim_ts_1.to_le_bytes());
+        packed.extend_from_slice(&reward_debt_1.to_le_bytes());
         packed.extend_from_slice(&pool_2_key_raw);
         packed.extend_from_slice(&liquidity_amount_2.to_le_bytes());
         packed.extend_from_slice(&rewards_owed_2.to_le_bytes());
@@ -578,6 +1142,7 @@ mod tests {
         packed.extend_from_slice(&cumulative_interest_2.to_le_bytes());
         packed.extend_from_slice(&last_update_ts_2.to_le_bytes());
         packed.extend_from_slice(&next_claim_ts_2.to_le_bytes());
+        packed.extend_from_slice(&reward_debt_2.to_le_bytes());
 
         packed.extend_from_slice(&[0u8; (MAX_LIQUIDITY_POSITIONS - 2) * LIQUIDITY_POSITION_SIZE]);
 
@@ -591,4 +1156,181 @@ mod tests {
         let err = LiquidityProvider::unpack(&packed).unwrap_err();
         assert_eq!(err, ProgramError::UninitializedAccount);
     }
+
+    /// Pack a liquidity provider in a legacy layout with `slots` positions
+    fn pack_legacy(liquidity_provider: &LiquidityProvider, slots: usize) -> Vec<u8> {
+        let mut packed = vec![liquidity_provider.is_initialized as u8];
+        packed.extend_from_slice(liquidity_provider.owner.as_ref());
+        packed.push(liquidity_provider.positions.len() as u8);
+        for position in &liquidity_provider.positions {
+            packed.extend_from_slice(position.pool.as_ref());
+            packed.extend_from_slice(&position.liquidity_amount.to_le_bytes());
+            packed.extend_from_slice(&position.rewards_owed.to_le_bytes());
+            packed.extend_from_slice(&position.rewards_estimated.to_le_bytes());
+            packed.extend_from_slice(&(position.cumulative_interest as u64).to_le_bytes());
+            packed.extend_from_slice(&position.last_update_ts.to_le_bytes());
+            packed.extend_from_slice(&position.next_claim_ts.to_le_bytes());
+        }
+        packed.resize(
+            LIQUIDITY_PROVIDER_HEADER_SIZE + LEGACY_LIQUIDITY_POSITION_SIZE * slots,
+            0,
+        );
+        packed
+    }
+
+    #[test]
+    fn test_unpack_versioned() {
+        let liquidity_provider = LiquidityProvider {
+            is_initialized: true,
+            owner: Pubkey::new_unique(),
+            positions: (0..LEGACY_MAX_LIQUIDITY_POSITIONS)
+                .map(|i| LiquidityPosition {
+                    pool: Pubkey::new_unique(),
+                    liquidity_amount: i as u64,
+                    rewards_owed: 10 + i as u64,
+                    rewards_estimated: 20 + i as u64,
+                    cumulative_interest: u64::MAX as u128 - i as u128,
+                    last_update_ts: 30 + i as i64,
+                    next_claim_ts: 40 + i as i64,
+                    reward_debt: 0,
+                })
+                .collect(),
+        };
+
+        let mut packed = [0u8; LiquidityProvider::LEN];
+        liquidity_provider.pack_into_slice(&mut packed);
+        assert_eq!(
+            LiquidityProvider::unpack_versioned(&packed).unwrap(),
+            liquidity_provider
+        );
+
+        let mut no_reward_debt = packed[..LIQUIDITY_PROVIDER_HEADER_SIZE].to_vec();
+        for position in
+            packed[LIQUIDITY_PROVIDER_HEADER_SIZE..].chunks_exact(LIQUIDITY_POSITION_SIZE)
+        {
+            no_reward_debt.extend_from_slice(&position[..NO_REWARD_DEBT_LIQUIDITY_POSITION_SIZE]);
+        }
+        assert_eq!(no_reward_debt.len(), NO_REWARD_DEBT_LIQUIDITY_PROVIDER_SIZE);
+        assert_eq!(
+            LiquidityProvider::unpack_versioned(&no_reward_debt).unwrap(),
+            liquidity_provider
+        );
+
+        let legacy = pack_legacy(&liquidity_provider, LEGACY_MAX_LIQUIDITY_POSITIONS);
+        assert_eq!(legacy.len(), LEGACY_LIQUIDITY_PROVIDER_SIZE);
+        assert_eq!(
+            LiquidityProvider::unpack_versioned(&legacy).unwrap(),
+            liquidity_provider
+        );
+
+        let u64_interest = pack_legacy(&liquidity_provider, MAX_LIQUIDITY_POSITIONS);
+        assert_eq!(u64_interest.len(), U64_INTEREST_LIQUIDITY_PROVIDER_SIZE);
+        assert_eq!(
+            LiquidityProvider::unpack_versioned(&u64_interest).unwrap(),
+            liquidity_provider
+        );
+
+        assert_eq!(
+            LiquidityProvider::unpack_versioned(&legacy[..LEGACY_LIQUIDITY_PROVIDER_SIZE - 1])
+                .unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        assert_eq!(
+            LiquidityProvider::unpack_versioned(&[0u8; LEGACY_LIQUIDITY_PROVIDER_SIZE])
+                .unwrap_err(),
+            ProgramError::UninitializedAccount
+        );
+    }
+
+    #[test]
+    fn test_resync_claim_ts() {
+        let last_update_ts = 1_000_000;
+        let mut position = LiquidityPosition {
+            liquidity_amount: 100,
+            last_update_ts,
+            // scheduled under a one week period
+            next_claim_ts: last_update_ts + 604_800,
+            ..LiquidityPosition::default()
+        };
+
+        position.resync_claim_ts().unwrap();
+        assert_eq!(position.next_claim_ts, last_update_ts + MIN_CLAIM_PERIOD);
+
+        position.last_update_ts = i64::MAX;
+        assert_eq!(
+            position.resync_claim_ts(),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
+
+    #[test]
+    fn test_claim_past_u64_cumulative_interest() {
+        let mut position = LiquidityPosition {
+            cumulative_interest: u64::MAX as u128 - 1,
+            ..LiquidityPosition::default()
+        };
+
+        for _ in 0..3 {
+            position.rewards_owed = u64::MAX;
+            assert_eq!(position.claim_rewards(), Ok(u64::MAX));
+        }
+        assert_eq!(position.cumulative_interest, 4 * u64::MAX as u128 - 1);
+
+        let mut packed = [0u8; LiquidityProvider::LEN];
+        let liquidity_provider = LiquidityProvider {
+            is_initialized: true,
+            owner: Pubkey::new_unique(),
+            positions: vec![position],
+        };
+        liquidity_provider.pack_into_slice(&mut packed);
+        assert_eq!(
+            LiquidityProvider::unpack(&packed).unwrap(),
+            liquidity_provider
+        );
+    }
+
+    #[test]
+    fn test_settle_rewards() {
+        let reward_per_share = |per_share: u64| Decimal::from(per_share).try_div(100).unwrap();
+
+        // entering at different accumulator values
+        let mut early = LiquidityPosition::default();
+        early.deposit(1_000, reward_per_share(10)).unwrap();
+        let mut late = LiquidityPosition::default();
+        late.deposit(3_000, reward_per_share(30)).unwrap();
+        assert_eq!(
+            early.reward_debt,
+            reward_per_share(10).to_scaled_val().unwrap()
+        );
+        assert_eq!(early.rewards_estimated, 0);
+        assert_eq!(late.rewards_estimated, 0);
+
+        assert_eq!(
+            early.pending_accumulated_rewards(reward_per_share(50)),
+            Ok(400)
+        );
+        assert_eq!(
+            late.pending_accumulated_rewards(reward_per_share(50)),
+            Ok(600)
+        );
+        assert_eq!(early.settle_rewards(reward_per_share(50)), Ok(400));
+        assert_eq!(early.rewards_estimated, 400);
+        assert_eq!(early.settle_rewards(reward_per_share(50)), Ok(0));
+        assert_eq!(early.rewards_estimated, 400);
+
+        // a deposit into an existing position settles at the old liquidity
+        late.deposit(1_000, reward_per_share(60)).unwrap();
+        assert_eq!(late.rewards_estimated, 900);
+        assert_eq!(late.liquidity_amount, 4_000);
+        assert_eq!(
+            late.pending_accumulated_rewards(reward_per_share(70)),
+            Ok(400)
+        );
+
+        // the accumulator never goes backwards
+        assert_eq!(
+            late.settle_rewards(reward_per_share(50)),
+            Err(SwapError::CalculationFailure.into())
+        );
+    }
 }
@@ -1,12 +1,14 @@
 //! State used in DeFi
 
 mod config;
+mod cooldown;
 mod fees;
 mod liquidity;
 mod rewards;
 mod swap;
 
 pub use config::*;
+pub use cooldown::*;
 pub use fees::*;
 pub use liquidity::*;
 pub use rewards::*;
@@ -54,6 +56,9 @@ pub const DEFAULT_TEST_FEES: Fees = Fees {
     trade_fee_denominator: 100,
     withdraw_fee_numerator: 6,
     withdraw_fee_denominator: 100,
+    trade_fee_enabled: true,
+    withdraw_fee_enabled: true,
+    min_fee: 0,
 };
 
 #[cfg(test)]
@@ -64,6 +69,9 @@ pub const DEFAULT_TEST_REWARDS: Rewards = Rewards {
     trade_reward_cap: 100,
     liquidity_reward_numerator: 1,
     liquidity_reward_denominator: 1000,
+    deposit_reward_numerator: 1,
+    deposit_reward_denominator: 100,
+    deposit_reward_cap: 1_000,
 };
 
 #[cfg(test)]
@@ -21,6 +21,12 @@ pub struct Rewards {
     pub liquidity_reward_numerator: u64,
     /// LP reward denominator
     pub liquidity_reward_denominator: u64,
+    /// Deposit reward numerator, per LP token minted
+    pub deposit_reward_numerator: u64,
+    /// Deposit reward denominator, 0 disables deposit rewards
+    pub deposit_reward_denominator: u64,
+    /// Deposit reward cap
+    pub deposit_reward_cap: u64,
 }
 
 impl Rewards {
@@ -40,6 +46,9 @@ impl Rewards {
             trade_reward_cap: params.trade_reward_cap,
             liquidity_reward_numerator: params.liquidity_reward_numerator,
             liquidity_reward_denominator: params.liquidity_reward_denominator,
+            deposit_reward_numerator: params.deposit_reward_numerator,
+            deposit_reward_denominator: params.deposit_reward_denominator,
+            deposit_reward_cap: params.deposit_reward_cap,
         }
     }
 
@@ -80,6 +89,30 @@ impl Rewards {
             .try_div(self.liquidity_reward_denominator)?
             .try_floor_u64()
     }
+
+    /// Calc deposit reward amount with [`u64`]
+    ///
+    /// # Arguments
+    ///
+    /// * pool_token_amount - LP tokens minted by the deposit.
+    ///
+    /// # Return value
+    ///
+    /// deposit reward, zero when deposit rewards are disabled.
+    pub fn deposit_reward_u64(&self, pool_token_amount: u64) -> Result<u64, ProgramError> {
+        if self.deposit_reward_denominator == 0 {
+            return Ok(0);
+        }
+        let c_reward = Decimal::from(pool_token_amount)
+            .try_mul(self.deposit_reward_numerator)?
+            .try_div(self.deposit_reward_denominator)?;
+
+        Ok(if c_reward > Decimal::from(self.deposit_reward_cap) {
+            self.deposit_reward_cap
+        } else {
+            c_reward.try_floor_u64()?
+        })
+    }
 }
 
 impl Sealed for Rewards {}
@@ -89,7 +122,7 @@ impl IsInitialized for Rewards {
     }
 }
 
-const REWARDS_SIZE: usize = 40;
+const REWARDS_SIZE: usize = 64;
 impl Pack for Rewards {
     const LEN: usize = REWARDS_SIZE;
     fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
@@ -101,13 +134,19 @@ impl Pack for Rewards {
             trade_reward_cap,
             liquidity_reward_numerator,
             liquidity_reward_denominator,
-        ) = array_refs![input, 8, 8, 8, 8, 8];
+            deposit_reward_numerator,
+            deposit_reward_denominator,
+            deposit_reward_cap,
+        ) = array_refs![input, 8, 8, 8, 8, 8, 8, 8, 8];
         Ok(Self {
             trade_reward_numerator: u64::from_le_bytes(*trade_reward_numerator),
             trade_reward_denominator: u64::from_le_bytes(*trade_reward_denominator),
             trade_reward_cap: u64::from_le_bytes(*trade_reward_cap),
             liquidity_reward_numerator: u64::from_le_bytes(*liquidity_reward_numerator),
             liquidity_reward_denominator: u64::from_le_bytes(*liquidity_reward_denominator),
+            deposit_reward_numerator: u64::from_le_bytes(*deposit_reward_numerator),
+            deposit_reward_denominator: u64::from_le_bytes(*deposit_reward_denominator),
+            deposit_reward_cap: u64::from_le_bytes(*deposit_reward_cap),
         })
     }
     fn pack_into_slice(&self, output: &mut [u8]) {
@@ -118,12 +157,18 @@ impl Pack for Rewards {
             trade_reward_cap,
             liquidity_reward_numerator,
             liquidity_reward_denominator,
-        ) = mut_array_refs![output, 8, 8, 8, 8, 8];
+            deposit_reward_numerator,
+            deposit_reward_denominator,
+            deposit_reward_cap,
+        ) = mut_array_refs![output, 8, 8, 8, 8, 8, 8, 8, 8];
         *trade_reward_numerator = self.trade_reward_numerator.to_le_bytes();
         *trade_reward_denominator = self.trade_reward_denominator.to_le_bytes();
         *trade_reward_cap = self.trade_reward_cap.to_le_bytes();
         *liquidity_reward_numerator = self.liquidity_reward_numerator.to_le_bytes();
         *liquidity_reward_denominator = self.liquidity_reward_denominator.to_le_bytes();
+        *deposit_reward_numerator = self.deposit_reward_numerator.to_le_bytes();
+        *deposit_reward_denominator = self.deposit_reward_denominator.to_le_bytes();
+        *deposit_reward_cap = self.deposit_reward_cap.to_le_bytes();
     }
 }
 
@@ -147,6 +192,9 @@ mod tests {
         packed.extend_from_slice(&rewards.trade_reward_cap.to_le_bytes());
         packed.extend_from_slice(&rewards.liquidity_reward_numerator.to_le_bytes());
         packed.extend_from_slice(&rewards.liquidity_reward_denominator.to_le_bytes());
+        packed.extend_from_slice(&rewards.deposit_reward_numerator.to_le_bytes());
+        packed.extend_from_slice(&rewards.deposit_reward_denominator.to_le_bytes());
+        packed.extend_from_slice(&rewards.deposit_reward_cap.to_le_bytes());
         let unpacked = Rewards::unpack_from_slice(&packed).unwrap();
         assert_eq!(rewards, unpacked);
     }
@@ -166,6 +214,7 @@ mod tests {
             trade_reward_cap: 0,
             liquidity_reward_numerator,
             liquidity_reward_denominator,
+            ..Rewards::default()
         };
 
         // Low reward cap
@@ -195,4 +244,27 @@ mod tests {
             assert_eq!(lp_reward, expected_lp_reward);
         }
     }
+
+    #[test]
+    fn deposit_reward_results() {
+        let mut rewards = Rewards {
+            deposit_reward_numerator: 1,
+            deposit_reward_denominator: 100,
+            deposit_reward_cap: 5_000,
+            ..Rewards::default()
+        };
+
+        // scales with the LP tokens minted
+        assert_eq!(rewards.deposit_reward_u64(100_000).unwrap(), 1_000);
+        assert_eq!(rewards.deposit_reward_u64(200_000).unwrap(), 2_000);
+        assert_eq!(rewards.deposit_reward_u64(99).unwrap(), 0);
+
+        // capped per deposit
+        assert_eq!(rewards.deposit_reward_u64(500_000).unwrap(), 5_000);
+        assert_eq!(rewards.deposit_reward_u64(10_000_000).unwrap(), 5_000);
+
+        // disabled without a denominator
+        rewards.deposit_reward_denominator = 0;
+        assert_eq!(rewards.deposit_reward_u64(100_000).unwrap(), 0);
+    }
 }
@@ -1,12 +1,73 @@
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 use solana_program::{
+    entrypoint::ProgramResult,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::{Pubkey, PUBKEY_BYTES},
 };
 
 use super::*;
-use crate::{curve::PoolState, math::*};
+use crate::{
+    curve::{PoolState, BPS_DENOMINATOR},
+    error::SwapError,
+    math::*,
+    processor::authority_id,
+};
+
+/// Seconds since the last pool update within which the oracle price may move
+/// at most `max_oracle_move_bps` from the price seen at that update
+pub const ORACLE_MOVE_WINDOW: u64 = 60;
+
+/// Reward tokens a newly initialized pool may mint to swaps before the admin
+/// tops up its budget, 1M DELTAFI at 9 decimals
+pub const DEFAULT_REWARD_BUDGET: u64 = 1_000_000_000_000_000;
+
+/// Bitfield of operations paused on a swap pool.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PauseFlags(u8);
+
+impl PauseFlags {
+    /// Nothing paused
+    pub const NONE: Self = Self(0);
+    /// Swaps paused
+    pub const SWAPS: Self = Self(1 << 0);
+    /// Deposits paused
+    pub const DEPOSITS: Self = Self(1 << 1);
+    /// Withdrawals paused
+    pub const WITHDRAWALS: Self = Self(1 << 2);
+    /// Every operation paused
+    pub const ALL: Self = Self(Self::SWAPS.0 | Self::DEPOSITS.0 | Self::WITHDRAWALS.0);
+
+    /// Create flags from raw bits, rejecting unknown bits
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        if bits & !Self::ALL.0 == 0 {
+            Some(Self(bits))
+        } else {
+            None
+        }
+    }
+
+    /// Raw bits
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Check if all of `other` flags are set
+    pub fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Set `other` flags
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    /// Clear `other` flags
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+}
 
 /// Swap states.
 #[repr(C)]
@@ -15,8 +76,8 @@ pub struct SwapInfo {
     /// Initialized state
     pub is_initialized: bool,
 
-    /// Paused state
-    pub is_paused: bool,
+    /// Paused operations
+    pub pause_flags: PauseFlags,
 
     /// Nonce used in program address
     /// The program address is created deterministically with the nonce,
@@ -55,8 +116,57 @@ pub struct SwapInfo {
     pub block_timestamp_last: u64,
     /// cumulative ticks in seconds
     pub cumulative_ticks: u64,
-    /// base price cumulative last - twap
+    /// base price cumulative last - twap. Wraps around modulo 2^128 of its
+    /// scaled value, so readers must difference two snapshots with
+    /// `Decimal::wrapping_sub`, see `SwapInfo::twap_since`
     pub base_price_cumulative_last: Decimal,
+
+    /// Public key of the account that initialized the pool
+    pub creator_key: Pubkey,
+    /// Share of the trade fee paid to the creator, in basis points
+    pub creator_fee_bps: u16,
+
+    /// Oracle A is quoted as USD/TOKEN and must be inverted
+    pub invert_a: bool,
+    /// Oracle B is quoted as USD/TOKEN and must be inverted
+    pub invert_b: bool,
+
+    /// Config the pool was created under
+    pub config_key: Pubkey,
+
+    /// Liquidity a position needs to accrue rewards
+    pub min_reward_liquidity: u64,
+
+    /// Oracle price seen at the last pool update
+    pub last_oracle_price: Decimal,
+    /// Largest oracle move from `last_oracle_price` accepted within
+    /// `ORACLE_MOVE_WINDOW`, in basis points, 0 disables the check
+    pub max_oracle_move_bps: u16,
+    /// Price step quoted mid prices snap to, 0 disables quantization
+    pub price_tick: Decimal,
+    /// Largest `amount_in` a single swap accepts, 0 disables the cap
+    pub max_trade_amount: u64,
+    /// Unix timestamp the pool was initialized at
+    pub creation_ts: u64,
+    /// Minimum seconds between two swaps of the same user, 0 disables the
+    /// cooldown
+    pub swap_cooldown_seconds: u64,
+    /// Largest slippage in bps a swap's `minimum_amount_out` may accept
+    /// against the mid price estimate, 0 disables the cap
+    pub max_allowed_slippage_bps: u64,
+    /// Price swaps, deposits and withdrawals from the manually set market
+    /// price instead of the oracle
+    pub oracle_free: bool,
+    /// base price squared cumulative last - realized volatility, accumulated
+    /// and wrapping like `base_price_cumulative_last`, see
+    /// `SwapInfo::realized_variance_since`
+    pub base_price_squared_cumulative_last: Decimal,
+    /// Reward tokens swaps on this pool may still mint, topped up by the admin
+    pub reward_budget_remaining: u64,
+    /// Decimals of the pool mint, recorded at initialize
+    pub pool_mint_decimals: u8,
+    /// Quote token volume traded through the pool since it was initialized
+    pub cumulative_volume: u64,
 }
 
 impl Sealed for SwapInfo {}
@@ -65,7 +175,7 @@ impl IsInitialized for SwapInfo {
         self.is_initialized
     }
 }
-const SWAP_INFO_SIZE: usize = 461;
+const SWAP_INFO_SIZE: usize = 671;
 impl Pack for SwapInfo {
     const LEN: usize = SWAP_INFO_SIZE;
 
@@ -75,7 +185,7 @@ impl Pack for SwapInfo {
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             is_initialized,
-            is_paused,
+            pause_flags,
             nonce,
             token_a,
             token_b,
@@ -91,6 +201,24 @@ impl Pack for SwapInfo {
             block_timestamp_last,
             cumulative_ticks,
             base_price_cumulative_last,
+            creator_key,
+            creator_fee_bps,
+            invert_a,
+            invert_b,
+            config_key,
+            min_reward_liquidity,
+            last_oracle_price,
+            max_oracle_move_bps,
+            price_tick,
+            max_trade_amount,
+            creation_ts,
+            swap_cooldown_seconds,
+            max_allowed_slippage_bps,
+            oracle_free,
+            base_price_squared_cumulative_last,
+            reward_budget_remaining,
+            pool_mint_decimals,
+            cumulative_volume,
         ) = array_refs![
             input,
             1,
@@ -109,11 +237,30 @@ impl Pack for SwapInfo {
             1,
             8,
             8,
-            16
+            16,
+            PUBKEY_BYTES,
+            2,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            16,
+            2,
+            16,
+            8,
+            8,
+            8,
+            8,
+            1,
+            16,
+            8,
+            1,
+            8
         ];
         Ok(Self {
             is_initialized: unpack_bool(is_initialized)?,
-            is_paused: unpack_bool(is_paused)?,
+            pause_flags: PauseFlags::from_bits(u8::from_le_bytes(*pause_flags))
+                .ok_or(ProgramError::InvalidAccountData)?,
             nonce: u8::from_le_bytes(*nonce),
             token_a: Pubkey::new_from_array(*token_a),
             token_b: Pubkey::new_from_array(*token_b),
@@ -129,6 +276,24 @@ impl Pack for SwapInfo {
             block_timestamp_last: u64::from_le_bytes(*block_timestamp_last),
             cumulative_ticks: u64::from_le_bytes(*cumulative_ticks),
             base_price_cumulative_last: unpack_decimal(base_price_cumulative_last),
+            creator_key: Pubkey::new_from_array(*creator_key),
+            creator_fee_bps: u16::from_le_bytes(*creator_fee_bps),
+            invert_a: unpack_bool(invert_a)?,
+            invert_b: unpack_bool(invert_b)?,
+            config_key: Pubkey::new_from_array(*config_key),
+            min_reward_liquidity: u64::from_le_bytes(*min_reward_liquidity),
+            last_oracle_price: unpack_decimal(last_oracle_price),
+            max_oracle_move_bps: u16::from_le_bytes(*max_oracle_move_bps),
+            price_tick: unpack_decimal(price_tick),
+            max_trade_amount: u64::from_le_bytes(*max_trade_amount),
+            creation_ts: u64::from_le_bytes(*creation_ts),
+            swap_cooldown_seconds: u64::from_le_bytes(*swap_cooldown_seconds),
+            max_allowed_slippage_bps: u64::from_le_bytes(*max_allowed_slippage_bps),
+            oracle_free: unpack_bool(oracle_free)?,
+            base_price_squared_cumulative_last: unpack_decimal(base_price_squared_cumulative_last),
+            reward_budget_remaining: u64::from_le_bytes(*reward_budget_remaining),
+            pool_mint_decimals: u8::from_le_bytes(*pool_mint_decimals),
+            cumulative_volume: u64::from_le_bytes(*cumulative_volume),
         })
     }
 
@@ -137,7 +302,7 @@ impl Pack for SwapInfo {
         #[allow(clippy::ptr_offset_with_cast)]
         let (
             is_initialized,
-            is_paused,
+            pause_flags,
             nonce,
             token_a,
             token_b,
@@ -153,6 +318,24 @@ impl Pack for SwapInfo {
             block_timestamp_last,
             cumulative_ticks,
             base_price_cumulative_last,
+            creator_key,
+            creator_fee_bps,
+            invert_a,
+            invert_b,
+            config_key,
+            min_reward_liquidity,
+            last_oracle_price,
+            max_oracle_move_bps,
+            price_tick,
+            max_trade_amount,
+            creation_ts,
+            swap_cooldown_seconds,
+            max_allowed_slippage_bps,
+            oracle_free,
+            base_price_squared_cumulative_last,
+            reward_budget_remaining,
+            pool_mint_decimals,
+            cumulative_volume,
         ) = mut_array_refs![
             output,
             1,
@@ -171,10 +354,28 @@ impl Pack for SwapInfo {
             1,
             8,
             8,
-            16
+            16,
+            PUBKEY_BYTES,
+            2,
+            1,
+            1,
+            PUBKEY_BYTES,
+            8,
+            16,
+            2,
+            16,
+            8,
+            8,
+            8,
+            8,
+            1,
+            16,
+            8,
+            1,
+            8
         ];
         pack_bool(self.is_initialized, is_initialized);
-        pack_bool(self.is_paused, is_paused);
+        *pause_flags = self.pause_flags.bits().to_le_bytes();
         *nonce = self.nonce.to_le_bytes();
         token_a.copy_from_slice(self.token_a.as_ref());
         token_b.copy_from_slice(self.token_b.as_ref());
@@ -190,6 +391,246 @@ impl Pack for SwapInfo {
         *block_timestamp_last = self.block_timestamp_last.to_le_bytes();
         *cumulative_ticks = self.cumulative_ticks.to_le_bytes();
         pack_decimal(self.base_price_cumulative_last, base_price_cumulative_last);
+        creator_key.copy_from_slice(self.creator_key.as_ref());
+        *creator_fee_bps = self.creator_fee_bps.to_le_bytes();
+        pack_bool(self.invert_a, invert_a);
+        pack_bool(self.invert_b, invert_b);
+        config_key.copy_from_slice(self.config_key.as_ref());
+        *min_reward_liquidity = self.min_reward_liquidity.to_le_bytes();
+        pack_decimal(self.last_oracle_price, last_oracle_price);
+        *max_oracle_move_bps = self.max_oracle_move_bps.to_le_bytes();
+        pack_decimal(self.price_tick, price_tick);
+        *max_trade_amount = self.max_trade_amount.to_le_bytes();
+        *creation_ts = self.creation_ts.to_le_bytes();
+        *swap_cooldown_seconds = self.swap_cooldown_seconds.to_le_bytes();
+        *max_allowed_slippage_bps = self.max_allowed_slippage_bps.to_le_bytes();
+        pack_bool(self.oracle_free, oracle_free);
+        pack_decimal(
+            self.base_price_squared_cumulative_last,
+            base_price_squared_cumulative_last,
+        );
+        *reward_budget_remaining = self.reward_budget_remaining.to_le_bytes();
+        *pool_mint_decimals = self.pool_mint_decimals.to_le_bytes();
+        *cumulative_volume = self.cumulative_volume.to_le_bytes();
+    }
+}
+
+impl SwapInfo {
+    /// Reserves attributable to liquidity providers
+    ///
+    /// Admin and creator fees are swept to their own token accounts in the
+    /// swap that charges them, so the pool state reserves hold no fees
+    /// earmarked for the admin and reconcile with the token account balances.
+    ///
+    /// # Return value
+    ///
+    /// (base reserve, quote reserve)
+    pub fn lp_reserves(&self) -> (Decimal, Decimal) {
+        (self.pool_state.base_reserve, self.pool_state.quote_reserve)
+    }
+
+    /// Creator share carved out of the trade fee
+    ///
+    /// # Arguments
+    ///
+    /// * trade_fee - trade fee amount.
+    ///
+    /// # Return value
+    ///
+    /// creator fee
+    pub fn creator_fee(&self, trade_fee: u64) -> Result<u64, ProgramError> {
+        trade_fee
+            .checked_mul(self.creator_fee_bps as u64)
+            .ok_or(SwapError::CalculationFailure)?
+            .checked_div(BPS_DENOMINATOR)
+            .ok_or_else(|| SwapError::CalculationFailure.into())
+    }
+
+    /// Time weighted average mid price since an earlier snapshot of the
+    /// accumulator, correct across a wrap of the cumulative price
+    ///
+    /// # Arguments
+    ///
+    /// * cumulative_then - `base_price_cumulative_last` at the snapshot.
+    /// * timestamp_then - `block_timestamp_last` at the snapshot.
+    ///
+    /// # Return value
+    ///
+    /// average price over the window
+    pub fn twap_since(
+        &self,
+        cumulative_then: Decimal,
+        timestamp_then: u64,
+    ) -> Result<Decimal, ProgramError> {
+        let elapsed = self
+            .block_timestamp_last
+            .checked_sub(timestamp_then)
+            .ok_or(SwapError::CalculationFailure)?;
+        self.base_price_cumulative_last
+            .wrapping_sub(cumulative_then)
+            .try_div(elapsed)
+    }
+
+    /// Time weighted variance of the mid price since an earlier snapshot of
+    /// both accumulators, the mean of the squared price less the squared mean
+    ///
+    /// # Arguments
+    ///
+    /// * cumulative_then - `base_price_cumulative_last` at the snapshot.
+    /// * squared_cumulative_then - `base_price_squared_cumulative_last` at
+    ///   the snapshot.
+    /// * timestamp_then - `block_timestamp_last` at the snapshot.
+    ///
+    /// # Return value
+    ///
+    /// price variance over the window
+    pub fn realized_variance_since(
+        &self,
+        cumulative_then: Decimal,
+        squared_cumulative_then: Decimal,
+        timestamp_then: u64,
+    ) -> Result<Decimal, ProgramError> {
+        let mean = self.twap_since(cumulative_then, timestamp_then)?;
+        let mean_of_squares = self
+            .base_price_squared_cumulative_last
+            .wrapping_sub(squared_cumulative_then)
+            .try_div(
+                self.block_timestamp_last
+                    .checked_sub(timestamp_then)
+                    .ok_or(SwapError::CalculationFailure)?,
+            )?;
+        let squared_mean = mean.try_mul(mean)?;
+        // rounding can leave a constant price's mean of squares just under
+        // its squared mean
+        if mean_of_squares < squared_mean {
+            return Ok(Decimal::zero());
+        }
+        mean_of_squares.try_sub(squared_mean)
+    }
+
+    /// Seconds since the pool was initialized
+    ///
+    /// # Arguments
+    ///
+    /// * timestamp - current unix timestamp.
+    pub fn pool_age(&self, timestamp: u64) -> Result<u64, ProgramError> {
+        timestamp
+            .checked_sub(self.creation_ts)
+            .ok_or_else(|| SwapError::CalculationFailure.into())
+    }
+
+    /// Time weighted average mid price since the pool was initialized
+    ///
+    /// # Arguments
+    ///
+    /// * base_price_cumulative - accumulated mid price up to `timestamp`.
+    /// * timestamp - current unix timestamp.
+    ///
+    /// # Return value
+    ///
+    /// average price over the pool's lifetime
+    pub fn twap_since_creation(
+        &self,
+        base_price_cumulative: Decimal,
+        timestamp: u64,
+    ) -> Result<Decimal, ProgramError> {
+        base_price_cumulative.try_div(self.pool_age(timestamp)?)
+    }
+
+    /// Reject an oracle price that jumped too far from the price seen at the
+    /// last pool update, if that update was within `ORACLE_MOVE_WINDOW`
+    ///
+    /// # Arguments
+    ///
+    /// * oracle_price - new oracle price.
+    /// * timestamp - current unix timestamp.
+    pub fn check_oracle_move(&self, oracle_price: Decimal, timestamp: u64) -> ProgramResult {
+        if self.max_oracle_move_bps == 0
+            || self.last_oracle_price.is_zero()
+            || timestamp.saturating_sub(self.block_timestamp_last) > ORACLE_MOVE_WINDOW
+        {
+            return Ok(());
+        }
+        let moved = if oracle_price > self.last_oracle_price {
+            oracle_price.try_sub(self.last_oracle_price)?
+        } else {
+            self.last_oracle_price.try_sub(oracle_price)?
+        };
+        if moved
+            > self
+                .last_oracle_price
+                .try_mul(Decimal::from_bps(self.max_oracle_move_bps as u64))?
+        {
+            return Err(SwapError::OraclePriceMoveTooLarge.into());
+        }
+        Ok(())
+    }
+
+    /// Collect the keys a client needs to build a transaction against this swap
+    pub fn accounts_bundle(
+        &self,
+        program_id: &Pubkey,
+        swap_pubkey: &Pubkey,
+    ) -> Result<SwapAccountsBundle, ProgramError> {
+        Ok(SwapAccountsBundle {
+            token_a: self.token_a,
+            token_b: self.token_b,
+            pool_mint: self.pool_mint,
+            authority: authority_id(program_id, swap_pubkey, self.nonce)?,
+        })
+    }
+}
+
+/// Token accounts and derived authority of a swap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SwapAccountsBundle {
+    /// Token A
+    pub token_a: Pubkey,
+    /// Token B
+    pub token_b: Pubkey,
+    /// Pool token mint
+    pub pool_mint: Pubkey,
+    /// Swap authority derived from the swap key and nonce
+    pub authority: Pubkey,
+}
+
+impl Sealed for SwapAccountsBundle {}
+const SWAP_ACCOUNTS_BUNDLE_SIZE: usize = 128;
+impl Pack for SwapAccountsBundle {
+    const LEN: usize = SWAP_ACCOUNTS_BUNDLE_SIZE;
+
+    fn unpack_from_slice(input: &[u8]) -> Result<Self, ProgramError> {
+        let input = array_ref![input, 0, SWAP_ACCOUNTS_BUNDLE_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (token_a, token_b, pool_mint, authority) = array_refs![
+            input,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES
+        ];
+        Ok(Self {
+            token_a: Pubkey::new_from_array(*token_a),
+            token_b: Pubkey::new_from_array(*token_b),
+            pool_mint: Pubkey::new_from_array(*pool_mint),
+            authority: Pubkey::new_from_array(*authority),
+        })
+    }
+
+    fn pack_into_slice(&self, output: &mut [u8]) {
+        let output = array_mut_ref![output, 0, SWAP_ACCOUNTS_BUNDLE_SIZE];
+        #[allow(clippy::ptr_offset_with_cast)]
+        let (token_a, token_b, pool_mint, authority) = mut_array_refs![
+            output,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES,
+            PUBKEY_BYTES
+        ];
+        token_a.copy_from_slice(self.token_a.as_ref());
+        token_b.copy_from_slice(self.token_b.as_ref());
+        pool_mint.copy_from_slice(self.pool_mint.as_ref());
+        authority.copy_from_slice(self.authority.as_ref());
     }
 }
 
@@ -206,7 +647,7 @@ mod tests {
     #[test]
     fn test_swap_info_packing() {
         let is_initialized = true;
-        let is_paused = false;
+        let pause_flags = PauseFlags::DEPOSITS;
         let nonce = 255;
         let token_a_raw = [3u8; 32];
         let token_b_raw = [4u8; 32];
@@ -241,10 +682,30 @@ mod tests {
             .unwrap();
         let cumulative_ticks = 0;
         let base_price_cumulative_last = Decimal::zero();
+        let creator_key_raw = [10u8; 32];
+        let creator_key = Pubkey::new_from_array(creator_key_raw);
+        let creator_fee_bps = 2_000u16;
+        let invert_a = false;
+        let invert_b = true;
+        let config_key_raw = [11u8; 32];
+        let config_key = Pubkey::new_from_array(config_key_raw);
+        let min_reward_liquidity = 1_000_000u64;
+        let last_oracle_price = default_market_price();
+        let max_oracle_move_bps = 1_000u16;
+        let price_tick = Decimal::one().try_div(100).unwrap();
+        let max_trade_amount = 5_000_000_000u64;
+        let creation_ts = 1_650_000_000u64;
+        let swap_cooldown_seconds = 30u64;
+        let max_allowed_slippage_bps = 500u64;
+        let oracle_free = true;
+        let base_price_squared_cumulative_last = Decimal::from(2_000u64);
+        let reward_budget_remaining = 7_500_000u64;
+        let pool_mint_decimals = 6u8;
+        let cumulative_volume = 90_000_000_000u64;
 
         let swap_info = SwapInfo {
             is_initialized,
-            is_paused,
+            pause_flags,
             nonce,
             token_a,
             token_b,
@@ -260,6 +721,24 @@ mod tests {
             block_timestamp_last,
             cumulative_ticks,
             base_price_cumulative_last,
+            creator_key,
+            creator_fee_bps,
+            invert_a,
+            invert_b,
+            config_key,
+            min_reward_liquidity,
+            last_oracle_price,
+            max_oracle_move_bps,
+            price_tick,
+            max_trade_amount,
+            creation_ts,
+            swap_cooldown_seconds,
+            max_allowed_slippage_bps,
+            oracle_free,
+            base_price_squared_cumulative_last,
+            reward_budget_remaining,
+            pool_mint_decimals,
+            cumulative_volume,
         };
 
         let mut packed = [0u8; SwapInfo::LEN];
@@ -267,7 +746,7 @@ mod tests {
         let unpacked = SwapInfo::unpack(&packed).unwrap();
         assert_eq!(swap_info, unpacked);
 
-        let mut packed: Vec<u8> = vec![1, 0, nonce];
+        let mut packed: Vec<u8> = vec![1, pause_flags.bits(), nonce];
         packed.extend_from_slice(&token_a_raw);
         packed.extend_from_slice(&token_b_raw);
         packed.extend_from_slice(&pool_mint_raw);
@@ -294,6 +773,33 @@ mod tests {
             &mut packed_base_price_cumulative_last,
         );
         packed.extend_from_slice(&packed_base_price_cumulative_last);
+        packed.extend_from_slice(&creator_key_raw);
+        packed.extend_from_slice(&creator_fee_bps.to_le_bytes());
+        packed.extend_from_slice(&(invert_a as u8).to_le_bytes());
+        packed.extend_from_slice(&(invert_b as u8).to_le_bytes());
+        packed.extend_from_slice(&config_key_raw);
+        packed.extend_from_slice(&min_reward_liquidity.to_le_bytes());
+        let mut packed_last_oracle_price = [0u8; 16];
+        pack_decimal(last_oracle_price, &mut packed_last_oracle_price);
+        packed.extend_from_slice(&packed_last_oracle_price);
+        packed.extend_from_slice(&max_oracle_move_bps.to_le_bytes());
+        let mut packed_price_tick = [0u8; 16];
+        pack_decimal(price_tick, &mut packed_price_tick);
+        packed.extend_from_slice(&packed_price_tick);
+        packed.extend_from_slice(&max_trade_amount.to_le_bytes());
+        packed.extend_from_slice(&creation_ts.to_le_bytes());
+        packed.extend_from_slice(&swap_cooldown_seconds.to_le_bytes());
+        packed.extend_from_slice(&max_allowed_slippage_bps.to_le_bytes());
+        packed.extend_from_slice(&(oracle_free as u8).to_le_bytes());
+        let mut packed_base_price_squared_cumulative_last = [0u8; 16];
+        pack_decimal(
+            base_price_squared_cumulative_last,
+            &mut packed_base_price_squared_cumulative_last,
+        );
+        packed.extend_from_slice(&packed_base_price_squared_cumulative_last);
+        packed.extend_from_slice(&reward_budget_remaining.to_le_bytes());
+        packed.push(pool_mint_decimals);
+        packed.extend_from_slice(&cumulative_volume.to_le_bytes());
 
         let unpacked = SwapInfo::unpack(&packed).unwrap();
         assert_eq!(swap_info, unpacked);
@@ -305,4 +811,191 @@ mod tests {
         let err = SwapInfo::unpack(&packed).unwrap_err();
         assert_eq!(err, ProgramError::UninitializedAccount);
     }
+
+    #[test]
+    fn test_accounts_bundle() {
+        let program_id = crate::id();
+        let swap_pubkey = Pubkey::new_unique();
+        let (authority, nonce) = Pubkey::find_program_address(&[swap_pubkey.as_ref()], &program_id);
+        let swap_info = SwapInfo {
+            is_initialized: true,
+            nonce,
+            token_a: Pubkey::new_unique(),
+            token_b: Pubkey::new_unique(),
+            pool_mint: Pubkey::new_unique(),
+            ..SwapInfo::default()
+        };
+
+        let bundle = swap_info
+            .accounts_bundle(&program_id, &swap_pubkey)
+            .unwrap();
+        assert_eq!(bundle.token_a, swap_info.token_a);
+        assert_eq!(bundle.token_b, swap_info.token_b);
+        assert_eq!(bundle.pool_mint, swap_info.pool_mint);
+        assert_eq!(bundle.authority, authority);
+        assert_eq!(
+            bundle.authority,
+            authority_id(&program_id, &swap_pubkey, nonce).unwrap()
+        );
+
+        let mut packed = [0u8; SwapAccountsBundle::LEN];
+        bundle.pack_into_slice(&mut packed);
+        assert_eq!(
+            SwapAccountsBundle::unpack_from_slice(&packed).unwrap(),
+            bundle
+        );
+    }
+
+    #[test]
+    fn test_pause_flags() {
+        let mut flags = PauseFlags::default();
+        assert_eq!(flags, PauseFlags::NONE);
+        assert!(!flags.contains(PauseFlags::SWAPS));
+
+        flags.insert(PauseFlags::DEPOSITS);
+        assert!(flags.contains(PauseFlags::DEPOSITS));
+        assert!(!flags.contains(PauseFlags::SWAPS));
+        assert!(!flags.contains(PauseFlags::WITHDRAWALS));
+
+        flags.insert(PauseFlags::WITHDRAWALS);
+        assert!(flags.contains(PauseFlags::DEPOSITS));
+        assert!(flags.contains(PauseFlags::WITHDRAWALS));
+        assert!(!flags.contains(PauseFlags::ALL));
+
+        flags.insert(PauseFlags::SWAPS);
+        assert_eq!(flags, PauseFlags::ALL);
+
+        flags.remove(PauseFlags::DEPOSITS);
+        assert!(!flags.contains(PauseFlags::DEPOSITS));
+        assert!(flags.contains(PauseFlags::SWAPS));
+
+        assert_eq!(PauseFlags::from_bits(0b111), Some(PauseFlags::ALL));
+        assert_eq!(PauseFlags::from_bits(0b1000), None);
+
+        let mut packed = [0u8; SwapInfo::LEN];
+        SwapInfo {
+            is_initialized: true,
+            ..SwapInfo::default()
+        }
+        .pack_into_slice(&mut packed);
+        packed[1] = 0b1000;
+        assert_eq!(
+            SwapInfo::unpack(&packed).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn test_creator_fee() {
+        let mut swap_info = SwapInfo::default();
+        assert_eq!(swap_info.creator_fee(1_000).unwrap(), 0);
+
+        swap_info.creator_fee_bps = 2_000;
+        assert_eq!(swap_info.creator_fee(1_000).unwrap(), 200);
+        assert_eq!(swap_info.creator_fee(1).unwrap(), 0);
+
+        swap_info.creator_fee_bps = BPS_DENOMINATOR as u16;
+        assert_eq!(swap_info.creator_fee(1_000).unwrap(), 1_000);
+        assert_eq!(
+            swap_info.creator_fee(u64::MAX).unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
+
+    #[test]
+    fn test_check_oracle_move() {
+        let mut swap_info = SwapInfo {
+            block_timestamp_last: 1_000,
+            last_oracle_price: Decimal::from(20u64),
+            max_oracle_move_bps: 1_000,
+            ..SwapInfo::default()
+        };
+
+        // 10% either way is at the cap
+        swap_info
+            .check_oracle_move(Decimal::from(22u64), 1_000)
+            .unwrap();
+        swap_info
+            .check_oracle_move(Decimal::from(18u64), 1_000)
+            .unwrap();
+        assert_eq!(
+            swap_info.check_oracle_move(Decimal::from(26u64), 1_000),
+            Err(SwapError::OraclePriceMoveTooLarge.into())
+        );
+        assert_eq!(
+            swap_info.check_oracle_move(Decimal::from(14u64), 1_000 + ORACLE_MOVE_WINDOW),
+            Err(SwapError::OraclePriceMoveTooLarge.into())
+        );
+
+        // outside the window any move is accepted
+        swap_info
+            .check_oracle_move(Decimal::from(26u64), 1_001 + ORACLE_MOVE_WINDOW)
+            .unwrap();
+
+        // no reference price yet, or check disabled
+        swap_info.last_oracle_price = Decimal::zero();
+        swap_info
+            .check_oracle_move(Decimal::from(26u64), 1_000)
+            .unwrap();
+        swap_info.last_oracle_price = Decimal::from(20u64);
+        swap_info.max_oracle_move_bps = 0;
+        swap_info
+            .check_oracle_move(Decimal::from(26u64), 1_000)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_twap_across_wrap() {
+        let mut swap_info = SwapInfo {
+            block_timestamp_last: 1_000,
+            // ten seconds at price 2 short of the wrap
+            base_price_cumulative_last: Decimal::from_scaled_val(
+                u128::MAX - Decimal::from(20u64).to_scaled_val().unwrap() + 1,
+            ),
+            ..SwapInfo::default()
+        };
+        let cumulative_then = swap_info.base_price_cumulative_last;
+        let timestamp_then = swap_info.block_timestamp_last;
+
+        // 30 seconds at 2 then 10 seconds at 6 wrap the accumulator
+        for (price, elapsed) in [(2u64, 30u64), (6, 10)] {
+            swap_info.base_price_cumulative_last = swap_info
+                .base_price_cumulative_last
+                .wrapping_add(Decimal::from(price).try_mul(elapsed).unwrap());
+            swap_info.block_timestamp_last += elapsed;
+        }
+        assert!(swap_info.base_price_cumulative_last < cumulative_then);
+
+        // (2 * 30 + 6 * 10) / 40
+        assert_eq!(
+            swap_info
+                .twap_since(cumulative_then, timestamp_then)
+                .unwrap(),
+            Decimal::from(3u64)
+        );
+        assert_eq!(
+            swap_info.twap_since(cumulative_then, 2_000).unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+    }
+
+    #[test]
+    fn test_twap_since_creation() {
+        let swap_info = SwapInfo {
+            creation_ts: 1_000,
+            ..SwapInfo::default()
+        };
+        assert_eq!(swap_info.pool_age(1_500).unwrap(), 500);
+        assert_eq!(
+            swap_info.pool_age(999).unwrap_err(),
+            SwapError::CalculationFailure.into()
+        );
+
+        // 500 seconds at price 3 since creation
+        let cumulative = Decimal::from(3u64).try_mul(500).unwrap();
+        assert_eq!(
+            swap_info.twap_since_creation(cumulative, 1_500).unwrap(),
+            Decimal::from(3u64)
+        );
+    }
 }
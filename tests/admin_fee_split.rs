@@ -0,0 +1,272 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use assert_matches::*;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_admin_split, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const SWAP_AMOUNT: u64 = 2_000_000_000;
+const WITHDRAW_AMOUNT: u64 = 2_000_000_000;
+
+struct SplitTestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    liquidity_provider: TestLiquidityProvider,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn try_set_admin_split(
+    banks_client: &mut BanksClient,
+    swap_config: &TestSwapConfig,
+    admin_split_bps: u16,
+    payer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_admin_split(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.admin.pubkey(),
+            admin_split_bps,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[payer, &swap_config.admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+/// Set up a pool, a liquidity position and a trader, with `admin_split_bps`
+/// of admin fees going to the primary admin fee accounts
+async fn setup(admin_split_bps: u16) -> SplitTestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, WITHDRAW_AMOUNT);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_matches!(
+        try_set_admin_split(&mut banks_client, &swap_config, admin_split_bps, &payer).await,
+        Ok(())
+    );
+    assert_eq!(
+        swap_config
+            .get_state(&mut banks_client)
+            .await
+            .admin_split_bps,
+        admin_split_bps
+    );
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    SplitTestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn try_swap(ctx: &mut SplitTestContext) -> Result<(), TransactionError> {
+    ctx.swap_info
+        .try_swap(
+            &mut ctx.banks_client,
+            &ctx.swap_config,
+            &ctx.user_account_owner,
+            ctx.sol_user_account,
+            ctx.srm_user_account,
+            ctx.deltafi_user_account,
+            SWAP_AMOUNT,
+            0,
+            SwapDirection::SellBase,
+            &ctx.payer,
+        )
+        .await
+}
+
+/// Balances of the primary and secondary admin fee accounts for token b
+async fn admin_fees_b(ctx: &mut SplitTestContext) -> (u64, u64) {
+    (
+        get_token_balance(&mut ctx.banks_client, ctx.swap_info.admin_fee_b_key).await,
+        get_token_balance(
+            &mut ctx.banks_client,
+            ctx.swap_info.secondary_admin_fee_b_key,
+        )
+        .await,
+    )
+}
+
+#[tokio::test]
+async fn test_swap_admin_fee_split() {
+    let mut ctx = setup(10_000).await;
+    assert_matches!(try_swap(&mut ctx).await, Ok(()));
+    let (admin_fee, secondary) = admin_fees_b(&mut ctx).await;
+    assert!(admin_fee > 0);
+    assert_eq!(secondary, 0);
+
+    let mut ctx = setup(3_000).await;
+    assert_matches!(try_swap(&mut ctx).await, Ok(()));
+    let (primary, secondary) = admin_fees_b(&mut ctx).await;
+    assert_eq!(primary + secondary, admin_fee);
+    assert_eq!(primary, admin_fee * 3_000 / 10_000);
+}
+
+#[tokio::test]
+async fn test_withdraw_admin_fee_split() {
+    let mut ctx = setup(3_000).await;
+
+    let sol_withdraw_account = create_and_mint_to_token_account(
+        &mut ctx.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &ctx.payer,
+        ctx.user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    ctx.swap_info
+        .withdraw(
+            &mut ctx.banks_client,
+            &ctx.liquidity_provider,
+            &ctx.user_account_owner,
+            sol_withdraw_account,
+            ctx.srm_user_account,
+            ctx.swap_info.pool_token,
+            WITHDRAW_AMOUNT,
+            0,
+            0,
+            &ctx.payer,
+        )
+        .await;
+
+    for (primary_key, secondary_key, withdrawn) in [
+        (
+            ctx.swap_info.admin_fee_a_key,
+            ctx.swap_info.secondary_admin_fee_a_key,
+            sol_withdraw_account,
+        ),
+        (
+            ctx.swap_info.admin_fee_b_key,
+            ctx.swap_info.secondary_admin_fee_b_key,
+            ctx.srm_user_account,
+        ),
+    ] {
+        let primary = get_token_balance(&mut ctx.banks_client, primary_key).await;
+        let secondary = get_token_balance(&mut ctx.banks_client, secondary_key).await;
+        let withdrawn = get_token_balance(&mut ctx.banks_client, withdrawn).await;
+
+        // admin takes 2/5 of the 2% withdraw fee charged on the gross amount
+        let withdraw_fee = (withdrawn * 100 / 98) * 2 / 100;
+        let admin_fee = primary + secondary;
+        assert!(admin_fee.abs_diff(withdraw_fee * 2 / 5) <= 2);
+        assert_eq!(primary, admin_fee * 3_000 / 10_000);
+    }
+}
+
+#[tokio::test]
+async fn test_secondary_not_owned_by_admin() {
+    let mut ctx = setup(3_000).await;
+
+    ctx.swap_info.secondary_admin_fee_b_key = ctx.srm_user_account;
+
+    assert_eq!(
+        try_swap(&mut ctx).await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidAdmin as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_split() {
+    let mut ctx = setup(10_000).await;
+
+    assert_eq!(
+        try_set_admin_split(&mut ctx.banks_client, &ctx.swap_config, 10_001, &ctx.payer).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidInput as u32)
+        ))
+    );
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::batch_set_fees,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::Fees,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const NEW_FEES: Fees = Fees {
+    trade_fee_numerator: 1,
+    withdraw_fee_numerator: 1,
+    ..TEST_FEES
+};
+
+fn add_pool(test: &mut ProgramTest, swap_config: &TestSwapConfig) -> TestSwapInfo {
+    let sol_oracle = add_sol_oracle(test);
+    let srm_oracle = add_srm_oracle(test);
+    let srm_mint = add_srm_mint(test);
+
+    add_swap_info(
+        test,
+        swap_config,
+        &Keypair::new(),
+        &Keypair::new(),
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    )
+}
+
+async fn try_batch_set_fees(
+    banks_client: &mut BanksClient,
+    swap_config: &TestSwapConfig,
+    pools: &[&TestSwapInfo],
+    payer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[batch_set_fees(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.admin.pubkey(),
+            pools.iter().map(|pool| pool.pubkey).collect(),
+            NEW_FEES,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[payer, &swap_config.admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_batch_set_fees() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let swap_config = add_swap_config(&mut test);
+    let pools = [
+        add_pool(&mut test, &swap_config),
+        add_pool(&mut test, &swap_config),
+        add_pool(&mut test, &swap_config),
+    ];
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        try_batch_set_fees(
+            &mut banks_client,
+            &swap_config,
+            &pools.iter().collect::<Vec<_>>(),
+            &payer,
+        )
+        .await,
+        Ok(())
+    );
+    for pool in pools.iter() {
+        assert_eq!(pool.get_state(&mut banks_client).await.fees, NEW_FEES);
+    }
+}
+
+#[tokio::test]
+async fn test_batch_set_fees_reverts_on_invalid_pool() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let swap_config = add_swap_config(&mut test);
+    let other_config = add_swap_config(&mut test);
+    let first = add_pool(&mut test, &swap_config);
+    let foreign = add_pool(&mut test, &other_config);
+    let last = add_pool(&mut test, &swap_config);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        try_batch_set_fees(
+            &mut banks_client,
+            &swap_config,
+            &[&first, &foreign, &last],
+            &payer,
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+
+    // the pool updated before the foreign one is reverted with it
+    for pool in [&first, &foreign, &last] {
+        assert_eq!(pool.get_state(&mut banks_client).await.fees, TEST_FEES);
+    }
+}
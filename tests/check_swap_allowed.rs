@@ -0,0 +1,278 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::{
+        check_swap_allowed, set_max_allowed_slippage, set_max_trade_amount, SwapDirection,
+    },
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+async fn check(
+    banks_client: &mut BanksClient,
+    swap_info: &TestSwapInfo,
+    market_price: Decimal,
+    amount_in: u64,
+    payer: &Keypair,
+) -> (bool, Decimal) {
+    // tolerate 1% off the oracle price, trade fee included
+    let minimum_amount_out = Decimal::from(amount_in)
+        .try_div(market_price)
+        .unwrap()
+        .try_mul(99u64)
+        .unwrap()
+        .try_div(100u64)
+        .unwrap()
+        .try_floor_u64()
+        .unwrap();
+    let return_data = simulate_return_data(
+        banks_client,
+        check_swap_allowed(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            amount_in,
+            minimum_amount_out,
+            SwapDirection::SellQuote,
+        )
+        .unwrap(),
+        payer,
+    )
+    .await;
+    assert_eq!(return_data.len(), 17);
+    (
+        return_data[0] == 1,
+        Decimal::from_scaled_val(u128::from_le_bytes(return_data[1..].try_into().unwrap())),
+    )
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let market_price = sol_oracle.price.try_div(srm_oracle.price).unwrap();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price,
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+    let before = swap_info.get_state(&mut banks_client).await;
+
+    let (allowed, small_deviation) = check(
+        &mut banks_client,
+        &swap_info,
+        market_price,
+        1_000_000_000,
+        &payer,
+    )
+    .await;
+    assert!(allowed);
+    assert!(small_deviation < Decimal::one().try_div(100u64).unwrap());
+
+    let (allowed, large_deviation) = check(
+        &mut banks_client,
+        &swap_info,
+        market_price,
+        700_000_000_000,
+        &payer,
+    )
+    .await;
+    assert!(!allowed);
+    assert!(large_deviation > Decimal::one().try_div(100u64).unwrap());
+    assert!(large_deviation > small_deviation);
+
+    // nothing is written back
+    assert_eq!(swap_info.get_state(&mut banks_client).await, before);
+}
+
+#[tokio::test]
+async fn test_trade_too_large() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let market_price = sol_oracle.price.try_div(srm_oracle.price).unwrap();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price,
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_max_trade_amount(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            500_000_000,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &swap_config.admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // swap rejects this amount with TradeTooLarge
+    let (allowed, _) = check(
+        &mut context.banks_client,
+        &swap_info,
+        market_price,
+        1_000_000_000,
+        &context.payer,
+    )
+    .await;
+    assert!(!allowed);
+
+    let (allowed, _) = check(
+        &mut context.banks_client,
+        &swap_info,
+        market_price,
+        500_000_000,
+        &context.payer,
+    )
+    .await;
+    assert!(allowed);
+}
+
+#[tokio::test]
+async fn test_slippage_cap() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let market_price = sol_oracle.price.try_div(srm_oracle.price).unwrap();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price,
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_max_allowed_slippage(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            500,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &swap_config.admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // a 1% tolerance is within the 5% cap
+    let (allowed, _) = check(
+        &mut context.banks_client,
+        &swap_info,
+        market_price,
+        1_000_000_000,
+        &context.payer,
+    )
+    .await;
+    assert!(allowed);
+
+    // swap rejects a zero minimum with SlippageCapExceeded
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        check_swap_allowed(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            1_000_000_000,
+            0,
+            SwapDirection::SellQuote,
+        )
+        .unwrap(),
+        &context.payer,
+    )
+    .await;
+    assert_eq!(return_data[0], 0);
+}
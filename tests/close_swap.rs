@@ -0,0 +1,260 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{close_swap, execute_drain, propose_drain, set_pause_flags},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{PauseFlags, DRAIN_TIMELOCK},
+};
+use solana_program::{
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const START_TS: i64 = 1_000_000;
+
+async fn setup() -> (ProgramTestContext, TestSwapConfig, TestSwapInfo, Keypair) {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+    set_clock(&mut context, START_TS).await;
+
+    (context, swap_config, swap_info, user_account_owner)
+}
+
+async fn set_clock(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+async fn try_process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+/// Move all reserves to a treasury through the emergency drain
+async fn drain(
+    context: &mut ProgramTestContext,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+) {
+    let treasury_a = create_token_account(
+        &mut context.banks_client,
+        swap_info.token_a_mint,
+        &context.payer,
+        None,
+        None,
+    )
+    .await;
+    let treasury_b = create_token_account(
+        &mut context.banks_client,
+        swap_info.token_b_mint,
+        &context.payer,
+        None,
+        None,
+    )
+    .await;
+
+    let admin = &swap_config.admin;
+    try_process(
+        context,
+        propose_drain(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            admin.pubkey(),
+            treasury_a,
+            treasury_b,
+        )
+        .unwrap(),
+        admin,
+    )
+    .await
+    .unwrap();
+    set_clock(context, START_TS + DRAIN_TIMELOCK).await;
+    try_process(
+        context,
+        execute_drain(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_info.authority,
+            admin.pubkey(),
+            swap_info.token_a,
+            swap_info.token_b,
+            treasury_a,
+            treasury_b,
+        )
+        .unwrap(),
+        admin,
+    )
+    .await
+    .unwrap();
+}
+
+/// Burn every pool token, leaving the pool mint with zero supply
+async fn burn_pool_tokens(
+    context: &mut ProgramTestContext,
+    swap_info: &TestSwapInfo,
+    user_account_owner: &Keypair,
+) {
+    let amount = get_token_balance(&mut context.banks_client, swap_info.pool_token).await;
+    try_process(
+        context,
+        spl_token::instruction::burn(
+            &spl_token::id(),
+            &swap_info.pool_token,
+            &swap_info.pool_mint,
+            &user_account_owner.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap(),
+        user_account_owner,
+    )
+    .await
+    .unwrap();
+}
+
+async fn try_close_swap(
+    context: &mut ProgramTestContext,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    destination: Pubkey,
+) -> Result<(), TransactionError> {
+    try_process(
+        context,
+        close_swap(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_info.authority,
+            swap_config.admin.pubkey(),
+            swap_info.pool_mint,
+            swap_info.token_a,
+            swap_info.token_b,
+            destination,
+        )
+        .unwrap(),
+        &swap_config.admin,
+    )
+    .await
+}
+
+fn swap_error(error: SwapError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_close_drained_pool() {
+    let (mut context, swap_config, swap_info, user_account_owner) = setup().await;
+
+    drain(&mut context, &swap_config, &swap_info).await;
+    burn_pool_tokens(&mut context, &swap_info, &user_account_owner).await;
+
+    let banks_client = &mut context.banks_client;
+    let reclaimed = banks_client.get_balance(swap_info.pubkey).await.unwrap()
+        + banks_client.get_balance(swap_info.token_a).await.unwrap()
+        + banks_client.get_balance(swap_info.token_b).await.unwrap();
+
+    let destination = Pubkey::new_unique();
+    try_close_swap(&mut context, &swap_config, &swap_info, destination)
+        .await
+        .unwrap();
+
+    let banks_client = &mut context.banks_client;
+    assert_eq!(
+        banks_client.get_balance(destination).await.unwrap(),
+        reclaimed
+    );
+    for closed in [swap_info.pubkey, swap_info.token_a, swap_info.token_b] {
+        assert_eq!(banks_client.get_account(closed).await.unwrap(), None);
+    }
+}
+
+#[tokio::test]
+async fn test_close_with_supply() {
+    let (mut context, swap_config, swap_info, _) = setup().await;
+
+    drain(&mut context, &swap_config, &swap_info).await;
+
+    assert_eq!(
+        try_close_swap(&mut context, &swap_config, &swap_info, Pubkey::new_unique()).await,
+        Err(swap_error(SwapError::InvalidSupply))
+    );
+}
+
+#[tokio::test]
+async fn test_close_with_reserves() {
+    let (mut context, swap_config, swap_info, user_account_owner) = setup().await;
+
+    burn_pool_tokens(&mut context, &swap_info, &user_account_owner).await;
+    try_process(
+        &mut context,
+        set_pause_flags(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            PauseFlags::ALL,
+        )
+        .unwrap(),
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        try_close_swap(&mut context, &swap_config, &swap_info, Pubkey::new_unique()).await,
+        Err(swap_error(SwapError::PoolNotClosed))
+    );
+}
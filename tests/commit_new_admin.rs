@@ -93,3 +93,41 @@ async fn test_invalid_owner() {
         )
     );
 }
+
+#[tokio::test]
+async fn test_market_authority_as_admin() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[commit_new_admin(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.admin.pubkey(),
+            swap_config.deltafi_mint,
+            swap_config.market_authority,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOutputOwner as u32)
+        )
+    );
+    assert_eq!(
+        swap_config.get_state(&mut banks_client).await.admin_key,
+        swap_config.admin.pubkey()
+    );
+}
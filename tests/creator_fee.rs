@@ -0,0 +1,198 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::SwapDirection,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::SwapInfo,
+};
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    program_pack::Pack,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use utils::*;
+
+struct CreatorFeeTestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+/// Set up a pool and a trader, with the creator taking `creator_fee_bps` of trade fees
+async fn setup(creator_fee_bps: u16) -> CreatorFeeTestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let creator_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let mut swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &creator_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let mut swap_account: Account = context
+        .banks_client
+        .get_account(swap_info.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = SwapInfo::unpack(&swap_account.data).unwrap();
+    state.creator_fee_bps = creator_fee_bps;
+    SwapInfo::pack(state, &mut swap_account.data).unwrap();
+    context.set_account(&swap_info.pubkey, &AccountSharedData::from(swap_account));
+    swap_info.creator_fee_bps = creator_fee_bps;
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    CreatorFeeTestContext {
+        context,
+        swap_config,
+        swap_info,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+/// Swap 2 SOL into the pool and return (amount out, admin fee, creator fee)
+async fn swap_and_collect(ctx: &mut CreatorFeeTestContext) -> (u64, u64, u64) {
+    let banks_client = &mut ctx.context.banks_client;
+    let token_b_before = get_token_balance(banks_client, ctx.swap_info.token_b).await;
+
+    ctx.swap_info
+        .swap(
+            banks_client,
+            &ctx.swap_config,
+            &ctx.user_account_owner,
+            ctx.sol_user_account,
+            ctx.srm_user_account,
+            ctx.deltafi_user_account,
+            2_000_000_000,
+            35_000_000_000,
+            SwapDirection::SellBase,
+            &ctx.context.payer,
+        )
+        .await;
+
+    let token_b_after = get_token_balance(banks_client, ctx.swap_info.token_b).await;
+    let amount_out = get_token_balance(banks_client, ctx.srm_user_account).await;
+    let admin_fee = get_token_balance(banks_client, ctx.swap_info.admin_fee_b_key).await;
+    let creator_fee = get_token_balance(banks_client, ctx.swap_info.creator_fee_b_key).await;
+
+    // everything leaving the reserve is accounted for by the trader, admin and creator
+    assert_eq!(
+        token_b_before - token_b_after,
+        amount_out + admin_fee + creator_fee
+    );
+
+    (amount_out, admin_fee, creator_fee)
+}
+
+#[tokio::test]
+async fn test_creator_receives_share() {
+    let (base_amount_out, base_admin_fee, base_creator_fee) =
+        swap_and_collect(&mut setup(0).await).await;
+    assert_eq!(base_creator_fee, 0);
+
+    let (amount_out, admin_fee, creator_fee) = swap_and_collect(&mut setup(2_000).await).await;
+
+    // trader pays the same total fee
+    assert_eq!(amount_out, base_amount_out);
+
+    // admin takes 2/5 of the trade fee, the creator 1/5 of it off the top
+    assert!(creator_fee > 0);
+    assert!((creator_fee * 2).abs_diff(base_admin_fee) <= 2);
+    // admin share is applied to the remaining 4/5
+    assert!(admin_fee < base_admin_fee);
+    assert!((admin_fee * 5).abs_diff(base_admin_fee * 4) <= 5);
+}
+
+#[tokio::test]
+async fn test_wrong_creator_account() {
+    let mut ctx = setup(2_000).await;
+
+    ctx.swap_info.creator_fee_b_key = ctx.srm_user_account;
+
+    assert_eq!(
+        ctx.swap_info
+            .try_swap(
+                &mut ctx.context.banks_client,
+                &ctx.swap_config,
+                &ctx.user_account_owner,
+                ctx.sol_user_account,
+                ctx.srm_user_account,
+                ctx.deltafi_user_account,
+                2_000_000_000,
+                35_000_000_000,
+                SwapDirection::SellBase,
+                &ctx.context.payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        ))
+    );
+}
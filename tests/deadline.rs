@@ -0,0 +1,185 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::SwapDirection,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{clock::Clock, instruction::InstructionError};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use utils::*;
+
+fn deadline_exceeded(index: u8) -> Result<(), TransactionError> {
+    Err(TransactionError::InstructionError(
+        index,
+        InstructionError::Custom(SwapError::DeadlineExceeded as u32),
+    ))
+}
+
+#[tokio::test]
+async fn test_deadline() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let now = banks_client
+        .get_sysvar::<Clock>()
+        .await
+        .unwrap()
+        .unix_timestamp;
+    let past = Some(now - 1);
+    let future = Some(now + 3_600);
+
+    let sol_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        20_000_000_000,
+    )
+    .await;
+    let srm_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    // instructions without a deadline, as built before it existed, never expire
+    for (deadline, expected) in [
+        (past, deadline_exceeded(1)),
+        (future, Ok(())),
+        (None, Ok(())),
+    ] {
+        assert_eq!(
+            swap_info
+                .try_swap_with_deadline(
+                    &mut banks_client,
+                    &swap_config,
+                    &liquidity_owner,
+                    sol_account,
+                    srm_account,
+                    deltafi_account,
+                    1_000_000_000,
+                    0,
+                    SwapDirection::SellBase,
+                    deadline,
+                    &payer,
+                )
+                .await,
+            expected
+        );
+    }
+    assert_eq!(
+        get_token_balance(&mut banks_client, sol_account).await,
+        18_000_000_000
+    );
+
+    for (deadline, expected) in [(past, deadline_exceeded(2)), (future, Ok(()))] {
+        assert_eq!(
+            swap_info
+                .try_deposit_with_deadline(
+                    &mut banks_client,
+                    &swap_config,
+                    &liquidity_provider,
+                    &liquidity_owner,
+                    sol_account,
+                    srm_account,
+                    pool_token_account,
+                    8_000_000_000,
+                    160_000_000_000,
+                    0,
+                    deadline,
+                    &payer,
+                )
+                .await,
+            expected
+        );
+    }
+    let pool_tokens = get_token_balance(&mut banks_client, pool_token_account).await;
+    assert!(pool_tokens > 0);
+
+    for (deadline, expected) in [(past, deadline_exceeded(1)), (future, Ok(()))] {
+        assert_eq!(
+            swap_info
+                .try_withdraw_with_deadline(
+                    &mut banks_client,
+                    &liquidity_provider,
+                    &liquidity_owner,
+                    sol_account,
+                    srm_account,
+                    pool_token_account,
+                    pool_tokens / 2,
+                    0,
+                    0,
+                    deadline,
+                    &payer,
+                )
+                .await,
+            expected
+        );
+    }
+    assert_eq!(
+        get_token_balance(&mut banks_client, pool_token_account).await,
+        pool_tokens - pool_tokens / 2
+    );
+}
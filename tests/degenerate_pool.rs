@@ -0,0 +1,232 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{sync_reserves, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::SwapInfo,
+};
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    program_pack::Pack,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+async fn try_sync_reserves(
+    context: &mut ProgramTestContext,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[sync_reserves(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            swap_info.token_a,
+            swap_info.token_b,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &swap_config.admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn empty_token_account(context: &mut ProgramTestContext, pubkey: Pubkey) {
+    let mut account: Account = context
+        .banks_client
+        .get_account(pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut token_account = spl_token::state::Account::unpack(&account.data).unwrap();
+    token_account.amount = 0;
+    spl_token::state::Account::pack(token_account, &mut account.data).unwrap();
+    context.set_account(&pubkey, &AccountSharedData::from(account));
+}
+
+#[tokio::test]
+async fn test_single_sided_reserve() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+
+    let mut context = test.start_with_context().await;
+
+    // withdraws rounded the quote reserve away while pool tokens remain
+    let mut swap_account: Account = context
+        .banks_client
+        .get_account(swap_info.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = SwapInfo::unpack(&swap_account.data).unwrap();
+    state.pool_state.quote_reserve = Decimal::zero();
+    SwapInfo::pack(state, &mut swap_account.data).unwrap();
+    context.set_account(&swap_info.pubkey, &AccountSharedData::from(swap_account));
+    empty_token_account(&mut context, swap_info.token_b).await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        liquidity_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_info.pool_mint,
+        None,
+        &context.payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let degenerate = |index| {
+        Err(TransactionError::InstructionError(
+            index,
+            InstructionError::Custom(SwapError::DegeneratePool as u32),
+        ))
+    };
+    assert_eq!(
+        swap_info
+            .try_deposit(
+                &mut context.banks_client,
+                &swap_config,
+                &liquidity_provider,
+                &liquidity_owner,
+                sol_user_account,
+                srm_user_account,
+                pool_token_account,
+                2_100_000_000,
+                40_000_000_000,
+                0,
+                &context.payer,
+            )
+            .await,
+        degenerate(2)
+    );
+    assert_eq!(
+        swap_info
+            .try_swap(
+                &mut context.banks_client,
+                &swap_config,
+                &liquidity_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                1_000_000_000,
+                0,
+                SwapDirection::SellBase,
+                &context.payer,
+            )
+            .await,
+        degenerate(1)
+    );
+
+    // syncing alone cannot fill the empty reserve
+    assert_eq!(
+        try_sync_reserves(&mut context, &swap_config, &swap_info).await,
+        degenerate(0)
+    );
+
+    // the admin tops up the empty token account and syncs the reserves
+    mint_to(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        &context.payer,
+        swap_info.token_b,
+        &srm_mint.authority,
+        800_000_000_000,
+    )
+    .await;
+    assert_eq!(
+        try_sync_reserves(&mut context, &swap_config, &swap_info).await,
+        Ok(())
+    );
+    let pool_state = swap_info
+        .get_state(&mut context.banks_client)
+        .await
+        .pool_state;
+    assert_eq!(pool_state.base_reserve, Decimal::from(42_000_000_000u64));
+    assert_eq!(pool_state.quote_reserve, Decimal::from(800_000_000_000u64));
+    assert_eq!(pool_state.validate_invariants(), Ok(()));
+
+    swap_info
+        .deposit(
+            &mut context.banks_client,
+            &swap_config,
+            &liquidity_provider,
+            &liquidity_owner,
+            sol_user_account,
+            srm_user_account,
+            pool_token_account,
+            2_100_000_000,
+            40_000_000_000,
+            0,
+            &context.payer,
+        )
+        .await;
+    assert!(get_token_balance(&mut context.banks_client, pool_token_account).await > 0);
+}
@@ -0,0 +1,229 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::set_new_rewards,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::Rewards,
+};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+const DEPOSIT_REWARD_CAP: u64 = 30_000_000;
+
+struct DepositContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    liquidity_provider: TestLiquidityProvider,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+}
+
+/// Deposit reward credited to the position and not yet owed
+async fn estimated_rewards(ctx: &mut DepositContext) -> u64 {
+    ctx.liquidity_provider
+        .get_state(&mut ctx.banks_client)
+        .await
+        .positions
+        .iter()
+        .find(|position| position.pool == ctx.swap_info.pubkey)
+        .map_or(0, |position| position.rewards_estimated)
+}
+
+/// Deposit into the pool, returning the LP tokens minted and the DELTAFI
+/// credited to the position
+async fn deposit(ctx: &mut DepositContext, token_a_amount: u64, token_b_amount: u64) -> (u64, u64) {
+    let lp_before = get_token_balance(&mut ctx.banks_client, ctx.swap_info.pool_token).await;
+    let reward_before = estimated_rewards(ctx).await;
+    ctx.swap_info
+        .deposit(
+            &mut ctx.banks_client,
+            &ctx.swap_config,
+            &ctx.liquidity_provider,
+            &ctx.user_account_owner,
+            ctx.sol_user_account,
+            ctx.srm_user_account,
+            ctx.swap_info.pool_token,
+            token_a_amount,
+            token_b_amount,
+            0,
+            &ctx.payer,
+        )
+        .await;
+    (
+        get_token_balance(&mut ctx.banks_client, ctx.swap_info.pool_token).await - lp_before,
+        estimated_rewards(ctx).await - reward_before,
+    )
+}
+
+/// Withdraw LP tokens deposited by the position
+async fn withdraw(ctx: &mut DepositContext, pool_token_amount: u64) {
+    ctx.swap_info
+        .withdraw(
+            &mut ctx.banks_client,
+            &ctx.liquidity_provider,
+            &ctx.user_account_owner,
+            ctx.sol_user_account,
+            ctx.srm_user_account,
+            ctx.swap_info.pool_token,
+            pool_token_amount,
+            0,
+            0,
+            &ctx.payer,
+        )
+        .await;
+}
+
+async fn setup() -> DepositContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let liquidity_provider = add_liquidity_provider(&mut test, &user_account_owner);
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_new_rewards(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            Rewards {
+                deposit_reward_numerator: 1,
+                deposit_reward_denominator: 100,
+                deposit_reward_cap: DEPOSIT_REWARD_CAP,
+                ..TEST_REWARDS
+            },
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+
+    DepositContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+    }
+}
+
+#[tokio::test]
+async fn test_deposit_reward() {
+    let mut ctx = setup().await;
+
+    let (lp_minted, reward) = deposit(&mut ctx, 1_000_000_000, 19_000_000_000).await;
+    assert!(lp_minted > 0);
+    assert_eq!(reward, lp_minted / 100);
+
+    // a larger deposit earns a proportionally larger reward
+    let (double_lp_minted, double_reward) = deposit(&mut ctx, 2_000_000_000, 38_000_000_000).await;
+    assert!(double_lp_minted > lp_minted);
+    assert!(double_reward > reward);
+    assert_eq!(double_reward, double_lp_minted / 100);
+
+    // a large deposit is capped
+    let (large_lp_minted, large_reward) = deposit(&mut ctx, 5_000_000_000, 95_000_000_000).await;
+    assert!(large_lp_minted / 100 > DEPOSIT_REWARD_CAP);
+    assert_eq!(large_reward, DEPOSIT_REWARD_CAP);
+
+    // the reward vests with the position instead of being minted
+    let mut liquidity_provider = ctx
+        .liquidity_provider
+        .get_state(&mut ctx.banks_client)
+        .await;
+    let (position, _) = liquidity_provider
+        .find_position(ctx.swap_info.pubkey)
+        .unwrap();
+    assert_eq!(position.rewards_owed, 0);
+    assert_eq!(
+        position.rewards_estimated,
+        reward + double_reward + large_reward
+    );
+    let config = ctx.swap_config.get_state(&mut ctx.banks_client).await;
+    assert_eq!(config.total_rewards_minted, 0);
+}
+
+#[tokio::test]
+async fn test_deposit_then_withdraw() {
+    let mut ctx = setup().await;
+
+    // a deposit withdrawn right away leaves nothing behind
+    let (lp_minted, reward) = deposit(&mut ctx, 1_000_000_000, 19_000_000_000).await;
+    assert!(reward > 0);
+    withdraw(&mut ctx, lp_minted).await;
+    assert_eq!(estimated_rewards(&mut ctx).await, 0);
+
+    // cycling liquidity through a standing position does not grow its reward
+    let (lp_minted, _) = deposit(&mut ctx, 1_000_000_000, 19_000_000_000).await;
+    let standing_reward = estimated_rewards(&mut ctx).await;
+    for _ in 0..3 {
+        let (cycled_lp_minted, _) = deposit(&mut ctx, 2_000_000_000, 38_000_000_000).await;
+        withdraw(&mut ctx, cycled_lp_minted).await;
+        assert!(estimated_rewards(&mut ctx).await <= standing_reward);
+    }
+    withdraw(&mut ctx, lp_minted).await;
+    assert_eq!(estimated_rewards(&mut ctx).await, 0);
+
+    let config = ctx.swap_config.get_state(&mut ctx.banks_client).await;
+    assert_eq!(config.total_rewards_minted, 0);
+}
@@ -3,16 +3,18 @@
 mod utils;
 
 use deltafi_swap::{
+    error::SwapError,
     math::{Decimal, TryDiv},
     processor::process,
     state::LiquidityProvider,
 };
 
-use solana_program::program_pack::Pack;
+use solana_program::{instruction::InstructionError, program_pack::Pack};
 use solana_program_test::*;
 use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signer},
+    transaction::TransactionError,
 };
 use utils::*;
 
@@ -88,6 +90,7 @@ async fn test_success() {
     swap_info
         .deposit(
             &mut banks_client,
+            &swap_config,
             &liquidity_provider,
             &liquidity_owner,
             sol_deposit_account,
@@ -131,3 +134,107 @@ async fn test_success() {
         get_token_balance(&mut banks_client, pool_token_account).await
     );
 }
+
+#[tokio::test]
+async fn test_one_sided_deposit() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+
+    let srm_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    for (token_a_amount, token_b_amount) in [(8_000_000_000, 0), (0, 160_000_000_000)] {
+        assert_eq!(
+            swap_info
+                .try_deposit(
+                    &mut banks_client,
+                    &swap_config,
+                    &liquidity_provider,
+                    &liquidity_owner,
+                    sol_deposit_account,
+                    srm_deposit_account,
+                    pool_token_account,
+                    token_a_amount,
+                    token_b_amount,
+                    0,
+                    &payer,
+                )
+                .await,
+            Err(TransactionError::InstructionError(
+                2,
+                InstructionError::Custom(SwapError::ImbalancedDeposit as u32)
+            ))
+        );
+    }
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, swap_info.token_a).await,
+        42_000_000_000,
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, swap_info.token_b).await,
+        800_000_000_000,
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, pool_token_account).await,
+        0
+    );
+}
@@ -0,0 +1,385 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{execute_drain, propose_drain},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{PauseFlags, DRAIN_TIMELOCK},
+};
+use solana_program::{clock::Clock, instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const START_TS: i64 = 1_000_000;
+
+struct TestTreasury {
+    token_a: Pubkey,
+    token_b: Pubkey,
+}
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestSwapConfig,
+    TestSwapInfo,
+    TestTreasury,
+) {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+    set_clock(&mut context, START_TS).await;
+
+    let treasury = TestTreasury {
+        token_a: create_token_account(
+            &mut context.banks_client,
+            swap_info.token_a_mint,
+            &context.payer,
+            None,
+            None,
+        )
+        .await,
+        token_b: create_token_account(
+            &mut context.banks_client,
+            swap_info.token_b_mint,
+            &context.payer,
+            None,
+            None,
+        )
+        .await,
+    };
+
+    (context, swap_config, swap_info, treasury)
+}
+
+async fn set_clock(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+async fn try_propose_drain(
+    context: &mut ProgramTestContext,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    treasury: &TestTreasury,
+    admin: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[propose_drain(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            admin.pubkey(),
+            treasury.token_a,
+            treasury.token_b,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn try_execute_drain(
+    context: &mut ProgramTestContext,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    treasury: &TestTreasury,
+    admin: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[execute_drain(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_info.authority,
+            admin.pubkey(),
+            swap_info.token_a,
+            swap_info.token_b,
+            treasury.token_a,
+            treasury.token_b,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+fn swap_error(error: SwapError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_execute_after_unlock() {
+    let (mut context, swap_config, swap_info, treasury) = setup().await;
+
+    try_propose_drain(
+        &mut context,
+        &swap_config,
+        &swap_info,
+        &treasury,
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        swap_config
+            .get_state(&mut context.banks_client)
+            .await
+            .drain_unlock_ts,
+        START_TS + DRAIN_TIMELOCK
+    );
+
+    set_clock(&mut context, START_TS + DRAIN_TIMELOCK - 1).await;
+    assert_eq!(
+        try_execute_drain(
+            &mut context,
+            &swap_config,
+            &swap_info,
+            &treasury,
+            &swap_config.admin
+        )
+        .await,
+        Err(swap_error(SwapError::DrainTimelockActive))
+    );
+
+    set_clock(&mut context, START_TS + DRAIN_TIMELOCK).await;
+    try_execute_drain(
+        &mut context,
+        &swap_config,
+        &swap_info,
+        &treasury,
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    let banks_client = &mut context.banks_client;
+    assert_eq!(get_token_balance(banks_client, swap_info.token_a).await, 0);
+    assert_eq!(get_token_balance(banks_client, swap_info.token_b).await, 0);
+    assert_eq!(
+        get_token_balance(banks_client, treasury.token_a).await,
+        42_000_000_000
+    );
+    assert_eq!(
+        get_token_balance(banks_client, treasury.token_b).await,
+        800_000_000_000
+    );
+    let state = swap_info.get_state(banks_client).await;
+    assert_eq!(state.pause_flags, PauseFlags::ALL);
+    // the recorded reserves follow the emptied vaults
+    assert!(state.pool_state.base_reserve.is_zero());
+    assert!(state.pool_state.quote_reserve.is_zero());
+    assert_eq!(swap_config.get_state(banks_client).await.drain_unlock_ts, 0);
+
+    // every drain needs its own proposal
+    assert_eq!(
+        try_execute_drain(
+            &mut context,
+            &swap_config,
+            &swap_info,
+            &treasury,
+            &swap_config.admin
+        )
+        .await,
+        Err(swap_error(SwapError::DrainNotProposed))
+    );
+}
+
+#[tokio::test]
+async fn test_propose_resets_timelock() {
+    let (mut context, swap_config, swap_info, treasury) = setup().await;
+
+    try_propose_drain(
+        &mut context,
+        &swap_config,
+        &swap_info,
+        &treasury,
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    let second_propose_ts = START_TS + DRAIN_TIMELOCK - 100;
+    set_clock(&mut context, second_propose_ts).await;
+    try_propose_drain(
+        &mut context,
+        &swap_config,
+        &swap_info,
+        &treasury,
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        swap_config
+            .get_state(&mut context.banks_client)
+            .await
+            .drain_unlock_ts,
+        second_propose_ts + DRAIN_TIMELOCK
+    );
+
+    set_clock(&mut context, START_TS + DRAIN_TIMELOCK).await;
+    assert_eq!(
+        try_execute_drain(
+            &mut context,
+            &swap_config,
+            &swap_info,
+            &treasury,
+            &swap_config.admin
+        )
+        .await,
+        Err(swap_error(SwapError::DrainTimelockActive))
+    );
+
+    set_clock(&mut context, second_propose_ts + DRAIN_TIMELOCK).await;
+    try_execute_drain(
+        &mut context,
+        &swap_config,
+        &swap_info,
+        &treasury,
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_execute_without_proposal() {
+    let (mut context, swap_config, swap_info, treasury) = setup().await;
+
+    assert_eq!(
+        try_execute_drain(
+            &mut context,
+            &swap_config,
+            &swap_info,
+            &treasury,
+            &swap_config.admin
+        )
+        .await,
+        Err(swap_error(SwapError::DrainNotProposed))
+    );
+}
+
+#[tokio::test]
+async fn test_unauthorized() {
+    let (mut context, swap_config, swap_info, treasury) = setup().await;
+    let invalid_admin = Keypair::new();
+
+    assert_eq!(
+        try_propose_drain(
+            &mut context,
+            &swap_config,
+            &swap_info,
+            &treasury,
+            &invalid_admin
+        )
+        .await,
+        Err(swap_error(SwapError::Unauthorized))
+    );
+
+    try_propose_drain(
+        &mut context,
+        &swap_config,
+        &swap_info,
+        &treasury,
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+    set_clock(&mut context, START_TS + DRAIN_TIMELOCK).await;
+    assert_eq!(
+        try_execute_drain(
+            &mut context,
+            &swap_config,
+            &swap_info,
+            &treasury,
+            &invalid_admin
+        )
+        .await,
+        Err(swap_error(SwapError::Unauthorized))
+    );
+}
+
+#[tokio::test]
+async fn test_execute_into_other_treasury() {
+    let (mut context, swap_config, swap_info, treasury) = setup().await;
+
+    try_propose_drain(
+        &mut context,
+        &swap_config,
+        &swap_info,
+        &treasury,
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+    set_clock(&mut context, START_TS + DRAIN_TIMELOCK).await;
+
+    let other_treasury = TestTreasury {
+        token_a: create_token_account(
+            &mut context.banks_client,
+            swap_info.token_a_mint,
+            &context.payer,
+            None,
+            None,
+        )
+        .await,
+        token_b: treasury.token_b,
+    };
+    assert_eq!(
+        try_execute_drain(
+            &mut context,
+            &swap_config,
+            &swap_info,
+            &other_treasury,
+            &swap_config.admin
+        )
+        .await,
+        Err(swap_error(SwapError::DrainTargetMismatch))
+    );
+}
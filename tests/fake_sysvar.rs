@@ -0,0 +1,147 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{init_liquidity_provider, refresh_liquidity_obligation},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::LiquidityProvider,
+};
+use solana_program::{
+    clock::Clock,
+    instruction::InstructionError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::{self, Sysvar},
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    system_instruction::create_account,
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+/// Account shaped like a sysvar but living at an arbitrary address
+fn add_fake_sysvar(test: &mut ProgramTest, len: usize) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    test.add_account(
+        pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: vec![0; len],
+            owner: sysvar::id(),
+            ..Account::default()
+        },
+    );
+    pubkey
+}
+
+#[tokio::test]
+async fn test_fake_clock() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+    let fake_clock = add_fake_sysvar(&mut test, Clock::size_of());
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut instruction = refresh_liquidity_obligation(
+        deltafi_swap::id(),
+        swap_info.pubkey,
+        vec![liquidity_provider.pubkey],
+    )
+    .unwrap();
+    instruction.accounts[1].pubkey = fake_clock;
+
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::InvalidArgument
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_fake_rent() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let fake_rent = add_fake_sysvar(&mut test, Rent::size_of());
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = Keypair::new();
+    let rent = banks_client.get_rent().await.unwrap();
+
+    let mut instruction = init_liquidity_provider(
+        deltafi_swap::id(),
+        liquidity_provider.pubkey(),
+        liquidity_owner.pubkey(),
+    )
+    .unwrap();
+    instruction.accounts[2].pubkey = fake_rent;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_account(
+                &payer.pubkey(),
+                &liquidity_provider.pubkey(),
+                rent.minimum_balance(LiquidityProvider::LEN),
+                LiquidityProvider::LEN as u64,
+                &deltafi_swap::id(),
+            ),
+            instruction,
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(
+        &[&payer, &liquidity_provider, &liquidity_owner],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap()),
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::InvalidArgument
+        ))
+    );
+}
@@ -0,0 +1,243 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::SwapDirection,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::Fees,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+use utils::*;
+
+struct FeeTestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    liquidity_provider: TestLiquidityProvider,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup(fees: Fees) -> FeeTestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    swap_info
+        .set_new_fees(&mut banks_client, &swap_config, fees, &payer)
+        .await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        100_000_000_000,
+    )
+    .await;
+
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    FeeTestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+async fn admin_fee_balances(ctx: &mut FeeTestContext) -> (u64, u64) {
+    (
+        get_token_balance(&mut ctx.banks_client, ctx.swap_info.admin_fee_a_key).await,
+        get_token_balance(&mut ctx.banks_client, ctx.swap_info.admin_fee_b_key).await,
+    )
+}
+
+async fn swap_admin_fee_collected(ctx: &mut FeeTestContext) -> bool {
+    let before = admin_fee_balances(ctx).await;
+    ctx.swap_info
+        .swap(
+            &mut ctx.banks_client,
+            &ctx.swap_config,
+            &ctx.user_account_owner,
+            ctx.sol_user_account,
+            ctx.srm_user_account,
+            ctx.deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &ctx.payer,
+        )
+        .await;
+    admin_fee_balances(ctx).await != before
+}
+
+async fn withdraw_admin_fee_collected(ctx: &mut FeeTestContext) -> bool {
+    let before = admin_fee_balances(ctx).await;
+    ctx.swap_info
+        .withdraw(
+            &mut ctx.banks_client,
+            &ctx.liquidity_provider,
+            &ctx.user_account_owner,
+            ctx.sol_user_account,
+            ctx.srm_user_account,
+            ctx.swap_info.pool_token,
+            1_000_000_000,
+            0,
+            0,
+            &ctx.payer,
+        )
+        .await;
+    admin_fee_balances(ctx).await != before
+}
+
+#[tokio::test]
+async fn test_all_fees_enabled() {
+    let mut ctx = setup(TEST_FEES).await;
+
+    assert!(swap_admin_fee_collected(&mut ctx).await);
+    assert!(withdraw_admin_fee_collected(&mut ctx).await);
+}
+
+#[tokio::test]
+async fn test_trade_fee_disabled() {
+    let mut ctx = setup(Fees {
+        trade_fee_enabled: false,
+        ..TEST_FEES
+    })
+    .await;
+
+    assert!(!swap_admin_fee_collected(&mut ctx).await);
+    assert!(withdraw_admin_fee_collected(&mut ctx).await);
+}
+
+#[tokio::test]
+async fn test_withdraw_fee_disabled() {
+    let mut ctx = setup(Fees {
+        withdraw_fee_enabled: false,
+        ..TEST_FEES
+    })
+    .await;
+
+    assert!(swap_admin_fee_collected(&mut ctx).await);
+    assert!(!withdraw_admin_fee_collected(&mut ctx).await);
+}
+
+#[tokio::test]
+async fn test_disabled_fees_with_zero_denominators() {
+    let mut ctx = setup(Fees {
+        admin_trade_fee_denominator: 0,
+        admin_withdraw_fee_denominator: 0,
+        trade_fee_denominator: 0,
+        withdraw_fee_denominator: 0,
+        trade_fee_enabled: false,
+        withdraw_fee_enabled: false,
+        ..TEST_FEES
+    })
+    .await;
+
+    assert!(!swap_admin_fee_collected(&mut ctx).await);
+    assert!(!withdraw_admin_fee_collected(&mut ctx).await);
+}
+
+/// SRM received by the user for selling SOL
+async fn sell_sol(ctx: &mut FeeTestContext, amount_in: u64) -> u64 {
+    let before = get_token_balance(&mut ctx.banks_client, ctx.srm_user_account).await;
+    ctx.swap_info
+        .swap(
+            &mut ctx.banks_client,
+            &ctx.swap_config,
+            &ctx.user_account_owner,
+            ctx.sol_user_account,
+            ctx.srm_user_account,
+            ctx.deltafi_user_account,
+            amount_in,
+            0,
+            SwapDirection::SellBase,
+            &ctx.payer,
+        )
+        .await;
+    get_token_balance(&mut ctx.banks_client, ctx.srm_user_account).await - before
+}
+
+#[tokio::test]
+async fn test_min_fee() {
+    const MIN_FEE: u64 = 50;
+    let mut ctx = setup(TEST_FEES).await;
+    let mut floored_ctx = setup(Fees {
+        min_fee: MIN_FEE,
+        ..TEST_FEES
+    })
+    .await;
+
+    // a large trade pays the percentage fee
+    let output = sell_sol(&mut ctx, 1_000_000_000).await;
+    assert_eq!(sell_sol(&mut floored_ctx, 1_000_000_000).await, output);
+
+    // a dust trade whose percentage fee rounds to zero pays the floor
+    let output = sell_sol(&mut ctx, 5).await;
+    assert_eq!(sell_sol(&mut floored_ctx, 5).await, output - MIN_FEE);
+}
@@ -0,0 +1,561 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        claim_liquidity_rewards, pause, propose_drain, set_fee_account, set_min_reward_liquidity,
+        set_new_fees, set_new_rewards, set_pause_flags, set_targets, unpause, SetTargetsData,
+        SwapDirection,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{ConfigInfo, Fees, PauseFlags, Rewards, PROGRAM_VERSION},
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+/// Config sharing the real deltafi mint but administered by an attacker who
+/// takes every admin fee through the secondary account
+fn add_foreign_config(test: &mut ProgramTest, swap_config: &TestSwapConfig) -> TestSwapConfig {
+    let pubkey = Pubkey::new_unique();
+    let (market_authority, bump_seed) =
+        Pubkey::find_program_address(&[pubkey.as_ref()], &deltafi_swap::id());
+    let admin = Keypair::new();
+
+    test.add_packable_account(
+        pubkey,
+        u32::MAX as u64,
+        &ConfigInfo {
+            version: PROGRAM_VERSION,
+            bump_seed,
+            admin_key: admin.pubkey(),
+            deltafi_mint: swap_config.deltafi_mint,
+            fees: swap_config.fees.clone(),
+            rewards: swap_config.rewards.clone(),
+            admin_split_bps: 0,
+            ..ConfigInfo::default()
+        },
+        &deltafi_swap::id(),
+    );
+
+    TestSwapConfig {
+        pubkey,
+        admin,
+        market_authority,
+        deltafi_mint: swap_config.deltafi_mint,
+        fees: swap_config.fees.clone(),
+        rewards: swap_config.rewards.clone(),
+    }
+}
+
+/// Process an admin instruction signed by the admin of `swap_config`
+async fn try_admin_instruction(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_config: &TestSwapConfig,
+    instruction: Instruction,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[payer, &swap_config.admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+fn add_pool(
+    test: &mut ProgramTest,
+    swap_config: &TestSwapConfig,
+    user_account_owner: &Keypair,
+) -> (TestSwapInfo, TestMint) {
+    let sol_oracle = add_sol_oracle(test);
+    let srm_oracle = add_srm_oracle(test);
+    let srm_mint = add_srm_mint(test);
+
+    let swap_info = add_swap_info(
+        test,
+        swap_config,
+        user_account_owner,
+        &Keypair::new(),
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    (swap_info, srm_mint)
+}
+
+#[tokio::test]
+async fn test_swap_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (mut swap_info, srm_mint) = add_pool(&mut test, &swap_config, &user_account_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let attacker_fee_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        foreign_config.admin.pubkey(),
+        0,
+    )
+    .await;
+    swap_info.secondary_admin_fee_b_key = attacker_fee_account;
+
+    assert_eq!(
+        swap_info
+            .try_swap(
+                &mut banks_client,
+                &foreign_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                1_000_000_000,
+                0,
+                SwapDirection::SellBase,
+                &payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_claim_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (swap_info, _) = add_pool(&mut test, &swap_config, &user_account_owner);
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[claim_liquidity_rewards(
+            deltafi_swap::id(),
+            foreign_config.pubkey,
+            swap_info.pubkey,
+            foreign_config.market_authority,
+            liquidity_provider.pubkey,
+            user_account_owner.pubkey(),
+            deltafi_user_account,
+            foreign_config.deltafi_mint,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &user_account_owner], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_deposit_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (swap_info, srm_mint) = add_pool(&mut test, &swap_config, &user_account_owner);
+    let liquidity_owner = Keypair::new();
+    let liquidity_provider = add_liquidity_provider(&mut test, &liquidity_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        2_100_000_000,
+    )
+    .await;
+    let srm_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        liquidity_owner.pubkey(),
+        40_000_000_000,
+    )
+    .await;
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    // the position would accrue rewards minted under the foreign config
+    assert_eq!(
+        swap_info
+            .try_deposit(
+                &mut banks_client,
+                &foreign_config,
+                &liquidity_provider,
+                &liquidity_owner,
+                sol_deposit_account,
+                srm_deposit_account,
+                pool_token_account,
+                2_100_000_000,
+                40_000_000_000,
+                0,
+                &payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+    assert!(liquidity_provider
+        .get_state(&mut banks_client)
+        .await
+        .positions
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_withdraw_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (mut swap_info, srm_mint) = add_pool(&mut test, &swap_config, &user_account_owner);
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_withdraw_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let srm_withdraw_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    swap_info.config = foreign_config.pubkey;
+    assert_eq!(
+        swap_info
+            .try_withdraw(
+                &mut banks_client,
+                &liquidity_provider,
+                &user_account_owner,
+                sol_withdraw_account,
+                srm_withdraw_account,
+                swap_info.pool_token,
+                1_000_000_000,
+                0,
+                0,
+                &payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_propose_drain_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (swap_info, srm_mint) = add_pool(&mut test, &swap_config, &user_account_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let treasury_a = create_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        &payer,
+        Some(foreign_config.admin.pubkey()),
+        None,
+    )
+    .await;
+    let treasury_b = create_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        &payer,
+        Some(foreign_config.admin.pubkey()),
+        None,
+    )
+    .await;
+
+    assert_eq!(
+        try_admin_instruction(
+            &mut banks_client,
+            &payer,
+            &foreign_config,
+            propose_drain(
+                deltafi_swap::id(),
+                foreign_config.pubkey,
+                swap_info.pubkey,
+                foreign_config.admin.pubkey(),
+                treasury_a,
+                treasury_b,
+            )
+            .unwrap(),
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_admin_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (swap_info, srm_mint) = add_pool(&mut test, &swap_config, &user_account_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let attacker_fee_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        foreign_config.admin.pubkey(),
+        0,
+    )
+    .await;
+    let state = swap_info.get_state(&mut banks_client).await;
+
+    let program_id = deltafi_swap::id();
+    let config = foreign_config.pubkey;
+    let swap = swap_info.pubkey;
+    let admin = foreign_config.admin.pubkey();
+    for instruction in [
+        pause(program_id, config, swap, admin),
+        unpause(program_id, config, swap, admin),
+        set_fee_account(
+            program_id,
+            config,
+            swap,
+            swap_info.authority,
+            admin,
+            attacker_fee_account,
+        ),
+        set_new_fees(
+            program_id,
+            config,
+            swap,
+            admin,
+            Fees {
+                trade_fee_numerator: 0,
+                ..swap_config.fees.clone()
+            },
+        ),
+        set_new_rewards(
+            program_id,
+            config,
+            swap,
+            admin,
+            Rewards {
+                trade_reward_numerator: 0,
+                ..swap_config.rewards.clone()
+            },
+        ),
+        set_pause_flags(program_id, config, swap, admin, PauseFlags::SWAPS),
+    ] {
+        assert_eq!(
+            try_admin_instruction(
+                &mut banks_client,
+                &payer,
+                &foreign_config,
+                instruction.unwrap()
+            )
+            .await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(SwapError::InvalidConfig as u32)
+            ))
+        );
+    }
+    assert_eq!(swap_info.get_state(&mut banks_client).await, state);
+}
+
+#[tokio::test]
+async fn test_set_targets_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (swap_info, _) = add_pool(&mut test, &swap_config, &user_account_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+    let pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+
+    // targets far from the reserves would leave the curve open to arbitrage
+    assert_eq!(
+        try_admin_instruction(
+            &mut banks_client,
+            &payer,
+            &foreign_config,
+            set_targets(
+                deltafi_swap::id(),
+                foreign_config.pubkey,
+                swap_info.pubkey,
+                foreign_config.admin.pubkey(),
+                SetTargetsData {
+                    base_target: Decimal::from(1u64).to_scaled_val().unwrap(),
+                    quote_target: Decimal::from(1u64).to_scaled_val().unwrap(),
+                },
+            )
+            .unwrap(),
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+    assert_eq!(
+        swap_info.get_state(&mut banks_client).await.pool_state,
+        pool_state
+    );
+}
+
+#[tokio::test]
+async fn test_set_min_reward_liquidity_with_foreign_config() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let foreign_config = add_foreign_config(&mut test, &swap_config);
+    let user_account_owner = Keypair::new();
+    let (swap_info, _) = add_pool(&mut test, &swap_config, &user_account_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    // a pool no position qualifies for stops accruing liquidity rewards
+    assert_eq!(
+        try_admin_instruction(
+            &mut banks_client,
+            &payer,
+            &foreign_config,
+            set_min_reward_liquidity(
+                deltafi_swap::id(),
+                foreign_config.pubkey,
+                swap_info.pubkey,
+                foreign_config.admin.pubkey(),
+                u64::MAX,
+            )
+            .unwrap(),
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidConfig as u32)
+        ))
+    );
+    assert_eq!(
+        swap_info
+            .get_state(&mut banks_client)
+            .await
+            .min_reward_liquidity,
+        0
+    );
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::{get_arb_amount_to_oracle, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    pyth,
+};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{account::AccountSharedData, signature::Keypair};
+use utils::*;
+
+async fn set_oracle_price(context: &mut ProgramTestContext, price_pubkey: Pubkey, price: u64) {
+    let mut account = context
+        .banks_client
+        .get_account(price_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let pyth_price = pyth::load_mut::<pyth::Price>(account.data.as_mut_slice()).unwrap();
+    let decimals = 10u64.pow(pyth_price.expo.unsigned_abs());
+    pyth_price.agg.price = (price * decimals).try_into().unwrap();
+    context.set_account(&price_pubkey, &AccountSharedData::from(account));
+}
+
+async fn get_arb_amount(
+    context: &mut ProgramTestContext,
+    swap_info: &TestSwapInfo,
+    swap_direction: SwapDirection,
+) -> u64 {
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        get_arb_amount_to_oracle(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_direction,
+        )
+        .unwrap(),
+        &context.payer,
+    )
+    .await;
+    u64::from_le_bytes(return_data[..].try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+    let mut pool_state = swap_info
+        .get_state(&mut context.banks_client)
+        .await
+        .pool_state;
+
+    // a pool at the oracle price has at most rounding dust to arbitrage
+    let oracle_price = sol_oracle.price.try_div(srm_oracle.price).unwrap();
+    for swap_direction in [SwapDirection::SellBase, SwapDirection::SellQuote] {
+        let amount_in = get_arb_amount(&mut context, &swap_info, swap_direction).await;
+        assert!(amount_in <= 1);
+        assert_eq!(
+            amount_in,
+            pool_state
+                .arb_amount_to_oracle(oracle_price, swap_direction)
+                .unwrap()
+        );
+    }
+
+    // SOL doubles, leaving the pool priced below the oracle
+    set_oracle_price(&mut context, sol_oracle.price_pubkey, 300).await;
+    let oracle_price = Decimal::from(300u64).try_div(srm_oracle.price).unwrap();
+    assert_eq!(
+        get_arb_amount(&mut context, &swap_info, SwapDirection::SellBase).await,
+        0
+    );
+    let amount_in = get_arb_amount(&mut context, &swap_info, SwapDirection::SellQuote).await;
+    assert!(amount_in > 0);
+    assert_eq!(
+        amount_in,
+        pool_state
+            .arb_amount_to_oracle(oracle_price, SwapDirection::SellQuote)
+            .unwrap()
+    );
+
+    // SOL halves, leaving the pool priced above the oracle
+    set_oracle_price(&mut context, sol_oracle.price_pubkey, 75).await;
+    let oracle_price = Decimal::from(75u64).try_div(srm_oracle.price).unwrap();
+    assert_eq!(
+        get_arb_amount(&mut context, &swap_info, SwapDirection::SellQuote).await,
+        0
+    );
+    let amount_in = get_arb_amount(&mut context, &swap_info, SwapDirection::SellBase).await;
+    assert!(amount_in > 0);
+    assert_eq!(
+        amount_in,
+        pool_state
+            .arb_amount_to_oracle(oracle_price, SwapDirection::SellBase)
+            .unwrap()
+    );
+}
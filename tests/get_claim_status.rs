@@ -0,0 +1,92 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::get_claim_status,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{ClaimStatus, MIN_CLAIM_PERIOD},
+};
+use solana_program::{clock::Clock, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    // position is created at timestamp 0, so its next claim is at MIN_CLAIM_PERIOD
+    let liquidity_provider = add_position(&mut test, &swap_info, &user_account_owner, 1_000);
+
+    let mut context = test.start_with_context().await;
+
+    // before next claim timestamp
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = MIN_CLAIM_PERIOD - 100;
+    context.set_sysvar(&clock);
+
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        get_claim_status(
+            deltafi_swap::id(),
+            liquidity_provider.pubkey,
+            swap_info.pubkey,
+        )
+        .unwrap(),
+        &context.payer,
+    )
+    .await;
+    let claim_status = ClaimStatus::unpack_from_slice(&return_data).unwrap();
+    assert_eq!(claim_status.rewards_owed, 0);
+    assert_eq!(claim_status.rewards_estimated, 0);
+    assert_eq!(claim_status.next_claim_ts, MIN_CLAIM_PERIOD);
+    assert_eq!(claim_status.seconds_until_claim, 100);
+
+    // after next claim timestamp
+    clock.unix_timestamp = MIN_CLAIM_PERIOD + 100;
+    context.set_sysvar(&clock);
+
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        get_claim_status(
+            deltafi_swap::id(),
+            liquidity_provider.pubkey,
+            swap_info.pubkey,
+        )
+        .unwrap(),
+        &context.payer,
+    )
+    .await;
+    let claim_status = ClaimStatus::unpack_from_slice(&return_data).unwrap();
+    assert_eq!(claim_status.next_claim_ts, MIN_CLAIM_PERIOD);
+    assert_eq!(claim_status.seconds_until_claim, 0);
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::get_config_stats,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::ConfigStats,
+};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let return_data = simulate_return_data(
+        &mut banks_client,
+        get_config_stats(deltafi_swap::id(), swap_config.pubkey).unwrap(),
+        &payer,
+    )
+    .await;
+    let stats = ConfigStats::unpack_from_slice(&return_data).unwrap();
+    assert_eq!(stats.pools_created, 0);
+
+    for _ in 0..3 {
+        let user_accounts_owner = Keypair::new();
+        let sol_user_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            spl_token::native_mint::id(),
+            None,
+            &payer,
+            user_accounts_owner.pubkey(),
+            42_000_000_000,
+        )
+        .await;
+        let srm_user_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            srm_mint.pubkey,
+            Some(&srm_mint.authority),
+            &payer,
+            user_accounts_owner.pubkey(),
+            // SOL/SRM oracle price is 150 / 7, so this deposit is priced at it
+            900_000_000_000,
+        )
+        .await;
+
+        let admin_fee_accounts = Keypair::new();
+        let sol_admin_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            spl_token::native_mint::id(),
+            None,
+            &payer,
+            admin_fee_accounts.pubkey(),
+            0,
+        )
+        .await;
+        let srm_admin_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            srm_mint.pubkey,
+            Some(&srm_mint.authority),
+            &payer,
+            admin_fee_accounts.pubkey(),
+            0,
+        )
+        .await;
+
+        TestSwapInfo::init(
+            &mut banks_client,
+            &swap_config,
+            &sol_oracle,
+            &srm_oracle,
+            spl_token::native_mint::id(),
+            srm_mint.pubkey,
+            sol_user_account,
+            srm_user_account,
+            sol_admin_account,
+            srm_admin_account,
+            &user_accounts_owner,
+            &payer,
+            &SwapInitArgs {
+                mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+                slope: Decimal::one()
+                    .try_div(2)
+                    .unwrap()
+                    .to_scaled_val()
+                    .unwrap()
+                    .try_into()
+                    .unwrap(),
+                is_open_twap: true,
+                creator_fee_bps: 0,
+                invert_a: false,
+                invert_b: false,
+                slope_in_bps: false,
+            },
+        )
+        .await;
+    }
+
+    let return_data = simulate_return_data(
+        &mut banks_client,
+        get_config_stats(deltafi_swap::id(), swap_config.pubkey).unwrap(),
+        &payer,
+    )
+    .await;
+    let stats = ConfigStats::unpack_from_slice(&return_data).unwrap();
+
+    assert_eq!(stats.pools_created, 3);
+    assert_eq!(stats.admin_key, swap_config.admin.pubkey());
+    assert_eq!(stats.fees, TEST_FEES);
+    assert_eq!(stats.rewards, TEST_REWARDS);
+    assert_eq!(stats.drain_unlock_ts, 0);
+
+    let config = swap_config.get_state(&mut banks_client).await;
+    assert_eq!(config.pools_created, 3);
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::{get_execution_price, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let swap_state = swap_info.get_state(&mut banks_client).await;
+    for direction in [SwapDirection::SellBase, SwapDirection::SellQuote] {
+        let mut prices = vec![];
+        for amount_in in [1_000_000_000, 10_000_000_000] {
+            let return_data = simulate_return_data(
+                &mut banks_client,
+                get_execution_price(deltafi_swap::id(), swap_info.pubkey, amount_in, direction)
+                    .unwrap(),
+                &payer,
+            )
+            .await;
+            let price =
+                Decimal::from_scaled_val(u128::from_le_bytes(return_data[..].try_into().unwrap()));
+            assert_eq!(
+                price,
+                swap_state
+                    .pool_state
+                    .execution_price(amount_in, direction)
+                    .unwrap()
+            );
+            prices.push(price);
+        }
+
+        // the larger trade gets the worse price
+        match direction {
+            SwapDirection::SellBase => assert!(prices[1] < prices[0]),
+            SwapDirection::SellQuote => assert!(prices[1] > prices[0]),
+        }
+    }
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{get_fee_apr, SwapDirection},
+    math::{Decimal, TryAdd, TryDiv, TryMul},
+    processor::process,
+    state::SwapInfo,
+};
+use solana_program::{clock::Clock, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    signature::{Keypair, Signer},
+};
+use std::convert::TryInto;
+use utils::*;
+
+const CREATION_TS: i64 = 1_000_000;
+const ONE_DAY: i64 = 86_400;
+
+async fn set_clock(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+async fn fee_apr(context: &mut ProgramTestContext, swap_info: &TestSwapInfo) -> Decimal {
+    let payer = context.payer.insecure_clone();
+    let data = simulate_return_data(
+        &mut context.banks_client,
+        get_fee_apr(deltafi_swap::id(), swap_info.pubkey).unwrap(),
+        &payer,
+    )
+    .await;
+    Decimal::from_scaled_val(u128::from_le_bytes(data[..].try_into().unwrap()))
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    // 1M quote tokens traded over the pool's first day
+    let mut account = context
+        .banks_client
+        .get_account(swap_info.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = SwapInfo::unpack(&account.data).unwrap();
+    state.creation_ts = CREATION_TS as u64;
+    state.cumulative_volume = 1_000_000_000_000;
+    SwapInfo::pack(state.clone(), &mut account.data).unwrap();
+    context.set_account(&swap_info.pubkey, &AccountSharedData::from(account));
+
+    // nothing has accrued yet at creation
+    set_clock(&mut context, CREATION_TS).await;
+    assert_eq!(fee_apr(&mut context, &swap_info).await, Decimal::zero());
+
+    set_clock(&mut context, CREATION_TS + ONE_DAY).await;
+    // 0.5% trade fee is 5B, the admin keeps 2/5 of it, leaving 3B a day to LPs
+    let liquidity = state
+        .pool_state
+        .base_reserve
+        .try_mul(state.pool_state.get_mid_price().unwrap())
+        .unwrap()
+        .try_add(state.pool_state.quote_reserve)
+        .unwrap();
+    let expected = Decimal::from(3_000_000_000u64)
+        .try_mul(365)
+        .unwrap()
+        .try_div(liquidity)
+        .unwrap();
+    assert_eq!(fee_apr(&mut context, &swap_info).await, expected);
+}
+
+#[tokio::test]
+async fn test_swap_adds_volume() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .cumulative_volume,
+        0
+    );
+
+    swap_info
+        .swap(
+            &mut context.banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &payer,
+        )
+        .await;
+
+    // selling base counts the quote paid out before the trade fee
+    let volume = swap_info
+        .get_state(&mut context.banks_client)
+        .await
+        .cumulative_volume;
+    let amount_out = get_token_balance(&mut context.banks_client, srm_user_account).await;
+    assert_eq!(
+        volume - TEST_FEES.proportional_trade_fee(volume).unwrap(),
+        amount_out
+    );
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::get_implied_prices,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let return_data = simulate_return_data(
+        &mut banks_client,
+        get_implied_prices(deltafi_swap::id(), swap_info.pubkey).unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(return_data.len(), 32);
+    let reserve_ratio_price =
+        Decimal::from_scaled_val(u128::from_le_bytes(return_data[..16].try_into().unwrap()));
+    let mid_price =
+        Decimal::from_scaled_val(u128::from_le_bytes(return_data[16..].try_into().unwrap()));
+
+    let pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+    assert_eq!(
+        reserve_ratio_price,
+        Decimal::from(800_000_000_000u64)
+            .try_div(42_000_000_000u64)
+            .unwrap()
+    );
+    assert_eq!(
+        reserve_ratio_price,
+        pool_state.reserve_ratio_price().unwrap()
+    );
+    assert_eq!(mid_price, pool_state.clone().get_mid_price().unwrap());
+}
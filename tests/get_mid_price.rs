@@ -0,0 +1,138 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::{get_implied_prices, get_mid_price, get_price_health, set_price_tick},
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+async fn get_price(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_info: &TestSwapInfo,
+) -> Decimal {
+    let return_data = simulate_return_data(
+        banks_client,
+        get_mid_price(deltafi_swap::id(), swap_info.pubkey).unwrap(),
+        payer,
+    )
+    .await;
+    Decimal::from_scaled_val(u128::from_le_bytes(return_data[..].try_into().unwrap()))
+}
+
+#[tokio::test]
+async fn test_snaps_to_tick() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let mid_price = swap_info
+        .get_state(&mut banks_client)
+        .await
+        .pool_state
+        .get_mid_price()
+        .unwrap();
+
+    // no tick configured, the raw mid price is quoted
+    assert_eq!(
+        get_price(&mut banks_client, &payer, &swap_info).await,
+        mid_price
+    );
+
+    let tick = Decimal::one().try_div(10).unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[set_price_tick(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            tick.to_scaled_val().unwrap(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let price = get_price(&mut banks_client, &payer, &swap_info).await;
+    assert_ne!(price, mid_price);
+    let ticks = price.try_div(tick).unwrap().try_round_u64().unwrap();
+    assert_eq!(tick.try_mul(ticks).unwrap(), price);
+    assert_eq!(
+        price,
+        swap_info
+            .get_state(&mut banks_client)
+            .await
+            .pool_state
+            .quantized_mid_price(tick)
+            .unwrap()
+    );
+
+    // the other mid price quotes snap to the same tick
+    let implied_prices = simulate_return_data(
+        &mut banks_client,
+        get_implied_prices(deltafi_swap::id(), swap_info.pubkey).unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(
+        Decimal::from_scaled_val(u128::from_le_bytes(
+            implied_prices[16..].try_into().unwrap()
+        )),
+        price
+    );
+    let price_health = simulate_return_data(
+        &mut banks_client,
+        get_price_health(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(
+        Decimal::from_scaled_val(u128::from_le_bytes(price_health[..16].try_into().unwrap())),
+        price
+    );
+}
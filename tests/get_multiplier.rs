@@ -0,0 +1,142 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    curve::Multiplier,
+    instruction::{get_multiplier, set_targets, SetTargetsData},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+async fn multiplier(
+    banks_client: &mut BanksClient,
+    swap_info: &TestSwapInfo,
+    payer: &Keypair,
+) -> (u8, u64) {
+    let return_data = simulate_return_data(
+        banks_client,
+        get_multiplier(deltafi_swap::id(), swap_info.pubkey).unwrap(),
+        payer,
+    )
+    .await;
+    assert_eq!(return_data.len(), 9);
+    (
+        return_data[0],
+        u64::from_le_bytes(return_data[1..].try_into().unwrap()),
+    )
+}
+
+async fn apply_targets(
+    banks_client: &mut BanksClient,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    payer: &Keypair,
+    base_target: u64,
+    quote_target: u64,
+) {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_targets(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            SetTargetsData {
+                base_target: Decimal::from(base_target).to_scaled_val().unwrap(),
+                quote_target: Decimal::from(quote_target).to_scaled_val().unwrap(),
+            },
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[payer, &swap_config.admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+    assert_eq!(pool_state.multiplier, Multiplier::One);
+    assert_eq!(
+        multiplier(&mut banks_client, &swap_info, &payer).await,
+        (0, 0)
+    );
+
+    // reserves past the base target and short of the quote target
+    apply_targets(
+        &mut banks_client,
+        &swap_config,
+        &swap_info,
+        &payer,
+        40_000_000_000,
+        850_000_000_000,
+    )
+    .await;
+    // the recomputed quote target can sit on the reserve at these sizes, so
+    // the headroom is only checked against the stored state
+    let pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+    assert_eq!(pool_state.multiplier, Multiplier::BelowOne);
+    let (below_one, headroom) = multiplier(&mut banks_client, &swap_info, &payer).await;
+    assert_eq!(below_one, 2);
+    assert_eq!(headroom, pool_state.multiplier_headroom().unwrap());
+
+    // reserves short of the base target and past the quote target
+    apply_targets(
+        &mut banks_client,
+        &swap_config,
+        &swap_info,
+        &payer,
+        45_000_000_000,
+        750_000_000_000,
+    )
+    .await;
+    let pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+    assert_eq!(pool_state.multiplier, Multiplier::AboveOne);
+    let (above_one, headroom) = multiplier(&mut banks_client, &swap_info, &payer).await;
+    assert_eq!(above_one, 1);
+    assert_eq!(headroom, pool_state.multiplier_headroom().unwrap());
+}
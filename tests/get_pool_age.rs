@@ -0,0 +1,97 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::get_pool_age,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::SwapInfo,
+};
+use solana_program::{clock::Clock, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{account::AccountSharedData, signature::Keypair};
+use std::convert::TryInto;
+use utils::*;
+
+const CREATION_TS: i64 = 1_000_000;
+
+async fn set_clock(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+/// Query the pool age, returning the creation timestamp and the age
+async fn pool_age(context: &mut ProgramTestContext, swap_info: &TestSwapInfo) -> (u64, u64) {
+    let payer = context.payer.insecure_clone();
+    let data = simulate_return_data(
+        &mut context.banks_client,
+        get_pool_age(deltafi_swap::id(), swap_info.pubkey).unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(data.len(), 16);
+    (
+        u64::from_le_bytes(data[..8].try_into().unwrap()),
+        u64::from_le_bytes(data[8..].try_into().unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_pool_age_increases() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    // record the pool as created at CREATION_TS
+    let mut account = context
+        .banks_client
+        .get_account(swap_info.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = SwapInfo::unpack(&account.data).unwrap();
+    state.creation_ts = CREATION_TS as u64;
+    SwapInfo::pack(state, &mut account.data).unwrap();
+    context.set_account(&swap_info.pubkey, &AccountSharedData::from(account));
+
+    set_clock(&mut context, CREATION_TS + 100).await;
+    assert_eq!(
+        pool_age(&mut context, &swap_info).await,
+        (CREATION_TS as u64, 100)
+    );
+
+    set_clock(&mut context, CREATION_TS + 3_700).await;
+    assert_eq!(
+        pool_age(&mut context, &swap_info).await,
+        (CREATION_TS as u64, 3_700)
+    );
+}
@@ -0,0 +1,120 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::{get_price_source, PriceSource},
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+    state::SwapInfo,
+};
+use solana_program::{clock::Clock, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{account::AccountSharedData, signature::Keypair};
+use utils::*;
+
+async fn price_source(
+    context: &mut ProgramTestContext,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    oracles: (&TestOracle, &TestOracle),
+) -> (u8, Decimal) {
+    let payer = context.payer.insecure_clone();
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        get_price_source(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            oracles.0.price_pubkey,
+            oracles.1.price_pubkey,
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(return_data.len(), 17);
+    (
+        return_data[0],
+        Decimal::from_scaled_val(u128::from_le_bytes(return_data[1..].try_into().unwrap())),
+    )
+}
+
+#[tokio::test]
+async fn test_fresh_and_stale_oracle() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    // the pool trades well below the oracle
+    let oracle_price = sol_oracle.price.try_div(srm_oracle.price).unwrap();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: oracle_price.try_mul(9).unwrap().try_div(10).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let oracles = (&sol_oracle, &srm_oracle);
+
+    let mut context = test.start_with_context().await;
+    let mid_price = swap_info
+        .get_state(&mut context.banks_client)
+        .await
+        .pool_state
+        .get_mid_price()
+        .unwrap();
+
+    assert_eq!(
+        price_source(&mut context, &swap_config, &swap_info, oracles).await,
+        (PriceSource::Pyth as u8, oracle_price)
+    );
+
+    context.warp_to_slot(100).unwrap();
+
+    // a twap of a constant mid price is the mid price itself
+    assert_eq!(
+        price_source(&mut context, &swap_config, &swap_info, oracles).await,
+        (PriceSource::PoolMid as u8, mid_price)
+    );
+
+    // twap of twice the mid price over the last 100 seconds
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let now = clock.unix_timestamp as u64;
+    let mut account = context
+        .banks_client
+        .get_account(swap_info.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = SwapInfo::unpack(&account.data).unwrap();
+    state.creation_ts = now - 100;
+    state.block_timestamp_last = now;
+    state.base_price_cumulative_last = mid_price.try_mul(200).unwrap();
+    SwapInfo::pack(state, &mut account.data).unwrap();
+    context.set_account(&swap_info.pubkey, &AccountSharedData::from(account));
+
+    assert_eq!(
+        price_source(&mut context, &swap_config, &swap_info, oracles).await,
+        (PriceSource::Twap as u8, mid_price.try_mul(2).unwrap())
+    );
+}
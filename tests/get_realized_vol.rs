@@ -0,0 +1,93 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{get_realized_vol, RealizedVolData},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::SwapInfo,
+};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::{account::AccountSharedData, signature::Keypair};
+use std::convert::TryInto;
+use utils::*;
+
+const SNAPSHOT_TS: u64 = 1_000_000;
+
+#[tokio::test]
+async fn test_realized_vol() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    // mid prices 10, 12, 8, 11 and 9 held for 60, 30, 90, 45 and 15 seconds
+    // since the snapshot
+    let mut account = context
+        .banks_client
+        .get_account(swap_info.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = SwapInfo::unpack(&account.data).unwrap();
+    state.block_timestamp_last = SNAPSHOT_TS + 240;
+    state.base_price_cumulative_last = Decimal::from(2_310u64);
+    state.base_price_squared_cumulative_last = Decimal::from(22_740u64);
+    SwapInfo::pack(state, &mut account.data).unwrap();
+    context.set_account(&swap_info.pubkey, &AccountSharedData::from(account));
+
+    let payer = context.payer.insecure_clone();
+    let data = simulate_return_data(
+        &mut context.banks_client,
+        get_realized_vol(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            RealizedVolData {
+                base_price_cumulative: 0,
+                base_price_squared_cumulative: 0,
+                block_timestamp: SNAPSHOT_TS,
+            },
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(data.len(), 32);
+    let variance = Decimal::from_scaled_val(u128::from_le_bytes(data[..16].try_into().unwrap()));
+    let volatility = Decimal::from_scaled_val(u128::from_le_bytes(data[16..].try_into().unwrap()));
+
+    assert_eq!(
+        variance,
+        Decimal::from(2_109_375u64).try_div(1_000_000).unwrap()
+    );
+    // sqrt(2.109375) = 1.45236875...
+    assert_eq!(volatility, Decimal::from_scaled_val(1_452_368_754));
+}
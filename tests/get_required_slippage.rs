@@ -0,0 +1,163 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{get_required_slippage, SwapDirection},
+    math::{Decimal, TryDiv, TryMul, TrySub},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use utils::*;
+
+async fn get_slippage_bps(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_info: &TestSwapInfo,
+    amount_in: u64,
+) -> u64 {
+    let return_data = simulate_return_data(
+        banks_client,
+        get_required_slippage(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            amount_in,
+            SwapDirection::SellBase,
+        )
+        .unwrap(),
+        payer,
+    )
+    .await;
+    u64::from_le_bytes(return_data[..].try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let small_bps = get_slippage_bps(&mut banks_client, &payer, &swap_info, 1_000_000_000).await;
+    let large_bps = get_slippage_bps(&mut banks_client, &payer, &swap_info, 10_000_000_000).await;
+    assert!(large_bps > small_bps);
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let mid_price = swap_info
+        .get_state(&mut banks_client)
+        .await
+        .pool_state
+        .get_mid_price()
+        .unwrap();
+    let minimum_amount_out = |bps: u64| {
+        Decimal::from(10_000_000_000u64)
+            .try_mul(mid_price)
+            .unwrap()
+            .try_mul(Decimal::one().try_sub(Decimal::from_bps(bps)).unwrap())
+            .unwrap()
+            .try_floor_u64()
+            .unwrap()
+    };
+
+    // one basis point short of the required tolerance reverts
+    assert_eq!(
+        swap_info
+            .try_swap(
+                &mut banks_client,
+                &swap_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                10_000_000_000,
+                minimum_amount_out(large_bps - 1),
+                SwapDirection::SellBase,
+                &payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::ExceededSlippage as u32)
+        ))
+    );
+
+    swap_info
+        .swap(
+            &mut banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            10_000_000_000,
+            minimum_amount_out(large_bps),
+            SwapDirection::SellBase,
+            &payer,
+        )
+        .await;
+    assert!(
+        get_token_balance(&mut banks_client, srm_user_account).await
+            >= minimum_amount_out(large_bps)
+    );
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::get_reward_apr,
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+    state::{MIN_CLAIM_PERIOD, SECONDS_PER_YEAR},
+};
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let pool_args = |token_b_amount| AddSwapInfoArgs {
+        token_a_mint: spl_token::native_mint::id(),
+        token_b_mint: srm_mint.pubkey,
+        token_a_amount: 42_000_000_000,
+        token_b_amount,
+        is_open_twap: true,
+        oracle_a: sol_oracle.price_pubkey,
+        oracle_b: srm_oracle.price_pubkey,
+        market_price: Decimal::from(20u64),
+        slope: Decimal::one().try_div(2).unwrap(),
+    };
+    // lp tokens are minted for the base side, so an lp token of the balanced
+    // pool is worth twice the mid price and one of the quote heavy pool three times
+    let balanced_swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        pool_args(840_000_000_000),
+    );
+    let quote_heavy_swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        pool_args(1_680_000_000_000),
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let mut apr = Vec::new();
+    for swap_info in [&balanced_swap_info, &quote_heavy_swap_info] {
+        let return_data = simulate_return_data(
+            &mut banks_client,
+            get_reward_apr(deltafi_swap::id(), swap_info.pubkey, swap_info.pool_mint).unwrap(),
+            &payer,
+        )
+        .await;
+        apr.push(Decimal::from_scaled_val(u128::from_le_bytes(
+            return_data[..].try_into().unwrap(),
+        )));
+    }
+
+    // rewards pay out the mid price per lp token per claim period, so the apr
+    // is the number of claim periods in a year over the lp token value in mid
+    // prices, less rounding of the rate
+    let periods_per_year = Decimal::from(SECONDS_PER_YEAR)
+        .try_div(MIN_CLAIM_PERIOD as u64)
+        .unwrap();
+    for (apr, lp_value) in apr.iter().zip([2u64, 3u64]) {
+        let expected = periods_per_year.try_div(lp_value).unwrap();
+        assert!(*apr <= expected);
+        assert!(*apr > expected.try_mul(999u64).unwrap().try_div(1_000u64).unwrap());
+    }
+    assert!(apr[0] > apr[1]);
+}
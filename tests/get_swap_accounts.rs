@@ -0,0 +1,65 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::get_swap_accounts,
+    math::{Decimal, TryDiv},
+    processor::{authority_id, process},
+    state::SwapAccountsBundle,
+};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let return_data = simulate_return_data(
+        &mut banks_client,
+        get_swap_accounts(deltafi_swap::id(), swap_info.pubkey).unwrap(),
+        &payer,
+    )
+    .await;
+    let bundle = SwapAccountsBundle::unpack_from_slice(&return_data).unwrap();
+
+    assert_eq!(bundle.token_a, swap_info.token_a);
+    assert_eq!(bundle.token_b, swap_info.token_b);
+    assert_eq!(bundle.pool_mint, swap_info.pool_mint);
+    assert_eq!(bundle.authority, swap_info.authority);
+    assert_eq!(
+        bundle.authority,
+        authority_id(&deltafi_swap::id(), &swap_info.pubkey, swap_info.nonce).unwrap()
+    );
+}
@@ -9,14 +9,23 @@ use deltafi_swap::{
     instruction::{initialize, InitializeData},
     math::{Decimal, TryDiv},
     processor::process,
+    state::SwapInfo,
 };
+use solana_program::program_pack::Pack;
 use solana_program_test::*;
 use solana_sdk::{
+    account::AccountSharedData,
     instruction::InstructionError,
     pubkey::Pubkey,
     signature::{Keypair, Signer},
+    system_instruction::create_account,
     transaction::{Transaction, TransactionError},
 };
+use spl_token::{
+    instruction::{initialize_mint, set_authority, AuthorityType},
+    native_mint::DECIMALS,
+    state::{Account as Token, Mint},
+};
 use utils::*;
 
 #[tokio::test]
@@ -49,7 +58,8 @@ async fn test_success() {
         Some(&srm_mint.authority),
         &payer,
         user_accounts_owner.pubkey(),
-        800_000_000_000,
+        // SOL/SRM oracle price is 150 / 7, so this deposit is priced at it
+        900_000_000_000,
     )
     .await;
 
@@ -96,6 +106,10 @@ async fn test_success() {
                 .try_into()
                 .unwrap(),
             is_open_twap: true,
+            creator_fee_bps: 2_000,
+            invert_a: false,
+            invert_b: false,
+            slope_in_bps: false,
         },
     )
     .await;
@@ -103,6 +117,120 @@ async fn test_success() {
     test_swap_info.validate_state(&mut banks_client).await;
 }
 
+#[tokio::test]
+async fn test_slope_in_bps() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        900_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    let init_args = |slope| SwapInitArgs {
+        mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+        slope,
+        is_open_twap: true,
+        creator_fee_bps: 0,
+        invert_a: false,
+        invert_b: false,
+        slope_in_bps: true,
+    };
+
+    // above 10_000 bps the slope leaves [0, 1]
+    for slope in [10_001, u16::MAX as u64 + 1] {
+        let result = TestSwapInfo::try_init(
+            &mut banks_client,
+            &swap_config,
+            &sol_oracle,
+            &srm_oracle,
+            spl_token::native_mint::id(),
+            srm_mint.pubkey,
+            sol_user_account,
+            srm_user_account,
+            sol_admin_account,
+            srm_admin_account,
+            &user_accounts_owner,
+            &payer,
+            &init_args(slope),
+        )
+        .await;
+        assert_eq!(
+            result.err(),
+            Some(TransactionError::InstructionError(
+                7,
+                InstructionError::Custom(SwapError::InvalidSlope as u32)
+            ))
+        );
+    }
+
+    let test_swap_info = TestSwapInfo::init(
+        &mut banks_client,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        spl_token::native_mint::id(),
+        srm_mint.pubkey,
+        sol_user_account,
+        srm_user_account,
+        sol_admin_account,
+        srm_admin_account,
+        &user_accounts_owner,
+        &payer,
+        &init_args(5_000),
+    )
+    .await;
+    assert_eq!(
+        test_swap_info
+            .get_state(&mut banks_client)
+            .await
+            .pool_state
+            .slope,
+        Decimal::one().try_div(2).unwrap()
+    );
+}
+
 #[tokio::test]
 async fn test_already_initialized() {
     let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
@@ -146,10 +274,13 @@ async fn test_already_initialized() {
             existing_swap.admin_fee_b_key,
             existing_swap.token_a,
             existing_swap.token_b,
+            existing_swap.token_a_mint,
+            existing_swap.token_b_mint,
             existing_swap.pool_mint,
             existing_swap.pool_token,
             sol_oracle.price_pubkey,
             srm_oracle.price_pubkey,
+            user_account_owner.pubkey(),
             InitializeData {
                 nonce: existing_swap.nonce,
                 mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
@@ -161,13 +292,17 @@ async fn test_already_initialized() {
                     .try_into()
                     .unwrap(),
                 is_open_twap: true,
+                creator_fee_bps: 0,
+                invert_a: false,
+                invert_b: false,
+                slope_in_bps: false,
             },
         )
         .unwrap()],
         Some(&payer.pubkey()),
     );
 
-    transaction.sign(&[&payer], recent_blockhash);
+    transaction.sign(&[&payer, &user_account_owner], recent_blockhash);
     assert_eq!(
         banks_client
             .process_transaction(transaction)
@@ -180,3 +315,555 @@ async fn test_already_initialized() {
         )
     );
 }
+
+#[tokio::test]
+async fn test_divergent_initial_deposit() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        // half the oracle price of 150 / 7
+        450_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    let result = TestSwapInfo::try_init(
+        &mut banks_client,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        spl_token::native_mint::id(),
+        srm_mint.pubkey,
+        sol_user_account,
+        srm_user_account,
+        sol_admin_account,
+        srm_admin_account,
+        &user_accounts_owner,
+        &payer,
+        &SwapInitArgs {
+            mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            creator_fee_bps: 2_000,
+            invert_a: false,
+            invert_b: false,
+            slope_in_bps: false,
+        },
+    )
+    .await;
+
+    assert_eq!(
+        result.err(),
+        Some(TransactionError::InstructionError(
+            7,
+            InstructionError::Custom(SwapError::PriceDeviationTooLarge as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_admin_fee_account_is_reserve() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        900_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    for (admin_fee_a_key, admin_fee_b_key) in [
+        (sol_user_account, srm_admin_account),
+        (sol_admin_account, srm_user_account),
+        (srm_user_account, srm_admin_account),
+        (sol_admin_account, sol_user_account),
+    ] {
+        let result = TestSwapInfo::try_init(
+            &mut banks_client,
+            &swap_config,
+            &sol_oracle,
+            &srm_oracle,
+            spl_token::native_mint::id(),
+            srm_mint.pubkey,
+            sol_user_account,
+            srm_user_account,
+            admin_fee_a_key,
+            admin_fee_b_key,
+            &user_accounts_owner,
+            &payer,
+            &SwapInitArgs {
+                mid_price: sol_oracle
+                    .price
+                    .try_div(srm_oracle.price)
+                    .unwrap()
+                    .to_scaled_val()
+                    .unwrap(),
+                slope: Decimal::one()
+                    .try_div(2)
+                    .unwrap()
+                    .to_scaled_val()
+                    .unwrap()
+                    .try_into()
+                    .unwrap(),
+                is_open_twap: true,
+                creator_fee_bps: 2_000,
+                invert_a: false,
+                invert_b: false,
+                slope_in_bps: false,
+            },
+        )
+        .await;
+
+        assert_eq!(
+            result.err(),
+            Some(TransactionError::InstructionError(
+                7,
+                InstructionError::Custom(SwapError::InvalidAdmin as u32)
+            ))
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_prefunded_destination() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let mut context = test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        900_000_000_000,
+    )
+    .await;
+    let sol_admin_account = create_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        &payer,
+        None,
+        None,
+    )
+    .await;
+    let srm_admin_account = create_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        &payer,
+        None,
+        None,
+    )
+    .await;
+
+    let swap_info = Keypair::new();
+    let (swap_authority, nonce) =
+        Pubkey::find_program_address(&[swap_info.pubkey().as_ref()], &deltafi_swap::id());
+
+    let pool_mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_account(
+                &payer.pubkey(),
+                &pool_mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            initialize_mint(
+                &spl_token::id(),
+                &pool_mint.pubkey(),
+                &swap_authority,
+                None,
+                DECIMALS,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &pool_mint], context.last_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // a pool token account already holding tokens before any were minted
+    let destination = create_token_account(
+        &mut context.banks_client,
+        pool_mint.pubkey(),
+        &payer,
+        Some(user_accounts_owner.pubkey()),
+        None,
+    )
+    .await;
+    let mut account = context
+        .banks_client
+        .get_account(destination)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut token = Token::unpack(&account.data).unwrap();
+    token.amount = 1_000;
+    Token::pack(token, &mut account.data).unwrap();
+    context.set_account(&destination, &AccountSharedData::from(account));
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            set_authority(
+                &spl_token::id(),
+                &sol_user_account,
+                Some(&swap_authority),
+                AuthorityType::AccountOwner,
+                &user_accounts_owner.pubkey(),
+                &[],
+            )
+            .unwrap(),
+            set_authority(
+                &spl_token::id(),
+                &srm_user_account,
+                Some(&swap_authority),
+                AuthorityType::AccountOwner,
+                &user_accounts_owner.pubkey(),
+                &[],
+            )
+            .unwrap(),
+            create_account(
+                &payer.pubkey(),
+                &swap_info.pubkey(),
+                rent.minimum_balance(SwapInfo::LEN),
+                SwapInfo::LEN as u64,
+                &deltafi_swap::id(),
+            ),
+            initialize(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey(),
+                swap_authority,
+                sol_admin_account,
+                srm_admin_account,
+                sol_user_account,
+                srm_user_account,
+                spl_token::native_mint::id(),
+                srm_mint.pubkey,
+                pool_mint.pubkey(),
+                destination,
+                sol_oracle.price_pubkey,
+                srm_oracle.price_pubkey,
+                user_accounts_owner.pubkey(),
+                InitializeData {
+                    nonce,
+                    mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+                    slope: Decimal::one()
+                        .try_div(2)
+                        .unwrap()
+                        .to_scaled_val()
+                        .unwrap()
+                        .try_into()
+                        .unwrap(),
+                    is_open_twap: true,
+                    creator_fee_bps: 0,
+                    invert_a: false,
+                    invert_b: false,
+                    slope_in_bps: false,
+                },
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &swap_info],
+        recent_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            3,
+            InstructionError::Custom(SwapError::DestinationNotEmpty as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_wrong_pool_mint_decimals() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let mut context = test.start_with_context().await;
+    let payer = context.payer.insecure_clone();
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        900_000_000_000,
+    )
+    .await;
+    let sol_admin_account = create_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        &payer,
+        None,
+        None,
+    )
+    .await;
+    let srm_admin_account = create_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        &payer,
+        None,
+        None,
+    )
+    .await;
+
+    let swap_info = Keypair::new();
+    let (swap_authority, nonce) =
+        Pubkey::find_program_address(&[swap_info.pubkey().as_ref()], &deltafi_swap::id());
+
+    let pool_mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            create_account(
+                &payer.pubkey(),
+                &pool_mint.pubkey(),
+                rent.minimum_balance(Mint::LEN),
+                Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            initialize_mint(
+                &spl_token::id(),
+                &pool_mint.pubkey(),
+                &swap_authority,
+                None,
+                DECIMALS - 3,
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer, &pool_mint], context.last_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let destination = create_token_account(
+        &mut context.banks_client,
+        pool_mint.pubkey(),
+        &payer,
+        Some(user_accounts_owner.pubkey()),
+        None,
+    )
+    .await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            set_authority(
+                &spl_token::id(),
+                &sol_user_account,
+                Some(&swap_authority),
+                AuthorityType::AccountOwner,
+                &user_accounts_owner.pubkey(),
+                &[],
+            )
+            .unwrap(),
+            set_authority(
+                &spl_token::id(),
+                &srm_user_account,
+                Some(&swap_authority),
+                AuthorityType::AccountOwner,
+                &user_accounts_owner.pubkey(),
+                &[],
+            )
+            .unwrap(),
+            create_account(
+                &payer.pubkey(),
+                &swap_info.pubkey(),
+                rent.minimum_balance(SwapInfo::LEN),
+                SwapInfo::LEN as u64,
+                &deltafi_swap::id(),
+            ),
+            initialize(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey(),
+                swap_authority,
+                sol_admin_account,
+                srm_admin_account,
+                sol_user_account,
+                srm_user_account,
+                spl_token::native_mint::id(),
+                srm_mint.pubkey,
+                pool_mint.pubkey(),
+                destination,
+                sol_oracle.price_pubkey,
+                srm_oracle.price_pubkey,
+                user_accounts_owner.pubkey(),
+                InitializeData {
+                    nonce,
+                    mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+                    slope: Decimal::one()
+                        .try_div(2)
+                        .unwrap()
+                        .to_scaled_val()
+                        .unwrap()
+                        .try_into()
+                        .unwrap(),
+                    is_open_twap: true,
+                    creator_fee_bps: 0,
+                    invert_a: false,
+                    invert_b: false,
+                    slope_in_bps: false,
+                },
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(
+        &[&payer, &user_accounts_owner, &swap_info],
+        recent_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            3,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        )
+    );
+}
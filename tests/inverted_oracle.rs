@@ -0,0 +1,279 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::{convert::TryInto, str::FromStr};
+
+use deltafi_swap::{
+    error::SwapError,
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+    pyth,
+};
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    signature::{read_keypair_file, Keypair, Signer},
+    transaction::TransactionError,
+};
+use utils::*;
+
+const SOL_PRICE: u64 = 150;
+const SRM_PRICE: u64 = 8;
+
+/// SRM feed quoted as USD/SRM, i.e. SRM per dollar
+fn add_inverted_srm_oracle(test: &mut ProgramTest) -> TestOracle {
+    let oracle_program_id = read_keypair_file("tests/fixtures/pyth_program_id.json").unwrap();
+    let product_pubkey = Pubkey::from_str(SRM_PYTH_PRODUCT).unwrap();
+    let price_pubkey = Pubkey::from_str(SRM_PYTH_PRICE).unwrap();
+    let price = Decimal::one().try_div(SRM_PRICE).unwrap();
+
+    test.add_account_with_file_data(
+        product_pubkey,
+        u32::MAX as u64,
+        oracle_program_id.pubkey(),
+        &format!("{}.bin", product_pubkey),
+    );
+
+    let mut pyth_price_data = read_file(find_file(&format!("{}.bin", price_pubkey)).unwrap());
+    let pyth_price = pyth::load_mut::<pyth::Price>(pyth_price_data.as_mut_slice()).unwrap();
+    let decimals = 10u64.pow(pyth_price.expo.unsigned_abs());
+    pyth_price.valid_slot = 0;
+    pyth_price.agg.price = price
+        .try_mul(decimals)
+        .unwrap()
+        .try_round_u64()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    // the fixture confidence is sized for the USD quote and would read as volatile
+    pyth_price.agg.conf = 0;
+
+    test.add_account(
+        price_pubkey,
+        Account {
+            lamports: u32::MAX as u64,
+            data: pyth_price_data,
+            owner: oracle_program_id.pubkey(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+
+    TestOracle {
+        product_pubkey,
+        price_pubkey,
+        price,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn init_pool_market_price(
+    test: ProgramTest,
+    swap_config: &TestSwapConfig,
+    oracle_a: &TestOracle,
+    oracle_b: &TestOracle,
+    srm_mint: &TestMint,
+    srm_is_base: bool,
+    invert_a: bool,
+    invert_b: bool,
+) -> Result<Decimal, TransactionError> {
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let user_accounts_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_accounts_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_accounts_owner.pubkey(),
+        800_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    let sol = (
+        spl_token::native_mint::id(),
+        sol_user_account,
+        sol_admin_account,
+    );
+    let srm = (srm_mint.pubkey, srm_user_account, srm_admin_account);
+    let (token_a, token_b) = if srm_is_base { (srm, sol) } else { (sol, srm) };
+
+    let swap_info = TestSwapInfo::try_init(
+        &mut banks_client,
+        swap_config,
+        oracle_a,
+        oracle_b,
+        token_a.0,
+        token_b.0,
+        token_a.1,
+        token_b.1,
+        token_a.2,
+        token_b.2,
+        &user_accounts_owner,
+        &payer,
+        &SwapInitArgs {
+            mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            creator_fee_bps: 0,
+            invert_a,
+            invert_b,
+            slope_in_bps: false,
+        },
+    )
+    .await?;
+    swap_info.validate_state(&mut banks_client).await;
+
+    Ok(swap_info
+        .get_state(&mut banks_client)
+        .await
+        .pool_state
+        .market_price)
+}
+
+#[tokio::test]
+async fn test_inverted_feed() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_oracle(
+        &mut test,
+        Pubkey::from_str(SOL_PYTH_PRODUCT).unwrap(),
+        Pubkey::from_str(SOL_PYTH_PRICE).unwrap(),
+        Decimal::from(SOL_PRICE),
+    );
+    let srm_oracle = add_inverted_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let market_price = init_pool_market_price(
+        test,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        &srm_mint,
+        false,
+        false,
+        true,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        market_price,
+        Decimal::from(SOL_PRICE).try_div(SRM_PRICE).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_inverted_feed_without_flag() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_oracle(
+        &mut test,
+        Pubkey::from_str(SOL_PYTH_PRODUCT).unwrap(),
+        Pubkey::from_str(SOL_PYTH_PRICE).unwrap(),
+        Decimal::from(SOL_PRICE),
+    );
+    let srm_oracle = add_inverted_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    // the raw USD/SRM quote multiplies instead of divides by the SRM price,
+    // which lands far from the deposit ratio and is refused
+    let result = init_pool_market_price(
+        test,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        &srm_mint,
+        false,
+        false,
+        false,
+    )
+    .await;
+
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(
+            7,
+            InstructionError::Custom(SwapError::PriceDeviationTooLarge as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_quote_direction_follows_token_order() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_oracle(
+        &mut test,
+        Pubkey::from_str(SOL_PYTH_PRODUCT).unwrap(),
+        Pubkey::from_str(SOL_PYTH_PRICE).unwrap(),
+        Decimal::from(SOL_PRICE),
+    );
+    let srm_oracle = add_oracle(
+        &mut test,
+        Pubkey::from_str(SRM_PYTH_PRODUCT).unwrap(),
+        Pubkey::from_str(SRM_PYTH_PRICE).unwrap(),
+        Decimal::from(SRM_PRICE),
+    );
+    let srm_mint = add_srm_mint(&mut test);
+
+    // SRM is the base token, so the price must stay below one rather than
+    // being flipped to the larger ratio
+    let market_price = init_pool_market_price(
+        test,
+        &swap_config,
+        &srm_oracle,
+        &sol_oracle,
+        &srm_mint,
+        true,
+        false,
+        false,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        market_price,
+        Decimal::from(SRM_PRICE).try_div(SOL_PRICE).unwrap()
+    );
+}
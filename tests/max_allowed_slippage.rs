@@ -0,0 +1,151 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    curve::min_out_with_slippage,
+    error::SwapError,
+    instruction::{set_max_allowed_slippage, SwapDirection},
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+#[tokio::test]
+async fn test_max_allowed_slippage() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_max_allowed_slippage(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            500,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &swap_config.admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let state = swap_info.get_state(&mut context.banks_client).await;
+    assert_eq!(state.max_allowed_slippage_bps, 500);
+
+    let amount_in = 100_000_000;
+    let ideal_out = state
+        .pool_state
+        .clone()
+        .get_mid_price()
+        .unwrap()
+        .try_mul(amount_in)
+        .unwrap()
+        .try_floor_u64()
+        .unwrap();
+
+    let payer = context.payer.insecure_clone();
+    macro_rules! try_sell {
+        ($minimum_amount_out:expr) => {
+            swap_info.try_swap(
+                &mut context.banks_client,
+                &swap_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                amount_in,
+                $minimum_amount_out,
+                SwapDirection::SellBase,
+                &payer,
+            )
+        };
+    }
+
+    // a zero minimum accepts any slippage
+    assert_eq!(
+        try_sell!(0).await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::SlippageCapExceeded as u32)
+        ))
+    );
+    // just past the cap
+    assert_eq!(
+        try_sell!(min_out_with_slippage(ideal_out, 500).unwrap() - 1).await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::SlippageCapExceeded as u32)
+        ))
+    );
+    try_sell!(min_out_with_slippage(ideal_out, 400).unwrap())
+        .await
+        .unwrap();
+}
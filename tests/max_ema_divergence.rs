@@ -0,0 +1,73 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use assert_matches::*;
+
+use deltafi_swap::{error::SwapError, instruction::set_max_ema_divergence, processor::process};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+#[tokio::test]
+async fn test_set_max_ema_divergence() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let swap_config = add_swap_config(&mut test);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        swap_config
+            .get_state(&mut banks_client)
+            .await
+            .max_ema_divergence_bps,
+        0
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_max_ema_divergence(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.admin.pubkey(),
+            500,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    assert_eq!(
+        swap_config
+            .get_state(&mut banks_client)
+            .await
+            .max_ema_divergence_bps,
+        500
+    );
+
+    // only the admin can change it
+    let impostor = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            set_max_ema_divergence(deltafi_swap::id(), swap_config.pubkey, impostor.pubkey(), 0)
+                .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &impostor], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
@@ -0,0 +1,148 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_max_trade_amount, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+async fn set_cap(
+    context: &mut ProgramTestContext,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    max_trade_amount: u64,
+) {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_max_trade_amount(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            max_trade_amount,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &swap_config.admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .max_trade_amount,
+        max_trade_amount
+    );
+}
+
+#[tokio::test]
+async fn test_max_trade_amount() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let payer = context.payer.insecure_clone();
+    macro_rules! try_sell {
+        ($amount_in:expr) => {
+            swap_info.try_swap(
+                &mut context.banks_client,
+                &swap_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                $amount_in,
+                0,
+                SwapDirection::SellBase,
+                &payer,
+            )
+        };
+    }
+
+    set_cap(&mut context, &swap_config, &swap_info, 1_000_000_000).await;
+    assert_eq!(
+        try_sell!(1_000_000_001).await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::TradeTooLarge as u32)
+        ))
+    );
+    // a trade at the cap is accepted
+    try_sell!(1_000_000_000).await.unwrap();
+
+    set_cap(&mut context, &swap_config, &swap_info, 2_000_000_000).await;
+    try_sell!(1_500_000_000).await.unwrap();
+
+    set_cap(&mut context, &swap_config, &swap_info, 0).await;
+    try_sell!(3_000_000_000).await.unwrap();
+}
@@ -0,0 +1,209 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use assert_matches::*;
+
+use deltafi_swap::{
+    instruction::migrate_liquidity_provider,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{
+        LiquidityPosition, LiquidityProvider, LEGACY_LIQUIDITY_PROVIDER_SIZE,
+        LEGACY_MAX_LIQUIDITY_POSITIONS,
+    },
+};
+use solana_program::{
+    instruction::InstructionError, program_pack::Pack, pubkey::Pubkey, rent::Rent,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+#[tokio::test]
+async fn test_migrate_liquidity_provider() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_owner = Keypair::new();
+    let legacy_positions: Vec<LiquidityPosition> = (0..LEGACY_MAX_LIQUIDITY_POSITIONS)
+        .map(|_| LiquidityPosition {
+            pool: Pubkey::new_unique(),
+            liquidity_amount: 1_000,
+            cumulative_interest: 500,
+            ..LiquidityPosition::default()
+        })
+        .collect();
+
+    // legacy layout: 10 position slots with a u64 cumulative interest
+    let mut data = vec![1];
+    data.extend_from_slice(liquidity_owner.pubkey().as_ref());
+    data.push(legacy_positions.len() as u8);
+    for position in &legacy_positions {
+        data.extend_from_slice(position.pool.as_ref());
+        data.extend_from_slice(&position.liquidity_amount.to_le_bytes());
+        data.extend_from_slice(&position.rewards_owed.to_le_bytes());
+        data.extend_from_slice(&position.rewards_estimated.to_le_bytes());
+        data.extend_from_slice(&(position.cumulative_interest as u64).to_le_bytes());
+        data.extend_from_slice(&position.last_update_ts.to_le_bytes());
+        data.extend_from_slice(&position.next_claim_ts.to_le_bytes());
+    }
+    assert_eq!(data.len(), LEGACY_LIQUIDITY_PROVIDER_SIZE);
+
+    let liquidity_provider_pubkey = Pubkey::new_unique();
+    test.add_account(
+        liquidity_provider_pubkey,
+        Account {
+            lamports: Rent::default().minimum_balance(LEGACY_LIQUIDITY_PROVIDER_SIZE),
+            data,
+            owner: deltafi_swap::id(),
+            ..Account::default()
+        },
+    );
+    let liquidity_provider = TestLiquidityProvider {
+        pubkey: liquidity_provider_pubkey,
+        owner: liquidity_owner.pubkey(),
+        positions: legacy_positions.clone(),
+    };
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+
+    let srm_deposit_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        liquidity_owner.pubkey(),
+        200_000_000_000,
+    )
+    .await;
+
+    let pool_token_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_info.pool_mint,
+        None,
+        &payer,
+        liquidity_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    // legacy layout must be migrated before it can take new positions
+    assert_eq!(
+        swap_info
+            .try_deposit(
+                &mut banks_client,
+                &swap_config,
+                &liquidity_provider,
+                &liquidity_owner,
+                sol_deposit_account,
+                srm_deposit_account,
+                pool_token_account,
+                8_000_000_000,
+                160_000_000_000,
+                0,
+                &payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            2,
+            InstructionError::InvalidAccountData
+        ))
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[migrate_liquidity_provider(
+            deltafi_swap::id(),
+            liquidity_provider_pubkey,
+            liquidity_owner.pubkey(),
+            payer.pubkey(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &liquidity_owner], recent_blockhash);
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+    let lp = banks_client
+        .get_account(liquidity_provider_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(lp.data.len(), LiquidityProvider::LEN);
+    assert!(lp.lamports >= Rent::default().minimum_balance(LiquidityProvider::LEN));
+    let lp_state = LiquidityProvider::unpack(&lp.data[..]).unwrap();
+    assert_eq!(lp_state.owner, liquidity_owner.pubkey());
+    assert_eq!(lp_state.positions, legacy_positions);
+
+    assert_eq!(
+        swap_info
+            .try_deposit(
+                &mut banks_client,
+                &swap_config,
+                &liquidity_provider,
+                &liquidity_owner,
+                sol_deposit_account,
+                srm_deposit_account,
+                pool_token_account,
+                8_000_000_000,
+                160_000_000_000,
+                0,
+                &payer,
+            )
+            .await,
+        Ok(())
+    );
+
+    let lp = banks_client
+        .get_account(liquidity_provider_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let lp_state = LiquidityProvider::unpack(&lp.data[..]).unwrap();
+    assert_eq!(lp_state.positions.len(), LEGACY_MAX_LIQUIDITY_POSITIONS + 1);
+    assert_eq!(
+        lp_state.positions[LEGACY_MAX_LIQUIDITY_POSITIONS].liquidity_amount,
+        get_token_balance(&mut banks_client, pool_token_account).await
+    );
+}
@@ -0,0 +1,173 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use assert_matches::*;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::set_min_pool_supply,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+#[tokio::test]
+async fn test_withdraw_respects_floor() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    // initial shares never exceed the base amount, so this covers the supply
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 42_000_000_000);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    // the user holds the whole pool token supply
+    let supply = get_token_balance(&mut banks_client, swap_info.pool_token).await;
+    let min_pool_supply = supply / 4;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_min_pool_supply(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.admin.pubkey(),
+            min_pool_supply,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    assert_eq!(
+        swap_config
+            .get_state(&mut banks_client)
+            .await
+            .min_pool_supply,
+        min_pool_supply
+    );
+
+    let sol_withdraw_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let srm_withdraw_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    // down to the floor
+    assert_matches!(
+        swap_info
+            .try_withdraw(
+                &mut banks_client,
+                &liquidity_provider,
+                &user_account_owner,
+                sol_withdraw_account,
+                srm_withdraw_account,
+                swap_info.pool_token,
+                supply - min_pool_supply,
+                0,
+                0,
+                &payer,
+            )
+            .await,
+        Ok(())
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, swap_info.pool_token).await,
+        min_pool_supply
+    );
+
+    // a partial withdraw below the floor
+    assert_eq!(
+        swap_info
+            .try_withdraw(
+                &mut banks_client,
+                &liquidity_provider,
+                &user_account_owner,
+                sol_withdraw_account,
+                srm_withdraw_account,
+                swap_info.pool_token,
+                1,
+                0,
+                0,
+                &payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::WithdrawNotEnough as u32)
+        ))
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, swap_info.pool_token).await,
+        min_pool_supply
+    );
+
+    // closing the pool out entirely is still allowed
+    assert_matches!(
+        swap_info
+            .try_withdraw(
+                &mut banks_client,
+                &liquidity_provider,
+                &user_account_owner,
+                sol_withdraw_account,
+                srm_withdraw_account,
+                swap_info.pool_token,
+                min_pool_supply,
+                0,
+                0,
+                &payer,
+            )
+            .await,
+        Ok(())
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, swap_info.pool_token).await,
+        0
+    );
+}
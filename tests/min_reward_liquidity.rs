@@ -0,0 +1,132 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use assert_matches::*;
+
+use deltafi_swap::{
+    instruction::{get_claim_status, refresh_liquidity_obligation, set_min_reward_liquidity},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{ClaimStatus, MIN_CLAIM_PERIOD},
+};
+use solana_program::{clock::Clock, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+const MIN_REWARD_LIQUIDITY: u64 = 1_000_000;
+
+async fn claim_status(
+    context: &mut ProgramTestContext,
+    swap_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+) -> ClaimStatus {
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        get_claim_status(deltafi_swap::id(), liquidity_provider_pubkey, swap_pubkey).unwrap(),
+        &context.payer,
+    )
+    .await;
+    ClaimStatus::unpack_from_slice(&return_data).unwrap()
+}
+
+#[tokio::test]
+async fn test_dust_position_accrues_nothing() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let dust = add_position(
+        &mut test,
+        &swap_info,
+        &user_account_owner,
+        MIN_REWARD_LIQUIDITY - 1,
+    );
+    let eligible = add_position(
+        &mut test,
+        &swap_info,
+        &user_account_owner,
+        MIN_REWARD_LIQUIDITY,
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_min_reward_liquidity(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            MIN_REWARD_LIQUIDITY,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, &swap_config.admin], recent_blockhash);
+    assert_matches!(
+        context.banks_client.process_transaction(transaction).await,
+        Ok(())
+    );
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .min_reward_liquidity,
+        MIN_REWARD_LIQUIDITY
+    );
+
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = MIN_CLAIM_PERIOD / 2;
+    context.set_sysvar(&clock);
+
+    let mut transaction = Transaction::new_with_payer(
+        &[refresh_liquidity_obligation(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            vec![dust.pubkey, eligible.pubkey],
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    assert_matches!(
+        context.banks_client.process_transaction(transaction).await,
+        Ok(())
+    );
+
+    let dust_status = claim_status(&mut context, swap_info.pubkey, dust.pubkey).await;
+    assert_eq!(dust_status.rewards_estimated, 0);
+    let eligible_status = claim_status(&mut context, swap_info.pubkey, eligible.pubkey).await;
+    assert!(eligible_status.rewards_estimated > 0);
+}
@@ -0,0 +1,292 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    curve::{MAX_MARKET_PRICE, MIN_MARKET_PRICE},
+    error::SwapError,
+    instruction::{set_manual_price, set_oracle_free, SwapDirection},
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+};
+use solana_program::{instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+async fn try_admin(
+    context: &mut ProgramTestContext,
+    instruction: solana_program::instruction::Instruction,
+    admin: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, admin], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_oracle_free_swap() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let market_price = sol_oracle.price.try_div(srm_oracle.price).unwrap();
+    let mut swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price,
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let manual_price = market_price.try_mul(Decimal::from_percent(110)).unwrap();
+    let set_price = |market_price: u128| {
+        set_manual_price(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            market_price,
+        )
+        .unwrap()
+    };
+
+    // the oracle still prices the pool
+    assert_eq!(
+        try_admin(
+            &mut context,
+            set_price(manual_price.to_scaled_val().unwrap()),
+            &swap_config.admin
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::NotOracleFree as u32)
+        ))
+    );
+
+    try_admin(
+        &mut context,
+        set_oracle_free(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            true,
+        )
+        .unwrap(),
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    for (market_price, error) in [
+        (MIN_MARKET_PRICE - 1, SwapError::MarketPriceTooLow),
+        (MAX_MARKET_PRICE + 1, SwapError::MarketPriceTooHigh),
+    ] {
+        assert_eq!(
+            try_admin(&mut context, set_price(market_price), &swap_config.admin).await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(error as u32)
+            ))
+        );
+    }
+    try_admin(
+        &mut context,
+        set_price(manual_price.to_scaled_val().unwrap()),
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .pool_state
+            .market_price,
+        manual_price
+    );
+
+    // the pyth accounts are not read in oracle-free mode
+    swap_info.oracle_a = Pubkey::new_unique();
+    swap_info.oracle_b = Pubkey::new_unique();
+    swap_info
+        .swap(
+            &mut context.banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+    assert!(get_token_balance(&mut context.banks_client, srm_user_account).await > 0);
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .pool_state
+            .market_price,
+        manual_price
+    );
+}
+
+#[tokio::test]
+async fn test_manual_price_requires_admin() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let market_price = sol_oracle.price.try_div(srm_oracle.price).unwrap();
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price,
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let not_admin = Keypair::new();
+    assert_eq!(
+        try_admin(
+            &mut context,
+            set_oracle_free(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey,
+                not_admin.pubkey(),
+                true,
+            )
+            .unwrap(),
+            &not_admin,
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        ))
+    );
+
+    try_admin(
+        &mut context,
+        set_oracle_free(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            true,
+        )
+        .unwrap(),
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        try_admin(
+            &mut context,
+            set_manual_price(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey,
+                not_admin.pubkey(),
+                market_price.to_scaled_val().unwrap(),
+            )
+            .unwrap(),
+            &not_admin,
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        ))
+    );
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .pool_state
+            .market_price,
+        market_price
+    );
+}
@@ -0,0 +1,190 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{set_max_oracle_move, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    pyth,
+    state::ORACLE_MOVE_WINDOW,
+};
+use solana_program::{clock::Clock, instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use std::convert::TryInto;
+use utils::*;
+
+async fn set_oracle_price(context: &mut ProgramTestContext, price_pubkey: Pubkey, price: u64) {
+    let mut account = context
+        .banks_client
+        .get_account(price_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let pyth_price = pyth::load_mut::<pyth::Price>(account.data.as_mut_slice()).unwrap();
+    let decimals = 10u64.pow(pyth_price.expo.unsigned_abs());
+    pyth_price.agg.price = (price * decimals).try_into().unwrap();
+    context.set_account(&price_pubkey, &AccountSharedData::from(account));
+}
+
+#[tokio::test]
+async fn test_oracle_jump_rejected() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let mut transaction = Transaction::new_with_payer(
+        &[set_max_oracle_move(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            1_000,
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    transaction.sign(
+        &[&context.payer, &swap_config.admin],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    // the first swap records the oracle price
+    swap_info
+        .swap(
+            &mut context.banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .last_oracle_price,
+        sol_oracle.price.try_div(srm_oracle.price).unwrap()
+    );
+
+    // SOL jumps 30% before the next swap
+    set_oracle_price(&mut context, sol_oracle.price_pubkey, 195).await;
+    assert_eq!(
+        swap_info
+            .try_swap(
+                &mut context.banks_client,
+                &swap_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                1_000_000_000,
+                0,
+                SwapDirection::SellBase,
+                &context.payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::OraclePriceMoveTooLarge as u32)
+        ))
+    );
+
+    // once the window has passed the new price is accepted
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp += ORACLE_MOVE_WINDOW as i64 + 1;
+    context.set_sysvar(&clock);
+    swap_info
+        .swap(
+            &mut context.banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+    assert_eq!(
+        swap_info
+            .get_state(&mut context.banks_client)
+            .await
+            .last_oracle_price,
+        Decimal::from(195u64).try_div(srm_oracle.price).unwrap()
+    );
+}
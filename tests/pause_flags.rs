@@ -0,0 +1,293 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{pause, unpause, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::PauseFlags,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+struct PauseTestContext {
+    banks_client: BanksClient,
+    payer: Keypair,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    liquidity_provider: TestLiquidityProvider,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+    deltafi_user_account: Pubkey,
+}
+
+async fn setup() -> PauseTestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        100_000_000_000,
+    )
+    .await;
+
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    PauseTestContext {
+        banks_client,
+        payer,
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+        deltafi_user_account,
+    }
+}
+
+fn expected_result(
+    pause_flags: PauseFlags,
+    operation: PauseFlags,
+    instruction_index: u8,
+) -> Result<(), TransactionError> {
+    if pause_flags.contains(operation) {
+        Err(TransactionError::InstructionError(
+            instruction_index,
+            InstructionError::Custom(SwapError::IsPaused as u32),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+async fn check_operations(ctx: &mut PauseTestContext, pause_flags: PauseFlags) {
+    assert_eq!(
+        ctx.swap_info
+            .try_swap(
+                &mut ctx.banks_client,
+                &ctx.swap_config,
+                &ctx.user_account_owner,
+                ctx.sol_user_account,
+                ctx.srm_user_account,
+                ctx.deltafi_user_account,
+                1_000_000_000,
+                0,
+                SwapDirection::SellBase,
+                &ctx.payer,
+            )
+            .await,
+        expected_result(pause_flags, PauseFlags::SWAPS, 1)
+    );
+
+    assert_eq!(
+        ctx.swap_info
+            .try_deposit(
+                &mut ctx.banks_client,
+                &ctx.swap_config,
+                &ctx.liquidity_provider,
+                &ctx.user_account_owner,
+                ctx.sol_user_account,
+                ctx.srm_user_account,
+                ctx.swap_info.pool_token,
+                1_000_000_000,
+                19_000_000_000,
+                0,
+                &ctx.payer,
+            )
+            .await,
+        // deposit follows two approvals
+        expected_result(pause_flags, PauseFlags::DEPOSITS, 2)
+    );
+
+    assert_eq!(
+        ctx.swap_info
+            .try_withdraw(
+                &mut ctx.banks_client,
+                &ctx.liquidity_provider,
+                &ctx.user_account_owner,
+                ctx.sol_user_account,
+                ctx.srm_user_account,
+                ctx.swap_info.pool_token,
+                1_000_000_000,
+                0,
+                0,
+                &ctx.payer,
+            )
+            .await,
+        expected_result(pause_flags, PauseFlags::WITHDRAWALS, 1)
+    );
+}
+
+async fn check_pause_flags(pause_flags: PauseFlags) {
+    let mut ctx = setup().await;
+
+    ctx.swap_info
+        .set_pause_flags(
+            &mut ctx.banks_client,
+            &ctx.swap_config,
+            pause_flags,
+            &ctx.payer,
+        )
+        .await;
+    assert_eq!(
+        ctx.swap_info
+            .get_state(&mut ctx.banks_client)
+            .await
+            .pause_flags,
+        pause_flags
+    );
+
+    check_operations(&mut ctx, pause_flags).await;
+}
+
+fn flags(list: &[PauseFlags]) -> PauseFlags {
+    let mut pause_flags = PauseFlags::NONE;
+    for flag in list {
+        pause_flags.insert(*flag);
+    }
+    pause_flags
+}
+
+#[tokio::test]
+async fn test_pause_none() {
+    check_pause_flags(PauseFlags::NONE).await;
+}
+
+#[tokio::test]
+async fn test_pause_swaps() {
+    check_pause_flags(PauseFlags::SWAPS).await;
+}
+
+#[tokio::test]
+async fn test_pause_deposits() {
+    check_pause_flags(PauseFlags::DEPOSITS).await;
+}
+
+#[tokio::test]
+async fn test_pause_withdrawals() {
+    check_pause_flags(PauseFlags::WITHDRAWALS).await;
+}
+
+#[tokio::test]
+async fn test_pause_swaps_and_deposits() {
+    check_pause_flags(flags(&[PauseFlags::SWAPS, PauseFlags::DEPOSITS])).await;
+}
+
+#[tokio::test]
+async fn test_pause_swaps_and_withdrawals() {
+    check_pause_flags(flags(&[PauseFlags::SWAPS, PauseFlags::WITHDRAWALS])).await;
+}
+
+#[tokio::test]
+async fn test_pause_deposits_and_withdrawals() {
+    check_pause_flags(flags(&[PauseFlags::DEPOSITS, PauseFlags::WITHDRAWALS])).await;
+}
+
+#[tokio::test]
+async fn test_pause_all() {
+    check_pause_flags(PauseFlags::ALL).await;
+}
+
+#[tokio::test]
+async fn test_pause_and_unpause() {
+    let mut ctx = setup().await;
+
+    for (instruction, pause_flags) in [
+        (
+            pause as fn(_, _, _, _) -> Result<Instruction, _>,
+            PauseFlags::ALL,
+        ),
+        (unpause, PauseFlags::NONE),
+    ] {
+        let mut transaction = Transaction::new_with_payer(
+            &[instruction(
+                deltafi_swap::id(),
+                ctx.swap_config.pubkey,
+                ctx.swap_info.pubkey,
+                ctx.swap_config.admin.pubkey(),
+            )
+            .unwrap()],
+            Some(&ctx.payer.pubkey()),
+        );
+        let recent_blockhash = ctx.banks_client.get_recent_blockhash().await.unwrap();
+        transaction.sign(&[&ctx.payer, &ctx.swap_config.admin], recent_blockhash);
+        ctx.banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap())
+            .unwrap();
+
+        assert_eq!(
+            ctx.swap_info
+                .get_state(&mut ctx.banks_client)
+                .await
+                .pause_flags,
+            pause_flags
+        );
+        check_operations(&mut ctx, pause_flags).await;
+    }
+}
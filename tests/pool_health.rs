@@ -0,0 +1,162 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    health::{PoolHealth, MAX_HEALTH_SCORE},
+    instruction::get_pool_health,
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+    state::PauseFlags,
+};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use utils::*;
+
+struct HealthTestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    sol_oracle: TestOracle,
+    srm_oracle: TestOracle,
+}
+
+/// Balanced pool created at `market_price` against SOL $150 / SRM $7 oracles
+async fn setup(market_price: Decimal) -> HealthTestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: market_price
+                .try_mul(42_000_000_000)
+                .unwrap()
+                .try_floor_u64()
+                .unwrap(),
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price,
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    HealthTestContext {
+        context: test.start_with_context().await,
+        swap_config,
+        swap_info,
+        sol_oracle,
+        srm_oracle,
+    }
+}
+
+async fn pool_health(ctx: &mut HealthTestContext) -> (u8, PoolHealth) {
+    let data = simulate_return_data(
+        &mut ctx.context.banks_client,
+        get_pool_health(
+            deltafi_swap::id(),
+            ctx.swap_info.pubkey,
+            ctx.sol_oracle.price_pubkey,
+            ctx.srm_oracle.price_pubkey,
+        )
+        .unwrap(),
+        &ctx.context.payer,
+    )
+    .await;
+    (data[0], PoolHealth::unpack_from_slice(&data).unwrap())
+}
+
+fn oracle_price() -> Decimal {
+    Decimal::from(150u64).try_div(7).unwrap()
+}
+
+fn healthy() -> PoolHealth {
+    PoolHealth {
+        oracle_fresh: true,
+        deviation_within_bounds: true,
+        not_paused: true,
+        reserves_balanced: true,
+    }
+}
+
+#[tokio::test]
+async fn test_healthy_pool() {
+    let mut ctx = setup(oracle_price()).await;
+
+    assert_eq!(pool_health(&mut ctx).await, (MAX_HEALTH_SCORE, healthy()));
+}
+
+#[tokio::test]
+async fn test_paused_pool() {
+    let mut ctx = setup(oracle_price()).await;
+    let payer = ctx.context.payer.insecure_clone();
+    ctx.swap_info
+        .set_pause_flags(
+            &mut ctx.context.banks_client,
+            &ctx.swap_config,
+            PauseFlags::DEPOSITS,
+            &payer,
+        )
+        .await;
+
+    assert_eq!(
+        pool_health(&mut ctx).await,
+        (
+            75,
+            PoolHealth {
+                not_paused: false,
+                ..healthy()
+            }
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_divergent_pool() {
+    let mut ctx = setup(Decimal::from(165u64).try_div(7).unwrap()).await;
+
+    assert_eq!(
+        pool_health(&mut ctx).await,
+        (
+            75,
+            PoolHealth {
+                deviation_within_bounds: false,
+                ..healthy()
+            }
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_stale_oracle() {
+    let mut ctx = setup(oracle_price()).await;
+    ctx.context.warp_to_slot(100).unwrap();
+
+    assert_eq!(
+        pool_health(&mut ctx).await,
+        (
+            50,
+            PoolHealth {
+                oracle_fresh: false,
+                deviation_within_bounds: false,
+                ..healthy()
+            }
+        )
+    );
+}
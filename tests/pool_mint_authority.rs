@@ -0,0 +1,163 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{instruction::InstructionError, program_option::COption, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    program_pack::Pack,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+use spl_token::state::Mint;
+use utils::*;
+
+struct MintAuthorityTestContext {
+    context: ProgramTestContext,
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    liquidity_provider: TestLiquidityProvider,
+    user_account_owner: Keypair,
+    sol_user_account: Pubkey,
+    srm_user_account: Pubkey,
+}
+
+/// Set up a pool whose mint authority was moved away from the swap authority
+async fn setup() -> MintAuthorityTestContext {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+
+    let mut context = test.start_with_context().await;
+
+    let mut pool_mint_account: Account = context
+        .banks_client
+        .get_account(swap_info.pool_mint)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut pool_mint = Mint::unpack(&pool_mint_account.data).unwrap();
+    pool_mint.mint_authority = COption::Some(Pubkey::new_unique());
+    Mint::pack(pool_mint, &mut pool_mint_account.data).unwrap();
+    context.set_account(
+        &swap_info.pool_mint,
+        &AccountSharedData::from(pool_mint_account),
+    );
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        100_000_000_000,
+    )
+    .await;
+
+    MintAuthorityTestContext {
+        context,
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+        sol_user_account,
+        srm_user_account,
+    }
+}
+
+#[tokio::test]
+async fn test_deposit() {
+    let mut ctx = setup().await;
+
+    assert_eq!(
+        ctx.swap_info
+            .try_deposit(
+                &mut ctx.context.banks_client,
+                &ctx.swap_config,
+                &ctx.liquidity_provider,
+                &ctx.user_account_owner,
+                ctx.sol_user_account,
+                ctx.srm_user_account,
+                ctx.swap_info.pool_token,
+                1_000_000_000,
+                19_000_000_000,
+                0,
+                &ctx.context.payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_withdraw() {
+    let mut ctx = setup().await;
+
+    assert_eq!(
+        ctx.swap_info
+            .try_withdraw(
+                &mut ctx.context.banks_client,
+                &ctx.liquidity_provider,
+                &ctx.user_account_owner,
+                ctx.sol_user_account,
+                ctx.srm_user_account,
+                ctx.swap_info.pool_token,
+                1_000_000_000,
+                0,
+                0,
+                &ctx.context.payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        ))
+    );
+}
@@ -0,0 +1,157 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::preview_fee_change,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::Fees,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    const SAMPLE_AMOUNT: u64 = 1_000_000_000;
+    let current_fees = swap_info.get_state(&mut banks_client).await.fees;
+    let new_fees = Fees {
+        trade_fee_numerator: current_fees.trade_fee_numerator * 2,
+        ..current_fees.clone()
+    };
+    assert_eq!(new_fees.validate_against_policy(), Ok(()));
+
+    let return_data = simulate_return_data(
+        &mut banks_client,
+        preview_fee_change(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            SAMPLE_AMOUNT,
+            new_fees.clone(),
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(return_data.len(), 32);
+    let values: Vec<u64> = return_data
+        .chunks(8)
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .collect();
+
+    let (current_net, current_admin_fee, _) = current_fees
+        .apply_trade_fees(SAMPLE_AMOUNT, Decimal::one())
+        .unwrap();
+    let (new_net, new_admin_fee, _) = new_fees
+        .apply_trade_fees(SAMPLE_AMOUNT, Decimal::one())
+        .unwrap();
+    assert_eq!(
+        values,
+        vec![current_net, current_admin_fee, new_net, new_admin_fee]
+    );
+    // doubling the trade fee costs the trader and pays the admin more
+    assert!(new_net < current_net);
+    assert!(new_admin_fee > current_admin_fee);
+
+    // the preview leaves the pool fees untouched
+    assert_eq!(
+        swap_info.get_state(&mut banks_client).await.fees,
+        current_fees
+    );
+}
+
+#[tokio::test]
+async fn test_fees_outside_policy() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let current_fees = swap_info.get_state(&mut banks_client).await.fees;
+    // a 100% trade fee
+    let new_fees = Fees {
+        trade_fee_numerator: current_fees.trade_fee_denominator,
+        ..current_fees
+    };
+
+    let mut transaction = Transaction::new_with_payer(
+        &[preview_fee_change(deltafi_swap::id(), swap_info.pubkey, 1_000_000, new_fees).unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[&payer], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::FeeExceedsPolicy as u32)
+        ))
+    );
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::preview_redeem,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::{pubkey::Pubkey, signature::Keypair};
+use spl_token::state::Mint;
+use std::convert::TryInto;
+use utils::*;
+
+async fn get_redeem_amounts(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    swap_info: &TestSwapInfo,
+    pyth_a: Pubkey,
+    pyth_b: Pubkey,
+    pool_token_amount: u64,
+) -> (u64, u64) {
+    let data = simulate_return_data(
+        banks_client,
+        preview_redeem(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.pool_mint,
+            pyth_a,
+            pyth_b,
+            pool_token_amount,
+        )
+        .unwrap(),
+        payer,
+    )
+    .await;
+    assert_eq!(data.len(), 16);
+    (
+        u64::from_le_bytes(data[..8].try_into().unwrap()),
+        u64::from_le_bytes(data[8..].try_into().unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_preview_redeem() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let pool_mint = banks_client
+        .get_account(swap_info.pool_mint)
+        .await
+        .unwrap()
+        .unwrap();
+    let total_supply = Mint::unpack(&pool_mint.data).unwrap().supply;
+
+    // the whole supply redeems every reserve
+    let full = get_redeem_amounts(
+        &mut banks_client,
+        &payer,
+        &swap_info,
+        sol_oracle.price_pubkey,
+        srm_oracle.price_pubkey,
+        total_supply,
+    )
+    .await;
+    assert_eq!(
+        full,
+        (
+            get_token_balance(&mut banks_client, swap_info.token_a).await,
+            get_token_balance(&mut banks_client, swap_info.token_b).await,
+        )
+    );
+
+    // smaller redemptions take the same share of each reserve, rounded down
+    for parts in [2, 3, 4, 7] {
+        let pool_token_amount = total_supply / parts;
+        let (base_amount, quote_amount) = get_redeem_amounts(
+            &mut banks_client,
+            &payer,
+            &swap_info,
+            sol_oracle.price_pubkey,
+            srm_oracle.price_pubkey,
+            pool_token_amount,
+        )
+        .await;
+        let pro_rata = |reserve: u64| {
+            (reserve as u128 * pool_token_amount as u128 / total_supply as u128) as u64
+        };
+        assert_eq!(base_amount, pro_rata(full.0));
+        assert_eq!(quote_amount, pro_rata(full.1));
+    }
+}
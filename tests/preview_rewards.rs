@@ -0,0 +1,137 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use assert_matches::*;
+
+use deltafi_swap::{
+    instruction::{get_claim_status, preview_rewards, refresh_liquidity_obligation},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{ClaimStatus, MIN_CLAIM_PERIOD},
+};
+use solana_program::{clock::Clock, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+async fn preview(
+    context: &mut ProgramTestContext,
+    swap_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+) -> ClaimStatus {
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        preview_rewards(deltafi_swap::id(), swap_pubkey, liquidity_provider_pubkey).unwrap(),
+        &context.payer,
+    )
+    .await;
+    ClaimStatus::unpack_from_slice(&return_data).unwrap()
+}
+
+async fn claim_status(
+    context: &mut ProgramTestContext,
+    swap_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+) -> ClaimStatus {
+    let return_data = simulate_return_data(
+        &mut context.banks_client,
+        get_claim_status(deltafi_swap::id(), liquidity_provider_pubkey, swap_pubkey).unwrap(),
+        &context.payer,
+    )
+    .await;
+    ClaimStatus::unpack_from_slice(&return_data).unwrap()
+}
+
+async fn refresh(
+    context: &mut ProgramTestContext,
+    swap_pubkey: Pubkey,
+    liquidity_provider_pubkey: Pubkey,
+) {
+    let mut transaction = Transaction::new_with_payer(
+        &[refresh_liquidity_obligation(
+            deltafi_swap::id(),
+            swap_pubkey,
+            vec![liquidity_provider_pubkey],
+        )
+        .unwrap()],
+        Some(&context.payer.pubkey()),
+    );
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer], recent_blockhash);
+    assert_matches!(
+        context.banks_client.process_transaction(transaction).await,
+        Ok(())
+    );
+}
+
+#[tokio::test]
+async fn test_preview_matches_refresh() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    // position is created at timestamp 0, so its next claim is at MIN_CLAIM_PERIOD
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+
+    let mut context = test.start_with_context().await;
+
+    // halfway through the claim period rewards are only estimated
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = MIN_CLAIM_PERIOD / 2;
+    context.set_sysvar(&clock);
+
+    let previewed = preview(&mut context, swap_info.pubkey, liquidity_provider.pubkey).await;
+    assert!(previewed.rewards_estimated > 0);
+    assert_eq!(previewed.rewards_owed, 0);
+
+    // previewing leaves the position untouched
+    let stored = claim_status(&mut context, swap_info.pubkey, liquidity_provider.pubkey).await;
+    assert_eq!(stored.rewards_estimated, 0);
+
+    refresh(&mut context, swap_info.pubkey, liquidity_provider.pubkey).await;
+    let refreshed = claim_status(&mut context, swap_info.pubkey, liquidity_provider.pubkey).await;
+    assert_eq!(previewed, refreshed);
+
+    // past the claim timestamp the estimate rolls into owed rewards
+    clock.unix_timestamp = MIN_CLAIM_PERIOD + 100;
+    context.set_sysvar(&clock);
+
+    let previewed = preview(&mut context, swap_info.pubkey, liquidity_provider.pubkey).await;
+    assert!(previewed.rewards_owed > refreshed.rewards_estimated);
+    assert_eq!(previewed.rewards_estimated, 0);
+    assert_eq!(previewed.next_claim_ts, 2 * MIN_CLAIM_PERIOD);
+
+    refresh(&mut context, swap_info.pubkey, liquidity_provider.pubkey).await;
+    let refreshed = claim_status(&mut context, swap_info.pubkey, liquidity_provider.pubkey).await;
+    assert_eq!(previewed, refreshed);
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::preview_withdraw,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program_test::*;
+use solana_sdk::signature::{Keypair, Signer};
+use std::convert::TryInto;
+use utils::*;
+
+#[tokio::test]
+async fn test_preview_withdraw_matches_withdraw() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider =
+        add_position(&mut test, &swap_info, &user_account_owner, 2_000_000_000);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let pool_token_amount = 1_000_000_000;
+    let data = simulate_return_data(
+        &mut banks_client,
+        preview_withdraw(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            swap_info.pool_mint,
+            pool_token_amount,
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(data.len(), 16);
+    let preview_a = u64::from_le_bytes(data[..8].try_into().unwrap());
+    let preview_b = u64::from_le_bytes(data[8..].try_into().unwrap());
+    assert!(preview_a > 0 && preview_b > 0);
+
+    swap_info
+        .withdraw(
+            &mut banks_client,
+            &liquidity_provider,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            swap_info.pool_token,
+            pool_token_amount,
+            0,
+            0,
+            &payer,
+        )
+        .await;
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, sol_user_account).await,
+        preview_a
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, srm_user_account).await,
+        preview_b
+    );
+}
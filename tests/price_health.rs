@@ -0,0 +1,101 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use deltafi_swap::{
+    instruction::get_price_health,
+    math::{Decimal, TryDiv, TryMul},
+    processor::process,
+    state::PauseFlags,
+};
+use solana_program_test::*;
+use solana_sdk::signature::Keypair;
+use utils::*;
+
+/// Pool mid price, oracle price and their deviation in bps for a balanced
+/// pool created at `market_price`, against SOL $150 / SRM $7 oracles
+async fn price_health(market_price: Decimal, pause_flags: PauseFlags) -> (Decimal, Decimal, u64) {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: market_price
+                .try_mul(42_000_000_000)
+                .unwrap()
+                .try_floor_u64()
+                .unwrap(),
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price,
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+    swap_info
+        .set_pause_flags(&mut banks_client, &swap_config, pause_flags, &payer)
+        .await;
+
+    let data = simulate_return_data(
+        &mut banks_client,
+        get_price_health(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            sol_oracle.price_pubkey,
+            srm_oracle.price_pubkey,
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await;
+    assert_eq!(data.len(), 40);
+    (
+        Decimal::from_scaled_val(u128::from_le_bytes(data[..16].try_into().unwrap())),
+        Decimal::from_scaled_val(u128::from_le_bytes(data[16..32].try_into().unwrap())),
+        u64::from_le_bytes(data[32..].try_into().unwrap()),
+    )
+}
+
+#[tokio::test]
+async fn test_aligned_prices() {
+    let oracle_price = Decimal::from(150u64).try_div(7).unwrap();
+
+    let (mid_price, reported_oracle_price, deviation_bps) =
+        price_health(oracle_price, PauseFlags::NONE).await;
+
+    assert_eq!(mid_price, oracle_price);
+    assert_eq!(reported_oracle_price, oracle_price);
+    assert_eq!(deviation_bps, 0);
+}
+
+#[tokio::test]
+async fn test_divergent_prices_on_paused_pool() {
+    let oracle_price = Decimal::from(150u64).try_div(7).unwrap();
+    let pool_price = Decimal::from(165u64).try_div(7).unwrap();
+
+    let (mid_price, reported_oracle_price, deviation_bps) =
+        price_health(pool_price, PauseFlags::ALL).await;
+
+    assert_eq!(mid_price, pool_price);
+    assert_eq!(reported_oracle_price, oracle_price);
+    assert_eq!(deviation_bps, 1_000);
+}
@@ -0,0 +1,157 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::resync_claim_schedule,
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{LiquidityPosition, LiquidityProvider, MIN_CLAIM_PERIOD},
+};
+use solana_program::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+/// Claim period the positions were scheduled under
+const OLD_CLAIM_PERIOD: i64 = 604_800;
+
+async fn try_process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_resync_claim_schedule() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let last_update_timestamps = [1_000_000, 2_000_000];
+    let liquidity_providers: Vec<TestLiquidityProvider> = last_update_timestamps
+        .iter()
+        .map(|&last_update_ts| {
+            let liquidity_provider = LiquidityProvider {
+                is_initialized: true,
+                owner: Keypair::new().pubkey(),
+                positions: vec![LiquidityPosition {
+                    pool: swap_info.pubkey,
+                    liquidity_amount: 1_000_000,
+                    last_update_ts,
+                    next_claim_ts: last_update_ts + OLD_CLAIM_PERIOD,
+                    ..LiquidityPosition::default()
+                }],
+            };
+            let pubkey = Pubkey::new_unique();
+            test.add_packable_account(
+                pubkey,
+                u32::MAX as u64,
+                &liquidity_provider,
+                &deltafi_swap::id(),
+            );
+            TestLiquidityProvider {
+                pubkey,
+                owner: liquidity_provider.owner,
+                positions: liquidity_provider.positions,
+            }
+        })
+        .collect();
+    let liquidity_provider_pubkeys: Vec<Pubkey> = liquidity_providers
+        .iter()
+        .map(|liquidity_provider| liquidity_provider.pubkey)
+        .collect();
+
+    let mut context = test.start_with_context().await;
+
+    // only the config admin can resync
+    let not_admin = Keypair::new();
+    assert_eq!(
+        try_process(
+            &mut context,
+            resync_claim_schedule(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey,
+                not_admin.pubkey(),
+                liquidity_provider_pubkeys.clone(),
+            )
+            .unwrap(),
+            &not_admin,
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        ))
+    );
+
+    try_process(
+        &mut context,
+        resync_claim_schedule(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            liquidity_provider_pubkeys,
+        )
+        .unwrap(),
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    for (liquidity_provider, last_update_ts) in
+        liquidity_providers.iter().zip(last_update_timestamps)
+    {
+        let position = liquidity_provider
+            .get_state(&mut context.banks_client)
+            .await
+            .positions[0]
+            .clone();
+        assert_eq!(position.last_update_ts, last_update_ts);
+        assert_eq!(position.next_claim_ts, last_update_ts + MIN_CLAIM_PERIOD);
+    }
+}
@@ -0,0 +1,341 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{top_up_reward_budget, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{SwapInfo, DEFAULT_REWARD_BUDGET},
+};
+use solana_program::program_pack::Pack;
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::convert::TryInto;
+use utils::*;
+
+const AMOUNT_IN: u64 = 1_000_000_000;
+// sqrt(1e9) / 1000
+const FULL_REWARD: u64 = 31;
+const INITIAL_BUDGET: u64 = 40;
+const TOP_UP: u64 = 100;
+
+#[tokio::test]
+async fn test_reward_budget() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+
+    let mut account = context
+        .banks_client
+        .get_account(swap_info.pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut state = SwapInfo::unpack(&account.data).unwrap();
+    state.reward_budget_remaining = INITIAL_BUDGET;
+    SwapInfo::pack(state, &mut account.data).unwrap();
+    context.set_account(&swap_info.pubkey, &AccountSharedData::from(account));
+
+    let payer = context.payer.insecure_clone();
+    let banks_client = &mut context.banks_client;
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let mut expected_sol_balance = 10_000_000_000;
+    // each swap mints the smaller of its reward and the remaining budget
+    for (minted, budget_after) in [
+        (FULL_REWARD, INITIAL_BUDGET - FULL_REWARD),
+        (INITIAL_BUDGET - FULL_REWARD, 0),
+        (0, 0),
+    ] {
+        let rewards_before = get_token_balance(banks_client, deltafi_user_account).await;
+        swap_info
+            .swap(
+                banks_client,
+                &swap_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                AMOUNT_IN,
+                1,
+                SwapDirection::SellBase,
+                &payer,
+            )
+            .await;
+        expected_sol_balance -= AMOUNT_IN;
+
+        // the swap itself goes through even with the budget drained
+        assert_eq!(
+            get_token_balance(banks_client, sol_user_account).await,
+            expected_sol_balance
+        );
+        assert_eq!(
+            get_token_balance(banks_client, deltafi_user_account).await - rewards_before,
+            minted
+        );
+        assert_eq!(
+            swap_info
+                .get_state(banks_client)
+                .await
+                .reward_budget_remaining,
+            budget_after
+        );
+    }
+
+    let mut transaction = Transaction::new_with_payer(
+        &[top_up_reward_budget(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            TOP_UP,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &swap_config.admin], recent_blockhash);
+    banks_client.process_transaction(transaction).await.unwrap();
+    assert_eq!(
+        swap_info
+            .get_state(banks_client)
+            .await
+            .reward_budget_remaining,
+        TOP_UP
+    );
+
+    let rewards_before = get_token_balance(banks_client, deltafi_user_account).await;
+    swap_info
+        .swap(
+            banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            AMOUNT_IN,
+            1,
+            SwapDirection::SellBase,
+            &payer,
+        )
+        .await;
+    assert_eq!(
+        get_token_balance(banks_client, deltafi_user_account).await - rewards_before,
+        FULL_REWARD
+    );
+    assert_eq!(
+        swap_info
+            .get_state(banks_client)
+            .await
+            .reward_budget_remaining,
+        TOP_UP - FULL_REWARD
+    );
+}
+
+#[tokio::test]
+async fn test_initialized_pool_mints_rewards() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let creator_account_owner = Keypair::new();
+    let sol_creator_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        creator_account_owner.pubkey(),
+        42_000_000_000,
+    )
+    .await;
+    let srm_creator_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        creator_account_owner.pubkey(),
+        // SOL/SRM oracle price is 150 / 7, so this deposit is priced at it
+        900_000_000_000,
+    )
+    .await;
+
+    let admin_fee_accounts = Keypair::new();
+    let sol_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+    let srm_admin_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        admin_fee_accounts.pubkey(),
+        0,
+    )
+    .await;
+
+    // created through Initialize rather than written into the test genesis
+    let swap_info = TestSwapInfo::init(
+        &mut banks_client,
+        &swap_config,
+        &sol_oracle,
+        &srm_oracle,
+        spl_token::native_mint::id(),
+        srm_mint.pubkey,
+        sol_creator_account,
+        srm_creator_account,
+        sol_admin_account,
+        srm_admin_account,
+        &creator_account_owner,
+        &payer,
+        &SwapInitArgs {
+            mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+            slope: Decimal::one()
+                .try_div(2)
+                .unwrap()
+                .to_scaled_val()
+                .unwrap()
+                .try_into()
+                .unwrap(),
+            is_open_twap: true,
+            creator_fee_bps: 0,
+            invert_a: false,
+            invert_b: false,
+            slope_in_bps: false,
+        },
+    )
+    .await;
+    assert_eq!(
+        swap_info
+            .get_state(&mut banks_client)
+            .await
+            .reward_budget_remaining,
+        DEFAULT_REWARD_BUDGET
+    );
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        AMOUNT_IN,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    swap_info
+        .swap(
+            &mut banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            AMOUNT_IN,
+            1,
+            SwapDirection::SellBase,
+            &payer,
+        )
+        .await;
+
+    assert_eq!(
+        get_token_balance(&mut banks_client, deltafi_user_account).await,
+        FULL_REWARD
+    );
+    assert_eq!(
+        swap_info
+            .get_state(&mut banks_client)
+            .await
+            .reward_budget_remaining,
+        DEFAULT_REWARD_BUDGET - FULL_REWARD
+    );
+}
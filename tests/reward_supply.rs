@@ -0,0 +1,254 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{claim_liquidity_rewards, seed_position, SeedPositionData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::AccountSharedData,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::Mint;
+use utils::*;
+
+const START_TS: i64 = 1_000_000;
+
+async fn setup() -> (
+    ProgramTestContext,
+    TestSwapConfig,
+    TestSwapInfo,
+    TestLiquidityProvider,
+    Keypair,
+    TestMint,
+) {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let liquidity_provider = add_liquidity_provider(&mut test, &user_account_owner);
+
+    let mut context = test.start_with_context().await;
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = START_TS;
+    context.set_sysvar(&clock);
+
+    (
+        context,
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+        srm_mint,
+    )
+}
+
+/// Overwrite the supply of a mint, leaving `headroom` tokens before `u64::MAX`
+async fn set_mint_headroom(context: &mut ProgramTestContext, mint_pubkey: Pubkey, headroom: u64) {
+    let mut account = context
+        .banks_client
+        .get_account(mint_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut mint = Mint::unpack(&account.data).unwrap();
+    mint.supply = u64::MAX - headroom;
+    Mint::pack(mint, &mut account.data).unwrap();
+    context.set_account(&mint_pubkey, &AccountSharedData::from(account));
+}
+
+async fn try_process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_swap_reward_supply_exceeded() {
+    let (mut context, swap_config, swap_info, _, _, srm_mint) = setup().await;
+
+    let user_account_owner = Keypair::new();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    // a 1 SOL trade rewards 31 DELTAFI, one more than the mint can still issue
+    set_mint_headroom(&mut context, swap_config.deltafi_mint, 30).await;
+    assert_eq!(
+        swap_info
+            .try_swap(
+                &mut context.banks_client,
+                &swap_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                1_000_000_000,
+                0,
+                SwapDirection::SellBase,
+                &context.payer,
+            )
+            .await,
+        Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::RewardSupplyExceeded as u32)
+        ))
+    );
+
+    set_mint_headroom(&mut context, swap_config.deltafi_mint, 31).await;
+    swap_info
+        .swap(
+            &mut context.banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            1_000_000_000,
+            0,
+            SwapDirection::SellBase,
+            &context.payer,
+        )
+        .await;
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, deltafi_user_account).await,
+        31
+    );
+}
+
+#[tokio::test]
+async fn test_claim_reward_supply_exceeded() {
+    let (mut context, swap_config, swap_info, liquidity_provider, user_account_owner, _) =
+        setup().await;
+
+    try_process(
+        &mut context,
+        seed_position(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            liquidity_provider.pubkey,
+            swap_config.admin.pubkey(),
+            SeedPositionData {
+                liquidity_amount: 2_000_000_000,
+                rewards_owed: 1_000,
+                rewards_estimated: 0,
+                cumulative_interest: 0,
+                last_update_ts: START_TS,
+                next_claim_ts: START_TS,
+            },
+        )
+        .unwrap(),
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let claim = claim_liquidity_rewards(
+        deltafi_swap::id(),
+        swap_config.pubkey,
+        swap_info.pubkey,
+        swap_config.market_authority,
+        liquidity_provider.pubkey,
+        user_account_owner.pubkey(),
+        deltafi_user_account,
+        swap_config.deltafi_mint,
+    )
+    .unwrap();
+
+    set_mint_headroom(&mut context, swap_config.deltafi_mint, 999).await;
+    assert_eq!(
+        try_process(&mut context, claim.clone(), &user_account_owner).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::RewardSupplyExceeded as u32)
+        ))
+    );
+
+    set_mint_headroom(&mut context, swap_config.deltafi_mint, 1_000).await;
+    try_process(&mut context, claim, &user_account_owner)
+        .await
+        .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, deltafi_user_account).await,
+        1_000
+    );
+}
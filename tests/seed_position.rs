@@ -0,0 +1,190 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        claim_liquidity_rewards, refresh_liquidity_obligation, seed_position, SeedPositionData,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{LiquidityPosition, MIN_CLAIM_PERIOD},
+};
+use solana_program::{
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const START_TS: i64 = 1_000_000;
+
+async fn try_process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_seed_then_refresh_and_claim() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+    let liquidity_provider = add_liquidity_provider(&mut test, &user_account_owner);
+
+    let mut context = test.start_with_context().await;
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = START_TS;
+    context.set_sysvar(&clock);
+
+    let seed = SeedPositionData {
+        liquidity_amount: 2_000_000_000,
+        rewards_owed: 0,
+        rewards_estimated: 1_000,
+        cumulative_interest: u64::MAX as u128 + 7_000,
+        last_update_ts: START_TS - 100,
+        next_claim_ts: START_TS,
+    };
+    let seed_instruction = seed_position(
+        deltafi_swap::id(),
+        swap_config.pubkey,
+        swap_info.pubkey,
+        liquidity_provider.pubkey,
+        swap_config.admin.pubkey(),
+        seed.clone(),
+    )
+    .unwrap();
+    try_process(&mut context, seed_instruction.clone(), &swap_config.admin)
+        .await
+        .unwrap();
+    assert_eq!(
+        liquidity_provider
+            .get_state(&mut context.banks_client)
+            .await
+            .positions,
+        vec![LiquidityPosition {
+            pool: swap_info.pubkey,
+            liquidity_amount: seed.liquidity_amount,
+            rewards_owed: seed.rewards_owed,
+            rewards_estimated: seed.rewards_estimated,
+            cumulative_interest: seed.cumulative_interest,
+            last_update_ts: seed.last_update_ts,
+            next_claim_ts: seed.next_claim_ts,
+            reward_debt: 0,
+        }]
+    );
+
+    // the pool already has a position for this provider
+    assert_eq!(
+        try_process(&mut context, seed_instruction, &swap_config.admin).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::PositionAlreadyExists as u32)
+        ))
+    );
+
+    // refreshing accrues from the seeded timestamp and matures the claim period
+    let payer = context.payer.insecure_clone();
+    try_process(
+        &mut context,
+        refresh_liquidity_obligation(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            vec![liquidity_provider.pubkey],
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await
+    .unwrap();
+    let position = liquidity_provider
+        .get_state(&mut context.banks_client)
+        .await
+        .positions[0]
+        .clone();
+    assert!(position.rewards_owed > seed.rewards_estimated);
+    assert_eq!(position.rewards_estimated, 0);
+    assert_eq!(position.last_update_ts, START_TS);
+    assert_eq!(position.next_claim_ts, START_TS + MIN_CLAIM_PERIOD);
+
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &context.payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    try_process(
+        &mut context,
+        claim_liquidity_rewards(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.market_authority,
+            liquidity_provider.pubkey,
+            user_account_owner.pubkey(),
+            deltafi_user_account,
+            swap_config.deltafi_mint,
+        )
+        .unwrap(),
+        &user_account_owner,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        get_token_balance(&mut context.banks_client, deltafi_user_account).await,
+        position.rewards_owed
+    );
+    let claimed = liquidity_provider
+        .get_state(&mut context.banks_client)
+        .await
+        .positions[0]
+        .clone();
+    assert_eq!(claimed.rewards_owed, 0);
+    assert_eq!(
+        claimed.cumulative_interest,
+        seed.cumulative_interest + position.rewards_owed as u128
+    );
+}
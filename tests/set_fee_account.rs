@@ -0,0 +1,135 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::set_fee_account,
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+async fn try_set_fee_account(
+    banks_client: &mut BanksClient,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    new_fee_account: Pubkey,
+    payer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_fee_account(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_info.authority,
+            swap_config.admin.pubkey(),
+            new_fee_account,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[payer, &swap_config.admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_set_fee_account() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    // program authorities have no private key to move the fees out
+    for owner in [swap_info.authority, swap_config.market_authority] {
+        let fee_account = create_and_mint_to_token_account(
+            &mut banks_client,
+            srm_mint.pubkey,
+            Some(&srm_mint.authority),
+            &payer,
+            owner,
+            0,
+        )
+        .await;
+        assert_eq!(
+            try_set_fee_account(
+                &mut banks_client,
+                &swap_config,
+                &swap_info,
+                fee_account,
+                &payer
+            )
+            .await,
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(SwapError::InvalidOutputOwner as u32)
+            ))
+        );
+    }
+    assert_eq!(
+        swap_info.get_state(&mut banks_client).await.admin_fee_key_b,
+        swap_info.admin_fee_b_key
+    );
+
+    let fee_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        Keypair::new().pubkey(),
+        0,
+    )
+    .await;
+    assert_eq!(
+        try_set_fee_account(
+            &mut banks_client,
+            &swap_config,
+            &swap_info,
+            fee_account,
+            &payer
+        )
+        .await,
+        Ok(())
+    );
+    assert_eq!(
+        swap_info.get_state(&mut banks_client).await.admin_fee_key_b,
+        fee_account
+    );
+}
@@ -0,0 +1,227 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{claim_liquidity_rewards, set_reward_mint},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{LiquidityPosition, LiquidityProvider},
+};
+use solana_program::{instruction::InstructionError, program_option::COption, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::{native_mint::DECIMALS, state::Mint};
+use utils::*;
+
+const REWARDS_OWED: u64 = 500;
+
+fn add_mint(test: &mut ProgramTest, mint_authority: Pubkey) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    test.add_packable_account(
+        pubkey,
+        u32::MAX as u64,
+        &Mint {
+            is_initialized: true,
+            decimals: DECIMALS,
+            mint_authority: COption::Some(mint_authority),
+            ..Mint::default()
+        },
+        &spl_token::id(),
+    );
+    pubkey
+}
+
+async fn try_set_reward_mint(
+    banks_client: &mut BanksClient,
+    swap_config: &TestSwapConfig,
+    deltafi_mint: Pubkey,
+    payer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_reward_mint(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_config.market_authority,
+            deltafi_mint,
+            swap_config.admin.pubkey(),
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[payer, &swap_config.admin], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn try_claim(
+    banks_client: &mut BanksClient,
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    liquidity_provider: Pubkey,
+    owner: &Keypair,
+    destination: Pubkey,
+    deltafi_mint: Pubkey,
+    payer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(
+        &[claim_liquidity_rewards(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.market_authority,
+            liquidity_provider,
+            owner.pubkey(),
+            destination,
+            deltafi_mint,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[payer, owner], recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+#[tokio::test]
+async fn test_rotate_reward_mint() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider = Pubkey::new_unique();
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            positions: vec![LiquidityPosition {
+                pool: swap_info.pubkey,
+                liquidity_amount: 1_000_000_000,
+                rewards_owed: REWARDS_OWED,
+                ..LiquidityPosition::default()
+            }],
+        },
+        &deltafi_swap::id(),
+    );
+
+    let new_mint = add_mint(&mut test, swap_config.market_authority);
+    let foreign_mint = add_mint(&mut test, Pubkey::new_unique());
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    // a mint the market authority cannot mint from is rejected
+    assert_eq!(
+        try_set_reward_mint(&mut banks_client, &swap_config, foreign_mint, &payer).await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidOwner as u32)
+        ))
+    );
+    assert_eq!(
+        swap_config.get_state(&mut banks_client).await.deltafi_mint,
+        swap_config.deltafi_mint
+    );
+
+    assert_eq!(
+        try_set_reward_mint(&mut banks_client, &swap_config, new_mint, &payer).await,
+        Ok(())
+    );
+    assert_eq!(
+        swap_config.get_state(&mut banks_client).await.deltafi_mint,
+        new_mint
+    );
+
+    // claims from the old mint are refused, the new mint pays out
+    let old_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    assert_eq!(
+        try_claim(
+            &mut banks_client,
+            &swap_config,
+            &swap_info,
+            liquidity_provider,
+            &user_account_owner,
+            old_account,
+            swap_config.deltafi_mint,
+            &payer,
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectMint as u32)
+        ))
+    );
+
+    let new_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        new_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    assert_eq!(
+        try_claim(
+            &mut banks_client,
+            &swap_config,
+            &swap_info,
+            liquidity_provider,
+            &user_account_owner,
+            new_account,
+            new_mint,
+            &payer,
+        )
+        .await,
+        Ok(())
+    );
+    assert_eq!(
+        get_token_balance(&mut banks_client, new_account).await,
+        REWARDS_OWED
+    );
+}
@@ -0,0 +1,207 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    curve::{Multiplier, PoolState},
+    error::SwapError,
+    instruction::{set_targets, SetTargetsData},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::instruction::InstructionError;
+use solana_program_test::*;
+use solana_sdk::{
+    hash::Hash,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+fn setup() -> (ProgramTest, TestSwapConfig, TestSwapInfo) {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    (test, swap_config, swap_info)
+}
+
+fn set_targets_transaction(
+    swap_config: &TestSwapConfig,
+    swap_info: &TestSwapInfo,
+    admin: &Keypair,
+    payer: &Keypair,
+    base_target: u64,
+    quote_target: u64,
+    recent_blockhash: Hash,
+) -> Transaction {
+    let mut transaction = Transaction::new_with_payer(
+        &[set_targets(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            admin.pubkey(),
+            SetTargetsData {
+                base_target: Decimal::from(base_target).to_scaled_val().unwrap(),
+                quote_target: Decimal::from(quote_target).to_scaled_val().unwrap(),
+            },
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    transaction.sign(&[payer, admin], recent_blockhash);
+    transaction
+}
+
+#[tokio::test]
+async fn test_success() {
+    let (test, swap_config, swap_info) = setup();
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+    let mid_price = pool_state.get_mid_price().unwrap();
+
+    banks_client
+        .process_transaction(set_targets_transaction(
+            &swap_config,
+            &swap_info,
+            &swap_config.admin,
+            &payer,
+            40_000_000_000,
+            850_000_000_000,
+            recent_blockhash,
+        ))
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    let mut pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+    assert_eq!(pool_state.multiplier, Multiplier::BelowOne);
+    assert_eq!(pool_state.base_target, Decimal::from(40_000_000_000u64));
+    assert!(pool_state.quote_target >= pool_state.quote_reserve);
+    assert_ne!(pool_state.get_mid_price().unwrap(), mid_price);
+}
+
+#[tokio::test]
+async fn test_reset_to_canonical_targets() {
+    let (test, swap_config, swap_info) = setup();
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+    let (base_target, quote_target, multiplier) = PoolState::canonical_targets(
+        pool_state.market_price,
+        pool_state.base_reserve,
+        pool_state.quote_reserve,
+        pool_state.slope,
+    )
+    .unwrap();
+    // 42 SOL are worth 900 SRM, more than the 800 SRM reserve
+    assert_eq!(multiplier, Multiplier::BelowOne);
+
+    banks_client
+        .process_transaction(set_targets_transaction(
+            &swap_config,
+            &swap_info,
+            &swap_config.admin,
+            &payer,
+            0,
+            0,
+            recent_blockhash,
+        ))
+        .await
+        .map_err(|e| e.unwrap())
+        .unwrap();
+
+    let mut expected = pool_state.clone();
+    expected.set_targets(base_target, quote_target).unwrap();
+    assert_eq!(
+        swap_info.get_state(&mut banks_client).await.pool_state,
+        expected
+    );
+    assert_eq!(expected.base_target, base_target);
+}
+
+#[tokio::test]
+async fn test_invalid_targets() {
+    let (test, swap_config, swap_info) = setup();
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let pool_state = swap_info.get_state(&mut banks_client).await.pool_state;
+
+    // both reserves below their targets
+    assert_eq!(
+        banks_client
+            .process_transaction(set_targets_transaction(
+                &swap_config,
+                &swap_info,
+                &swap_config.admin,
+                &payer,
+                50_000_000_000,
+                900_000_000_000,
+                recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidTargets as u32)
+        )
+    );
+    assert_eq!(
+        swap_info.get_state(&mut banks_client).await.pool_state,
+        pool_state
+    );
+}
+
+#[tokio::test]
+async fn test_unauthorized() {
+    let (test, swap_config, swap_info) = setup();
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let invalid_admin = Keypair::new();
+    assert_eq!(
+        banks_client
+            .process_transaction(set_targets_transaction(
+                &swap_config,
+                &swap_info,
+                &invalid_admin,
+                &payer,
+                40_000_000_000,
+                850_000_000_000,
+                recent_blockhash,
+            ))
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::Unauthorized as u32)
+        )
+    );
+}
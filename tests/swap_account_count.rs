@@ -0,0 +1,87 @@
+#![cfg(feature = "test-bpf")]
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{swap, SwapData, SwapDirection},
+    processor::process,
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+/// Swap instruction whose accounts are never inspected past the count check
+fn swap_instruction(user_transfer_authority: &Keypair) -> Instruction {
+    swap(
+        deltafi_swap::id(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        user_transfer_authority.pubkey(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        SwapData {
+            amount_in: 1_000_000,
+            minimum_amount_out: 0,
+            swap_direction: SwapDirection::SellBase,
+            deadline: None,
+        },
+    )
+    .unwrap()
+}
+
+async fn process_swap_instruction(instruction: Instruction, user_transfer_authority: &Keypair) {
+    let test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let (mut banks_client, payer, recent_blockhash) = test.start().await;
+
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    transaction.sign(&[&payer, user_transfer_authority], recent_blockhash);
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap()),
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::InvalidAccountCount as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_swap_too_few_accounts() {
+    let user_transfer_authority = Keypair::new();
+    let mut instruction = swap_instruction(&user_transfer_authority);
+    instruction.accounts.pop();
+
+    process_swap_instruction(instruction, &user_transfer_authority).await;
+}
+
+#[tokio::test]
+async fn test_swap_too_many_accounts() {
+    let user_transfer_authority = Keypair::new();
+    let mut instruction = swap_instruction(&user_transfer_authority);
+    // one past the optional swap cooldown account
+    for _ in 0..2 {
+        instruction
+            .accounts
+            .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    }
+
+    process_swap_instruction(instruction, &user_transfer_authority).await;
+}
@@ -0,0 +1,217 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{init_swap_cooldown, set_swap_cooldown, swap, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{swap_cooldown_address, SwapCooldown},
+};
+use solana_program::{
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use utils::*;
+
+const START_TS: i64 = 1_000_000;
+const COOLDOWN: u64 = 30;
+
+async fn set_clock(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+async fn try_process(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signer: &Keypair,
+) -> Result<(), TransactionError> {
+    let mut transaction =
+        Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    transaction.sign(&[&context.payer, signer], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+fn swap_error(error: SwapError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+}
+
+#[tokio::test]
+async fn test_swap_cooldown() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let mut context = test.start_with_context().await;
+    set_clock(&mut context, START_TS).await;
+
+    try_process(
+        &mut context,
+        set_swap_cooldown(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.admin.pubkey(),
+            COOLDOWN,
+        )
+        .unwrap(),
+        &swap_config.admin,
+    )
+    .await
+    .unwrap();
+
+    let trader = Keypair::new();
+    let payer = context.payer.insecure_clone();
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        trader.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        trader.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut context.banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        trader.pubkey(),
+        0,
+    )
+    .await;
+
+    // the trader signs as its own transfer authority
+    let sell_sol = swap(
+        deltafi_swap::id(),
+        swap_config.pubkey,
+        swap_info.pubkey,
+        swap_config.market_authority,
+        swap_info.authority,
+        trader.pubkey(),
+        sol_user_account,
+        swap_info.token_a,
+        swap_info.token_b,
+        srm_user_account,
+        deltafi_user_account,
+        swap_config.deltafi_mint,
+        swap_info.admin_fee_b_key,
+        swap_info.secondary_admin_fee_b_key,
+        swap_info.creator_fee_b_key,
+        swap_info.oracle_a,
+        swap_info.oracle_b,
+        SwapData {
+            amount_in: 1_000_000_000,
+            minimum_amount_out: 0,
+            swap_direction: SwapDirection::SellBase,
+            deadline: None,
+        },
+    )
+    .unwrap();
+
+    // a pool with a cooldown needs the trader's cooldown account
+    assert_eq!(
+        try_process(&mut context, sell_sol.clone(), &trader).await,
+        Err(swap_error(SwapError::InvalidAccountCount))
+    );
+
+    try_process(
+        &mut context,
+        init_swap_cooldown(
+            deltafi_swap::id(),
+            swap_info.pubkey,
+            trader.pubkey(),
+            payer.pubkey(),
+        )
+        .unwrap(),
+        &payer,
+    )
+    .await
+    .unwrap();
+    let (swap_cooldown, _) =
+        swap_cooldown_address(&deltafi_swap::id(), &swap_info.pubkey, &trader.pubkey());
+    let mut sell_sol_with_cooldown = sell_sol;
+    sell_sol_with_cooldown
+        .accounts
+        .push(AccountMeta::new(swap_cooldown, false));
+
+    try_process(&mut context, sell_sol_with_cooldown.clone(), &trader)
+        .await
+        .unwrap();
+    let last_swap_ts = |data: &[u8]| SwapCooldown::unpack(data).unwrap().last_swap_ts;
+    let account = context
+        .banks_client
+        .get_account(swap_cooldown)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(last_swap_ts(&account.data), START_TS);
+
+    // a second swap within the cooldown is throttled
+    set_clock(&mut context, START_TS + COOLDOWN as i64 - 1).await;
+    assert_eq!(
+        try_process(&mut context, sell_sol_with_cooldown.clone(), &trader).await,
+        Err(swap_error(SwapError::CooldownActive))
+    );
+
+    // once it expires the trader can swap again
+    set_clock(&mut context, START_TS + COOLDOWN as i64).await;
+    try_process(&mut context, sell_sol_with_cooldown, &trader)
+        .await
+        .unwrap();
+    let account = context
+        .banks_client
+        .get_account(swap_cooldown)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(last_swap_ts(&account.data), START_TS + COOLDOWN as i64);
+}
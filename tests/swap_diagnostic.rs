@@ -0,0 +1,168 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    diagnostic::SwapDiagnostic,
+    error::SwapError,
+    instruction::{swap, SwapData, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+};
+use solana_program::{instruction::InstructionError, program_pack::Pack};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+#[tokio::test]
+async fn test_slippage_diagnostic() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    const AMOUNT_IN: u64 = 1_000_000_000;
+    const MINIMUM_AMOUNT_OUT: u64 = 1_000_000_000_000;
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        AMOUNT_IN,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    let user_transfer_authority = Keypair::new();
+    let mut transaction = Transaction::new_with_payer(
+        &[
+            approve(
+                &spl_token::id(),
+                &sol_user_account,
+                &user_transfer_authority.pubkey(),
+                &user_account_owner.pubkey(),
+                &[],
+                AMOUNT_IN,
+            )
+            .unwrap(),
+            swap(
+                deltafi_swap::id(),
+                swap_config.pubkey,
+                swap_info.pubkey,
+                swap_config.market_authority,
+                swap_info.authority,
+                user_transfer_authority.pubkey(),
+                sol_user_account,
+                swap_info.token_a,
+                swap_info.token_b,
+                srm_user_account,
+                deltafi_user_account,
+                swap_config.deltafi_mint,
+                swap_info.admin_fee_b_key,
+                swap_info.secondary_admin_fee_b_key,
+                swap_info.creator_fee_b_key,
+                swap_info.oracle_a,
+                swap_info.oracle_b,
+                SwapData {
+                    amount_in: AMOUNT_IN,
+                    minimum_amount_out: MINIMUM_AMOUNT_OUT,
+                    swap_direction: SwapDirection::SellBase,
+                    deadline: None,
+                },
+            )
+            .unwrap(),
+        ],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(
+        &[&payer, &user_account_owner, &user_transfer_authority],
+        recent_blockhash,
+    );
+
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulation.result,
+        Some(Err(TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SwapError::ExceededSlippage as u32)
+        )))
+    );
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, deltafi_swap::id());
+    let diagnostic = SwapDiagnostic::unpack_from_slice(&return_data.data).unwrap();
+    assert_eq!(diagnostic.error_code, SwapError::ExceededSlippage as u32);
+    assert_eq!(diagnostic.expected, MINIMUM_AMOUNT_OUT as u128);
+
+    // the reported amount is what the same swap pays out without the bound
+    swap_info
+        .swap(
+            &mut banks_client,
+            &swap_config,
+            &user_account_owner,
+            sol_user_account,
+            srm_user_account,
+            deltafi_user_account,
+            AMOUNT_IN,
+            0,
+            SwapDirection::SellBase,
+            &payer,
+        )
+        .await;
+    assert_eq!(
+        diagnostic.actual,
+        get_token_balance(&mut banks_client, srm_user_account).await as u128
+    );
+}
@@ -0,0 +1,474 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use bytemuck::Pod;
+use deltafi_swap::{
+    error::SwapError,
+    instruction::{
+        initialize, set_allowed_token_program, swap, top_up_reward_budget, InitializeData,
+        SwapData, SwapDirection,
+    },
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::SwapInfo,
+};
+use solana_program::{
+    instruction::Instruction, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+};
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token_2022::{
+    extension::{
+        immutable_owner::ImmutableOwner, permanent_delegate::PermanentDelegate,
+        transfer_fee::TransferFeeAmount, Extension, ExtensionType, StateWithExtensions,
+        StateWithExtensionsMut,
+    },
+    native_mint::DECIMALS,
+    state::{Account as Token, AccountState, Mint},
+};
+use utils::*;
+
+/// Token-2022 accounts for a SOL/SRM-priced pool awaiting initialization
+struct TestPool {
+    swap_info: Pubkey,
+    authority: Pubkey,
+    nonce: u8,
+    token_a_mint: Pubkey,
+    token_b_mint: Pubkey,
+    token_a: Pubkey,
+    token_b: Pubkey,
+    pool_mint: Pubkey,
+    pool_token: Pubkey,
+    admin_fee_a: Pubkey,
+    admin_fee_b: Pubkey,
+}
+
+fn add_mint(test: &mut ProgramTest, mint_authority: Pubkey) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    let mut account = Account::new(u32::MAX as u64, Mint::LEN, &spl_token_2022::id());
+    Mint {
+        is_initialized: true,
+        decimals: DECIMALS,
+        mint_authority: COption::Some(mint_authority),
+        ..Mint::default()
+    }
+    .pack_into_slice(&mut account.data);
+    test.add_account(pubkey, account);
+    pubkey
+}
+
+/// Mint carrying extension `M` after its base state
+fn add_mint_with_extension<M: Extension + Pod + Default>(
+    test: &mut ProgramTest,
+    mint_authority: Pubkey,
+) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&[M::TYPE]).unwrap();
+    let mut account = Account::new(u32::MAX as u64, len, &spl_token_2022::id());
+    let mut state =
+        StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut account.data).unwrap();
+    state.init_extension::<M>(true).unwrap();
+    state.base = Mint {
+        is_initialized: true,
+        decimals: DECIMALS,
+        mint_authority: COption::Some(mint_authority),
+        ..Mint::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    test.add_account(pubkey, account);
+    pubkey
+}
+
+/// Token account carrying extension `E` after its base state
+fn add_token_account<E: Extension + Pod + Default>(
+    test: &mut ProgramTest,
+    mint: Pubkey,
+    owner: Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    let len = ExtensionType::try_calculate_account_len::<Token>(&[E::TYPE]).unwrap();
+    let mut account = Account::new(u32::MAX as u64, len, &spl_token_2022::id());
+    let mut state =
+        StateWithExtensionsMut::<Token>::unpack_uninitialized(&mut account.data).unwrap();
+    state.init_extension::<E>(true).unwrap();
+    state.base = Token {
+        mint,
+        owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Token::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    test.add_account(pubkey, account);
+    pubkey
+}
+
+fn add_pool<E: Extension + Pod + Default>(
+    test: &mut ProgramTest,
+    user_account_owner: &Keypair,
+    admin_account_owner: &Keypair,
+) -> TestPool {
+    let token_a_mint = add_mint(test, Pubkey::new_unique());
+    add_pool_with_token_a_mint::<E>(test, token_a_mint, user_account_owner, admin_account_owner)
+}
+
+fn add_pool_with_token_a_mint<E: Extension + Pod + Default>(
+    test: &mut ProgramTest,
+    token_a_mint: Pubkey,
+    user_account_owner: &Keypair,
+    admin_account_owner: &Keypair,
+) -> TestPool {
+    let swap_info = Pubkey::new_unique();
+    test.add_account(
+        swap_info,
+        Account::new(u32::MAX as u64, SwapInfo::LEN, &deltafi_swap::id()),
+    );
+    let (authority, nonce) =
+        Pubkey::find_program_address(&[swap_info.as_ref()], &deltafi_swap::id());
+
+    let token_b_mint = add_mint(test, Pubkey::new_unique());
+    let pool_mint = add_mint(test, authority);
+
+    TestPool {
+        swap_info,
+        authority,
+        nonce,
+        token_a_mint,
+        token_b_mint,
+        token_a: add_token_account::<E>(test, token_a_mint, authority, 42_000_000_000),
+        // SOL/SRM oracle price is 150 / 7, so this deposit is priced at it
+        token_b: add_token_account::<E>(test, token_b_mint, authority, 900_000_000_000),
+        pool_mint,
+        pool_token: add_token_account::<E>(test, pool_mint, user_account_owner.pubkey(), 0),
+        admin_fee_a: add_token_account::<E>(test, token_a_mint, admin_account_owner.pubkey(), 0),
+        admin_fee_b: add_token_account::<E>(test, token_b_mint, admin_account_owner.pubkey(), 0),
+    }
+}
+
+/// Point an instruction built for spl_token at Token-2022
+fn with_token_2022(mut instruction: Instruction) -> Instruction {
+    for account in instruction.accounts.iter_mut() {
+        if account.pubkey == spl_token::id() {
+            account.pubkey = spl_token_2022::id();
+        }
+    }
+    instruction
+}
+
+fn initialize_pool(
+    swap_config: &TestSwapConfig,
+    pool: &TestPool,
+    sol_oracle: &TestOracle,
+    srm_oracle: &TestOracle,
+    creator: &Keypair,
+) -> Instruction {
+    with_token_2022(
+        initialize(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            pool.swap_info,
+            pool.authority,
+            pool.admin_fee_a,
+            pool.admin_fee_b,
+            pool.token_a,
+            pool.token_b,
+            pool.token_a_mint,
+            pool.token_b_mint,
+            pool.pool_mint,
+            pool.pool_token,
+            sol_oracle.price_pubkey,
+            srm_oracle.price_pubkey,
+            creator.pubkey(),
+            InitializeData {
+                nonce: pool.nonce,
+                mid_price: Decimal::from(20u64).to_scaled_val().unwrap(),
+                slope: Decimal::one()
+                    .try_div(2)
+                    .unwrap()
+                    .to_scaled_val()
+                    .unwrap()
+                    .try_into()
+                    .unwrap(),
+                is_open_twap: true,
+                creator_fee_bps: 0,
+                invert_a: false,
+                invert_b: false,
+                slope_in_bps: false,
+            },
+        )
+        .unwrap(),
+    )
+}
+
+async fn send(
+    banks_client: &mut BanksClient,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut transaction = Transaction::new_with_payer(instructions, Some(&signers[0].pubkey()));
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(signers, recent_blockhash);
+    banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn get_token_2022_balance(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
+    let account = banks_client.get_account(pubkey).await.unwrap().unwrap();
+    StateWithExtensions::<Token>::unpack(&account.data)
+        .unwrap()
+        .base
+        .amount
+}
+
+#[tokio::test]
+async fn test_success() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config_with_token_program(&mut test, &spl_token_2022::id());
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let pool = add_pool::<ImmutableOwner>(&mut test, &user_account_owner, &admin_account_owner);
+
+    let owner = user_account_owner.pubkey();
+    let source =
+        add_token_account::<ImmutableOwner>(&mut test, pool.token_a_mint, owner, 1_000_000);
+    let destination = add_token_account::<ImmutableOwner>(&mut test, pool.token_b_mint, owner, 0);
+    let reward_token =
+        add_token_account::<ImmutableOwner>(&mut test, swap_config.deltafi_mint, owner, 0);
+    let creator_fee_b = add_token_account::<ImmutableOwner>(&mut test, pool.token_b_mint, owner, 0);
+    let secondary_admin_fee_b = add_token_account::<ImmutableOwner>(
+        &mut test,
+        pool.token_b_mint,
+        swap_config.admin.pubkey(),
+        0,
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        send(
+            &mut banks_client,
+            &[
+                initialize_pool(
+                    &swap_config,
+                    &pool,
+                    &sol_oracle,
+                    &srm_oracle,
+                    &user_account_owner
+                ),
+                // new pools start without a reward budget
+                top_up_reward_budget(
+                    deltafi_swap::id(),
+                    swap_config.pubkey,
+                    pool.swap_info,
+                    swap_config.admin.pubkey(),
+                    1_000_000_000,
+                )
+                .unwrap(),
+            ],
+            &[&payer, &user_account_owner, &swap_config.admin],
+        )
+        .await,
+        Ok(())
+    );
+    assert!(get_token_2022_balance(&mut banks_client, pool.pool_token).await > 0);
+
+    assert_eq!(
+        send(
+            &mut banks_client,
+            &[with_token_2022(
+                swap(
+                    deltafi_swap::id(),
+                    swap_config.pubkey,
+                    pool.swap_info,
+                    swap_config.market_authority,
+                    pool.authority,
+                    owner,
+                    source,
+                    pool.token_a,
+                    pool.token_b,
+                    destination,
+                    reward_token,
+                    swap_config.deltafi_mint,
+                    pool.admin_fee_b,
+                    secondary_admin_fee_b,
+                    creator_fee_b,
+                    sol_oracle.price_pubkey,
+                    srm_oracle.price_pubkey,
+                    SwapData {
+                        amount_in: 1_000_000,
+                        minimum_amount_out: 0,
+                        swap_direction: SwapDirection::SellBase,
+                        deadline: None,
+                    },
+                )
+                .unwrap()
+            )],
+            &[&payer, &user_account_owner],
+        )
+        .await,
+        Ok(())
+    );
+
+    assert_eq!(get_token_2022_balance(&mut banks_client, source).await, 0);
+    assert!(get_token_2022_balance(&mut banks_client, destination).await > 0);
+    assert_eq!(
+        get_token_2022_balance(&mut banks_client, pool.token_a).await,
+        42_001_000_000
+    );
+    assert!(get_token_2022_balance(&mut banks_client, reward_token).await > 0);
+}
+
+#[tokio::test]
+async fn test_token_program_not_allowed() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let pool = add_pool::<ImmutableOwner>(&mut test, &user_account_owner, &admin_account_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let initialize_instruction = initialize_pool(
+        &swap_config,
+        &pool,
+        &sol_oracle,
+        &srm_oracle,
+        &user_account_owner,
+    );
+    assert_eq!(
+        send(
+            &mut banks_client,
+            std::slice::from_ref(&initialize_instruction),
+            &[&payer, &user_account_owner],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::IncorrectTokenProgramId as u32)
+        ))
+    );
+
+    // allowed once the admin whitelists Token-2022
+    assert_eq!(
+        send(
+            &mut banks_client,
+            &[
+                set_allowed_token_program(
+                    deltafi_swap::id(),
+                    swap_config.pubkey,
+                    swap_config.admin.pubkey(),
+                    spl_token_2022::id(),
+                )
+                .unwrap(),
+                initialize_instruction,
+            ],
+            &[&payer, &user_account_owner, &swap_config.admin],
+        )
+        .await,
+        Ok(())
+    );
+    assert_eq!(
+        swap_config
+            .get_state(&mut banks_client)
+            .await
+            .allowed_token_program,
+        spl_token_2022::id()
+    );
+}
+
+#[tokio::test]
+async fn test_transfer_fee_rejected() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config_with_token_program(&mut test, &spl_token_2022::id());
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    let pool = add_pool::<TransferFeeAmount>(&mut test, &user_account_owner, &admin_account_owner);
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        send(
+            &mut banks_client,
+            &[initialize_pool(
+                &swap_config,
+                &pool,
+                &sol_oracle,
+                &srm_oracle,
+                &user_account_owner
+            )],
+            &[&payer, &user_account_owner],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::UnsupportedMintExtension as u32)
+        ))
+    );
+}
+
+#[tokio::test]
+async fn test_permanent_delegate_rejected() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config_with_token_program(&mut test, &spl_token_2022::id());
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+    // the delegate could move tokens straight out of the pool accounts
+    let token_a_mint =
+        add_mint_with_extension::<PermanentDelegate>(&mut test, Pubkey::new_unique());
+    let pool = add_pool_with_token_a_mint::<ImmutableOwner>(
+        &mut test,
+        token_a_mint,
+        &user_account_owner,
+        &admin_account_owner,
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        send(
+            &mut banks_client,
+            &[initialize_pool(
+                &swap_config,
+                &pool,
+                &sol_oracle,
+                &srm_oracle,
+                &user_account_owner
+            )],
+            &[&payer, &user_account_owner],
+        )
+        .await,
+        Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SwapError::UnsupportedMintExtension as u32)
+        ))
+    );
+}
@@ -0,0 +1,176 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use std::convert::TryInto;
+
+use assert_matches::*;
+
+use deltafi_swap::{
+    instruction::{claim_liquidity_rewards, get_total_rewards_minted, SwapDirection},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{LiquidityPosition, LiquidityProvider},
+};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use utils::*;
+
+const REWARDS_OWED: u64 = 500;
+
+async fn total_rewards_minted(
+    banks_client: &mut BanksClient,
+    swap_config: &TestSwapConfig,
+    payer: &Keypair,
+) -> u128 {
+    let return_data = simulate_return_data(
+        banks_client,
+        get_total_rewards_minted(deltafi_swap::id(), swap_config.pubkey).unwrap(),
+        payer,
+    )
+    .await;
+    u128::from_le_bytes(return_data[..].try_into().unwrap())
+}
+
+#[tokio::test]
+async fn test_tracks_swaps_and_claims() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+
+    let swap_config = add_swap_config(&mut test);
+
+    let sol_oracle = add_sol_oracle(&mut test);
+    let srm_oracle = add_srm_oracle(&mut test);
+    let srm_mint = add_srm_mint(&mut test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        &mut test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider = Pubkey::new_unique();
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            positions: vec![LiquidityPosition {
+                pool: swap_info.pubkey,
+                liquidity_amount: 1_000_000_000,
+                rewards_owed: REWARDS_OWED,
+                ..LiquidityPosition::default()
+            }],
+        },
+        &deltafi_swap::id(),
+    );
+
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    assert_eq!(
+        total_rewards_minted(&mut banks_client, &swap_config, &payer).await,
+        0
+    );
+
+    let sol_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        spl_token::native_mint::id(),
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        10_000_000_000,
+    )
+    .await;
+    let srm_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        srm_mint.pubkey,
+        Some(&srm_mint.authority),
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+    let deltafi_user_account = create_and_mint_to_token_account(
+        &mut banks_client,
+        swap_config.deltafi_mint,
+        None,
+        &payer,
+        user_account_owner.pubkey(),
+        0,
+    )
+    .await;
+
+    for _ in 0..2 {
+        swap_info
+            .swap(
+                &mut banks_client,
+                &swap_config,
+                &user_account_owner,
+                sol_user_account,
+                srm_user_account,
+                deltafi_user_account,
+                1_000_000_000,
+                0,
+                SwapDirection::SellBase,
+                &payer,
+            )
+            .await;
+    }
+    let swap_rewards = get_token_balance(&mut banks_client, deltafi_user_account).await;
+    assert!(swap_rewards > 0);
+    assert_eq!(
+        total_rewards_minted(&mut banks_client, &swap_config, &payer).await,
+        swap_rewards as u128
+    );
+
+    let mut transaction = Transaction::new_with_payer(
+        &[claim_liquidity_rewards(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_config.market_authority,
+            liquidity_provider,
+            user_account_owner.pubkey(),
+            deltafi_user_account,
+            swap_config.deltafi_mint,
+        )
+        .unwrap()],
+        Some(&payer.pubkey()),
+    );
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[&payer, &user_account_owner], recent_blockhash);
+    assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+
+    let total = total_rewards_minted(&mut banks_client, &swap_config, &payer).await;
+    assert_eq!(total, (swap_rewards + REWARDS_OWED) as u128);
+    assert_eq!(
+        total,
+        get_token_balance(&mut banks_client, deltafi_user_account).await as u128
+    );
+    assert_eq!(
+        swap_config
+            .get_state(&mut banks_client)
+            .await
+            .total_rewards_minted,
+        total
+    );
+}
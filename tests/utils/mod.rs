@@ -5,23 +5,26 @@ use assert_matches::*;
 use deltafi_swap::{
     curve::{Multiplier, PoolState},
     instruction::{
-        deposit, init_liquidity_provider, initialize, initialize_config, swap, withdraw,
-        DepositData, InitializeData, SwapData, SwapDirection, WithdrawData,
+        deposit, init_liquidity_provider, initialize, initialize_config, set_new_fees,
+        set_pause_flags, swap, withdraw, DepositData, InitializeData, SwapData, SwapDirection,
+        WithdrawData,
     },
     math::Decimal,
     pyth,
     state::{
-        ConfigInfo, Fees, LiquidityPosition, LiquidityProvider, Rewards, SwapInfo, PROGRAM_VERSION,
+        ConfigInfo, Fees, LiquidityPosition, LiquidityProvider, PauseFlags, Rewards, SwapInfo,
+        DEFAULT_REWARD_BUDGET, PROGRAM_VERSION,
     },
 };
 use solana_program::{program_option::COption, program_pack::Pack, pubkey::Pubkey};
 use solana_program_test::*;
 use solana_sdk::{
     account::Account,
+    instruction::Instruction,
     signature::{read_keypair_file, Keypair},
     signer::Signer,
     system_instruction::create_account,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 use spl_token::{
     instruction::{approve, initialize_account, initialize_mint, set_authority, AuthorityType},
@@ -44,6 +47,9 @@ pub const TEST_FEES: Fees = Fees {
     trade_fee_denominator: 1_000,
     withdraw_fee_numerator: 2,
     withdraw_fee_denominator: 100,
+    trade_fee_enabled: true,
+    withdraw_fee_enabled: true,
+    min_fee: 0,
 };
 
 pub const TEST_REWARDS: Rewards = Rewards {
@@ -52,6 +58,9 @@ pub const TEST_REWARDS: Rewards = Rewards {
     trade_reward_cap: 10_000_000_000,
     liquidity_reward_numerator: 1,
     liquidity_reward_denominator: 1_000,
+    deposit_reward_numerator: 0,
+    deposit_reward_denominator: 0,
+    deposit_reward_cap: 0,
 };
 
 pub const SOL_PYTH_PRODUCT: &str = "3Mnn2fX6rQyUsyELYms1sBJyChWofzSNRoqYzvgMVz5E";
@@ -62,7 +71,7 @@ pub const SRM_PYTH_PRICE: &str = "992moaMQKs32GKZ9dxi8keyM2bUmbrwBZpK4p2K6X5Vs";
 
 pub const SRM_MINT: &str = "SRMuApVNdxXokk5GT7XD5cUUgXMBCoAz2LHeuAoKWRt";
 
-trait AddPacked {
+pub trait AddPacked {
     fn add_packable_account<T: Pack>(
         &mut self,
         pubkey: Pubkey,
@@ -99,6 +108,14 @@ pub struct TestMint {
 }
 
 pub fn add_swap_config(test: &mut ProgramTest) -> TestSwapConfig {
+    add_swap_config_with_token_program(test, &spl_token::id())
+}
+
+/// Swap config whose DELTAFI mint is owned by, and which accepts, a token program
+pub fn add_swap_config_with_token_program(
+    test: &mut ProgramTest,
+    token_program_id: &Pubkey,
+) -> TestSwapConfig {
     let swap_config_pubkey = Pubkey::new_unique();
     let (market_authority, bump_seed) =
         Pubkey::find_program_address(&[swap_config_pubkey.as_ref()], &deltafi_swap::id());
@@ -116,7 +133,7 @@ pub fn add_swap_config(test: &mut ProgramTest) -> TestSwapConfig {
             freeze_authority: COption::Some(admin.pubkey()),
             supply: 0,
         },
-        &spl_token::id(),
+        token_program_id,
     );
 
     test.add_packable_account(
@@ -129,6 +146,22 @@ pub fn add_swap_config(test: &mut ProgramTest) -> TestSwapConfig {
             deltafi_mint,
             fees: TEST_FEES,
             rewards: TEST_REWARDS,
+            drain_unlock_ts: 0,
+            pools_created: 0,
+            min_claim_amount: 0,
+            admin_split_bps: 10_000,
+            min_pool_supply: 0,
+            total_rewards_minted: 0,
+            strict_claim_destination: false,
+            allowed_token_program: if *token_program_id == spl_token::id() {
+                Pubkey::default()
+            } else {
+                *token_program_id
+            },
+            max_ema_divergence_bps: 0,
+            drain_swap: Pubkey::default(),
+            drain_treasury_a: Pubkey::default(),
+            drain_treasury_b: Pubkey::default(),
         },
         &deltafi_swap::id(),
     );
@@ -279,9 +312,65 @@ pub fn add_swap_info(
         &spl_token::id(),
     );
 
+    let secondary_admin_fee_a_key = Pubkey::new_unique();
+    test.add_packable_account(
+        secondary_admin_fee_a_key,
+        u32::MAX as u64,
+        &Token {
+            mint: token_a_mint,
+            owner: swap_config.admin.pubkey(),
+            amount: 0,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let secondary_admin_fee_b_key = Pubkey::new_unique();
+    test.add_packable_account(
+        secondary_admin_fee_b_key,
+        u32::MAX as u64,
+        &Token {
+            mint: token_b_mint,
+            owner: swap_config.admin.pubkey(),
+            amount: 0,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let creator_fee_a_key = Pubkey::new_unique();
+    test.add_packable_account(
+        creator_fee_a_key,
+        u32::MAX as u64,
+        &Token {
+            mint: token_a_mint,
+            owner: user_account_owner.pubkey(),
+            amount: 0,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
+    let creator_fee_b_key = Pubkey::new_unique();
+    test.add_packable_account(
+        creator_fee_b_key,
+        u32::MAX as u64,
+        &Token {
+            mint: token_b_mint,
+            owner: user_account_owner.pubkey(),
+            amount: 0,
+            state: AccountState::Initialized,
+            ..Token::default()
+        },
+        &spl_token::id(),
+    );
+
     let swap_info = SwapInfo {
         is_initialized: true,
-        is_paused: false,
+        pause_flags: PauseFlags::NONE,
         nonce,
         token_a,
         token_b,
@@ -294,6 +383,11 @@ pub fn add_swap_info(
         rewards: swap_config.rewards.clone(),
         is_open_twap,
         pool_state,
+        creator_key: user_account_owner.pubkey(),
+        config_key: swap_config.pubkey,
+        min_reward_liquidity: 0,
+        reward_budget_remaining: DEFAULT_REWARD_BUDGET,
+        pool_mint_decimals: DECIMALS,
         ..SwapInfo::default()
     };
 
@@ -305,6 +399,7 @@ pub fn add_swap_info(
     );
 
     TestSwapInfo {
+        config: swap_config.pubkey,
         pubkey: swap_info_pubkey,
         authority: swap_authority_pubkey,
         nonce,
@@ -316,7 +411,15 @@ pub fn add_swap_info(
         token_b_mint,
         admin_fee_a_key,
         admin_fee_b_key,
+        secondary_admin_fee_a_key,
+        secondary_admin_fee_b_key,
+        creator_key: user_account_owner.pubkey(),
+        creator_fee_a_key,
+        creator_fee_b_key,
+        creator_fee_bps: 0,
         is_open_twap,
+        invert_a: false,
+        invert_b: false,
         fees: swap_config.fees.clone(),
         rewards: swap_config.rewards.clone(),
         oracle_a,
@@ -362,7 +465,7 @@ pub fn add_position(
     liquidity_provider
         .find_or_add_position(swap_info.pubkey, 0)
         .unwrap()
-        .deposit(liquidity_amount)
+        .deposit(liquidity_amount, Decimal::zero())
         .unwrap();
 
     test.add_packable_account(
@@ -477,6 +580,7 @@ impl TestSwapConfig {
 }
 
 pub struct TestSwapInfo {
+    pub config: Pubkey,
     pub pubkey: Pubkey,
     pub authority: Pubkey,
     pub nonce: u8,
@@ -488,7 +592,15 @@ pub struct TestSwapInfo {
     pub token_b_mint: Pubkey,
     pub admin_fee_a_key: Pubkey,
     pub admin_fee_b_key: Pubkey,
+    pub secondary_admin_fee_a_key: Pubkey,
+    pub secondary_admin_fee_b_key: Pubkey,
+    pub creator_key: Pubkey,
+    pub creator_fee_a_key: Pubkey,
+    pub creator_fee_b_key: Pubkey,
+    pub creator_fee_bps: u16,
     pub is_open_twap: bool,
+    pub invert_a: bool,
+    pub invert_b: bool,
     pub fees: Fees,
     pub rewards: Rewards,
     pub oracle_a: Pubkey,
@@ -499,6 +611,10 @@ pub struct SwapInitArgs {
     pub mid_price: u128,
     pub slope: u64,
     pub is_open_twap: bool,
+    pub creator_fee_bps: u16,
+    pub invert_a: bool,
+    pub invert_b: bool,
+    pub slope_in_bps: bool,
 }
 
 impl TestSwapInfo {
@@ -517,6 +633,40 @@ impl TestSwapInfo {
         payer: &Keypair,
         args: &SwapInitArgs,
     ) -> Self {
+        Self::try_init(
+            banks_client,
+            swap_config,
+            cracle_a,
+            oracle_b,
+            token_a_mint,
+            token_b_mint,
+            token_a,
+            token_b,
+            admin_fee_a_key,
+            admin_fee_b_key,
+            user_account_owner,
+            payer,
+            args,
+        )
+        .await
+        .unwrap()
+    }
+
+    pub async fn try_init(
+        banks_client: &mut BanksClient,
+        swap_config: &TestSwapConfig,
+        cracle_a: &TestOracle,
+        oracle_b: &TestOracle,
+        token_a_mint: Pubkey,
+        token_b_mint: Pubkey,
+        token_a: Pubkey,
+        token_b: Pubkey,
+        admin_fee_a_key: Pubkey,
+        admin_fee_b_key: Pubkey,
+        user_account_owner: &Keypair,
+        payer: &Keypair,
+        args: &SwapInitArgs,
+    ) -> Result<Self, TransactionError> {
         let swap_info = Keypair::new();
         let swap_info_pubkey = swap_info.pubkey();
 
@@ -594,15 +744,22 @@ impl TestSwapInfo {
                     admin_fee_b_key,
                     token_a,
                     token_b,
+                    token_a_mint,
+                    token_b_mint,
                     pool_mint_keypair.pubkey(),
                     user_pool_token_keypair.pubkey(),
                     cracle_a.price_pubkey,
-                    oracle_b.product_pubkey,
+                    oracle_b.price_pubkey,
+                    user_account_owner.pubkey(),
                     InitializeData {
                         nonce,
                         mid_price: args.mid_price,
                         slope: args.slope,
                         is_open_twap: args.is_open_twap,
+                        creator_fee_bps: args.creator_fee_bps,
+                        invert_a: args.invert_a,
+                        invert_b: args.invert_b,
+                        slope_in_bps: args.slope_in_bps,
                     },
                 )
                 .unwrap(),
@@ -622,9 +779,46 @@ impl TestSwapInfo {
             recent_blockhash,
         );
 
-        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap())?;
 
-        Self {
+        let secondary_admin_fee_a_key = create_token_account(
+            banks_client,
+            token_a_mint,
+            payer,
+            Some(swap_config.admin.pubkey()),
+            None,
+        )
+        .await;
+        let secondary_admin_fee_b_key = create_token_account(
+            banks_client,
+            token_b_mint,
+            payer,
+            Some(swap_config.admin.pubkey()),
+            None,
+        )
+        .await;
+        let creator_fee_a_key = create_token_account(
+            banks_client,
+            token_a_mint,
+            payer,
+            Some(user_account_owner.pubkey()),
+            None,
+        )
+        .await;
+        let creator_fee_b_key = create_token_account(
+            banks_client,
+            token_b_mint,
+            payer,
+            Some(user_account_owner.pubkey()),
+            None,
+        )
+        .await;
+
+        Ok(Self {
+            config: swap_config.pubkey,
             pubkey: swap_info_pubkey,
             authority: swap_authority_pubkey,
             nonce,
@@ -634,14 +828,22 @@ impl TestSwapInfo {
             pool_mint: pool_mint_keypair.pubkey(),
             admin_fee_a_key,
             admin_fee_b_key,
+            secondary_admin_fee_a_key,
+            secondary_admin_fee_b_key,
+            creator_key: user_account_owner.pubkey(),
+            creator_fee_a_key,
+            creator_fee_b_key,
+            creator_fee_bps: args.creator_fee_bps,
             token_a_mint,
             token_b_mint,
             is_open_twap: args.is_open_twap,
+            invert_a: args.invert_a,
+            invert_b: args.invert_b,
             fees: swap_config.fees.clone(),
             rewards: swap_config.rewards.clone(),
             oracle_a: cracle_a.price_pubkey,
             oracle_b: oracle_b.price_pubkey,
-        }
+        })
     }
 
     pub async fn swap(
@@ -657,6 +859,67 @@ impl TestSwapInfo {
         swap_direction: SwapDirection,
         payer: &Keypair,
     ) {
+        assert_matches!(
+            self.try_swap(
+                banks_client,
+                config_info,
+                user_account_owner,
+                source_pubkey,
+                destination_pubkey,
+                reward_token_pubkey,
+                amount_in,
+                minimum_amount_out,
+                swap_direction,
+                payer,
+            )
+            .await,
+            Ok(())
+        );
+    }
+
+    pub async fn try_swap(
+        &self,
+        banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
+        user_account_owner: &Keypair,
+        source_pubkey: Pubkey,
+        destination_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        swap_direction: SwapDirection,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
+        self.try_swap_with_deadline(
+            banks_client,
+            config_info,
+            user_account_owner,
+            source_pubkey,
+            destination_pubkey,
+            reward_token_pubkey,
+            amount_in,
+            minimum_amount_out,
+            swap_direction,
+            None,
+            payer,
+        )
+        .await
+    }
+
+    pub async fn try_swap_with_deadline(
+        &self,
+        banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
+        user_account_owner: &Keypair,
+        source_pubkey: Pubkey,
+        destination_pubkey: Pubkey,
+        reward_token_pubkey: Pubkey,
+        amount_in: u64,
+        minimum_amount_out: u64,
+        swap_direction: SwapDirection,
+        deadline: Option<i64>,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
         let user_transfer_authority = Keypair::new();
         let mut transaction = Transaction::new_with_payer(
             &[
@@ -683,12 +946,15 @@ impl TestSwapInfo {
                     reward_token_pubkey,
                     config_info.deltafi_mint,
                     self.admin_fee_b_key,
+                    self.secondary_admin_fee_b_key,
+                    self.creator_fee_b_key,
                     self.oracle_a,
                     self.oracle_b,
                     SwapData {
                         amount_in,
                         minimum_amount_out,
                         swap_direction,
+                        deadline,
                     },
                 )
                 .unwrap(),
@@ -702,12 +968,16 @@ impl TestSwapInfo {
             recent_blockhash,
         );
 
-        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap())
     }
 
     pub async fn deposit(
         &self,
         banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
         liquidity_provider: &TestLiquidityProvider,
         user_account_owner: &Keypair,
         deposit_token_a_pubkey: Pubkey,
@@ -718,6 +988,71 @@ impl TestSwapInfo {
         min_mint_amount: u64,
         payer: &Keypair,
     ) {
+        assert_matches!(
+            self.try_deposit(
+                banks_client,
+                config_info,
+                liquidity_provider,
+                user_account_owner,
+                deposit_token_a_pubkey,
+                deposit_token_b_pubkey,
+                pool_token_pubkey,
+                token_a_amount,
+                token_b_amount,
+                min_mint_amount,
+                payer,
+            )
+            .await,
+            Ok(())
+        );
+    }
+
+    pub async fn try_deposit(
+        &self,
+        banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        deposit_token_a_pubkey: Pubkey,
+        deposit_token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        min_mint_amount: u64,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
+        self.try_deposit_with_deadline(
+            banks_client,
+            config_info,
+            liquidity_provider,
+            user_account_owner,
+            deposit_token_a_pubkey,
+            deposit_token_b_pubkey,
+            pool_token_pubkey,
+            token_a_amount,
+            token_b_amount,
+            min_mint_amount,
+            None,
+            payer,
+        )
+        .await
+    }
+
+    pub async fn try_deposit_with_deadline(
+        &self,
+        banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        deposit_token_a_pubkey: Pubkey,
+        deposit_token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        token_a_amount: u64,
+        token_b_amount: u64,
+        min_mint_amount: u64,
+        deadline: Option<i64>,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
         let user_transfer_authority = Keypair::new();
         let mut transaction = Transaction::new_with_payer(
             &[
@@ -741,6 +1076,7 @@ impl TestSwapInfo {
                 .unwrap(),
                 deposit(
                     deltafi_swap::id(),
+                    config_info.pubkey,
                     self.pubkey,
                     self.authority,
                     user_transfer_authority.pubkey(),
@@ -750,6 +1086,7 @@ impl TestSwapInfo {
                     self.token_b,
                     self.pool_mint,
                     pool_token_pubkey,
+                    config_info.deltafi_mint,
                     liquidity_provider.pubkey,
                     liquidity_provider.owner,
                     self.oracle_a,
@@ -758,6 +1095,7 @@ impl TestSwapInfo {
                         token_a_amount,
                         token_b_amount,
                         min_mint_amount,
+                        deadline,
                     },
                 )
                 .unwrap(),
@@ -771,7 +1109,10 @@ impl TestSwapInfo {
             recent_blockhash,
         );
 
-        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap())
     }
 
     pub async fn withdraw(
@@ -787,6 +1128,67 @@ impl TestSwapInfo {
         minimum_token_b_amount: u64,
         payer: &Keypair,
     ) {
+        assert_matches!(
+            self.try_withdraw(
+                banks_client,
+                liquidity_provider,
+                user_account_owner,
+                token_a_pubkey,
+                token_b_pubkey,
+                pool_token_pubkey,
+                pool_token_amount,
+                minimum_token_a_amount,
+                minimum_token_b_amount,
+                payer,
+            )
+            .await,
+            Ok(())
+        );
+    }
+
+    pub async fn try_withdraw(
+        &self,
+        banks_client: &mut BanksClient,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        token_a_pubkey: Pubkey,
+        token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        pool_token_amount: u64,
+        minimum_token_a_amount: u64,
+        minimum_token_b_amount: u64,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
+        self.try_withdraw_with_deadline(
+            banks_client,
+            liquidity_provider,
+            user_account_owner,
+            token_a_pubkey,
+            token_b_pubkey,
+            pool_token_pubkey,
+            pool_token_amount,
+            minimum_token_a_amount,
+            minimum_token_b_amount,
+            None,
+            payer,
+        )
+        .await
+    }
+
+    pub async fn try_withdraw_with_deadline(
+        &self,
+        banks_client: &mut BanksClient,
+        liquidity_provider: &TestLiquidityProvider,
+        user_account_owner: &Keypair,
+        token_a_pubkey: Pubkey,
+        token_b_pubkey: Pubkey,
+        pool_token_pubkey: Pubkey,
+        pool_token_amount: u64,
+        minimum_token_a_amount: u64,
+        minimum_token_b_amount: u64,
+        deadline: Option<i64>,
+        payer: &Keypair,
+    ) -> Result<(), TransactionError> {
         let user_transfer_authority = Keypair::new();
         let mut transaction = Transaction::new_with_payer(
             &[
@@ -801,6 +1203,7 @@ impl TestSwapInfo {
                 .unwrap(),
                 withdraw(
                     deltafi_swap::id(),
+                    self.config,
                     self.pubkey,
                     self.authority,
                     user_transfer_authority.pubkey(),
@@ -812,6 +1215,8 @@ impl TestSwapInfo {
                     token_b_pubkey,
                     self.admin_fee_a_key,
                     self.admin_fee_b_key,
+                    self.secondary_admin_fee_a_key,
+                    self.secondary_admin_fee_b_key,
                     liquidity_provider.pubkey,
                     liquidity_provider.owner,
                     self.oracle_a,
@@ -820,6 +1225,7 @@ impl TestSwapInfo {
                         pool_token_amount,
                         minimum_token_a_amount,
                         minimum_token_b_amount,
+                        deadline,
                     },
                 )
                 .unwrap(),
@@ -833,6 +1239,59 @@ impl TestSwapInfo {
             recent_blockhash,
         );
 
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .map_err(|e| e.unwrap())
+    }
+
+    pub async fn set_pause_flags(
+        &self,
+        banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
+        pause_flags: PauseFlags,
+        payer: &Keypair,
+    ) {
+        let mut transaction = Transaction::new_with_payer(
+            &[set_pause_flags(
+                deltafi_swap::id(),
+                config_info.pubkey,
+                self.pubkey,
+                config_info.admin.pubkey(),
+                pause_flags,
+            )
+            .unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+        transaction.sign(&[payer, &config_info.admin], recent_blockhash);
+
+        assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
+    }
+
+    pub async fn set_new_fees(
+        &self,
+        banks_client: &mut BanksClient,
+        config_info: &TestSwapConfig,
+        new_fees: Fees,
+        payer: &Keypair,
+    ) {
+        let mut transaction = Transaction::new_with_payer(
+            &[set_new_fees(
+                deltafi_swap::id(),
+                config_info.pubkey,
+                self.pubkey,
+                config_info.admin.pubkey(),
+                new_fees,
+            )
+            .unwrap()],
+            Some(&payer.pubkey()),
+        );
+
+        let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+        transaction.sign(&[payer, &config_info.admin], recent_blockhash);
+
         assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
     }
 
@@ -852,9 +1311,15 @@ impl TestSwapInfo {
         assert_eq!(swap_info.token_b, self.token_b);
         assert_eq!(swap_info.admin_fee_key_a, self.admin_fee_a_key);
         assert_eq!(swap_info.admin_fee_key_b, self.admin_fee_b_key);
+        assert_eq!(swap_info.creator_key, self.creator_key);
+        assert_eq!(swap_info.creator_fee_bps, self.creator_fee_bps);
         assert_eq!(swap_info.token_a_mint, self.token_a_mint);
         assert_eq!(swap_info.token_b_mint, self.token_b_mint);
+        assert_eq!(swap_info.pool_mint_decimals, DECIMALS);
         assert_eq!(swap_info.is_open_twap, self.is_open_twap);
+        assert_eq!(swap_info.invert_a, self.invert_a);
+        assert_eq!(swap_info.invert_b, self.invert_b);
+        assert_eq!(swap_info.config_key, self.config);
         assert_eq!(swap_info.fees, self.fees);
         assert_eq!(swap_info.rewards, self.rewards);
     }
@@ -1029,6 +1494,28 @@ pub async fn mint_to(
     assert_matches!(banks_client.process_transaction(transaction).await, Ok(()));
 }
 
+pub async fn simulate_return_data(
+    banks_client: &mut BanksClient,
+    instruction: Instruction,
+    payer: &Keypair,
+) -> Vec<u8> {
+    let mut transaction = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&[payer], recent_blockhash);
+
+    let simulation = banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_matches!(simulation.result, Some(Ok(())));
+    simulation
+        .simulation_details
+        .unwrap()
+        .return_data
+        .unwrap()
+        .data
+}
+
 pub async fn get_token_balance(banks_client: &mut BanksClient, pubkey: Pubkey) -> u64 {
     let token: Account = banks_client.get_account(pubkey).await.unwrap().unwrap();
 
@@ -1065,14 +1552,19 @@ pub fn add_oracle(
         .checked_pow(pyth_price.expo.checked_abs().unwrap().try_into().unwrap())
         .unwrap();
 
-    pyth_price.valid_slot = 0;
-    pyth_price.agg.price = price
+    let raw_price = price
         .try_round_u64()
         .unwrap()
         .checked_mul(decimals)
-        .unwrap()
-        .try_into()
         .unwrap();
+    assert_eq!(
+        pyth::pyth_exponent_to_decimal(raw_price, pyth_price.expo).unwrap(),
+        price,
+        "oracle price must round trip through the program conversion"
+    );
+
+    pyth_price.valid_slot = 0;
+    pyth_price.agg.price = raw_price.try_into().unwrap();
 
     test.add_account(
         price_pubkey,
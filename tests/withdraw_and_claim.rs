@@ -0,0 +1,253 @@
+#![cfg(feature = "test-bpf")]
+
+mod utils;
+
+use deltafi_swap::{
+    instruction::{set_min_claim_amount, withdraw_and_claim, WithdrawData},
+    math::{Decimal, TryDiv},
+    processor::process,
+    state::{LiquidityPosition, LiquidityProvider},
+};
+use solana_program::{instruction::Instruction, program_pack::Pack, pubkey::Pubkey};
+use solana_program_test::*;
+use solana_sdk::{
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::instruction::approve;
+use utils::*;
+
+const LIQUIDITY_AMOUNT: u64 = 1_000_000_000;
+const REWARDS_OWED: u64 = 500;
+
+struct TestExit {
+    swap_config: TestSwapConfig,
+    swap_info: TestSwapInfo,
+    liquidity_provider: Pubkey,
+    user_account_owner: Keypair,
+}
+
+fn add_exit(test: &mut ProgramTest) -> TestExit {
+    let swap_config = add_swap_config(test);
+
+    let sol_oracle = add_sol_oracle(test);
+    let srm_oracle = add_srm_oracle(test);
+    let srm_mint = add_srm_mint(test);
+
+    let user_account_owner = Keypair::new();
+    let admin_account_owner = Keypair::new();
+
+    let swap_info = add_swap_info(
+        test,
+        &swap_config,
+        &user_account_owner,
+        &admin_account_owner,
+        AddSwapInfoArgs {
+            token_a_mint: spl_token::native_mint::id(),
+            token_b_mint: srm_mint.pubkey,
+            token_a_amount: 42_000_000_000,
+            token_b_amount: 800_000_000_000,
+            is_open_twap: true,
+            oracle_a: sol_oracle.price_pubkey,
+            oracle_b: srm_oracle.price_pubkey,
+            market_price: sol_oracle.price.try_div(srm_oracle.price).unwrap(),
+            slope: Decimal::one().try_div(2).unwrap(),
+        },
+    );
+
+    let liquidity_provider = Pubkey::new_unique();
+    test.add_packable_account(
+        liquidity_provider,
+        u32::MAX as u64,
+        &LiquidityProvider {
+            is_initialized: true,
+            owner: user_account_owner.pubkey(),
+            positions: vec![LiquidityPosition {
+                pool: swap_info.pubkey,
+                liquidity_amount: LIQUIDITY_AMOUNT,
+                rewards_owed: REWARDS_OWED,
+                ..LiquidityPosition::default()
+            }],
+        },
+        &deltafi_swap::id(),
+    );
+
+    TestExit {
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+    }
+}
+
+/// Withdraw the whole position into fresh token accounts, returning them
+/// with the reward account
+async fn try_exit(
+    banks_client: &mut BanksClient,
+    exit: &TestExit,
+    pre_instructions: &[Instruction],
+    extra_signers: &[&Keypair],
+    payer: &Keypair,
+) -> (Result<(), TransactionError>, Pubkey, Pubkey, Pubkey) {
+    let TestExit {
+        swap_config,
+        swap_info,
+        liquidity_provider,
+        user_account_owner,
+    } = exit;
+    let owner = user_account_owner.pubkey();
+    let token_a = create_token_account(
+        banks_client,
+        swap_info.token_a_mint,
+        payer,
+        Some(owner),
+        None,
+    )
+    .await;
+    let token_b = create_token_account(
+        banks_client,
+        swap_info.token_b_mint,
+        payer,
+        Some(owner),
+        None,
+    )
+    .await;
+    let reward_token = create_token_account(
+        banks_client,
+        swap_config.deltafi_mint,
+        payer,
+        Some(owner),
+        None,
+    )
+    .await;
+
+    let user_transfer_authority = Keypair::new();
+    let mut instructions = pre_instructions.to_vec();
+    instructions.push(
+        approve(
+            &spl_token::id(),
+            &swap_info.pool_token,
+            &user_transfer_authority.pubkey(),
+            &owner,
+            &[],
+            LIQUIDITY_AMOUNT,
+        )
+        .unwrap(),
+    );
+    instructions.push(
+        withdraw_and_claim(
+            deltafi_swap::id(),
+            swap_config.pubkey,
+            swap_info.pubkey,
+            swap_info.authority,
+            user_transfer_authority.pubkey(),
+            swap_info.pool_mint,
+            swap_info.pool_token,
+            swap_info.token_a,
+            swap_info.token_b,
+            token_a,
+            token_b,
+            swap_info.admin_fee_a_key,
+            swap_info.admin_fee_b_key,
+            swap_info.secondary_admin_fee_a_key,
+            swap_info.secondary_admin_fee_b_key,
+            *liquidity_provider,
+            owner,
+            swap_info.oracle_a,
+            swap_info.oracle_b,
+            swap_config.market_authority,
+            reward_token,
+            swap_config.deltafi_mint,
+            WithdrawData {
+                pool_token_amount: LIQUIDITY_AMOUNT,
+                minimum_token_a_amount: 0,
+                minimum_token_b_amount: 0,
+                deadline: None,
+            },
+        )
+        .unwrap(),
+    );
+
+    let mut transaction = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    let mut signers = vec![payer, user_account_owner, &user_transfer_authority];
+    signers.extend_from_slice(extra_signers);
+    let recent_blockhash = banks_client.get_recent_blockhash().await.unwrap();
+    transaction.sign(&signers, recent_blockhash);
+
+    let result = banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap());
+    (result, token_a, token_b, reward_token)
+}
+
+async fn get_liquidity_provider(
+    banks_client: &mut BanksClient,
+    liquidity_provider: Pubkey,
+) -> LiquidityProvider {
+    let account = banks_client
+        .get_account(liquidity_provider)
+        .await
+        .unwrap()
+        .unwrap();
+    LiquidityProvider::unpack(&account.data).unwrap()
+}
+
+#[tokio::test]
+async fn test_withdraw_all_and_claim() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let exit = add_exit(&mut test);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let (result, token_a, token_b, reward_token) =
+        try_exit(&mut banks_client, &exit, &[], &[], &payer).await;
+    assert_eq!(result, Ok(()));
+
+    assert!(get_token_balance(&mut banks_client, token_a).await > 0);
+    assert!(get_token_balance(&mut banks_client, token_b).await > 0);
+    assert_eq!(
+        get_token_balance(&mut banks_client, reward_token).await,
+        REWARDS_OWED
+    );
+    // claimed before the withdraw, so the emptied position is removed
+    assert!(
+        get_liquidity_provider(&mut banks_client, exit.liquidity_provider)
+            .await
+            .positions
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn test_rewards_below_claim_minimum() {
+    let mut test = ProgramTest::new("deltafi_swap", deltafi_swap::id(), processor!(process));
+    let exit = add_exit(&mut test);
+    let (mut banks_client, payer, _recent_blockhash) = test.start().await;
+
+    let (result, token_a, token_b, reward_token) = try_exit(
+        &mut banks_client,
+        &exit,
+        &[set_min_claim_amount(
+            deltafi_swap::id(),
+            exit.swap_config.pubkey,
+            exit.swap_config.admin.pubkey(),
+            REWARDS_OWED + 1,
+        )
+        .unwrap()],
+        &[&exit.swap_config.admin],
+        &payer,
+    )
+    .await;
+    assert_eq!(result, Ok(()));
+
+    // the withdraw goes through and the rewards stay owed
+    assert!(get_token_balance(&mut banks_client, token_a).await > 0);
+    assert!(get_token_balance(&mut banks_client, token_b).await > 0);
+    assert_eq!(get_token_balance(&mut banks_client, reward_token).await, 0);
+    let positions = get_liquidity_provider(&mut banks_client, exit.liquidity_provider)
+        .await
+        .positions;
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].liquidity_amount, 0);
+    assert_eq!(positions[0].rewards_owed, REWARDS_OWED);
+}